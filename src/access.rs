@@ -0,0 +1,144 @@
+//! Restrict which client networks may connect to an exposed PostgreSQL cluster (pg_hba).
+
+use crate::cluster;
+use crate::exec::CommandExt;
+use crate::pods;
+use std::net::IpAddr;
+use std::path::Path;
+use std::process::Command;
+
+/// Cluster annotation recording the CIDRs applied by `--allow-cidr`.
+const ALLOWED_CIDRS_ANNOTATION: &str = "fdb.io/allowed-cidrs";
+
+/// Validate "10.0.0.0/8" / "2001:db8::/32"; a bare address becomes /32 or /128.
+pub fn parse_cidr(s: &str) -> Result<String, String> {
+    let s = s.trim();
+    let (addr, prefix) = match s.split_once('/') {
+        Some((a, p)) => (a, Some(p)),
+        None => (s, None),
+    };
+    let ip: IpAddr = addr
+        .parse()
+        .map_err(|_| format!("invalid CIDR: {s} (expected e.g. 10.0.0.0/8)"))?;
+    let max = if ip.is_ipv4() { 32 } else { 128 };
+    let prefix = match prefix {
+        Some(p) => p
+            .parse::<u8>()
+            .ok()
+            .filter(|p| *p <= max)
+            .ok_or_else(|| format!("invalid CIDR prefix: {s} (0-{max})"))?,
+        None => max,
+    };
+    Ok(format!("{ip}/{prefix}"))
+}
+
+/// pg_hba rules: the `postgres` superuser on the pod's own socket (fdb's psql, pg_dump and
+/// Patroni execs) without a password, password logins for everyone else on the socket and
+/// loopback, in-cluster clients and replication from `pod_cidrs`, client logins from `cidrs`.
+/// Without pod CIDRs, replication is only accepted from `cidrs`.
+pub fn pg_hba_rules(cidrs: &[String], pod_cidrs: &[String]) -> Vec<String> {
+    let mut rules = vec![
+        "local all postgres trust".to_string(),
+        "local all all md5".to_string(),
+        "host all all 127.0.0.1/32 md5".to_string(),
+        "host all all ::1/128 md5".to_string(),
+    ];
+    let replication = if pod_cidrs.is_empty() { cidrs } else { pod_cidrs };
+    rules.extend(replication.iter().map(|c| format!("host replication all {c} md5")));
+    let mut clients: Vec<&String> = pod_cidrs.iter().collect();
+    clients.extend(cidrs.iter().filter(|c| !pod_cidrs.contains(c)));
+    rules.extend(clients.iter().map(|c| format!("host all all {c} md5")));
+    rules
+}
+
+/// The nodes' pod CIDRs: in-cluster clients (the seed Job, apps in the cluster) and replicas
+/// connect from these. Empty when the nodes cannot be read or report none (some CNIs leave
+/// `spec.podCIDR` unset).
+pub fn pod_cidrs(kubectl: &Path, kubeconfig: &Path) -> Vec<String> {
+    let output = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["get", "nodes", "-o", "jsonpath={.items[*].spec.podCIDRs[*]}"])
+        .output_checked();
+    let mut pod_cidrs: Vec<String> = output
+        .map(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .split_whitespace()
+                .filter_map(|c| parse_cidr(c).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    pod_cidrs.sort();
+    pod_cidrs.dedup();
+    if pod_cidrs.is_empty() {
+        eprintln!(
+            "warning: no pod CIDR found on the nodes; in-cluster clients and replication only allowed from the \
+             --allow-cidr networks"
+        );
+    }
+    pod_cidrs
+}
+
+/// Apply pg_hba through Patroni's dynamic configuration on the primary and record the CIDRs on the cluster.
+pub fn apply_allowed_cidrs(
+    kubectl: &Path,
+    cluster_name: &str,
+    cidrs: &[String],
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    let pod_cidrs = pod_cidrs(kubectl, kubeconfig);
+    let rules = serde_json::to_string(&pg_hba_rules(cidrs, &pod_cidrs)).map_err(|e| e.to_string())?;
+    let pod = pods::primary_pod(kubectl, cluster_name, "postgresql", kubeconfig)?;
+    pods::exec(
+        kubectl,
+        &pod,
+        "postgresql",
+        &[
+            "patronictl",
+            "edit-config",
+            "--force",
+            "--set",
+            &format!("postgresql.pg_hba={rules}"),
+        ],
+        kubeconfig,
+    )?;
+    cluster::set_cluster_annotation(
+        kubectl,
+        cluster_name,
+        ALLOWED_CIDRS_ANNOTATION,
+        Some(&cidrs.join(",")),
+        kubeconfig,
+    )
+}
+
+/// CIDRs previously applied with `--allow-cidr`; empty when access is unrestricted.
+pub fn allowed_cidrs(kubectl: &Path, cluster_name: &str, kubeconfig: &Path) -> Result<Vec<String>, String> {
+    let value = cluster::cluster_annotation(kubectl, cluster_name, ALLOWED_CIDRS_ANNOTATION, kubeconfig)?;
+    Ok(value
+        .map(|v| v.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pg_hba_requires_passwords_and_limits_replication() {
+        let rules = pg_hba_rules(&["203.0.113.0/24".to_string()], &["10.244.0.0/16".to_string()]);
+        assert_eq!(
+            rules,
+            [
+                "local all postgres trust",
+                "local all all md5",
+                "host all all 127.0.0.1/32 md5",
+                "host all all ::1/128 md5",
+                "host replication all 10.244.0.0/16 md5",
+                "host all all 10.244.0.0/16 md5",
+                "host all all 203.0.113.0/24 md5",
+            ]
+        );
+        let rules = pg_hba_rules(&["203.0.113.0/24".to_string()], &[]);
+        assert!(rules.contains(&"host replication all 203.0.113.0/24 md5".to_string()), "{rules:?}");
+    }
+}
@@ -0,0 +1,820 @@
+//! Provision additional accounts and API keys on existing clusters (`fdb credentials add`).
+
+use crate::certs;
+use crate::cluster;
+use crate::config::{self, RedisAclUser};
+use crate::credentials::{self, ConnectionDetails};
+use crate::error::{self, Error, ExitCode};
+use crate::exec::CommandExt;
+use crate::expose;
+use crate::pods;
+use crate::portforward;
+use crate::redact;
+use crate::service::ServiceType;
+use std::path::Path;
+use std::process::Command;
+
+const NAMESPACE: &str = "default";
+pub const RABBITMQ_MANAGEMENT_PORT: u16 = 15672;
+/// Cluster annotation mapping temporary user names to their RFC 3339 expiry (JSON object).
+const TEMP_USERS_ANNOTATION: &str = "fdb.io/temp-users";
+
+/// What `fdb credentials add` should create.
+#[derive(Debug, Clone, Default)]
+pub struct AddOptions {
+    /// Qdrant: mint a read-only API key.
+    pub readonly: bool,
+    /// RabbitMQ/Redis: user name to create.
+    pub user: Option<String>,
+    /// RabbitMQ: vhost for the user's permissions (created if missing); default "/".
+    pub vhost: Option<String>,
+    /// RabbitMQ: regex applied to configure/write/read permissions; default ".*".
+    pub permissions: Option<String>,
+    /// Redis: ACL command rules, e.g. "+@read".
+    pub commands: Option<String>,
+    /// Redis: key patterns, e.g. "app:*".
+    pub keys: Option<String>,
+    /// PostgreSQL: issue a client certificate instead of a password.
+    pub cert: bool,
+    /// PostgreSQL: secret with the CA (`ca.crt`, `ca.key`) that signs client certificates.
+    pub ca_secret: Option<String>,
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters.
+pub fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// Secret holding Qdrant API keys (fields `api-key`, `read-only-api-key`).
+fn qdrant_keys_secret(cluster_name: &str) -> String {
+    format!("{cluster_name}-qdrant-api-keys")
+}
+
+/// Add an env var sourced from a secret key to the cluster's first component (KubeBlocks rolls the pods).
+fn add_component_secret_env(
+    kubectl: &Path,
+    cluster_name: &str,
+    env_name: &str,
+    secret: &str,
+    key: &str,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    let current = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args([
+            "get",
+            "cluster",
+            cluster_name,
+            "-n",
+            NAMESPACE,
+            "-o",
+            "jsonpath={.spec.componentSpecs[0].env[*].name}",
+        ])
+        .output_checked()?;
+    let names = String::from_utf8_lossy(&current.stdout).to_string();
+    if names.split_whitespace().any(|n| n == env_name) {
+        return Ok(());
+    }
+
+    let env = serde_json::json!({
+        "name": env_name,
+        "valueFrom": { "secretKeyRef": { "name": secret, "key": key } },
+    });
+    let op = if names.trim().is_empty() {
+        serde_json::json!({ "op": "add", "path": "/spec/componentSpecs/0/env", "value": [env] })
+    } else {
+        serde_json::json!({ "op": "add", "path": "/spec/componentSpecs/0/env/-", "value": env })
+    };
+    let patch = serde_json::json!([op]).to_string();
+
+    Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["patch", "cluster", cluster_name, "-n", NAMESPACE, "--type", "json", "-p", &patch])
+        .output_checked()?;
+    Ok(())
+}
+
+/// Mint a Qdrant API key. Without `readonly` this enables API keys (admin key);
+/// with `readonly` it adds a read-only key and requires API keys to be enabled already.
+/// Returns (api key, read-only key if any).
+pub fn add_qdrant_key(
+    kubectl: &Path,
+    cluster_name: &str,
+    readonly: bool,
+    kubeconfig: &Path,
+) -> Result<(String, Option<String>), String> {
+    let secret = qdrant_keys_secret(cluster_name);
+    let existing = credentials::read_secret_field(kubectl, &secret, "api-key", kubeconfig)?;
+
+    let api_key = match (existing, readonly) {
+        (Some(key), _) => key,
+        (None, true) => {
+            return Err(format!(
+                "API keys are not enabled for \"{cluster_name}\"; run `fdb credentials add {cluster_name}` first"
+            ));
+        }
+        (None, false) => {
+            let key = credentials::random_token(32)?;
+            credentials::write_secret_field(kubectl, &secret, "api-key", &key, kubeconfig)?;
+            add_component_secret_env(kubectl, cluster_name, "QDRANT__SERVICE__API_KEY", &secret, "api-key", kubeconfig)?;
+            key
+        }
+    };
+
+    if !readonly {
+        let read_only = credentials::read_secret_field(kubectl, &secret, "read-only-api-key", kubeconfig)?;
+        return Ok((api_key, read_only));
+    }
+
+    let read_only = credentials::random_token(32)?;
+    credentials::write_secret_field(kubectl, &secret, "read-only-api-key", &read_only, kubeconfig)?;
+    add_component_secret_env(
+        kubectl,
+        cluster_name,
+        "QDRANT__SERVICE__READ_ONLY_API_KEY",
+        &secret,
+        "read-only-api-key",
+        kubeconfig,
+    )?;
+    Ok((api_key, Some(read_only)))
+}
+
+/// Call the RabbitMQ management API (PUT with a JSON body, or DELETE with None).
+pub fn rabbitmq_api(base: &str, path: &str, body: Option<serde_json::Value>) -> Result<(), String> {
+    let url = format!("{base}{path}");
+    let (method, result) = match body {
+        Some(body) => (
+            "PUT",
+            ureq::put(&url)
+                .set("Content-Type", "application/json")
+                .send_string(&body.to_string()),
+        ),
+        None => ("DELETE", ureq::delete(&url).call()),
+    };
+    result.map_err(|e| match e {
+        ureq::Error::Status(code, resp) => {
+            let text = resp.into_string().unwrap_or_default();
+            format!("RabbitMQ management API {method} {path} failed ({code}): {}", text.trim())
+        }
+        e => format!("RabbitMQ management API {method} {path}: {e}"),
+    })?;
+    Ok(())
+}
+
+/// Port-forward to the management API; returns the forward (keep it alive) and the API base URL with admin auth.
+pub fn rabbitmq_management(
+    kubectl: &Path,
+    cluster_name: &str,
+    kubeconfig: &Path,
+) -> Result<(portforward::PortForward, String), String> {
+    let admin = ServiceType::RabbitMQ.default_user();
+    let admin_password = credentials::get_password(kubectl, ServiceType::RabbitMQ, cluster_name, kubeconfig)?
+        .unwrap_or_default();
+    let forward = portforward::start_port_forward(
+        kubectl,
+        &format!("svc/{cluster_name}-rabbitmq"),
+        RABBITMQ_MANAGEMENT_PORT,
+        None,
+        kubeconfig,
+    )?;
+    let base = format!(
+        "http://{}:{}@127.0.0.1:{}/api",
+        percent_encode(admin),
+        percent_encode(&admin_password),
+        forward.local_port
+    );
+    Ok((forward, base))
+}
+
+/// Create (or update) a RabbitMQ user with permissions on one vhost via the management API
+/// over a temporary port-forward. Returns the generated password.
+pub fn add_rabbitmq_user(
+    kubectl: &Path,
+    cluster_name: &str,
+    user: &str,
+    vhost: &str,
+    permissions: &str,
+    kubeconfig: &Path,
+) -> Result<String, String> {
+    let password = credentials::generate_password()?;
+    let (_forward, base) = rabbitmq_management(kubectl, cluster_name, kubeconfig)?;
+    let vhost_enc = percent_encode(vhost);
+    let user_enc = percent_encode(user);
+
+    rabbitmq_api(&base, &format!("/vhosts/{vhost_enc}"), Some(serde_json::json!({})))?;
+    rabbitmq_api(
+        &base,
+        &format!("/users/{user_enc}"),
+        Some(serde_json::json!({ "password": password, "tags": "" })),
+    )?;
+    rabbitmq_api(
+        &base,
+        &format!("/permissions/{vhost_enc}/{user_enc}"),
+        Some(serde_json::json!({ "configure": permissions, "write": permissions, "read": permissions })),
+    )?;
+    Ok(password)
+}
+
+/// Run one redis-cli command in a pod, authenticated from the pod env; the command goes
+/// through stdin since ACL SETUSER carries a password. Redis error replies (exit code is
+/// still 0) become Err.
+fn redis_cli(kubectl: &Path, pod: &str, args: &[&str], kubeconfig: &Path) -> Result<String, String> {
+    // redis-cli splits stdin lines like a shell: quote every argument.
+    let line: Vec<String> =
+        args.iter().map(|a| format!("\"{}\"", a.replace('\\', "\\\\").replace('"', "\\\""))).collect();
+    let command = ["sh", "-c", pods::REDIS_CLI_SCRIPT, "sh"];
+    let out = pods::exec_with_input(kubectl, pod, "redis", &command, &format!("{}\n", line.join(" ")), kubeconfig)?;
+    let reply = out.trim();
+    if ["ERR", "WRONGPASS", "NOPERM", "NOAUTH"]
+        .iter()
+        .any(|p| reply.starts_with(p))
+    {
+        return Err(format!("redis {}: {reply}", args.first().copied().unwrap_or("")));
+    }
+    Ok(reply.to_string())
+}
+
+/// Create (or reset) a Redis ACL user on every redis pod (ACLs are not replicated)
+/// and persist it. Returns the generated password.
+pub fn add_redis_user(
+    kubectl: &Path,
+    cluster_name: &str,
+    acl: &RedisAclUser,
+    kubeconfig: &Path,
+) -> Result<String, String> {
+    let password = credentials::generate_password()?;
+
+    let pass_rule = format!(">{password}");
+    let key_rules: Vec<String> = acl
+        .keys
+        .split_whitespace()
+        .map(|k| if k.starts_with('~') || k.starts_with('%') { k.to_string() } else { format!("~{k}") })
+        .collect();
+    let mut args = vec!["ACL", "SETUSER", acl.name.as_str(), "reset", "on", pass_rule.as_str()];
+    args.extend(key_rules.iter().map(String::as_str));
+    args.extend(acl.commands.split_whitespace());
+
+    for pod in pods::component_pods(kubectl, cluster_name, "redis", kubeconfig)? {
+        redis_cli(kubectl, &pod, &args, kubeconfig)?;
+        // ACL SAVE needs an aclfile; otherwise CONFIG REWRITE stores users in redis.conf.
+        if redis_cli(kubectl, &pod, &["ACL", "SAVE"], kubeconfig).is_err()
+            && let Err(e) = redis_cli(kubectl, &pod, &["CONFIG", "REWRITE"], kubeconfig)
+        {
+            eprintln!("warning: ACL user {} on {pod} not persisted: {e}", acl.name);
+        }
+    }
+    Ok(password)
+}
+
+/// Remove a Redis ACL user from every redis pod.
+fn remove_redis_user(kubectl: &Path, cluster_name: &str, user: &str, kubeconfig: &Path) -> Result<(), String> {
+    for pod in pods::component_pods(kubectl, cluster_name, "redis", kubeconfig)? {
+        redis_cli(kubectl, &pod, &["ACL", "DELUSER", user], kubeconfig)?;
+        if redis_cli(kubectl, &pod, &["ACL", "SAVE"], kubeconfig).is_err() {
+            let _ = redis_cli(kubectl, &pod, &["CONFIG", "REWRITE"], kubeconfig);
+        }
+    }
+    Ok(())
+}
+
+/// Run SQL as the postgres superuser on the primary via psql over the local socket.
+fn psql(kubectl: &Path, cluster_name: &str, sql: &str, kubeconfig: &Path) -> Result<String, String> {
+    let pod = pods::primary_pod(kubectl, cluster_name, "postgresql", kubeconfig)?;
+    pods::exec(
+        kubectl,
+        &pod,
+        "postgresql",
+        &["psql", "-U", "postgres", "-v", "ON_ERROR_STOP=1", "-tAc", sql],
+        kubeconfig,
+    )
+}
+
+/// Like `psql`, with the SQL on stdin: for statements carrying a password, which must not
+/// appear on the kubectl command line.
+fn psql_stdin(kubectl: &Path, cluster_name: &str, sql: &str, kubeconfig: &Path) -> Result<String, String> {
+    let pod = pods::primary_pod(kubectl, cluster_name, "postgresql", kubeconfig)?;
+    pods::exec_with_input(
+        kubectl,
+        &pod,
+        "postgresql",
+        &["psql", "-U", "postgres", "-v", "ON_ERROR_STOP=1", "-tAq", "-f", "-"],
+        sql,
+        kubeconfig,
+    )
+}
+
+/// Run JavaScript with mongosh on the replica set primary as the admin user.
+fn mongosh(kubectl: &Path, cluster_name: &str, script: &str, kubeconfig: &Path) -> Result<String, String> {
+    let pod = pods::primary_pod(kubectl, cluster_name, "mongodb", kubeconfig)?;
+    let shell = pods::mongodb_admin_script("mongosh");
+    let command = ["sh", "-c", &shell, "sh", "--quiet", "--eval", script];
+    pods::exec(kubectl, &pod, "mongodb", &command, kubeconfig)
+}
+
+/// Create (or reset) a MongoDB user in the admin database with one built-in role
+/// (`readAnyDatabase`, `readWriteAnyDatabase`). Returns the generated password.
+fn add_mongodb_user(kubectl: &Path, cluster_name: &str, user: &str, role: &str, kubeconfig: &Path) -> Result<String, String> {
+    let password = credentials::generate_password()?;
+    // JSON strings are valid JavaScript string literals.
+    let (user, pwd) = (serde_json::to_string(user).unwrap_or_default(), serde_json::to_string(&password).unwrap_or_default());
+    let script = format!(
+        "const admin = db.getSiblingDB('admin'); const roles = [{{ role: '{role}', db: 'admin' }}]; \
+         if (admin.getUser({user})) {{ admin.updateUser({user}, {{ pwd: {pwd}, roles }}) }} \
+         else {{ admin.createUser({{ user: {user}, pwd: {pwd}, roles }}) }}"
+    );
+    mongosh(kubectl, cluster_name, &script, kubeconfig)?;
+    Ok(password)
+}
+
+/// Create a PostgreSQL login role that stops working at `expires` (VALID UNTIL). Returns the password.
+fn add_postgres_temp_role(
+    kubectl: &Path,
+    cluster_name: &str,
+    role: &str,
+    readonly: bool,
+    expires: &str,
+    kubeconfig: &Path,
+) -> Result<String, String> {
+    let password = credentials::generate_password()?;
+    let grants = if readonly {
+        "pg_read_all_data"
+    } else {
+        "pg_read_all_data, pg_write_all_data"
+    };
+    psql_stdin(
+        kubectl,
+        cluster_name,
+        &format!(
+            "CREATE ROLE \"{role}\" LOGIN PASSWORD '{password}' VALID UNTIL '{expires}'; GRANT {grants} TO \"{role}\";"
+        ),
+        kubeconfig,
+    )?;
+    Ok(password)
+}
+
+/// Drop a PostgreSQL role, ending its sessions and reassigning anything it owns to postgres.
+fn drop_postgres_role(kubectl: &Path, cluster_name: &str, role: &str, kubeconfig: &Path) -> Result<(), String> {
+    psql(
+        kubectl,
+        cluster_name,
+        &format!(
+            "SELECT pg_terminate_backend(pid) FROM pg_stat_activity WHERE usename = '{role}'; \
+             REASSIGN OWNED BY \"{role}\" TO postgres; DROP OWNED BY \"{role}\"; DROP ROLE IF EXISTS \"{role}\";"
+        ),
+        kubeconfig,
+    )
+    .map(|_| ())
+}
+
+/// Temporary users recorded on the cluster: name -> RFC 3339 expiry.
+fn temp_users(
+    kubectl: &Path,
+    cluster_name: &str,
+    kubeconfig: &Path,
+) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let raw = cluster::cluster_annotation(kubectl, cluster_name, TEMP_USERS_ANNOTATION, kubeconfig)?;
+    Ok(raw
+        .and_then(|r| serde_json::from_str(&r).ok())
+        .unwrap_or_default())
+}
+
+fn save_temp_users(
+    kubectl: &Path,
+    cluster_name: &str,
+    users: &serde_json::Map<String, serde_json::Value>,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    let value = if users.is_empty() {
+        None
+    } else {
+        Some(serde_json::Value::Object(users.clone()).to_string())
+    };
+    cluster::set_cluster_annotation(kubectl, cluster_name, TEMP_USERS_ANNOTATION, value.as_deref(), kubeconfig)
+}
+
+/// `fdb credentials temp`: create a user that `fdb gc` revokes after `ttl` and print its connection string.
+pub fn add_temp_credentials(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    ttl: std::time::Duration,
+    opts: &AddOptions,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    let user = format!("fdb_tmp_{}", credentials::random_token(3)?);
+    let expires = (chrono::Utc::now() + chrono::Duration::seconds(ttl.as_secs() as i64))
+        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+
+    let password = match service {
+        ServiceType::PostgreSQL => add_postgres_temp_role(kubectl, cluster_name, &user, opts.readonly, &expires, kubeconfig)?,
+        ServiceType::Redis => {
+            let commands = if opts.readonly { "+@read".to_string() } else { config::default_acl_commands() };
+            let acl = RedisAclUser {
+                name: user.clone(),
+                commands,
+                keys: opts.keys.clone().unwrap_or_else(config::default_acl_keys),
+            };
+            add_redis_user(kubectl, cluster_name, &acl, kubeconfig)?
+        }
+        ServiceType::RabbitMQ => add_rabbitmq_user(
+            kubectl,
+            cluster_name,
+            &user,
+            opts.vhost.as_deref().unwrap_or("/"),
+            opts.permissions.as_deref().unwrap_or(".*"),
+            kubeconfig,
+        )?,
+        ServiceType::MongoDB => {
+            let role = if opts.readonly { "readAnyDatabase" } else { "readWriteAnyDatabase" };
+            add_mongodb_user(kubectl, cluster_name, &user, role, kubeconfig)?
+        }
+        _ => {
+            return Err(format!("temporary credentials are not supported for {}", service.kbcli_name()));
+        }
+    };
+
+    let mut users = temp_users(kubectl, cluster_name, kubeconfig)?;
+    users.insert(user.clone(), expires.clone().into());
+    save_temp_users(kubectl, cluster_name, &users, kubeconfig)?;
+
+    println!("Temporary user for \"{cluster_name}\" (expires {expires}; revoked by `fdb gc`):");
+    println!("  User:              {user}");
+    println!("  Password:          {}", redact::secret(&password));
+    if let (Ok(host), Ok(port)) = (
+        expose::server_host_from_kubeconfig(kubectl, kubeconfig),
+        expose::existing_nodeport(kubectl, service, cluster_name, kubeconfig),
+    ) {
+        let url = match service {
+            ServiceType::Redis => format!("redis://{user}:{password}@{host}:{port}"),
+            _ => service.connection_string(&user, Some(&password), &host, port),
+        };
+        println!("  Connection string: {}", redact::url(&url));
+    }
+    Ok(())
+}
+
+/// Revoke a user created by fdb.
+fn revoke_user(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    user: &str,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    match service {
+        ServiceType::PostgreSQL => drop_postgres_role(kubectl, cluster_name, user, kubeconfig),
+        ServiceType::Redis => remove_redis_user(kubectl, cluster_name, user, kubeconfig),
+        ServiceType::RabbitMQ => {
+            let (_forward, base) = rabbitmq_management(kubectl, cluster_name, kubeconfig)?;
+            rabbitmq_api(&base, &format!("/users/{}", percent_encode(user)), None)
+        }
+        ServiceType::MongoDB => {
+            let user = serde_json::to_string(user).unwrap_or_default();
+            mongosh(kubectl, cluster_name, &format!("db.getSiblingDB('admin').dropUser({user})"), kubeconfig).map(|_| ())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Client connections open right now, not counting KubeBlocks' own, replication and local
+/// ones. None for engines fdb does not sample (Qdrant, Kafka, search engines).
+pub fn client_connections(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    kubeconfig: &Path,
+) -> Result<Option<u64>, String> {
+    let count = match service {
+        ServiceType::PostgreSQL => {
+            let sql = "SELECT count(*) FROM pg_stat_activity WHERE backend_type = 'client backend' \
+                       AND client_addr IS NOT NULL AND usename NOT LIKE 'kb%'";
+            let out = psql(kubectl, cluster_name, sql, kubeconfig)?;
+            out.trim().parse().map_err(|_| format!("unexpected connection count: {}", out.trim()))?
+        }
+        ServiceType::Redis => {
+            let mut count = 0;
+            for pod in pods::component_pods(kubectl, cluster_name, "redis", kubeconfig)? {
+                let clients = redis_cli(kubectl, &pod, &["CLIENT", "LIST"], kubeconfig)?;
+                count += clients
+                    .lines()
+                    .filter(|l| {
+                        !l.contains("addr=127.0.0.1:")
+                            && !l.contains("addr=[::1]:")
+                            && !l.contains("name=sentinel-")
+                            && !l.split_whitespace().any(|f| f.starts_with("flags=") && f.contains(['S', 'M']))
+                    })
+                    .count() as u64;
+            }
+            count
+        }
+        ServiceType::RabbitMQ => {
+            let pod = pods::component_pods(kubectl, cluster_name, "rabbitmq", kubeconfig)?[0].clone();
+            let command = ["rabbitmqctl", "list_connections", "peer_host", "--silent", "--no-table-headers"];
+            let out = pods::exec(kubectl, &pod, "rabbitmq", &command, kubeconfig)?;
+            out.lines().filter(|l| !l.trim().is_empty()).count() as u64
+        }
+        ServiceType::MongoDB => {
+            // Our own mongosh connects over localhost; replica set members identify as internal clients.
+            let script = "db.getSiblingDB('admin').aggregate([{ $currentOp: { allUsers: true, idleConnections: true } }, \
+                          { $match: { client: { $exists: true, $not: /^127\\.0\\.0\\.1:/ }, \
+                          'clientMetadata.driver.name': { $ne: 'MongoDB Internal Client' } } }]).itcount()";
+            let out = mongosh(kubectl, cluster_name, script, kubeconfig)?;
+            out.trim().parse().map_err(|_| format!("unexpected connection count: {}", out.trim()))?
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(count))
+}
+
+/// Revoke expired temporary users of one cluster. Returns the revoked names.
+pub fn gc_expired(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    kubeconfig: &Path,
+) -> Result<Vec<String>, String> {
+    let mut users = temp_users(kubectl, cluster_name, kubeconfig)?;
+    let now = chrono::Utc::now();
+    let expired: Vec<String> = users
+        .iter()
+        .filter(|(_, exp)| {
+            exp.as_str()
+                .and_then(|e| chrono::DateTime::parse_from_rfc3339(e).ok())
+                .is_none_or(|e| e <= now)
+        })
+        .map(|(u, _)| u.clone())
+        .collect();
+    for user in &expired {
+        revoke_user(kubectl, service, cluster_name, user, kubeconfig)?;
+        users.remove(user);
+    }
+    if !expired.is_empty() {
+        save_temp_users(kubectl, cluster_name, &users, kubeconfig)?;
+    }
+    Ok(expired)
+}
+
+/// Redis URL for an ACL user, if the cluster is exposed.
+fn redis_user_url(
+    kubectl: &Path,
+    cluster_name: &str,
+    user: &str,
+    password: &str,
+    kubeconfig: &Path,
+) -> Option<String> {
+    let host = expose::server_host_from_kubeconfig(kubectl, kubeconfig).ok()?;
+    let port = expose::existing_nodeport(kubectl, ServiceType::Redis, cluster_name, kubeconfig).ok()?;
+    Some(format!(
+        "redis://{}:{}@{host}:{port}",
+        percent_encode(user),
+        percent_encode(password)
+    ))
+}
+
+/// Provision the configured Redis ACL users and print their restricted URLs.
+pub fn provision_redis_users(
+    kubectl: &Path,
+    cluster_name: &str,
+    users: &[RedisAclUser],
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    if users.is_empty() {
+        return Ok(());
+    }
+    println!();
+    println!("Restricted users:");
+    for acl in users {
+        let password = add_redis_user(kubectl, cluster_name, acl, kubeconfig)?;
+        let shown = match redis_user_url(kubectl, cluster_name, &acl.name, &password, kubeconfig) {
+            Some(url) => redact::url(&url),
+            None => format!("password {}", redact::secret(&password)),
+        };
+        println!("  {} ({}; keys {}): {shown}", acl.name, acl.commands, acl.keys);
+    }
+    Ok(())
+}
+
+/// Secret holding the app-scoped account created at `fdb create` (`user`, `password`).
+fn app_account_secret(cluster_name: &str) -> String {
+    format!("{cluster_name}-fdb-app")
+}
+
+/// Create a non-superuser account for applications and remember it in the app secret.
+/// Returns the password, or None for engines fdb manages no accounts on (Qdrant, Kafka,
+/// search engines).
+pub fn create_app_account(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    user: &str,
+    kubeconfig: &Path,
+) -> Result<Option<String>, String> {
+    let password = match service {
+        ServiceType::PostgreSQL => {
+            let password = credentials::generate_password()?;
+            psql_stdin(
+                kubectl,
+                cluster_name,
+                &format!(
+                    "CREATE ROLE \"{user}\" LOGIN PASSWORD '{password}'; \
+                     GRANT CONNECT ON DATABASE postgres TO \"{user}\"; \
+                     GRANT USAGE, CREATE ON SCHEMA public TO \"{user}\";"
+                ),
+                kubeconfig,
+            )?;
+            password
+        }
+        ServiceType::Redis => {
+            let acl = RedisAclUser {
+                name: user.to_string(),
+                commands: config::default_acl_commands(),
+                keys: config::default_acl_keys(),
+            };
+            add_redis_user(kubectl, cluster_name, &acl, kubeconfig)?
+        }
+        ServiceType::RabbitMQ => add_rabbitmq_user(kubectl, cluster_name, user, "/", ".*", kubeconfig)?,
+        ServiceType::MongoDB => add_mongodb_user(kubectl, cluster_name, user, "readWriteAnyDatabase", kubeconfig)?,
+        _ => return Ok(None),
+    };
+    let secret = app_account_secret(cluster_name);
+    credentials::write_secret_field(kubectl, &secret, "user", user, kubeconfig)?;
+    credentials::write_secret_field(kubectl, &secret, "password", &password, kubeconfig)?;
+    Ok(Some(password))
+}
+
+/// The app-scoped account (user, password) of a cluster, if one was created.
+pub fn app_account(kubectl: &Path, cluster_name: &str, kubeconfig: &Path) -> Result<Option<(String, String)>, String> {
+    let secret = app_account_secret(cluster_name);
+    let user = credentials::read_secret_field(kubectl, &secret, "user", kubeconfig)?;
+    let password = credentials::read_secret_field(kubectl, &secret, "password", kubeconfig)?;
+    Ok(user.zip(password))
+}
+
+/// `fdb creds rotate`: give the app account (or, with `admin`, the PostgreSQL superuser) a new
+/// password, set in the engine first and then in the secret clients read it from. Returns the
+/// user and the new password.
+pub fn rotate_password(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    admin: bool,
+    kubeconfig: &Path,
+) -> Result<(String, String), String> {
+    if admin {
+        if service != ServiceType::PostgreSQL {
+            return Err(format!("rotating the admin password is only supported for postgresql, not {}", service.kbcli_name()));
+        }
+        let user = service.default_user();
+        let password = credentials::generate_password()?;
+        let (secret, key) = credentials::password_secret(kubectl, service, cluster_name, kubeconfig)?;
+        psql_stdin(kubectl, cluster_name, &format!("ALTER ROLE \"{user}\" PASSWORD '{password}';"), kubeconfig)?;
+        credentials::write_secret_field(kubectl, &secret, &key, &password, kubeconfig)?;
+        return Ok((user.to_string(), password));
+    }
+
+    let Some((user, _)) = app_account(kubectl, cluster_name, kubeconfig)? else {
+        return Err(format!(
+            "\"{cluster_name}\" has no app account (`app-account = true` under [security] creates one at create time)"
+        ));
+    };
+    let password = match service {
+        ServiceType::PostgreSQL => {
+            let password = credentials::generate_password()?;
+            psql_stdin(kubectl, cluster_name, &format!("ALTER ROLE \"{user}\" PASSWORD '{password}';"), kubeconfig)?;
+            password
+        }
+        ServiceType::Redis => {
+            let acl = RedisAclUser {
+                name: user.clone(),
+                commands: config::default_acl_commands(),
+                keys: config::default_acl_keys(),
+            };
+            add_redis_user(kubectl, cluster_name, &acl, kubeconfig)?
+        }
+        ServiceType::RabbitMQ => add_rabbitmq_user(kubectl, cluster_name, &user, "/", ".*", kubeconfig)?,
+        ServiceType::MongoDB => add_mongodb_user(kubectl, cluster_name, &user, "readWriteAnyDatabase", kubeconfig)?,
+        _ => return Err(format!("fdb creds rotate is not supported for {} clusters", service.kbcli_name())),
+    };
+    credentials::write_secret_field(kubectl, &app_account_secret(cluster_name), "password", &password, kubeconfig)?;
+    Ok((user, password))
+}
+
+/// `fdb credentials add` entry point: dispatch on the cluster's service type.
+pub fn add_credentials(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    opts: &AddOptions,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    match service {
+        ServiceType::PostgreSQL if opts.cert => {
+            let user = opts.user.as_deref().unwrap_or(service.default_user());
+            let ca_secret = opts
+                .ca_secret
+                .clone()
+                .unwrap_or_else(|| certs::default_ca_secret(cluster_name));
+            let cert = certs::issue_client_cert(kubectl, cluster_name, user, &ca_secret, kubeconfig)?;
+            println!("Client certificate for \"{user}\" on \"{cluster_name}\":");
+            println!("  Certificate:       {}", cert.cert.display());
+            println!("  Key:               {}", cert.key.display());
+            println!("  Root certificate:  {}", cert.root_cert.display());
+            if let (Ok(host), Ok(port)) = (
+                expose::server_host_from_kubeconfig(kubectl, kubeconfig),
+                expose::existing_nodeport(kubectl, service, cluster_name, kubeconfig),
+            ) {
+                println!(
+                    "  Connection string: postgresql://{user}@{host}:{port}/postgres?{}",
+                    cert.query_params()
+                );
+            }
+            Ok(())
+        }
+        ServiceType::Qdrant => {
+            let (api_key, read_only) = add_qdrant_key(kubectl, cluster_name, opts.readonly, kubeconfig)?;
+            println!("Qdrant API keys for \"{cluster_name}\" (stored in secret {}):", qdrant_keys_secret(cluster_name));
+            println!("  API key:           {}", redact::secret(&api_key));
+            if let Some(ref k) = read_only {
+                println!("  Read-only API key: {}", redact::secret(k));
+            }
+            println!("Pods restart to pick up the keys; send them as the `api-key` header.");
+            Ok(())
+        }
+        ServiceType::RabbitMQ => {
+            let user = opts
+                .user
+                .as_deref()
+                .ok_or("RabbitMQ needs --user NAME")?;
+            let vhost = opts.vhost.as_deref().unwrap_or("/");
+            let permissions = opts.permissions.as_deref().unwrap_or(".*");
+            let password = add_rabbitmq_user(kubectl, cluster_name, user, vhost, permissions, kubeconfig)?;
+
+            println!("RabbitMQ user \"{user}\" on vhost \"{vhost}\" (configure/write/read: {permissions}):");
+            println!("  User:              {user}");
+            println!("  Password:          {}", redact::secret(&password));
+            if let (Ok(host), Ok(port)) = (
+                expose::server_host_from_kubeconfig(kubectl, kubeconfig),
+                expose::existing_nodeport(kubectl, service, cluster_name, kubeconfig),
+            ) {
+                let path = if vhost == "/" { String::new() } else { percent_encode(vhost) };
+                let url = format!("amqp://{}:{}@{host}:{port}/{path}", percent_encode(user), percent_encode(&password));
+                println!("  Connection string: {}", redact::url(&url));
+            }
+            Ok(())
+        }
+        ServiceType::Redis => {
+            let user = opts.user.clone().ok_or("Redis needs --user NAME")?;
+            let acl = RedisAclUser {
+                name: user,
+                commands: opts.commands.clone().unwrap_or_else(config::default_acl_commands),
+                keys: opts.keys.clone().unwrap_or_else(config::default_acl_keys),
+            };
+            provision_redis_users(kubectl, cluster_name, &[acl], kubeconfig)
+        }
+        _ => Err(format!(
+            "fdb credentials add is not supported for {} clusters",
+            service.kbcli_name()
+        )),
+    }
+}
+
+/// Connection details as `fdb info` shows them: the app account unless `admin`, and the
+/// NodePort endpoint when the cluster is exposed.
+pub fn connection_details(
+    kubectl: &Path,
+    service: ServiceType,
+    name: &str,
+    kubeconfig: &Path,
+    admin: bool,
+) -> Result<ConnectionDetails, Error> {
+    let endpoint = match (
+        expose::server_host_from_kubeconfig(kubectl, kubeconfig),
+        expose::existing_nodeport(kubectl, service, name, kubeconfig),
+    ) {
+        (Ok(h), Ok(p)) => Some((h, p)),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("warning: {e}");
+            None
+        }
+    };
+    let app = if admin {
+        None
+    } else {
+        app_account(kubectl, name, kubeconfig).map_err(error::code(ExitCode::Credentials))?
+    };
+    Ok(match app {
+        Some((user, password)) => ConnectionDetails::for_user(service, &user, Some(password), endpoint),
+        None => {
+            let password = credentials::get_password(kubectl, service, name, kubeconfig).map_err(error::code(ExitCode::Credentials))?;
+            let user = credentials::get_user(kubectl, service, name, kubeconfig).map_err(error::code(ExitCode::Credentials))?;
+            ConnectionDetails::for_user(service, &user, password, endpoint)
+        }
+    })
+}
@@ -0,0 +1,181 @@
+//! Off-hours schedules (`auto-stop = "19:00-08:00, weekends"` in `[clusters.<name>]`):
+//! `fdb gc` stops clusters inside the window and starts the ones it stopped once the window
+//! ends; `fdb start` wakes a cluster early and keeps gc away until the window ends.
+
+use crate::cluster::{self, ClusterInfo};
+use crate::config;
+use chrono::{Datelike, Local, NaiveDateTime, TimeZone, Timelike, Weekday};
+use std::path::Path;
+
+/// Set on clusters `fdb gc` stopped, so it only starts those again.
+const STOPPED_ANNOTATION: &str = "fdb.io/auto-stopped";
+/// RFC 3339 time until which `fdb gc` leaves a woken cluster running.
+const AWAKE_UNTIL_ANNOTATION: &str = "fdb.io/awake-until";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    /// Daily windows in minutes since midnight; `start > end` wraps past midnight.
+    ranges: Vec<(u32, u32)>,
+    /// Whole days off.
+    days: Vec<Weekday>,
+}
+
+impl Schedule {
+    /// Comma-separated `HH:MM-HH:MM` ranges, `weekends`, and weekday names (`fri`, `sunday`).
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut schedule = Schedule {
+            ranges: Vec::new(),
+            days: Vec::new(),
+        };
+        for item in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            if let Some((start, end)) = item.split_once('-') {
+                let minutes = |t: &str| {
+                    chrono::NaiveTime::parse_from_str(t.trim(), "%H:%M")
+                        .map(|t| t.hour() * 60 + t.minute())
+                        .map_err(|_| format!("auto-stop: \"{t}\" is not a HH:MM time"))
+                };
+                let (start, end) = (minutes(start)?, minutes(end)?);
+                if start == end {
+                    return Err(format!("auto-stop: \"{item}\" is an empty range"));
+                }
+                schedule.ranges.push((start, end));
+            } else if matches!(item.to_lowercase().as_str(), "weekend" | "weekends") {
+                schedule.days.extend([Weekday::Sat, Weekday::Sun]);
+            } else {
+                let day = item
+                    .parse::<Weekday>()
+                    .map_err(|_| format!("auto-stop: \"{item}\" is not a time range, weekday or \"weekends\""))?;
+                schedule.days.push(day);
+            }
+        }
+        if schedule.ranges.is_empty() && schedule.days.is_empty() {
+            return Err("auto-stop is empty".to_string());
+        }
+        Ok(schedule)
+    }
+
+    /// Whether `t` (local time) falls in an off-hours window.
+    pub fn is_off(&self, t: NaiveDateTime) -> bool {
+        let m = t.hour() * 60 + t.minute();
+        self.days.contains(&t.weekday())
+            || self
+                .ranges
+                .iter()
+                .any(|&(start, end)| if start < end { start <= m && m < end } else { m >= start || m < end })
+    }
+
+    /// First minute after `t` outside every window; None when the schedule never ends.
+    pub fn next_on(&self, t: NaiveDateTime) -> Option<NaiveDateTime> {
+        let t = t.with_second(0)?.with_nanosecond(0)?;
+        (1..=8 * 24 * 60)
+            .map(|m| t + chrono::Duration::minutes(m))
+            .find(|t| !self.is_off(*t))
+    }
+}
+
+/// Cluster names with an `auto-stop` schedule in their `[clusters.*]` profile.
+fn schedules() -> Result<Vec<(String, String, Schedule)>, String> {
+    let mut schedules = Vec::new();
+    for profile in config::profile_names() {
+        let profile = config::load_profile(&profile)?;
+        if let Some(spec) = profile.auto_stop {
+            let schedule = Schedule::parse(&spec)?;
+            schedules.push((profile.name, spec, schedule));
+        }
+    }
+    Ok(schedules)
+}
+
+fn format_local(t: NaiveDateTime) -> String {
+    t.format("%a %H:%M").to_string()
+}
+
+/// Stop running clusters inside their window and start the ones gc stopped once it ends.
+/// Limited to `name` when given; returns how many clusters were stopped or started.
+pub fn enforce(kbcli: &Path, kubectl: &Path, name: Option<&str>, kubeconfig: &Path) -> Result<usize, String> {
+    let schedules = schedules()?;
+    if schedules.is_empty() {
+        return Ok(0);
+    }
+    let clusters: Vec<ClusterInfo> = cluster::list_cluster_infos(kbcli, name, kubeconfig)?;
+    let now = Local::now();
+    let mut changed = 0;
+    for info in &clusters {
+        let Some((_, spec, schedule)) = schedules.iter().find(|(n, _, _)| *n == info.name) else {
+            continue;
+        };
+        let awake_until = cluster::cluster_annotation(kubectl, &info.name, AWAKE_UNTIL_ANNOTATION, kubeconfig)?
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(&t).ok());
+        let awake = awake_until.is_some_and(|t| now < t);
+        let off = schedule.is_off(now.naive_local());
+        if off && !awake && info.status == "Running" {
+            cluster::set_running(kbcli, &info.name, false, kubeconfig)?;
+            cluster::set_cluster_annotation(kubectl, &info.name, STOPPED_ANNOTATION, Some("true"), kubeconfig)?;
+            let back = match schedule.next_on(now.naive_local()) {
+                Some(t) => format!("; starts again {}", format_local(t)),
+                None => String::new(),
+            };
+            println!("Stopped \"{}\" (auto-stop \"{spec}\"{back})", info.name);
+            changed += 1;
+        } else if !off && info.status == "Stopped" {
+            let stopped_by_gc = cluster::cluster_annotation(kubectl, &info.name, STOPPED_ANNOTATION, kubeconfig)?;
+            if stopped_by_gc.as_deref() == Some("true") {
+                cluster::set_running(kbcli, &info.name, true, kubeconfig)?;
+                cluster::set_cluster_annotation(kubectl, &info.name, STOPPED_ANNOTATION, None, kubeconfig)?;
+                println!("Starting \"{}\" (outside auto-stop \"{spec}\")", info.name);
+                changed += 1;
+            }
+        }
+        if awake_until.is_some() && !awake {
+            cluster::set_cluster_annotation(kubectl, &info.name, AWAKE_UNTIL_ANNOTATION, None, kubeconfig)?;
+        }
+    }
+    Ok(changed)
+}
+
+/// Start the cluster on demand (`fdb start`). Inside an auto-stop window, gc leaves it running
+/// until the window ends; returns that time.
+pub fn wake(kbcli: &Path, kubectl: &Path, name: &str, kubeconfig: &Path) -> Result<Option<String>, String> {
+    cluster::set_running(kbcli, name, true, kubeconfig)?;
+    cluster::set_cluster_annotation(kubectl, name, STOPPED_ANNOTATION, None, kubeconfig)?;
+    let now = Local::now().naive_local();
+    let schedules = schedules()?;
+    let Some((_, _, schedule)) = schedules.iter().find(|(n, _, _)| n == name) else {
+        return Ok(None);
+    };
+    if !schedule.is_off(now) {
+        return Ok(None);
+    }
+    let Some(until) = schedule.next_on(now) else {
+        return Ok(None);
+    };
+    let Some(until_local) = Local.from_local_datetime(&until).earliest() else {
+        return Ok(None);
+    };
+    cluster::set_cluster_annotation(kubectl, name, AWAKE_UNTIL_ANNOTATION, Some(&until_local.to_rfc3339()), kubeconfig)?;
+    Ok(Some(format_local(until)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn parses_off_hours_and_finds_next_working_minute() {
+        let s = Schedule::parse("19:00-08:00, weekends").unwrap();
+        // 2026-10-16 is a Friday.
+        assert!(!s.is_off(at("2026-10-16 12:00")));
+        assert!(s.is_off(at("2026-10-16 19:00")));
+        assert!(s.is_off(at("2026-10-17 12:00")));
+        assert!(s.is_off(at("2026-10-19 07:59")));
+        assert!(!s.is_off(at("2026-10-19 08:00")));
+        assert_eq!(s.next_on(at("2026-10-16 20:30")), Some(at("2026-10-19 08:00")));
+        assert!(Schedule::parse("12:00-12:00").is_err());
+        assert!(Schedule::parse("nights").is_err());
+        assert!(Schedule::parse("fri, 22:00-06:00").is_ok());
+    }
+}
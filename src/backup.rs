@@ -0,0 +1,447 @@
+//! KubeBlocks backups and restores: on-demand backups (`fdb backup`, `fdb restore --as`),
+//! volume-snapshot clones (`fdb clone`) and PostgreSQL point-in-time recovery
+//! (`fdb restore --at`).
+
+use crate::cluster;
+use crate::error::{self, Error, ExitCode};
+use crate::exec::CommandExt;
+use crate::progress::{self, Progress};
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+const NAMESPACE: &str = "default";
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+fn kubectl_json(kubectl: &Path, args: &[&str], kubeconfig: &Path) -> Result<serde_json::Value, String> {
+    let output = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(args)
+        .args(["-o", "json"])
+        .output_checked()?;
+    serde_json::from_slice(&output.stdout).map_err(|e| format!("parse kubectl {}: {e}", args.join(" ")))
+}
+
+/// Check that every PVC of the cluster uses a storage class whose CSI driver has a
+/// VolumeSnapshotClass; the error names the first one that doesn't.
+pub fn check_snapshot_support(kubectl: &Path, cluster_name: &str, kubeconfig: &Path) -> Result<(), String> {
+    let selector = format!("app.kubernetes.io/instance={cluster_name}");
+    let pvcs = kubectl_json(kubectl, &["get", "pvc", "-n", NAMESPACE, "-l", &selector], kubeconfig)?;
+    let classes: Vec<String> = pvcs["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|p| p.pointer("/spec/storageClassName")?.as_str().map(str::to_string))
+        .collect();
+    if classes.is_empty() {
+        return Err(format!("no volumes with a storage class found for cluster \"{cluster_name}\""));
+    }
+    let snapshot_classes = kubectl_json(kubectl, &["get", "volumesnapshotclass"], kubeconfig)
+        .map_err(|e| format!("no CSI snapshot support in this cluster (VolumeSnapshotClass): {e}"))?;
+    let drivers: Vec<&str> = snapshot_classes["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|c| c["driver"].as_str())
+        .collect();
+    for class in classes {
+        let storage_class = kubectl_json(kubectl, &["get", "storageclass", &class], kubeconfig)?;
+        let provisioner = storage_class["provisioner"].as_str().unwrap_or_default();
+        if !drivers.contains(&provisioner) {
+            return Err(format!(
+                "storage class \"{class}\" ({provisioner}) has no VolumeSnapshotClass to snapshot its volumes"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Start a KubeBlocks backup of the cluster with the given method (e.g. `volume-snapshot`);
+/// without one, the default method of the cluster's backup policy.
+pub fn create_backup(
+    kbcli: &Path,
+    cluster_name: &str,
+    method: Option<&str>,
+    backup: &str,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    let mut cmd = Command::new(kbcli);
+    cmd.arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["cluster", "backup", cluster_name, "-n", NAMESPACE, "--name", backup]);
+    if let Some(method) = method {
+        cmd.args(["--method", method]);
+    }
+    cmd.output_checked()?;
+    Ok(())
+}
+
+/// Poll the Backup until it is Completed; Failed or the timeout end the wait.
+pub fn wait_backup(
+    kubectl: &Path,
+    backup: &str,
+    kubeconfig: &Path,
+    timeout: Duration,
+    progress: &Progress,
+) -> Result<(), Error> {
+    let start = Instant::now();
+    loop {
+        let phase = kubectl_json(kubectl, &["get", "backup", backup, "-n", NAMESPACE], kubeconfig)
+            .map(|b| b.pointer("/status/phase").and_then(|p| p.as_str()).unwrap_or_default().to_string())
+            .unwrap_or_default();
+        match phase.as_str() {
+            "Completed" => return Ok(()),
+            "Failed" => {
+                return Err(Error::new(
+                    ExitCode::Kbcli,
+                    format!("backup \"{backup}\" failed; see `kubectl describe backup {backup} -n {NAMESPACE}`"),
+                ));
+            }
+            _ => {}
+        }
+        if start.elapsed() >= timeout {
+            return Err(Error::new(
+                ExitCode::Timeout,
+                format!(
+                    "backup \"{backup}\" did not complete within {}",
+                    progress::format_elapsed(timeout)
+                ),
+            ));
+        }
+        progress.update(if phase.is_empty() { "pending" } else { &phase });
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// One Backup of a cluster, as `fdb backup list` shows it.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct BackupInfo {
+    pub name: String,
+    pub method: String,
+    pub phase: String,
+    /// As KubeBlocks reports it (e.g. "1.2Gi"); None until the backup has completed.
+    pub size: Option<String>,
+    pub created: String,
+}
+
+/// Backups in a `kubectl get backup -o json` list, oldest first.
+fn parse_backups(list: &serde_json::Value) -> Vec<BackupInfo> {
+    let field = |b: &serde_json::Value, pointer: &str| b.pointer(pointer).and_then(|v| v.as_str()).map(str::to_string);
+    let mut backups: Vec<BackupInfo> = list["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|b| BackupInfo {
+            name: field(b, "/metadata/name").unwrap_or_default(),
+            method: field(b, "/spec/backupMethod").unwrap_or_default(),
+            phase: field(b, "/status/phase").unwrap_or_default(),
+            size: field(b, "/status/totalSize"),
+            created: field(b, "/metadata/creationTimestamp").unwrap_or_default(),
+        })
+        .collect();
+    backups.sort_by(|a, b| a.created.cmp(&b.created));
+    backups
+}
+
+/// Backups of the cluster, oldest first.
+pub fn list_backups(kubectl: &Path, cluster_name: &str, kubeconfig: &Path) -> Result<Vec<BackupInfo>, String> {
+    let selector = format!("app.kubernetes.io/instance={cluster_name}");
+    let list = kubectl_json(kubectl, &["get", "backup", "-n", NAMESPACE, "-l", &selector], kubeconfig)?;
+    Ok(parse_backups(&list))
+}
+
+/// Name of the newest completed backup of the cluster.
+pub fn latest_backup(kubectl: &Path, cluster_name: &str, kubeconfig: &Path) -> Result<String, String> {
+    list_backups(kubectl, cluster_name, kubeconfig)?
+        .into_iter()
+        .rev()
+        .find(|b| b.phase == "Completed")
+        .map(|b| b.name)
+        .ok_or_else(|| format!("\"{cluster_name}\" has no completed backup; take one with `fdb backup {cluster_name}`"))
+}
+
+pub fn print_backups(cluster_name: &str, backups: &[BackupInfo]) {
+    if backups.is_empty() {
+        println!("No backups of \"{cluster_name}\". Take one with `fdb backup {cluster_name}`.");
+        return;
+    }
+    println!("{:<44} {:<18} {:<10} {:<8} CREATED", "NAME", "METHOD", "STATUS", "SIZE");
+    for b in backups {
+        let phase = if b.phase.is_empty() { "-" } else { &b.phase };
+        let size = b.size.as_deref().unwrap_or("-");
+        println!("{:<44} {:<18} {:<10} {:<8} {}", b.name, b.method, phase, size, b.created);
+    }
+}
+
+/// Back up the cluster on demand and wait until the backup is Completed.
+pub fn backup(
+    kbcli: &Path,
+    kubectl: &Path,
+    cluster_name: &str,
+    method: Option<&str>,
+    kubeconfig: &Path,
+    timeout: Duration,
+) -> Result<(), Error> {
+    let backup = format!("{cluster_name}-backup-{}", chrono::Utc::now().format("%Y%m%d%H%M%S"));
+    let mut progress = Progress::new();
+    progress.begin("backup", &format!("Backing up \"{cluster_name}\""));
+    create_backup(kbcli, cluster_name, method, &backup, kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    if let Err(e) = wait_backup(kubectl, &backup, kubeconfig, timeout, &progress) {
+        progress.fail(&e.message);
+        return Err(e);
+    }
+    progress.finish();
+    println!();
+    println!("Backed up \"{cluster_name}\" as \"{backup}\". Restore it with `fdb restore {backup} --as <new-name>`.");
+    progress.print_summary();
+    Ok(())
+}
+
+/// Create the new cluster `new_name` from a completed backup and wait for it to run.
+pub fn restore_backup(
+    kbcli: &Path,
+    kubectl: &Path,
+    backup: &str,
+    new_name: &str,
+    kubeconfig: &Path,
+    timeout: Duration,
+) -> Result<(), Error> {
+    let found = kubectl_json(kubectl, &["get", "backup", backup, "-n", NAMESPACE, "--ignore-not-found"], kubeconfig)
+        .unwrap_or_default();
+    match found.pointer("/status/phase").and_then(|p| p.as_str()) {
+        Some("Completed") => {}
+        None if found.is_null() => {
+            return Err(format!("backup \"{backup}\" not found; list backups with `fdb backup list <name>`").into());
+        }
+        phase => {
+            return Err(format!("backup \"{backup}\" is {}, not Completed", phase.unwrap_or("pending")).into());
+        }
+    }
+    let mut progress = Progress::new();
+    progress.begin("restore accepted", &format!("Restoring \"{backup}\" into \"{new_name}\""));
+    restore(kbcli, new_name, backup, kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    cluster::wait_until_running(kbcli, kubectl, new_name, kubeconfig, timeout, &mut progress)?;
+    progress.finish();
+    println!();
+    println!("Restored \"{backup}\" into \"{new_name}\". Run `fdb info {new_name}` for connection details.");
+    progress.print_summary();
+    Ok(())
+}
+
+/// Create a new cluster from a backup.
+pub fn restore(kbcli: &Path, new_name: &str, backup: &str, kubeconfig: &Path) -> Result<(), String> {
+    Command::new(kbcli)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["cluster", "restore", new_name, "-n", NAMESPACE, "--backup", backup])
+        .output_checked()?;
+    Ok(())
+}
+
+pub fn delete_backup(kubectl: &Path, backup: &str, kubeconfig: &Path) -> Result<(), String> {
+    Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["delete", "backup", backup, "-n", NAMESPACE, "--ignore-not-found=true"])
+        .output_checked()?;
+    Ok(())
+}
+
+/// How `fdb clone` fills the new cluster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloneMethod {
+    /// CSI volume snapshots of the source, taken now.
+    Snapshot,
+    /// The source's latest completed backup.
+    Backup,
+    /// Same engine, version and resources as the source, but no data.
+    Empty,
+}
+
+impl FromStr for CloneMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "snapshot" => Ok(CloneMethod::Snapshot),
+            "backup" => Ok(CloneMethod::Backup),
+            "empty" => Ok(CloneMethod::Empty),
+            _ => Err(format!("invalid --method: {s} (use snapshot, backup or empty)")),
+        }
+    }
+}
+
+/// Clone `source` into a new cluster `target` from CSI volume snapshots: back up with the
+/// `volume-snapshot` method, restore into `target`, wait for it to run, then drop the
+/// intermediate backup.
+pub fn clone_from_snapshot(
+    kbcli: &Path,
+    kubectl: &Path,
+    source: &str,
+    target: &str,
+    kubeconfig: &Path,
+    timeout: Duration,
+) -> Result<(), Error> {
+    let mut progress = Progress::new();
+    progress.begin("snapshot support", "Checking snapshot support");
+    check_snapshot_support(kubectl, source, kubeconfig).map_err(|e| format!("{e}; use `fdb copy` instead"))?;
+
+    let backup = format!("{source}-clone-{}", chrono::Utc::now().format("%Y%m%d%H%M%S"));
+    progress.begin("snapshot", &format!("Snapshotting \"{source}\""));
+    create_backup(kbcli, source, Some("volume-snapshot"), &backup, kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    wait_backup(kubectl, &backup, kubeconfig, timeout, &progress)?;
+
+    progress.begin("restore accepted", &format!("Provisioning \"{target}\" from the snapshot"));
+    restore(kbcli, target, &backup, kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    cluster::wait_until_running(kbcli, kubectl, target, kubeconfig, timeout, &mut progress)?;
+    progress.finish();
+
+    if let Err(e) = delete_backup(kubectl, &backup, kubeconfig) {
+        eprintln!("warning: could not delete intermediate backup \"{backup}\": {e}");
+    }
+    println!();
+    println!("Cloned \"{source}\" into \"{target}\". Run `fdb info {target}` for connection details.");
+    progress.print_summary();
+    Ok(())
+}
+
+/// Turn on scheduled base backups plus continuous WAL archiving, the prerequisite for PITR.
+pub fn enable_pitr(kbcli: &Path, cluster_name: &str, kubeconfig: &Path) -> Result<(), String> {
+    Command::new(kbcli)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args([
+            "cluster",
+            "update",
+            cluster_name,
+            "-n",
+            NAMESPACE,
+            "--backup-enabled=true",
+            "--backup-pitr-enabled=true",
+        ])
+        .output_checked()?;
+    Ok(())
+}
+
+/// Turn on scheduled backups on the cluster's backup policy (`[<engine>.backup]`).
+pub fn enable_schedule(
+    kbcli: &Path,
+    cluster_name: &str,
+    schedule: &crate::config::BackupSchedule,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    let mut cmd = Command::new(kbcli);
+    cmd.arg("--kubeconfig").arg(kubeconfig).args([
+        "cluster",
+        "update",
+        cluster_name,
+        "-n",
+        NAMESPACE,
+        "--backup-enabled=true",
+        "--backup-cron-expression",
+        &schedule.schedule,
+    ]);
+    if let Some(retention) = &schedule.retention {
+        cmd.args(["--backup-retention-period", retention]);
+    }
+    if let Some(method) = &schedule.method {
+        cmd.args(["--backup-method", method]);
+    }
+    cmd.output_checked()?;
+    Ok(())
+}
+
+/// Continuous (WAL archive) backups are the ones reporting a time range without being a
+/// one-off snapshot; their method names contain "archive" (`archive-wal`, `wal-g-archive`).
+fn is_continuous(backup: &serde_json::Value) -> bool {
+    backup
+        .pointer("/spec/backupMethod")
+        .and_then(|m| m.as_str())
+        .is_some_and(|m| m.contains("archive"))
+}
+
+fn time_at(backup: &serde_json::Value, pointer: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    let raw = backup.pointer(pointer)?.as_str()?;
+    chrono::DateTime::parse_from_rfc3339(raw).ok().map(|t| t.with_timezone(&chrono::Utc))
+}
+
+/// Newest completed base backup taken before `at`, after checking that continuous WAL
+/// archiving covers `at`.
+pub fn base_backup_for(
+    kubectl: &Path,
+    cluster_name: &str,
+    at: chrono::DateTime<chrono::Utc>,
+    kubeconfig: &Path,
+) -> Result<String, String> {
+    let selector = format!("app.kubernetes.io/instance={cluster_name}");
+    let list = kubectl_json(kubectl, &["get", "backup", "-n", NAMESPACE, "-l", &selector], kubeconfig)?;
+    let backups: Vec<&serde_json::Value> = list["items"].as_array().into_iter().flatten().collect();
+
+    let covered = backups.iter().filter(|b| is_continuous(b)).any(|b| {
+        let start = time_at(b, "/status/timeRange/start");
+        let end = time_at(b, "/status/timeRange/end");
+        start.is_some_and(|s| s <= at) && end.is_some_and(|e| e >= at)
+    });
+    if !covered {
+        return Err(format!(
+            "no continuous backup of \"{cluster_name}\" covers {}; PITR needs `pitr = true` under \
+             [postgresql] in fdb.toml when the cluster is created",
+            at.to_rfc3339()
+        ));
+    }
+
+    backups
+        .iter()
+        .filter(|b| !is_continuous(b) && b.pointer("/status/phase").and_then(|p| p.as_str()) == Some("Completed"))
+        .filter_map(|b| {
+            let done = time_at(b, "/status/completionTimestamp")?;
+            let name = b.pointer("/metadata/name")?.as_str()?;
+            (done <= at).then_some((done, name))
+        })
+        .max_by_key(|(done, _)| *done)
+        .map(|(_, name)| name.to_string())
+        .ok_or_else(|| format!("no completed base backup of \"{cluster_name}\" before {}", at.to_rfc3339()))
+}
+
+/// Restore `source` as it was at `at` into a new cluster `target`: the newest base backup
+/// before `at` plus the archived WAL up to it.
+pub fn restore_to_time(
+    kbcli: &Path,
+    kubectl: &Path,
+    source: &str,
+    target: &str,
+    at: chrono::DateTime<chrono::Utc>,
+    kubeconfig: &Path,
+    timeout: Duration,
+) -> Result<(), Error> {
+    let mut progress = Progress::new();
+    progress.begin("find backup", "Finding base backup");
+    let backup = base_backup_for(kubectl, source, at, kubeconfig)?;
+    let at_text = at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    progress.begin("restore accepted", &format!("Restoring \"{source}\" at {at_text} from \"{backup}\""));
+    Command::new(kbcli)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args([
+            "cluster",
+            "restore",
+            target,
+            "-n",
+            NAMESPACE,
+            "--backup",
+            &backup,
+            "--restore-to-time",
+            &at_text,
+        ])
+        .output_checked()
+        .map_err(error::code(ExitCode::Kbcli))?;
+    cluster::wait_until_running(kbcli, kubectl, target, kubeconfig, timeout, &mut progress)?;
+    progress.finish();
+    println!();
+    println!("Restored \"{source}\" as of {at_text} into \"{target}\". Run `fdb info {target}` for connection details.");
+    progress.print_summary();
+    Ok(())
+}
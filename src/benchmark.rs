@@ -0,0 +1,222 @@
+//! Quick throughput/latency sanity check of a cluster (`fdb benchmark`).
+
+use crate::accounts;
+use crate::credentials;
+use crate::exec::CommandExt;
+use crate::portforward;
+use crate::service::ServiceType;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Scratch database for pgbench tables; dropped afterwards.
+const PG_BENCH_DB: &str = "fdb_bench";
+const RABBITMQ_BENCH_QUEUE: &str = "fdb-bench";
+
+/// How hard to push.
+#[derive(Debug, Clone, Copy)]
+pub struct BenchOptions {
+    pub duration: Duration,
+    pub clients: u32,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        BenchOptions {
+            duration: Duration::from_secs(10),
+            clients: 4,
+        }
+    }
+}
+
+fn forward(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    kubeconfig: &Path,
+) -> Result<portforward::PortForward, String> {
+    portforward::start_port_forward(
+        kubectl,
+        &format!("svc/{cluster_name}-{}", service.kbcli_name()),
+        service.default_port(),
+        None,
+        kubeconfig,
+    )
+}
+
+fn missing_tool(tool: &str) -> impl Fn(String) -> String + '_ {
+    move |e| {
+        if e.starts_with("could not run") {
+            format!("{tool} not found; install it locally to benchmark this engine ({e})")
+        } else {
+            e
+        }
+    }
+}
+
+/// pgbench (TPC-B-like, scale 1) in a scratch database.
+fn bench_postgres(kubectl: &Path, cluster_name: &str, opts: BenchOptions, kubeconfig: &Path) -> Result<(), String> {
+    let service = ServiceType::PostgreSQL;
+    let password = credentials::get_password(kubectl, service, cluster_name, kubeconfig)?.unwrap_or_default();
+    let fwd = forward(kubectl, service, cluster_name, kubeconfig)?;
+    let port = fwd.local_port.to_string();
+
+    let mut admin = postgres::Config::new()
+        .host("127.0.0.1")
+        .port(fwd.local_port)
+        .user(service.default_user())
+        .password(&password)
+        .dbname("postgres")
+        .connect(postgres::NoTls)
+        .map_err(|e| format!("connect: {e}"))?;
+    admin
+        .simple_query(&format!("DROP DATABASE IF EXISTS {PG_BENCH_DB}; CREATE DATABASE {PG_BENCH_DB}"))
+        .map_err(|e| format!("create {PG_BENCH_DB}: {e}"))?;
+
+    let pgbench = |args: &[&str]| {
+        Command::new("pgbench")
+            .args(["-h", "127.0.0.1", "-p", &port, "-U", service.default_user()])
+            .args(args)
+            .arg(PG_BENCH_DB)
+            .env("PGPASSWORD", &password)
+            .output_checked()
+            .map_err(missing_tool("pgbench"))
+    };
+    println!("Initializing pgbench tables in {PG_BENCH_DB}...");
+    let result = pgbench(&["-i", "-s", "1", "-q"]).and_then(|_| {
+        println!("Running pgbench for {}s with {} clients...", opts.duration.as_secs(), opts.clients);
+        pgbench(&[
+            "-T",
+            &opts.duration.as_secs().to_string(),
+            "-c",
+            &opts.clients.to_string(),
+            "-j",
+            &opts.clients.to_string(),
+        ])
+    });
+    let _ = admin.simple_query(&format!("DROP DATABASE IF EXISTS {PG_BENCH_DB}"));
+    let output = result?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for line in stdout.lines() {
+        let line = line.trim();
+        if ["tps =", "latency average", "number of transactions actually processed"]
+            .iter()
+            .any(|p| line.starts_with(p))
+        {
+            println!("  {line}");
+        }
+    }
+    Ok(())
+}
+
+/// redis-benchmark on SET/GET/PING; the request count is sized to the duration.
+fn bench_redis(kubectl: &Path, cluster_name: &str, opts: BenchOptions, kubeconfig: &Path) -> Result<(), String> {
+    let service = ServiceType::Redis;
+    let password = credentials::get_password(kubectl, service, cluster_name, kubeconfig)?;
+    let fwd = forward(kubectl, service, cluster_name, kubeconfig)?;
+    let requests = (opts.duration.as_secs().max(1) * 2_000).to_string();
+
+    println!("Running redis-benchmark ({requests} requests per test, {} clients)...", opts.clients);
+    let mut cmd = Command::new("redis-benchmark");
+    cmd.args(["-h", "127.0.0.1", "-p", &fwd.local_port.to_string()])
+        .args(["-q", "-t", "ping,set,get", "-n", &requests, "-c", &opts.clients.to_string()]);
+    if let Some(ref p) = password {
+        cmd.args(["-a", p]);
+    }
+    let output = cmd.output_checked().map_err(missing_tool("redis-benchmark"))?;
+    for line in String::from_utf8_lossy(&output.stdout).split(['\r', '\n']) {
+        let line = line.trim();
+        if line.contains("requests per second") {
+            println!("  {line}");
+        }
+    }
+    Ok(())
+}
+
+fn http_error(what: &str) -> impl Fn(ureq::Error) -> String + '_ {
+    move |e| match e {
+        ureq::Error::Status(code, resp) => {
+            format!("{what} failed ({code}): {}", resp.into_string().unwrap_or_default().trim())
+        }
+        e => format!("{what}: {e}"),
+    }
+}
+
+/// Publish to and fetch from a scratch queue through the management API for the duration.
+/// This measures the HTTP API round trip, not AMQP throughput; good enough to spot a starved broker.
+fn bench_rabbitmq(kubectl: &Path, cluster_name: &str, opts: BenchOptions, kubeconfig: &Path) -> Result<(), String> {
+    let (_forward, base) = accounts::rabbitmq_management(kubectl, cluster_name, kubeconfig)?;
+    let queue_path = format!("/queues/%2F/{RABBITMQ_BENCH_QUEUE}");
+    accounts::rabbitmq_api(&base, &queue_path, Some(serde_json::json!({ "durable": false })))?;
+
+    println!("Publishing and consuming via the management API for {}s...", opts.duration.as_secs());
+    let publish_url = format!("{base}/exchanges/%2F/amq.default/publish");
+    let get_url = format!("{base}{queue_path}/get");
+    let body = serde_json::json!({
+        "properties": {},
+        "routing_key": RABBITMQ_BENCH_QUEUE,
+        "payload": "x".repeat(256),
+        "payload_encoding": "string",
+    })
+    .to_string();
+    let get_body = serde_json::json!({ "count": 1, "ackmode": "ack_requeue_false", "encoding": "auto" }).to_string();
+
+    let mut published = 0u64;
+    let mut consumed = 0u64;
+    let mut round_trips = Duration::ZERO;
+    let start = Instant::now();
+    let result = (|| {
+        while start.elapsed() < opts.duration {
+            let t = Instant::now();
+            ureq::post(&publish_url)
+                .set("Content-Type", "application/json")
+                .send_string(&body)
+                .map_err(http_error("publish"))?;
+            published += 1;
+            let messages: serde_json::Value = ureq::post(&get_url)
+                .set("Content-Type", "application/json")
+                .send_string(&get_body)
+                .map_err(http_error("get"))?
+                .into_string()
+                .map_err(|e| format!("get: {e}"))
+                .and_then(|s| serde_json::from_str(&s).map_err(|e| format!("get: {e}")))?;
+            consumed += messages.as_array().map_or(0, |m| m.len() as u64);
+            round_trips += t.elapsed();
+        }
+        Ok::<(), String>(())
+    })();
+    let _ = accounts::rabbitmq_api(&base, &queue_path, None);
+    result?;
+
+    let secs = start.elapsed().as_secs_f64();
+    println!("  published: {published} ({:.1} msg/s)", published as f64 / secs);
+    println!("  consumed:  {consumed} ({:.1} msg/s)", consumed as f64 / secs);
+    if published > 0 {
+        println!(
+            "  publish+get round trip: {:.1} ms average",
+            round_trips.as_secs_f64() * 1000.0 / published as f64
+        );
+    }
+    Ok(())
+}
+
+/// Run the engine's benchmark through a port-forward and print throughput/latency.
+pub fn run(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    opts: BenchOptions,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    println!(
+        "Benchmarking {} cluster \"{cluster_name}\" (through kubectl port-forward; results include tunnel overhead)",
+        service.kbcli_name()
+    );
+    match service {
+        ServiceType::PostgreSQL => bench_postgres(kubectl, cluster_name, opts, kubeconfig),
+        ServiceType::Redis => bench_redis(kubectl, cluster_name, opts, kubeconfig),
+        ServiceType::RabbitMQ => bench_rabbitmq(kubectl, cluster_name, opts, kubeconfig),
+        _ => Err(format!("fdb benchmark is not supported for {} clusters", service.kbcli_name())),
+    }
+}
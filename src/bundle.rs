@@ -0,0 +1,198 @@
+//! Shareable cluster bundles (`fdb bundle export|import|list`): one JSON file with the spec,
+//! endpoint and, encrypted with sops for an age recipient, the credentials.
+
+use crate::config;
+use crate::credentials::ConnectionDetails;
+use crate::exec::CommandExt;
+use crate::service::ServiceType;
+use crate::sops;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const VERSION: u32 = 1;
+/// Keys sops encrypts; everything else stays readable.
+const SECRET_KEYS: &str = "^(password|uri)$";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BundleSpec {
+    pub replicas: Option<u32>,
+    /// Cores.
+    pub cpu: Option<f64>,
+    pub memory_gi: Option<f64>,
+    pub storage_gi: Option<f64>,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bundle {
+    pub fdb_bundle: u32,
+    pub name: String,
+    pub engine: String,
+    /// Kube context the cluster was exported from.
+    pub context: String,
+    pub exported_at: String,
+    #[serde(default)]
+    pub spec: BundleSpec,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub user: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+}
+
+impl Bundle {
+    pub fn new(name: &str, engine: &str, context: &str, spec: BundleSpec, details: &ConnectionDetails) -> Self {
+        Bundle {
+            fdb_bundle: VERSION,
+            name: name.to_string(),
+            engine: engine.to_string(),
+            context: context.to_string(),
+            exported_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            spec,
+            host: details.endpoint.as_ref().map(|(h, _)| h.clone()),
+            port: details.endpoint.as_ref().map(|(_, p)| *p),
+            user: details.user.clone(),
+            password: details.password.clone(),
+            uri: details.connection_string.clone(),
+        }
+    }
+
+    pub fn details(&self) -> ConnectionDetails {
+        let service = self.engine.parse::<ServiceType>().ok();
+        let endpoint = self.host.clone().zip(self.port);
+        ConnectionDetails {
+            user: self.user.clone(),
+            password: self.password.clone(),
+            connection_string: self.uri.clone(),
+            label: service.map(|s| s.connection_label()).unwrap_or("Connection string"),
+            password_label: service.map(|s| s.password_label()).unwrap_or("Password"),
+            cli_command: service
+                .zip(endpoint.as_ref())
+                .and_then(|(s, (host, port))| s.cli_command(self.password.as_deref(), host, *port)),
+            endpoint,
+        }
+    }
+
+    /// Header, spec and connection details, like `fdb info`.
+    pub fn print(&self) {
+        println!(
+            "Cluster \"{}\" ({}) from bundle, context \"{}\", exported {}",
+            self.name, self.engine, self.context, self.exported_at
+        );
+        let s = &self.spec;
+        let show = |v: Option<String>| v.unwrap_or_else(|| "?".to_string());
+        println!(
+            "  replicas={} cpu={} memory={}Gi storage={}Gi version={}",
+            show(s.replicas.map(|r| r.to_string())),
+            show(s.cpu.map(|c| c.to_string())),
+            show(s.memory_gi.map(|m| m.to_string())),
+            show(s.storage_gi.map(|g| g.to_string())),
+            show(s.version.clone()),
+        );
+        println!();
+        if self.password.is_some() {
+            self.details().print();
+            return;
+        }
+        println!("Connection details (no credentials in the bundle; export with --age to include them):");
+        if let (Some(host), Some(port)) = (&self.host, self.port) {
+            println!("  Host: {host}");
+            println!("  Port: {port}");
+        }
+        println!("  User: {}", self.user);
+    }
+}
+
+fn bundles_dir() -> PathBuf {
+    config::fdb_home().join("bundles")
+}
+
+/// Write `contents` with mode 0600.
+fn write_private(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).write(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path).map_err(|e| format!("write {}: {e}", path.display()))?;
+    file.write_all(contents).map_err(|e| format!("write {}: {e}", path.display()))
+}
+
+/// Write the bundle to `out`. With an age recipient the credentials are encrypted by sops;
+/// without one they are left out.
+pub fn export(mut bundle: Bundle, age_recipient: Option<&str>, out: &Path) -> Result<(), String> {
+    let Some(recipient) = age_recipient else {
+        bundle.password = None;
+        bundle.uri = None;
+        let json = serde_json::to_vec_pretty(&bundle).map_err(|e| e.to_string())?;
+        return write_private(out, &json);
+    };
+    let plain = serde_json::to_vec_pretty(&bundle).map_err(|e| e.to_string())?;
+    let args = ["--age", recipient, "--encrypted-regex", SECRET_KEYS, "--input-type", "json", "--output-type", "json"];
+    write_private(out, &sops::encrypt(&plain, &args, out)?)
+}
+
+/// Parse a bundle file, decrypting its credentials with sops when they are encrypted.
+fn read_bundle(path: &Path) -> Result<Bundle, String> {
+    let raw = std::fs::read(path).map_err(|e| format!("read {}: {e}", path.display()))?;
+    let value: serde_json::Value =
+        serde_json::from_slice(&raw).map_err(|e| format!("{} is not an fdb bundle: {e}", path.display()))?;
+    let plain = if value.get("sops").is_some() {
+        Command::new("sops")
+            .args(["--decrypt", "--input-type", "json", "--output-type", "json"])
+            .arg(path)
+            .output_checked()?
+            .stdout
+    } else {
+        raw
+    };
+    serde_json::from_slice(&plain).map_err(|e| format!("{} is not an fdb bundle: {e}", path.display()))
+}
+
+/// Read a bundle file and register it in `~/.fdb/bundles/<name>.json` as received: the
+/// credentials stay encrypted there and are decrypted again by [`load`].
+pub fn import(file: &Path) -> Result<Bundle, String> {
+    let bundle = read_bundle(file)?;
+    if bundle.fdb_bundle != VERSION {
+        return Err(format!(
+            "{}: bundle format {} is not supported (expected {VERSION})",
+            file.display(),
+            bundle.fdb_bundle
+        ));
+    }
+    crate::names::validate(&bundle.name)?;
+    let dir = bundles_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| format!("create {}: {e}", dir.display()))?;
+    let raw = std::fs::read(file).map_err(|e| format!("read {}: {e}", file.display()))?;
+    write_private(&dir.join(format!("{}.json", bundle.name)), &raw)?;
+    Ok(bundle)
+}
+
+/// A registered bundle with its credentials decrypted; None when there is none with this name.
+pub fn load(name: &str) -> Result<Option<Bundle>, String> {
+    let path = bundles_dir().join(format!("{name}.json"));
+    if !path.exists() {
+        return Ok(None);
+    }
+    read_bundle(&path).map(Some)
+}
+
+/// Registered bundles, sorted by name; unreadable files are skipped. Credentials are left
+/// encrypted.
+pub fn list() -> Vec<Bundle> {
+    let Ok(entries) = std::fs::read_dir(bundles_dir()) else {
+        return Vec::new();
+    };
+    let mut bundles: Vec<Bundle> = entries
+        .filter_map(Result::ok)
+        .filter_map(|e| serde_json::from_slice(&std::fs::read(e.path()).ok()?).ok())
+        .collect();
+    bundles.sort_by(|a, b| a.name.cmp(&b.name));
+    bundles
+}
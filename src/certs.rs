@@ -0,0 +1,120 @@
+//! Client certificates (mTLS) for PostgreSQL, signed by the cluster CA, stored in ~/.fdb/certs.
+
+use crate::credentials;
+use crate::exec::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const CERT_DAYS: u32 = 365;
+
+/// Default secret holding the CA that signs client certificates (`ca.crt`, `ca.key`).
+pub fn default_ca_secret(cluster_name: &str) -> String {
+    format!("{cluster_name}-postgresql-ca")
+}
+
+/// Files written for one client certificate.
+#[derive(Debug, Clone)]
+pub struct ClientCert {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+    pub root_cert: PathBuf,
+}
+
+impl ClientCert {
+    /// libpq query parameters for cert-based auth.
+    pub fn query_params(&self) -> String {
+        format!(
+            "sslmode=verify-ca&sslcert={}&sslkey={}&sslrootcert={}",
+            self.cert.display(),
+            self.key.display(),
+            self.root_cert.display()
+        )
+    }
+}
+
+fn openssl(args: &[&str]) -> Result<(), String> {
+    Command::new("openssl")
+        .args(args)
+        .output_checked()?;
+    Ok(())
+}
+
+/// Make a key file readable by the owner only (libpq refuses group/world-readable keys).
+fn restrict(path: &Path) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("chmod {}: {e}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn write_private(path: &Path, content: &str) -> Result<(), String> {
+    std::fs::write(path, content).map_err(|e| format!("write {}: {e}", path.display()))?;
+    restrict(path)
+}
+
+/// Generate a key and a certificate with CN=<user> signed by the CA in `ca_secret`,
+/// under ~/.fdb/certs/<cluster>/.
+pub fn issue_client_cert(
+    kubectl: &Path,
+    cluster_name: &str,
+    user: &str,
+    ca_secret: &str,
+    kubeconfig: &Path,
+) -> Result<ClientCert, String> {
+    let ca_crt = credentials::read_secret_field(kubectl, ca_secret, "ca.crt", kubeconfig)?;
+    let ca_key = credentials::read_secret_field(kubectl, ca_secret, "ca.key", kubeconfig)?;
+    let (ca_crt, ca_key) = match (ca_crt, ca_key) {
+        (Some(c), Some(k)) => (c, k),
+        _ => {
+            return Err(format!(
+                "CA secret {ca_secret} with ca.crt and ca.key not found (use --ca-secret to point at the CA that signs the cluster's TLS certificates)"
+            ));
+        }
+    };
+
+    let dir = crate::config::fdb_home().join("certs").join(cluster_name);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("create {}: {e}", dir.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700));
+    }
+    let root_cert = dir.join("root.crt");
+    let ca_key_path = dir.join("ca.key.tmp");
+    let key = dir.join(format!("{user}.key"));
+    let csr = dir.join(format!("{user}.csr"));
+    let cert = dir.join(format!("{user}.crt"));
+
+    std::fs::write(&root_cert, &ca_crt).map_err(|e| format!("write {}: {e}", root_cert.display()))?;
+    write_private(&ca_key_path, &ca_key)?;
+
+    let days = CERT_DAYS.to_string();
+    let subject = format!("/CN={user}");
+    let result = openssl(&["genrsa", "-out", &key.to_string_lossy(), "2048"])
+        .and_then(|_| restrict(&key))
+        .and_then(|_| {
+            openssl(&[
+                "req", "-new", "-key", &key.to_string_lossy(), "-subj", &subject, "-out", &csr.to_string_lossy(),
+            ])
+        })
+        .and_then(|_| {
+            openssl(&[
+                "x509", "-req", "-in", &csr.to_string_lossy(),
+                "-CA", &root_cert.to_string_lossy(), "-CAkey", &ca_key_path.to_string_lossy(),
+                "-CAcreateserial", "-days", &days, "-out", &cert.to_string_lossy(),
+            ])
+        });
+    let _ = std::fs::remove_file(&ca_key_path);
+    let _ = std::fs::remove_file(&csr);
+    let _ = std::fs::remove_file(dir.join("root.srl"));
+    result?;
+
+    Ok(ClientCert {
+        cert,
+        key,
+        root_cert,
+    })
+}
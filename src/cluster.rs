@@ -1,44 +1,103 @@
 //! Create/delete/list clusters via kbcli.
 
+use crate::config;
+use crate::error::{Error, ExitCode};
+use crate::exec::{self, CommandExt};
+use crate::expose;
+use crate::hooks;
+use crate::load;
+use crate::metrics;
+use crate::output;
+use crate::pods;
+use crate::progress::Progress;
 use crate::service::ServiceType;
-use nanospinner::Spinner;
+use crate::suggest;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::io::{self, Write};
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Stdio};
 use std::time::Duration;
 
+const NAMESPACE: &str = "default";
 const POLL_INTERVAL_SECS: u64 = 3;
-const TIMEOUT_SECS: u64 = 300; // 5 minutes
+/// Annotation set by `fdb protect`; `fdb delete` refuses protected clusters.
+const PROTECTED_ANNOTATION: &str = "fdb.io/protected";
+/// Setup steps `fdb create --wait=false` left for `fdb wait` (JSON).
+const PENDING_SETUP_ANNOTATION: &str = "fdb.io/pending-setup";
+/// How long `fdb create` waits for Running.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
 
-/// Parse storage/memory for kbcli: "2Gi" or "0.8Gi" -> number string; unit is Gi.
-fn kbcli_quantity(s: &str) -> Result<String, String> {
-    let s = s.trim();
-    let num_str = s
-        .strip_suffix("Gi")
-        .or_else(|| s.strip_suffix("gi"))
-        .unwrap_or(s);
-    let num: f64 = num_str
+fn parse_positive(num: &str, original: &str, what: &str, example: &str) -> Result<f64, String> {
+    let n: f64 = num
         .trim()
         .parse()
-        .map_err(|_| format!("invalid quantity: {s} (expected number or e.g. 2Gi)"))?;
-    Ok(num.to_string())
+        .map_err(|_| format!("invalid {what}: \"{original}\" (expected e.g. {example})"))?;
+    if !n.is_finite() || n <= 0.0 {
+        return Err(format!("invalid {what}: \"{original}\" (must be greater than zero)"));
+    }
+    Ok(n)
+}
+
+/// Storage/memory in the Gi number kbcli expects: "512Mi" -> "0.5", "2Gi" or "2" -> "2", "1Ti" -> "1024".
+pub fn kbcli_gi(s: &str, what: &str) -> Result<String, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let factor = match unit {
+        "" | "Gi" | "gi" => 1.0,
+        "Mi" | "mi" => 1.0 / 1024.0,
+        "Ti" | "ti" => 1024.0,
+        "G" | "GB" | "M" | "MB" | "T" | "TB" | "g" | "gb" | "m" | "mb" => {
+            return Err(format!(
+                "invalid {what}: \"{s}\" (use binary units Mi, Gi or Ti, e.g. {}{}i)",
+                num.trim(),
+                &unit[..1].to_uppercase()
+            ));
+        }
+        _ => return Err(format!("invalid {what}: \"{s}\" (unknown unit {unit}; use Mi, Gi or Ti)")),
+    };
+    Ok((parse_positive(num, s, what, "2Gi")? * factor).to_string())
 }
 
-/// Run kbcli cluster create <service> <name> with config.
+/// Storage for kbcli like [`kbcli_gi`]; None when the engine runs without a volume by default
+/// (memcached) and none was configured.
+pub fn kbcli_storage(s: &str) -> Result<Option<String>, String> {
+    if s.trim().is_empty() {
+        return Ok(None);
+    }
+    kbcli_gi(s, "storage").map(Some)
+}
+
+/// CPU in cores for kbcli: "500m" -> "0.5", "1.5" -> "1.5".
+pub fn kbcli_cpu(s: &str) -> Result<String, String> {
+    let s = s.trim();
+    let cores = match s.strip_suffix('m') {
+        Some(milli) => parse_positive(milli, s, "cpu", "0.5 or 500m")? / 1000.0,
+        None => parse_positive(s, s, "cpu", "0.5 or 500m")?,
+    };
+    Ok(cores.to_string())
+}
+
+/// Run kbcli cluster create <service> <name> with config. Storage and memory are in Gi and cpu
+/// in cores, already normalized with [`kbcli_gi`] / [`kbcli_cpu`]; without `storage` the
+/// cluster gets no volume. Without `version` kbcli picks the engine's default version. `extra_args` are engine-specific flags, passed as is.
 pub fn create_cluster(
     kbcli: &Path,
     service: ServiceType,
     name: &str,
     kubeconfig: &Path,
     replicas: u32,
-    storage: &str,
+    storage: Option<&str>,
     cpu: &str,
     memory: &str,
+    version: Option<&str>,
+    extra_args: &[&str],
 ) -> Result<(), String> {
-    let storage_num = kbcli_quantity(storage)?;
-    let memory_num = kbcli_quantity(memory)?;
-    let output = Command::new(kbcli)
-        .arg("--kubeconfig")
+    let mut cmd = Command::new(kbcli);
+    cmd.arg("--kubeconfig")
         .arg(kubeconfig)
         .args([
             "cluster",
@@ -47,69 +106,598 @@ pub fn create_cluster(
             name,
             "--replicas",
             &replicas.to_string(),
-            "--storage",
-            &storage_num,
-            "--cpu",
-            cpu,
-            "--memory",
-            &memory_num,
-        ])
-        .output()
-        .map_err(|e| format!("kbcli failed: {e}"))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("kbcli cluster create failed: {stderr}"));
+        ]);
+    if let Some(s) = storage {
+        cmd.args(["--storage", s]);
+    }
+    cmd.args(["--cpu", cpu, "--memory", memory]);
+    if let Some(v) = version {
+        cmd.args(["--version", v]);
     }
+    cmd.args(extra_args);
+    cmd.output_checked()?;
     Ok(())
 }
 
-/// Poll kbcli cluster list until status is Running or timeout.
-pub fn wait_until_running(kbcli: &Path, name: &str, kubeconfig: &Path) -> Result<(), String> {
-    let spinner = Spinner::new("Waiting for cluster to be Running...").start();
+/// Poll until the cluster's pods are scheduled, then until kbcli reports it Running. Both phases
+/// share the timeout; Failed/Abnormal ends the wait early. The spinner shows each
+/// pod's state and, for unscheduled pods, their latest event; a timeout error repeats it.
+pub fn wait_until_running(
+    kbcli: &Path,
+    kubectl: &Path,
+    name: &str,
+    kubeconfig: &Path,
+    timeout: Duration,
+    progress: &mut Progress,
+) -> Result<(), Error> {
     let start = std::time::Instant::now();
+    let mut scheduled = false;
+    let mut pod_detail = String::new();
+    progress.begin("pods scheduling", "Scheduling pods");
 
     loop {
-        if start.elapsed().as_secs() >= TIMEOUT_SECS {
-            spinner.fail_with("Timeout waiting for cluster");
-            return Err("cluster did not become Running within 5 minutes".to_string());
+        if start.elapsed() >= timeout {
+            progress.fail("timeout");
+            let mut message = format!(
+                "cluster \"{name}\" did not become Running within {}",
+                crate::progress::format_elapsed(timeout)
+            );
+            if !pod_detail.is_empty() {
+                message.push_str(&format!("\n  pods: {pod_detail}"));
+            }
+            return Err(Error::new(ExitCode::Timeout, message));
         }
 
-        let output = match Command::new(kbcli)
-            .arg("--kubeconfig")
-            .arg(kubeconfig)
-            .args(["cluster", "list", name])
-            .output()
-        {
-            Ok(o) => o,
+        let clusters = match list_cluster_infos(kbcli, Some(name), kubeconfig) {
+            Ok(c) => c,
             Err(e) => {
-                spinner.fail_with("kbcli list failed");
-                return Err(format!("kbcli cluster list failed: {e}"));
+                progress.fail("kbcli list failed");
+                return Err(Error::new(ExitCode::Kbcli, e));
             }
         };
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        if parse_status(&stdout) == Some("Running") {
-            spinner.success();
+        match find_cluster(&clusters, name, NAMESPACE).map(|c| c.status.as_str()) {
+            Some("Running") => {
+                if !scheduled {
+                    progress.begin("pods ready", "Waiting for pods to be ready");
+                }
+                progress.finish();
+                return Ok(());
+            }
+            Some(phase @ ("Failed" | "Abnormal")) => {
+                progress.fail(&format!("cluster is {phase}"));
+                return Err(Error::new(
+                    ExitCode::Kbcli,
+                    format!("cluster \"{name}\" is {phase}; inspect it with `kbcli cluster describe {name} -n {NAMESPACE}`"),
+                ));
+            }
+            _ => {}
+        }
+
+        // Pod detail is only progress output; the cluster phase above decides success.
+        if let Ok(pods) = pods::cluster_pod_states(kubectl, name, kubeconfig)
+            && !pods.is_empty()
+        {
+            if !scheduled && pods.iter().all(|p| p.scheduled) {
+                scheduled = true;
+                progress.begin("pods ready", "Waiting for pods to be ready");
+            }
+            pod_detail = pods::describe_states(kubectl, &pods, kubeconfig);
+            progress.update(&pod_detail);
+        }
+
+        std::thread::sleep(Duration::from_secs(POLL_INTERVAL_SECS));
+    }
+}
+
+/// Poll until kbcli reports the cluster Stopped; Failed/Abnormal or the timeout end the wait.
+pub fn wait_until_stopped(
+    kbcli: &Path,
+    name: &str,
+    kubeconfig: &Path,
+    timeout: Duration,
+    progress: &mut Progress,
+) -> Result<(), Error> {
+    let start = std::time::Instant::now();
+    progress.begin("stopped", &format!("Stopping \"{name}\""));
+    loop {
+        if start.elapsed() >= timeout {
+            progress.fail("timeout");
+            return Err(Error::new(
+                ExitCode::Timeout,
+                format!("cluster \"{name}\" did not stop within {}", crate::progress::format_elapsed(timeout)),
+            ));
+        }
+        let clusters = list_cluster_infos(kbcli, Some(name), kubeconfig)
+            .inspect_err(|_| progress.fail("kbcli list failed"))
+            .map_err(|e| Error::new(ExitCode::Kbcli, e))?;
+        match find_cluster(&clusters, name, NAMESPACE).map(|c| c.status.as_str()) {
+            Some("Stopped") => {
+                progress.finish();
+                return Ok(());
+            }
+            Some(phase @ ("Failed" | "Abnormal")) => {
+                progress.fail(&format!("cluster is {phase}"));
+                return Err(Error::new(
+                    ExitCode::Kbcli,
+                    format!("cluster \"{name}\" is {phase}; inspect it with `kbcli cluster describe {name} -n {NAMESPACE}`"),
+                ));
+            }
+            Some(phase) if !phase.is_empty() => progress.update(phase),
+            _ => {}
+        }
+        std::thread::sleep(Duration::from_secs(POLL_INTERVAL_SECS));
+    }
+}
+
+/// Poll until the Cluster object is gone, deletion included; the timeout ends the wait.
+pub fn wait_until_deleted(
+    kubectl: &Path,
+    name: &str,
+    kubeconfig: &Path,
+    timeout: Duration,
+    progress: &mut Progress,
+) -> Result<(), Error> {
+    let start = std::time::Instant::now();
+    progress.begin("deleted", &format!("Waiting for \"{name}\" to be deleted"));
+    loop {
+        if start.elapsed() >= timeout {
+            progress.fail("timeout");
+            return Err(Error::new(
+                ExitCode::Timeout,
+                format!("cluster \"{name}\" was not deleted within {}", crate::progress::format_elapsed(timeout)),
+            ));
+        }
+        let output = Command::new(kubectl)
+            .arg("--kubeconfig")
+            .arg(kubeconfig)
+            .args(["get", "cluster", name, "-n", NAMESPACE, "--ignore-not-found", "-o", "name"])
+            .output_checked()
+            .inspect_err(|_| progress.fail("kubectl get cluster failed"))
+            .map_err(|e| Error::new(ExitCode::Kbcli, e))?;
+        if output.stdout.iter().all(u8::is_ascii_whitespace) {
+            progress.finish();
             return Ok(());
         }
+        std::thread::sleep(Duration::from_secs(POLL_INTERVAL_SECS));
+    }
+}
+
+/// Condition `fdb wait --for` blocks on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WaitFor {
+    #[default]
+    Running,
+    Stopped,
+    Deleted,
+}
+
+impl std::str::FromStr for WaitFor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "running" => Ok(WaitFor::Running),
+            "stopped" => Ok(WaitFor::Stopped),
+            "deleted" => Ok(WaitFor::Deleted),
+            _ => Err(format!("invalid --for: {s} (use running, stopped or deleted)")),
+        }
+    }
+}
+
+/// OpsRequest name from kbcli's "OpsRequest <name> created successfully" message.
+pub fn ops_request_name(kbcli_output: &str) -> Option<String> {
+    let rest = &kbcli_output[kbcli_output.find("OpsRequest ")? + "OpsRequest ".len()..];
+    rest.split_whitespace().next().map(str::to_string)
+}
+
+/// Poll OpsRequests until every one has succeeded; Failed, Cancelled or Aborted ends the wait
+/// early. Timeout and spinner work as in `wait_until_running`.
+pub fn wait_for_ops(
+    kubectl: &Path,
+    names: &[String],
+    kubeconfig: &Path,
+    timeout: Duration,
+    progress: &mut Progress,
+) -> Result<(), Error> {
+    let start = std::time::Instant::now();
+    progress.begin("ops", "Waiting for OpsRequests");
+    loop {
+        if start.elapsed() >= timeout {
+            progress.fail("timeout");
+            return Err(Error::new(
+                ExitCode::Timeout,
+                format!("{} did not finish within {}", names.join(", "), crate::progress::format_elapsed(timeout)),
+            ));
+        }
 
+        let mut pending = Vec::new();
+        for name in names {
+            let output = Command::new(kubectl)
+                .arg("--kubeconfig")
+                .arg(kubeconfig)
+                .args(["get", "opsrequest", name, "-n", NAMESPACE, "-o", "jsonpath={.status.phase}"])
+                .output_checked()
+                .inspect_err(|_| progress.fail("kubectl get opsrequest failed"))
+                .map_err(|e| Error::new(ExitCode::Kbcli, e))?;
+            match String::from_utf8_lossy(&output.stdout).trim() {
+                "Succeed" => {}
+                phase @ ("Failed" | "Cancelled" | "Aborted") => {
+                    progress.fail(&format!("{name} {phase}"));
+                    return Err(Error::new(
+                        ExitCode::Kbcli,
+                        format!(
+                            "OpsRequest {name} {phase}; inspect it with `kubectl describe opsrequest {name} -n {NAMESPACE}`"
+                        ),
+                    ));
+                }
+                "" => pending.push(format!("{name} Pending")),
+                phase => pending.push(format!("{name} {phase}")),
+            }
+        }
+        if pending.is_empty() {
+            progress.finish();
+            return Ok(());
+        }
+        progress.update(&pending.join("; "));
         std::thread::sleep(Duration::from_secs(POLL_INTERVAL_SECS));
     }
 }
 
-/// Parse STATUS column from kbcli cluster list output (whitespace-separated, 5th column).
-fn parse_status(stdout: &str) -> Option<&str> {
-    let lines: Vec<&str> = stdout.lines().collect();
-    if lines.len() < 2 {
-        return None;
+/// One cluster as reported by `kbcli cluster list -o json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterInfo {
+    pub name: String,
+    pub namespace: String,
+    /// Cluster definition (KubeBlocks 1.x `clusterDef`, 0.x `clusterDefinitionRef`),
+    /// or the first component definition when neither is set.
+    pub definition: String,
+    pub termination_policy: String,
+    /// Replicas of the first (main) component.
+    pub replicas: u32,
+    /// `status.phase`; empty while KubeBlocks has not reported one yet.
+    pub status: String,
+    pub created: String,
+    pub labels: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClusterObject {
+    #[serde(default)]
+    metadata: ClusterMetadata,
+    #[serde(default)]
+    spec: ClusterSpec,
+    #[serde(default)]
+    status: ClusterStatus,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClusterMetadata {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    namespace: String,
+    #[serde(default)]
+    creation_timestamp: String,
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClusterSpec {
+    #[serde(default, alias = "clusterDefinitionRef")]
+    cluster_def: String,
+    #[serde(default)]
+    termination_policy: String,
+    #[serde(default)]
+    component_specs: Vec<ComponentSpec>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ComponentSpec {
+    #[serde(default)]
+    component_def: String,
+    #[serde(default)]
+    replicas: u32,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ClusterStatus {
+    #[serde(default)]
+    phase: String,
+}
+
+impl From<ClusterObject> for ClusterInfo {
+    fn from(c: ClusterObject) -> Self {
+        let replicas = c.spec.component_specs.first().map_or(0, |s| s.replicas);
+        let definition = if c.spec.cluster_def.is_empty() {
+            c.spec
+                .component_specs
+                .into_iter()
+                .map(|s| s.component_def)
+                .find(|d| !d.is_empty())
+                .unwrap_or_default()
+        } else {
+            c.spec.cluster_def
+        };
+        ClusterInfo {
+            name: c.metadata.name,
+            namespace: c.metadata.namespace,
+            definition,
+            termination_policy: c.spec.termination_policy,
+            replicas,
+            status: c.status.phase,
+            created: c.metadata.creation_timestamp,
+            labels: c.metadata.labels,
+        }
+    }
+}
+
+/// Parse `kbcli cluster list -o json`: a `List` with `items`, a bare array, or a single Cluster.
+fn parse_cluster_list(json: &str) -> Result<Vec<ClusterInfo>, String> {
+    if json.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let value: serde_json::Value =
+        serde_json::from_str(json).map_err(|e| format!("parse kbcli cluster list output: {e}"))?;
+    let items = match value {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Object(mut obj) if obj.contains_key("items") => match obj.remove("items") {
+            Some(serde_json::Value::Array(items)) => items,
+            _ => Vec::new(),
+        },
+        object => vec![object],
+    };
+    items
+        .into_iter()
+        .map(|item| {
+            serde_json::from_value::<ClusterObject>(item)
+                .map(ClusterInfo::from)
+                .map_err(|e| format!("parse kbcli cluster list output: {e}"))
+        })
+        .collect()
+}
+
+/// The cluster with this name in this namespace (other clusters may share the output).
+fn find_cluster<'a>(clusters: &'a [ClusterInfo], name: &str, namespace: &str) -> Option<&'a ClusterInfo> {
+    clusters.iter().find(|c| c.name == name && c.namespace == namespace)
+}
+
+/// Clusters from `kbcli cluster list -o json`, optionally only the named one (in fdb's namespace).
+/// With a `[project] prefix`, listing all clusters only returns the project's.
+pub fn list_cluster_infos(kbcli: &Path, name: Option<&str>, kubeconfig: &Path) -> Result<Vec<ClusterInfo>, String> {
+    let mut cmd = Command::new(kbcli);
+    cmd.arg("--kubeconfig").arg(kubeconfig).args(["cluster", "list"]);
+    if let Some(name) = name {
+        cmd.args([name, "-n", NAMESPACE]);
+    }
+    let output = cmd
+        .args(["-o", "json"])
+        .output_checked()?;
+    let mut clusters = parse_cluster_list(&String::from_utf8_lossy(&output.stdout))?;
+    if name.is_none()
+        && let Some(prefix) = config::project_prefix()
+    {
+        clusters.retain(|c| c.name.starts_with(&format!("{prefix}-")));
+    }
+    Ok(clusters)
+}
+
+/// Service type from a cluster or component definition name ("postgresql", "redis-7-1.0.0").
+pub fn service_from_definition(definition: &str) -> Result<ServiceType, String> {
+    definition
+        .parse()
+        .or_else(|_| definition.split('-').next().unwrap_or_default().parse())
+}
+
+/// The named cluster in fdb's namespace, or a did-you-mean error.
+pub fn cluster_info(kbcli: &Path, name: &str, kubeconfig: &Path) -> Result<ClusterInfo, String> {
+    let clusters = list_cluster_infos(kbcli, Some(name), kubeconfig).unwrap_or_default();
+    find_cluster(&clusters, name, NAMESPACE).cloned().ok_or_else(|| not_found(kbcli, name, kubeconfig))
+}
+
+/// Detect the service type of an existing cluster from its cluster definition.
+pub fn cluster_service_type(kbcli: &Path, name: &str, kubeconfig: &Path) -> Result<ServiceType, String> {
+    service_from_definition(&cluster_info(kbcli, name, kubeconfig)?.definition)
+}
+
+/// Error for a missing cluster, suggesting the closest existing name.
+fn not_found(kbcli: &Path, name: &str, kubeconfig: &Path) -> String {
+    let names = cluster_names(kbcli, kubeconfig).unwrap_or_default();
+    match suggest::did_you_mean(name, names.iter().map(String::as_str)) {
+        Some(close) => format!("cluster \"{name}\" not found; did you mean `{close}`?"),
+        None => format!("cluster \"{name}\" not found"),
+    }
+}
+
+/// Fail with a did-you-mean error when the cluster does not exist.
+pub fn ensure_exists(kbcli: &Path, name: &str, kubeconfig: &Path) -> Result<(), String> {
+    let clusters = list_cluster_infos(kbcli, Some(name), kubeconfig).unwrap_or_default();
+    match find_cluster(&clusters, name, NAMESPACE) {
+        Some(_) => Ok(()),
+        None => Err(not_found(kbcli, name, kubeconfig)),
+    }
+}
+
+/// `fdb list` filters; every one that is set must match.
+#[derive(Debug, Clone, Default)]
+pub struct ListFilter {
+    pub engine: Option<ServiceType>,
+    /// Compared case-insensitively with the phase ("running" matches "Running").
+    pub status: Option<String>,
+    /// `key=value` pairs from repeated `--label`.
+    pub labels: Vec<(String, String)>,
+}
+
+impl ListFilter {
+    /// Parse one `--label key=value`.
+    pub fn add_label(&mut self, s: &str) -> Result<(), String> {
+        let (key, value) = s
+            .split_once('=')
+            .filter(|(k, _)| !k.is_empty())
+            .ok_or_else(|| format!("invalid --label: {s} (expected key=value)"))?;
+        self.labels.push((key.to_string(), value.to_string()));
+        Ok(())
+    }
+
+    pub fn matches(&self, cluster: &ClusterInfo) -> bool {
+        if let Some(engine) = self.engine
+            && service_from_definition(&cluster.definition) != Ok(engine)
+        {
+            return false;
+        }
+        if let Some(ref status) = self.status
+            && !cluster.status.eq_ignore_ascii_case(status)
+        {
+            return false;
+        }
+        self.labels
+            .iter()
+            .all(|(k, v)| cluster.labels.get(k).is_some_and(|actual| actual == v))
+    }
+}
+
+/// Names of all clusters.
+pub fn cluster_names(kbcli: &Path, kubeconfig: &Path) -> Result<Vec<String>, String> {
+    Ok(list_cluster_infos(kbcli, None, kubeconfig)?
+        .into_iter()
+        .map(|c| c.name)
+        .collect())
+}
+
+/// Read an annotation on the Cluster resource; None when absent.
+pub fn cluster_annotation(
+    kubectl: &Path,
+    name: &str,
+    key: &str,
+    kubeconfig: &Path,
+) -> Result<Option<String>, String> {
+    let output = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args([
+            "get",
+            "cluster",
+            name,
+            "-n",
+            NAMESPACE,
+            "-o",
+            &format!("jsonpath={{.metadata.annotations.{}}}", key.replace('.', "\\.")),
+        ])
+        .output_checked()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(if value.is_empty() { None } else { Some(value) })
+}
+
+/// Set (Some) or remove (None) an annotation on the Cluster resource.
+pub fn set_cluster_annotation(
+    kubectl: &Path,
+    name: &str,
+    key: &str,
+    value: Option<&str>,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    let arg = match value {
+        Some(v) => format!("{key}={v}"),
+        None => format!("{key}-"),
+    };
+    Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["annotate", "cluster", name, "-n", NAMESPACE, &arg, "--overwrite"])
+        .output_checked()?;
+    Ok(())
+}
+
+/// `kbcli cluster start` (true) or `stop` (false); returns once KubeBlocks accepted the request.
+pub fn set_running(kbcli: &Path, name: &str, running: bool, kubeconfig: &Path) -> Result<(), String> {
+    let action = if running { "start" } else { "stop" };
+    Command::new(kbcli)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["cluster", action, name, "-n", NAMESPACE, "--auto-approve"])
+        .output_checked()?;
+    Ok(())
+}
+
+/// `kbcli cluster restart`: a rolling restart of every component. Returns the OpsRequest
+/// name when kbcli printed it.
+pub fn restart(kbcli: &Path, name: &str, kubeconfig: &Path) -> Result<Option<String>, String> {
+    let output = Command::new(kbcli)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["cluster", "restart", name, "-n", NAMESPACE, "--auto-approve"])
+        .output_checked()?;
+    Ok(ops_request_name(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// `kubectl apply -f -` with `yaml` on stdin (echoed with `--debug`).
+pub fn apply_manifest(kubectl: &Path, yaml: &str, kubeconfig: &Path) -> Result<(), String> {
+    let mut apply = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["apply", "-f", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn_logged()
+        .map_err(|e| format!("kubectl apply: {e}"))?;
+    exec::echo_stdin(yaml);
+    if let Some(mut stdin) = apply.stdin.take() {
+        stdin.write_all(yaml.as_bytes()).map_err(|e| format!("stdin: {e}"))?;
+    }
+    let status = apply.wait().map_err(|e| format!("kubectl apply wait: {e}"))?;
+    if !status.success() {
+        return Err(format!("`kubectl apply -f -` failed ({status})"));
+    }
+    Ok(())
+}
+
+/// Whether deletion protection is on for the cluster.
+pub fn is_protected(kubectl: &Path, name: &str, kubeconfig: &Path) -> Result<bool, String> {
+    Ok(cluster_annotation(kubectl, name, PROTECTED_ANNOTATION, kubeconfig)?.as_deref() == Some("true"))
+}
+
+/// Turn deletion protection on or off.
+pub fn set_protected(kubectl: &Path, name: &str, protected: bool, kubeconfig: &Path) -> Result<(), String> {
+    let value = if protected { Some("true") } else { None };
+    set_cluster_annotation(kubectl, name, PROTECTED_ANNOTATION, value, kubeconfig)
+}
+
+/// Setup left by `fdb create --wait=false`, if any.
+pub fn pending_setup(kubectl: &Path, name: &str, kubeconfig: &Path) -> Result<Option<String>, String> {
+    cluster_annotation(kubectl, name, PENDING_SETUP_ANNOTATION, kubeconfig)
+}
+
+/// Record (Some) or clear (None) the setup `fdb wait` should finish.
+pub fn set_pending_setup(kubectl: &Path, name: &str, setup: Option<&str>, kubeconfig: &Path) -> Result<(), String> {
+    set_cluster_annotation(kubectl, name, PENDING_SETUP_ANNOTATION, setup, kubeconfig)
+}
+
+/// On contexts marked `production = true` in fdb.toml, require typing the cluster name
+/// (--yes does not skip this). Returns true when the name was confirmed.
+pub fn confirm_production(kubectl: &Path, name: &str, action: &str, kubeconfig: &Path) -> Result<bool, String> {
+    let context = crate::kubeconfig::current_context(kubectl, kubeconfig)?;
+    if !config::is_production_context(&context) {
+        return Ok(false);
+    }
+    eprintln!("Context \"{context}\" is marked production in fdb.toml.");
+    eprint!("Type the cluster name to {action} \"{name}\": ");
+    let _ = io::stderr().flush();
+    let mut line = String::new();
+    io::stdin()
+        .read_line(&mut line)
+        .map_err(|e| format!("read stdin: {e}"))?;
+    if line.trim() != name {
+        return Err("aborted: cluster name did not match".to_string());
     }
-    let data_line = lines.get(1)?;
-    let cols: Vec<&str> = data_line.split_whitespace().collect();
-    cols.get(4).copied()
+    Ok(true)
 }
 
-/// Delete cluster via kbcli cluster delete. If yes is false, prompt for confirmation.
+/// Delete cluster via kbcli cluster delete. Protected clusters are refused. If yes is false, prompt for confirmation.
 /// Also removes fdb-created external NodePort services for this cluster name.
 pub fn delete_cluster(
     kbcli: &Path,
@@ -118,7 +706,15 @@ pub fn delete_cluster(
     kubeconfig: &Path,
     yes: bool,
 ) -> Result<(), String> {
-    if !yes {
+    let info = cluster_info(kbcli, name, kubeconfig)?;
+    if is_protected(kubectl, name, kubeconfig)? {
+        return Err(format!(
+            "cluster \"{name}\" is protected; run `fdb unprotect {name}` first"
+        ));
+    }
+
+    let confirmed = confirm_production(kubectl, name, "delete", kubeconfig)?;
+    if !yes && !confirmed {
         print!("Delete cluster \"{name}\"? [y/N]: ");
         let _ = io::stdout().flush();
         let mut line = String::new();
@@ -131,58 +727,575 @@ pub fn delete_cluster(
         }
     }
 
+    hooks::pre_delete(kbcli, name, kubeconfig)?;
+
     let mut args = vec!["cluster", "delete", name];
-    if yes {
+    if yes || confirmed {
         args.push("--auto-approve");
     }
-    let output = Command::new(kbcli)
+    Command::new(kbcli)
         .arg("--kubeconfig")
         .arg(kubeconfig)
         .args(args)
-        .output()
-        .map_err(|e| format!("kbcli failed: {e}"))?;
+        .output_checked()?;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("kbcli cluster delete failed: {stderr}"));
-    }
-
-    // Remove our external NodePort services if they exist.
-    const NAMESPACE: &str = "default";
-    for suffix in ["postgresql", "redis", "rabbitmq", "qdrant"] {
-        let svc = format!("{name}-{suffix}-external");
+    // Remove our external Service if it exists.
+    if let Ok(service) = service_from_definition(&info.definition) {
+        let svc = expose::external_service_name(service, name);
         let _ = Command::new(kubectl)
             .arg("--kubeconfig")
             .arg(kubeconfig)
             .args(["delete", "svc", &svc, "-n", NAMESPACE, "--ignore-not-found=true"])
-            .output();
+            .output_logged();
     }
+    metrics::unwire(kubectl, name, kubeconfig);
     Ok(())
 }
 
-/// List clusters via kbcli cluster list; parse and print name, type, status.
-pub fn list_clusters(kbcli: &Path, kubeconfig: &Path) -> Result<(), String> {
-    let output = Command::new(kbcli)
+/// Whether `name` matches a shell-style `pattern` (`*` any run of characters, `?` one).
+fn glob_match(pattern: &str, name: &str) -> bool {
+    fn matches(p: &[char], n: &[char]) -> bool {
+        match p.split_first() {
+            None => n.is_empty(),
+            Some(('*', rest)) => (0..=n.len()).any(|i| matches(rest, &n[i..])),
+            Some((&c, rest)) => n
+                .split_first()
+                .is_some_and(|(&first, tail)| (c == '?' || c == first) && matches(rest, tail)),
+        }
+    }
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    matches(&p, &n)
+}
+
+/// Delete every cluster whose name matches `pattern` (`*` for all) after one confirmation
+/// listing the clusters and fdb's external Services that go with them. Protected clusters
+/// are skipped; production contexts are refused, since they need each name typed.
+/// Returns the names of the deleted clusters.
+pub fn delete_clusters(
+    kbcli: &Path,
+    kubectl: &Path,
+    pattern: &str,
+    kubeconfig: &Path,
+    yes: bool,
+) -> Result<Vec<String>, String> {
+    let context = crate::kubeconfig::current_context(kubectl, kubeconfig)?;
+    if config::is_production_context(&context) {
+        return Err(format!(
+            "context \"{context}\" is marked production in fdb.toml; delete clusters one at a time with \
+             `fdb delete <name>`"
+        ));
+    }
+    let mut clusters = list_cluster_infos(kbcli, None, kubeconfig)?;
+    clusters.retain(|c| glob_match(pattern, &c.name));
+    let mut targets = Vec::new();
+    for c in clusters {
+        if is_protected(kubectl, &c.name, kubeconfig)? {
+            eprintln!("Skipping protected cluster \"{}\" (run `fdb unprotect {}` to include it).", c.name, c.name);
+        } else {
+            targets.push(c);
+        }
+    }
+    if targets.is_empty() {
+        return Err(format!("no clusters to delete match \"{pattern}\""));
+    }
+
+    let output = Command::new(kubectl)
         .arg("--kubeconfig")
         .arg(kubeconfig)
-        .args(["cluster", "list"])
-        .output()
-        .map_err(|e| format!("kbcli cluster list failed: {e}"))?;
+        .args(["get", "svc", "-n", NAMESPACE, "-o", "jsonpath={.items[*].metadata.name}"])
+        .output_checked()?;
+    let services = String::from_utf8_lossy(&output.stdout).into_owned();
+    // The external Service fdb created for each cluster, when it still exists.
+    let targets: Vec<(String, Option<String>)> = targets
+        .into_iter()
+        .map(|c| {
+            let svc = service_from_definition(&c.definition)
+                .ok()
+                .map(|service| expose::external_service_name(service, &c.name))
+                .filter(|svc| services.split_whitespace().any(|s| s == svc));
+            (c.name, svc)
+        })
+        .collect();
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("kbcli cluster list failed: {stderr}"));
+    output::say!("This deletes {} cluster(s):", targets.len());
+    for (name, svc) in &targets {
+        match svc {
+            Some(svc) => output::say!("  {name} (and Service {svc})"),
+            None => output::say!("  {name}"),
+        }
+    }
+    if !yes && !load::confirm("Delete them?")? {
+        return Err("aborted".to_string());
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let lines: Vec<&str> = stdout.lines().collect();
-    if lines.is_empty() {
-        println!("No clusters found.");
-        return Ok(());
+    let mut deleted = Vec::new();
+    for (name, svc) in targets {
+        hooks::pre_delete(kbcli, &name, kubeconfig)?;
+        Command::new(kbcli)
+            .arg("--kubeconfig")
+            .arg(kubeconfig)
+            .args(["cluster", "delete", &name, "--auto-approve"])
+            .output_checked()?;
+        if let Some(svc) = svc {
+            let _ = Command::new(kubectl)
+                .arg("--kubeconfig")
+                .arg(kubeconfig)
+                .args(["delete", "svc", &svc, "-n", NAMESPACE, "--ignore-not-found=true"])
+                .output_logged();
+        }
+        metrics::unwire(kubectl, &name, kubeconfig);
+        output::say!("Cluster \"{name}\" deleted.");
+        deleted.push(name);
+    }
+    Ok(deleted)
+}
+
+/// Clusters matching the filter.
+fn filtered_clusters(kbcli: &Path, filter: &ListFilter, kubeconfig: &Path) -> Result<Vec<ClusterInfo>, String> {
+    let mut clusters = list_cluster_infos(kbcli, None, kubeconfig)?;
+    clusters.retain(|c| filter.matches(c));
+    Ok(clusters)
+}
+
+/// External endpoints for the exposure and host columns; None when the view has neither, or
+/// when the Services could not be read (the columns then show "?").
+fn list_endpoints(kubectl: &Path, view: &ListView, kubeconfig: &Path) -> Option<BTreeMap<String, expose::Endpoint>> {
+    if !output::structured() && !view.columns.iter().any(|c| matches!(c, ListColumn::Exposure | ListColumn::Host)) {
+        return None;
     }
-    // Pass through kbcli table as-is for consistency with kbcli output format.
-    for line in lines {
-        println!("{line}");
+    expose::external_endpoints(kubectl, kubeconfig)
+        .inspect_err(|e| eprintln!("warning: could not read external Services: {e}"))
+        .ok()
+}
+
+/// List clusters via kbcli cluster list; parse and print our own table.
+pub fn list_clusters(
+    kbcli: &Path,
+    kubectl: &Path,
+    filter: &ListFilter,
+    view: &ListView,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    let clusters = filtered_clusters(kbcli, filter, kubeconfig)?;
+    let endpoints = list_endpoints(kubectl, view, kubeconfig);
+    if output::structured() {
+        return print_cluster_rows(clusters, view, endpoints.as_ref());
     }
+    print_cluster_table(&clusters, view, endpoints.as_ref());
     Ok(())
 }
+
+/// One cluster in `fdb list -o json|yaml`: every column, whatever `--columns` says.
+#[derive(Serialize)]
+struct ListRow {
+    name: String,
+    namespace: String,
+    engine: String,
+    definition: String,
+    termination_policy: String,
+    status: String,
+    replicas: u32,
+    created: String,
+    /// None when the Services could not be read.
+    exposure: Option<String>,
+    host: Option<String>,
+    labels: BTreeMap<String, String>,
+}
+
+fn print_cluster_rows(
+    mut clusters: Vec<ClusterInfo>,
+    view: &ListView,
+    endpoints: Option<&BTreeMap<String, expose::Endpoint>>,
+) -> Result<(), String> {
+    view.sort(&mut clusters);
+    let now = chrono::Utc::now();
+    let rows: Vec<ListRow> = clusters
+        .into_iter()
+        .map(|c| {
+            let known = |col: ListColumn| endpoints.map(|_| col.value(&c, now, endpoints));
+            ListRow {
+                engine: ListColumn::Engine.value(&c, now, None),
+                exposure: known(ListColumn::Exposure),
+                host: known(ListColumn::Host).filter(|h| h != "-"),
+                name: c.name,
+                namespace: c.namespace,
+                definition: c.definition,
+                termination_policy: c.termination_policy,
+                status: c.status,
+                replicas: c.replicas,
+                created: c.created,
+                labels: c.labels,
+            }
+        })
+        .collect();
+    output::print(&rows)
+}
+
+/// Redraw the cluster table every few seconds until interrupted (`fdb list --watch`).
+/// A failed poll is shown above the last known table instead of ending the watch.
+pub fn watch_clusters(
+    kbcli: &Path,
+    kubectl: &Path,
+    filter: &ListFilter,
+    view: &ListView,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    let mut clusters = Vec::new();
+    loop {
+        let error = match filtered_clusters(kbcli, filter, kubeconfig) {
+            Ok(c) => {
+                clusters = c;
+                None
+            }
+            Err(e) => Some(e),
+        };
+        // Clear the screen and move the cursor home, like `watch`.
+        print!("\x1b[2J\x1b[H");
+        println!(
+            "Every {POLL_INTERVAL_SECS}s: fdb list    {}    (Ctrl-C to stop)\n",
+            chrono::Local::now().format("%H:%M:%S")
+        );
+        if let Some(e) = error {
+            println!("warning: refresh failed: {e}\n");
+        }
+        print_cluster_table(&clusters, view, list_endpoints(kubectl, view, kubeconfig).as_ref());
+        let _ = io::stdout().flush();
+        std::thread::sleep(Duration::from_secs(POLL_INTERVAL_SECS));
+    }
+}
+
+/// A column of `fdb list` (`--columns`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListColumn {
+    Name,
+    Namespace,
+    Engine,
+    Definition,
+    Termination,
+    Status,
+    Replicas,
+    Created,
+    Age,
+    Exposure,
+    Host,
+}
+
+impl ListColumn {
+    const ALL: [(&'static str, ListColumn); 11] = [
+        ("name", ListColumn::Name),
+        ("namespace", ListColumn::Namespace),
+        ("engine", ListColumn::Engine),
+        ("definition", ListColumn::Definition),
+        ("termination", ListColumn::Termination),
+        ("status", ListColumn::Status),
+        ("replicas", ListColumn::Replicas),
+        ("created", ListColumn::Created),
+        ("age", ListColumn::Age),
+        ("exposure", ListColumn::Exposure),
+        ("host", ListColumn::Host),
+    ];
+
+    /// Header and minimum width.
+    pub fn header(self) -> (&'static str, usize) {
+        match self {
+            ListColumn::Name => ("NAME", 24),
+            ListColumn::Namespace => ("NAMESPACE", 12),
+            ListColumn::Engine => ("ENGINE", 10),
+            ListColumn::Definition => ("CLUSTER-DEFINITION", 20),
+            ListColumn::Termination => ("TERMINATION-POLICY", 20),
+            ListColumn::Status => ("STATUS", 12),
+            ListColumn::Replicas => ("REPLICAS", 0),
+            ListColumn::Created => ("CREATED-TIME", 0),
+            ListColumn::Age => ("AGE", 6),
+            ListColumn::Exposure => ("EXPOSURE", 12),
+            ListColumn::Host => ("HOST", 0),
+        }
+    }
+
+    /// Cell for `c`. `endpoints` (from [`expose::external_endpoints`]) fills the exposure and
+    /// host columns; without it they show "?".
+    pub fn value(
+        self,
+        c: &ClusterInfo,
+        now: chrono::DateTime<chrono::Utc>,
+        endpoints: Option<&BTreeMap<String, expose::Endpoint>>,
+    ) -> String {
+        let endpoint = || {
+            let service = service_from_definition(&c.definition).ok()?;
+            endpoints?.get(&expose::external_service_name(service, &c.name))
+        };
+        match self {
+            ListColumn::Name => c.name.clone(),
+            ListColumn::Namespace => c.namespace.clone(),
+            ListColumn::Engine => service_from_definition(&c.definition)
+                .map(|s| s.kbcli_name().to_string())
+                .unwrap_or_else(|_| c.definition.clone()),
+            ListColumn::Definition => c.definition.clone(),
+            ListColumn::Termination => c.termination_policy.clone(),
+            ListColumn::Status => c.status.clone(),
+            ListColumn::Replicas => c.replicas.to_string(),
+            ListColumn::Created => c.created.clone(),
+            ListColumn::Age => created_at(c).map_or_else(|| "-".to_string(), |t| format_age(now - t)),
+            ListColumn::Exposure if endpoints.is_none() => "?".to_string(),
+            ListColumn::Exposure => endpoint().map_or_else(|| "none".to_string(), |e| e.kind.clone()),
+            ListColumn::Host if endpoints.is_none() => "?".to_string(),
+            ListColumn::Host => endpoint().map_or_else(|| "-".to_string(), |e| e.host.clone()),
+        }
+    }
+}
+
+/// Sort order of `fdb list` (`--sort`); default is kbcli's order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListSort {
+    Name,
+    /// Oldest first, like `kubectl --sort-by=.metadata.creationTimestamp`.
+    Age,
+    Status,
+}
+
+/// Columns and order of `fdb list`.
+#[derive(Debug, Clone)]
+pub struct ListView {
+    pub columns: Vec<ListColumn>,
+    pub sort: Option<ListSort>,
+}
+
+impl Default for ListView {
+    fn default() -> Self {
+        ListView {
+            columns: vec![
+                ListColumn::Name,
+                ListColumn::Engine,
+                ListColumn::Status,
+                ListColumn::Replicas,
+                ListColumn::Age,
+                ListColumn::Exposure,
+                ListColumn::Host,
+            ],
+            sort: None,
+        }
+    }
+}
+
+impl ListView {
+    /// Parse `--columns name,engine,status,age`.
+    pub fn set_columns(&mut self, s: &str) -> Result<(), String> {
+        let names: Vec<&str> = ListColumn::ALL.iter().map(|(n, _)| *n).collect();
+        self.columns = s
+            .split(',')
+            .map(|c| {
+                let c = c.trim().to_ascii_lowercase();
+                ListColumn::ALL
+                    .iter()
+                    .find(|(n, _)| *n == c)
+                    .map(|(_, col)| *col)
+                    .ok_or_else(|| format!("unknown column: {c} (available: {})", names.join(", ")))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(())
+    }
+
+    /// Parse `--sort age|name|status`.
+    pub fn set_sort(&mut self, s: &str) -> Result<(), String> {
+        self.sort = Some(match s {
+            "name" => ListSort::Name,
+            "age" => ListSort::Age,
+            "status" => ListSort::Status,
+            _ => return Err(format!("invalid --sort: {s} (use name, age or status)")),
+        });
+        Ok(())
+    }
+
+    fn sort(&self, clusters: &mut [ClusterInfo]) {
+        match self.sort {
+            Some(ListSort::Name) => clusters.sort_by(|a, b| a.name.cmp(&b.name)),
+            Some(ListSort::Age) => clusters.sort_by_key(created_at),
+            Some(ListSort::Status) => clusters.sort_by(|a, b| a.status.cmp(&b.status).then(a.name.cmp(&b.name))),
+            None => {}
+        }
+    }
+}
+
+pub fn created_at(c: &ClusterInfo) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(&c.created)
+        .ok()
+        .map(|t| t.with_timezone(&chrono::Utc))
+}
+
+/// Age like kubectl: "45s", "12m", "5h", "3d".
+pub fn format_age(age: chrono::TimeDelta) -> String {
+    let secs = age.num_seconds().max(0);
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+fn print_cluster_table(
+    clusters: &[ClusterInfo],
+    view: &ListView,
+    endpoints: Option<&BTreeMap<String, expose::Endpoint>>,
+) {
+    if clusters.is_empty() {
+        println!("No clusters found.");
+        return;
+    }
+    let mut clusters = clusters.to_vec();
+    view.sort(&mut clusters);
+    let now = chrono::Utc::now();
+    let rows: Vec<Vec<String>> = clusters
+        .iter()
+        .map(|c| view.columns.iter().map(|col| col.value(c, now, endpoints)).collect())
+        .collect();
+    let widths: Vec<usize> = view
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, col)| {
+            let (header, min) = col.header();
+            rows.iter().map(|r| r[i].len()).chain([header.len(), min]).max().unwrap_or(0)
+        })
+        .collect();
+    let line = |cells: Vec<&str>| {
+        let last = cells.len() - 1;
+        cells
+            .iter()
+            .enumerate()
+            .map(|(i, c)| if i == last { c.to_string() } else { format!("{c:<w$}", w = widths[i]) })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    println!("{}", line(view.columns.iter().map(|c| c.header().0).collect()));
+    for row in &rows {
+        println!("{}", line(row.iter().map(String::as_str).collect()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_glob_patterns() {
+        assert!(glob_match("*", "orders"));
+        assert!(glob_match("test-*", "test-cache"));
+        assert!(!glob_match("test-*", "orders-test"));
+        assert!(glob_match("pg-?", "pg-1"));
+        assert!(!glob_match("pg-?", "pg-10"));
+        assert!(glob_match("orders", "orders"));
+    }
+
+    #[test]
+    fn parses_kubeblocks_1_list() {
+        let clusters = parse_cluster_list(include_str!("../tests/fixtures/kbcli-cluster-list-1.0.json")).unwrap();
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].name, "cache");
+        assert_eq!(clusters[0].definition, "redis");
+        assert_eq!(clusters[0].status, "Creating");
+        assert_eq!(clusters[1].name, "orders");
+        assert_eq!(clusters[1].namespace, "default");
+        assert_eq!(clusters[1].status, "Running");
+        assert_eq!(clusters[1].termination_policy, "Delete");
+        assert_eq!(clusters[1].replicas, 2);
+    }
+
+    #[test]
+    fn finds_cluster_by_name_and_namespace() {
+        let mut clusters = parse_cluster_list(include_str!("../tests/fixtures/kbcli-cluster-list-1.0.json")).unwrap();
+        assert_eq!(find_cluster(&clusters, "orders", "default").map(|c| c.status.as_str()), Some("Running"));
+        assert_eq!(find_cluster(&clusters, "orders", "staging"), None);
+        clusters[0].name = "orders".to_string();
+        clusters[0].namespace = "staging".to_string();
+        assert_eq!(find_cluster(&clusters, "orders", "default").map(|c| c.status.as_str()), Some("Running"));
+    }
+
+    #[test]
+    fn parses_kubeblocks_0_9_cluster() {
+        let clusters = parse_cluster_list(include_str!("../tests/fixtures/kbcli-cluster-list-0.9.json")).unwrap();
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].definition, "postgresql");
+        assert_eq!(clusters[0].status, "Abnormal");
+    }
+
+    #[test]
+    fn falls_back_to_component_definition() {
+        let clusters = parse_cluster_list(include_str!("../tests/fixtures/kbcli-cluster-list-componentdef.json")).unwrap();
+        assert_eq!(clusters[0].definition, "rabbitmq-3.13-1.0.0");
+        assert_eq!(clusters[0].status, "");
+        assert_eq!(service_from_definition(&clusters[0].definition), Ok(ServiceType::RabbitMQ));
+    }
+
+    #[test]
+    fn filters_by_engine_status_and_label() {
+        let clusters = parse_cluster_list(include_str!("../tests/fixtures/kbcli-cluster-list-1.0.json")).unwrap();
+        let names = |filter: &ListFilter| -> Vec<&str> {
+            clusters.iter().filter(|c| filter.matches(c)).map(|c| c.name.as_str()).collect()
+        };
+        assert_eq!(names(&ListFilter::default()), ["cache", "orders"]);
+        let engine = ListFilter {
+            engine: Some(ServiceType::PostgreSQL),
+            ..Default::default()
+        };
+        assert_eq!(names(&engine), ["orders"]);
+        let status = ListFilter {
+            status: Some("creating".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(names(&status), ["cache"]);
+        let mut label = ListFilter::default();
+        label.add_label("team=payments").unwrap();
+        assert_eq!(names(&label), ["orders"]);
+        assert!(label.add_label("team").is_err());
+    }
+
+    #[test]
+    fn selects_columns_and_sorts() {
+        let mut clusters = parse_cluster_list(include_str!("../tests/fixtures/kbcli-cluster-list-1.0.json")).unwrap();
+        let mut view = ListView::default();
+        view.set_columns("name, ENGINE,age").unwrap();
+        assert_eq!(view.columns, [ListColumn::Name, ListColumn::Engine, ListColumn::Age]);
+        assert!(view.set_columns("name,size").unwrap_err().contains("available"));
+        view.set_sort("age").unwrap();
+        view.sort(&mut clusters);
+        assert_eq!(clusters[0].name, "orders");
+        assert!(view.set_sort("size").is_err());
+        assert_eq!(format_age(chrono::TimeDelta::seconds(7200)), "2h");
+    }
+
+    #[test]
+    fn normalizes_quantities() {
+        assert_eq!(kbcli_gi("2Gi", "storage").unwrap(), "2");
+        assert_eq!(kbcli_gi("512Mi", "memory").unwrap(), "0.5");
+        assert_eq!(kbcli_gi("1Ti", "storage").unwrap(), "1024");
+        assert_eq!(kbcli_gi("0.8", "memory").unwrap(), "0.8");
+        assert_eq!(kbcli_cpu("500m").unwrap(), "0.5");
+        assert_eq!(kbcli_cpu("1.5").unwrap(), "1.5");
+    }
+
+    #[test]
+    fn rejects_bad_quantities() {
+        assert!(kbcli_gi("2GB", "storage").unwrap_err().contains("2Gi"));
+        assert!(kbcli_gi("-1Gi", "storage").unwrap_err().contains("greater than zero"));
+        assert!(kbcli_gi("2Pi", "storage").is_err());
+        assert_eq!(kbcli_storage("").unwrap(), None);
+        assert_eq!(kbcli_storage("512Mi").unwrap().as_deref(), Some("0.5"));
+        assert!(kbcli_cpu("0").is_err());
+        assert!(kbcli_cpu("abc").is_err());
+    }
+
+    #[test]
+    fn reads_ops_request_name() {
+        let out = "OpsRequest orders-vscale-x8k2p created successfully, you can view the progress:\n";
+        assert_eq!(ops_request_name(out).as_deref(), Some("orders-vscale-x8k2p"));
+        assert_eq!(ops_request_name("\n"), None);
+    }
+
+    #[test]
+    fn empty_output_is_no_clusters() {
+        assert_eq!(parse_cluster_list("\n").unwrap(), Vec::new());
+        assert_eq!(parse_cluster_list(r#"{"apiVersion":"v1","items":[],"kind":"List"}"#).unwrap(), Vec::new());
+    }
+}
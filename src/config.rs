@@ -1,7 +1,9 @@
 //! Configuration from fdb.toml with defaults.
 
+use crate::autostop;
 use crate::service::ServiceType;
-use serde::Deserialize;
+use crate::suggest;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 const DEFAULT_KUBECONFIG: &str = "~/.kube/config";
@@ -13,7 +15,7 @@ struct KubernetesSection {
 }
 
 /// Deserialize TOML value as string: "2Gi", 2, or 0.8 all become a string for storage/memory.
-fn deser_string_or_number<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+pub(crate) fn deser_string_or_number<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
@@ -32,9 +34,22 @@ where
     }))
 }
 
+/// Deserialize a duration: "15m", or a number of seconds.
+fn deser_duration<'de, D>(deserializer: D) -> Result<Option<std::time::Duration>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deser_string_or_number(deserializer)?
+        .map(|s| parse_duration(&s).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+/// `[postgresql]`, `[redis]` and the other engine sections. The engine-specific keys (`pitr`,
+/// `backup`, `acl`, `jetstream`) parse under any engine; `KNOWN_KEYS` reports them where they do
+/// not belong.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "kebab-case")]
-struct PostgresqlSection {
+struct EngineSection {
     replicas: Option<u32>,
     #[serde(default, deserialize_with = "deser_string_or_number")]
     storage: Option<String>,
@@ -42,35 +57,254 @@ struct PostgresqlSection {
     cpu: Option<String>,
     #[serde(default, deserialize_with = "deser_string_or_number")]
     memory: Option<String>,
+    /// How long `fdb create` waits for the cluster to be Running.
+    #[serde(default, deserialize_with = "deser_duration")]
+    timeout: Option<std::time::Duration>,
+    /// PostgreSQL: enable continuous WAL archiving on create, for `fdb restore --at`.
+    #[serde(default)]
+    pitr: bool,
+    /// Scheduled backups turned on at create (`[postgresql.backup]`).
+    backup: Option<BackupSchedule>,
+    /// Redis: restricted users created after the cluster is running (`[[redis.acl]]`).
+    #[serde(default)]
+    acl: Vec<RedisAclUser>,
+    /// NATS: enable JetStream.
+    #[serde(default)]
+    jetstream: bool,
+    /// Overrides `[metrics] enabled` for this engine.
+    metrics: Option<bool>,
+}
+
+/// Automatic backups of every new cluster of an engine (`[<engine>.backup]`), set on the
+/// cluster's KubeBlocks backup policy.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BackupSchedule {
+    /// Cron expression in UTC, e.g. "0 3 * * *".
+    pub schedule: String,
+    /// How long backups are kept, e.g. "7d" or "12h"; the policy's default when unset.
+    pub retention: Option<String>,
+    /// Backup method, e.g. "volume-snapshot"; the policy's default when unset.
+    pub method: Option<String>,
+}
+
+impl BackupSchedule {
+    /// Reject schedules KubeBlocks would only refuse after the cluster exists.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.schedule.split_whitespace().count() != 5 {
+            return Err(format!(
+                "invalid backup schedule \"{}\" (expected a 5-field cron expression, e.g. \"0 3 * * *\")",
+                self.schedule
+            ));
+        }
+        if let Some(retention) = &self.retention {
+            let number = retention.strip_suffix('d').or_else(|| retention.strip_suffix('h'));
+            if number.and_then(|n| n.parse::<u32>().ok()).filter(|&n| n > 0).is_none() {
+                return Err(format!("invalid backup retention \"{retention}\" (use days or hours, e.g. 7d or 12h)"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Restricted Redis user created after the cluster is running (`[[redis.acl]]`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RedisAclUser {
+    pub name: String,
+    /// ACL command rules, whitespace-separated, e.g. "+@read +@write -@dangerous".
+    #[serde(default = "default_acl_commands")]
+    pub commands: String,
+    /// Key patterns, whitespace-separated, e.g. "app:* cache:*".
+    #[serde(default = "default_acl_keys")]
+    pub keys: String,
+}
+
+pub fn default_acl_commands() -> String {
+    "+@all -@dangerous".to_string()
+}
+
+pub fn default_acl_keys() -> String {
+    "*".to_string()
+}
+
+/// How Prometheus finds the exporters (`[metrics] mode`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MetricsMode {
+    /// Headless Service with `prometheus.io/*` scrape annotations.
+    #[default]
+    Annotations,
+    /// prometheus-operator PodMonitor.
+    PodMonitor,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct MetricsSection {
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default)]
+    mode: MetricsMode,
+    /// Labels on the PodMonitor, for the Prometheus `podMonitorSelector`.
+    #[serde(default)]
+    labels: std::collections::BTreeMap<String, String>,
+    interval: Option<String>,
+}
+
+/// Exporter and scrape wiring for a new cluster; kept in the pending setup for `fdb wait`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct MetricsConfig {
+    pub mode: MetricsMode,
+    pub labels: std::collections::BTreeMap<String, String>,
+    pub interval: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct AlertsSection {
+    #[serde(default)]
+    enabled: bool,
+    /// Labels on the PrometheusRule, for the Prometheus `ruleSelector`.
+    #[serde(default)]
+    labels: std::collections::BTreeMap<String, String>,
+    severity: Option<String>,
+}
+
+/// PrometheusRule to install for a new cluster (`[alerts]`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AlertsConfig {
+    pub labels: std::collections::BTreeMap<String, String>,
+    /// `severity` label on every alert.
+    pub severity: String,
+}
+
+/// Log agent fdb deploys (`[log-shipping] agent`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogAgent {
+    /// promtail pushing to Loki.
+    #[default]
+    Promtail,
+    /// fluent-bit writing to Elasticsearch/OpenSearch.
+    FluentBit,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct LogShippingSection {
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default)]
+    agent: LogAgent,
+    url: Option<String>,
+    #[serde(default)]
+    labels: std::collections::BTreeMap<String, String>,
+}
+
+/// Log agent to deploy or update when a cluster is created (`[log-shipping]`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LogShippingConfig {
+    pub agent: LogAgent,
+    /// Loki push URL (promtail) or Elasticsearch URL (fluent-bit).
+    pub url: String,
+    /// Static labels (Loki) or fields (Elasticsearch) added to every line.
+    pub labels: std::collections::BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "kebab-case")]
-struct RedisSection {
-    replicas: Option<u32>,
-    #[serde(default, deserialize_with = "deser_string_or_number")]
-    storage: Option<String>,
-    #[serde(default, deserialize_with = "deser_string_or_number")]
-    cpu: Option<String>,
-    #[serde(default, deserialize_with = "deser_string_or_number")]
-    memory: Option<String>,
+struct VaultSection {
+    address: Option<String>,
+    mount: Option<String>,
+    path: Option<String>,
+    secret_store: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "kebab-case")]
-struct RabbitmqSection {
-    replicas: Option<u32>,
-    #[serde(default, deserialize_with = "deser_string_or_number")]
-    storage: Option<String>,
-    #[serde(default, deserialize_with = "deser_string_or_number")]
-    cpu: Option<String>,
-    #[serde(default, deserialize_with = "deser_string_or_number")]
-    memory: Option<String>,
+struct SecuritySection {
+    password_length: Option<usize>,
+    password_charset: Option<String>,
+    /// Create a non-superuser account on create and show it instead of the admin one.
+    #[serde(default)]
+    app_account: bool,
+    app_user: Option<String>,
+}
+
+/// Monthly prices for `fdb report` (`[cost]`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct CostSection {
+    cpu_per_core: Option<f64>,
+    memory_per_gib: Option<f64>,
+    storage_per_gib: Option<f64>,
+    currency: Option<String>,
+}
+
+/// Tool resolution settings (`[tools]`).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ToolsConfig {
+    /// kubectl release `fdb tools upgrade` installs instead of the latest, e.g. "v1.31.2".
+    pub kubectl: Option<String>,
+    /// kbcli release tag `fdb tools upgrade` installs instead of the latest, e.g. "v1.0.0".
+    pub kbcli: Option<String>,
+    /// Never download tools, like `--offline`.
+    #[serde(default)]
+    pub offline: bool,
+    /// Local kubectl binary to install from when kubectl is missing.
+    pub kubectl_file: Option<String>,
+    /// Local kbcli release tarball (or binary) to install from when kbcli is missing.
+    pub kbcli_file: Option<String>,
+}
+
+/// Commands fdb runs around create and delete (`[hooks]`).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct HooksConfig {
+    /// Run after a cluster is Running and set up, with its connection details in the environment.
+    pub post_create: Option<String>,
+    /// Run before a cluster is deleted; a failure keeps the cluster.
+    pub pre_delete: Option<String>,
+    /// Time limit per hook, e.g. "2m"; default 5m.
+    pub timeout: Option<String>,
 }
 
+/// `fdb mcp` settings (`[mcp]`).
+#[derive(Debug, Clone, Default, Deserialize)]
+struct McpSection {
+    /// Destructive tools agents may call, e.g. ["delete"].
+    #[serde(default)]
+    allow: Vec<String>,
+}
+
+/// Team scoping (`[project]`).
+#[derive(Debug, Clone, Deserialize)]
+struct ProjectSection {
+    /// Prepended as `<prefix>-` to every cluster name; list and delete only see such clusters.
+    prefix: Option<String>,
+}
+
+/// Per kube context settings (`[contexts.<name>]`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ContextSection {
+    /// Destructive commands require typing the cluster name, even with --yes.
+    #[serde(default)]
+    production: bool,
+}
+
+/// Named cluster configuration (`[clusters.<name>]`) for `fdb create --profile` / `fdb up`.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "kebab-case")]
-struct QdrantSection {
+struct ProfileSection {
+    /// Cluster name; defaults to the section name.
+    name: Option<String>,
+    engine: String,
     replicas: Option<u32>,
     #[serde(default, deserialize_with = "deser_string_or_number")]
     storage: Option<String>,
@@ -78,15 +312,84 @@ struct QdrantSection {
     cpu: Option<String>,
     #[serde(default, deserialize_with = "deser_string_or_number")]
     memory: Option<String>,
+    namespace: Option<String>,
+    version: Option<String>,
+    expose: Option<String>,
+    /// Off-hours window, e.g. "19:00-08:00, weekends".
+    auto_stop: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 struct FdbToml {
     kubernetes: Option<KubernetesSection>,
-    postgresql: Option<PostgresqlSection>,
-    redis: Option<RedisSection>,
-    rabbitmq: Option<RabbitmqSection>,
-    qdrant: Option<QdrantSection>,
+    vault: Option<VaultSection>,
+    security: Option<SecuritySection>,
+    cost: Option<CostSection>,
+    tools: Option<ToolsConfig>,
+    hooks: Option<HooksConfig>,
+    project: Option<ProjectSection>,
+    mcp: Option<McpSection>,
+    metrics: Option<MetricsSection>,
+    alerts: Option<AlertsSection>,
+    #[serde(rename = "log-shipping")]
+    log_shipping: Option<LogShippingSection>,
+    #[serde(default)]
+    contexts: std::collections::HashMap<String, ContextSection>,
+    #[serde(default)]
+    clusters: std::collections::BTreeMap<String, ProfileSection>,
+    postgresql: Option<EngineSection>,
+    redis: Option<EngineSection>,
+    rabbitmq: Option<EngineSection>,
+    qdrant: Option<EngineSection>,
+    mongodb: Option<EngineSection>,
+    kafka: Option<EngineSection>,
+    elasticsearch: Option<EngineSection>,
+    opensearch: Option<EngineSection>,
+    milvus: Option<EngineSection>,
+    weaviate: Option<EngineSection>,
+    mariadb: Option<EngineSection>,
+    etcd: Option<EngineSection>,
+    pulsar: Option<EngineSection>,
+    nats: Option<EngineSection>,
+    minio: Option<EngineSection>,
+    cassandra: Option<EngineSection>,
+    scylladb: Option<EngineSection>,
+    cockroachdb: Option<EngineSection>,
+    tidb: Option<EngineSection>,
+    influxdb: Option<EngineSection>,
+    memcached: Option<EngineSection>,
+    neo4j: Option<EngineSection>,
+}
+
+impl FdbToml {
+    /// The section configuring `service`; custom engines are sized by their services.d file.
+    fn engine(&self, service: ServiceType) -> Option<&EngineSection> {
+        match service {
+            ServiceType::PostgreSQL => self.postgresql.as_ref(),
+            ServiceType::Redis => self.redis.as_ref(),
+            ServiceType::RabbitMQ => self.rabbitmq.as_ref(),
+            ServiceType::Qdrant => self.qdrant.as_ref(),
+            ServiceType::MongoDB => self.mongodb.as_ref(),
+            ServiceType::Kafka => self.kafka.as_ref(),
+            ServiceType::Elasticsearch => self.elasticsearch.as_ref(),
+            ServiceType::OpenSearch => self.opensearch.as_ref(),
+            ServiceType::Milvus => self.milvus.as_ref(),
+            ServiceType::Weaviate => self.weaviate.as_ref(),
+            ServiceType::MariaDB => self.mariadb.as_ref(),
+            ServiceType::Etcd => self.etcd.as_ref(),
+            ServiceType::Pulsar => self.pulsar.as_ref(),
+            ServiceType::Nats => self.nats.as_ref(),
+            ServiceType::MinIO => self.minio.as_ref(),
+            ServiceType::Cassandra => self.cassandra.as_ref(),
+            ServiceType::ScyllaDB => self.scylladb.as_ref(),
+            ServiceType::CockroachDB => self.cockroachdb.as_ref(),
+            ServiceType::TiDB => self.tidb.as_ref(),
+            ServiceType::InfluxDB => self.influxdb.as_ref(),
+            ServiceType::Memcached => self.memcached.as_ref(),
+            ServiceType::Neo4j => self.neo4j.as_ref(),
+            ServiceType::Custom(_) => None,
+        }
+    }
 }
 
 /// Merged configuration (fdb.toml + CLI overrides).
@@ -97,6 +400,113 @@ pub struct Config {
     pub storage: String,
     pub cpu: String,
     pub memory: String,
+    /// Engine version for kbcli; None uses kbcli's default.
+    pub version: Option<String>,
+    /// How long create waits for Running.
+    pub timeout: std::time::Duration,
+    /// Redis only: ACL users to provision after create.
+    pub redis_acl_users: Vec<RedisAclUser>,
+    /// Name of the app-scoped account to create (`[security] app-account = true`).
+    pub app_account: Option<String>,
+    /// PostgreSQL only: turn on continuous backup for point-in-time recovery.
+    pub pitr: bool,
+    /// Scheduled backups to turn on after create; None without an `[<engine>.backup]` section.
+    pub backup: Option<BackupSchedule>,
+    /// NATS only: enable JetStream, persisted on the cluster's storage.
+    pub jetstream: bool,
+    /// Exporters and Prometheus scrape config to set up; None when metrics are off.
+    pub metrics: Option<MetricsConfig>,
+    /// Alerting rules to install; None when `[alerts]` is off.
+    pub alerts: Option<AlertsConfig>,
+    /// Log agent to deploy; None when `[log-shipping]` is off.
+    pub log_shipping: Option<LogShippingConfig>,
+}
+
+/// A `[clusters.<name>]` profile. Size fields left out fall back to the engine section and
+/// defaults; command-line flags still win over the profile.
+#[derive(Debug, Clone)]
+pub struct Profile {
+    /// Section name, as given to `fdb up`.
+    pub profile: String,
+    /// Cluster name: `name = ...` in the section, or the section name.
+    pub name: String,
+    pub engine: ServiceType,
+    pub replicas: Option<u32>,
+    pub storage: Option<String>,
+    pub cpu: Option<String>,
+    pub memory: Option<String>,
+    pub version: Option<String>,
+    /// `expose = "none"` -> Some(true).
+    pub skip_expose: Option<bool>,
+    /// `auto-stop` schedule, checked by `fdb gc`.
+    pub auto_stop: Option<String>,
+}
+
+/// Where generated credentials are published (`[vault]` section).
+#[derive(Debug, Clone)]
+pub struct VaultConfig {
+    /// Vault server URL; `VAULT_ADDR` is used when not set in fdb.toml.
+    pub address: String,
+    /// KV v2 secrets engine mount.
+    pub mount: String,
+    /// Path inside the mount; `{cluster}` is replaced with the cluster name.
+    pub path: String,
+    /// SecretStore name referenced by generated ExternalSecret manifests.
+    pub secret_store: String,
+}
+
+/// Rules for passwords fdb generates (`[security]` section).
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    pub length: usize,
+    /// Characters passwords are drawn from.
+    pub charset: String,
+}
+
+/// Monthly price per requested core / GiB, used to estimate cost in `fdb report`.
+#[derive(Debug, Clone)]
+pub struct CostRates {
+    pub cpu_per_core: f64,
+    pub memory_per_gib: f64,
+    pub storage_per_gib: f64,
+    pub currency: String,
+}
+
+const DEFAULT_APP_USER: &str = "app";
+const DEFAULT_PASSWORD_LENGTH: usize = 32;
+const MIN_PASSWORD_LENGTH: usize = 12;
+const MAX_PASSWORD_LENGTH: usize = 128;
+
+/// Characters for a named `password-charset`. Symbols are limited to ones that need no quoting
+/// in URLs, SQL literals or Redis ACL rules.
+fn password_charset(name: &str) -> Result<String, String> {
+    const LOWER: &str = "abcdefghijklmnopqrstuvwxyz";
+    const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    const DIGITS: &str = "0123456789";
+    match name {
+        "hex" => Ok(format!("{DIGITS}abcdef")),
+        "alphanumeric" => Ok(format!("{LOWER}{UPPER}{DIGITS}")),
+        "symbols" => Ok(format!("{LOWER}{UPPER}{DIGITS}-_.~!*")),
+        _ => Err(format!(
+            "unknown [security] password-charset: {name} (supported: hex, alphanumeric, symbols)"
+        )),
+    }
+}
+
+/// Values given on the command line; they win over fdb.toml and defaults.
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    pub kubeconfig: Option<PathBuf>,
+    pub replicas: Option<u32>,
+    pub storage: Option<String>,
+    pub cpu: Option<String>,
+    pub memory: Option<String>,
+    pub version: Option<String>,
+    /// `--timeout`.
+    pub timeout: Option<std::time::Duration>,
+    pub redis_acl_user: Option<RedisAclUser>,
+    /// `--jetstream` (NATS).
+    pub jetstream: bool,
 }
 
 fn expand_tilde(path: &str) -> PathBuf {
@@ -113,117 +523,139 @@ fn expand_tilde(path: &str) -> PathBuf {
     PathBuf::from(path)
 }
 
-/// Default (replicas, storage, cpu, memory) per service type.
-fn defaults_for_service(service: ServiceType) -> (u32, String, String, String) {
-    match service {
-        ServiceType::PostgreSQL => (1, "2Gi".to_string(), "0.5".to_string(), "0.8Gi".to_string()),
-        ServiceType::Redis => (1, "1Gi".to_string(), "0.5".to_string(), "0.5Gi".to_string()),
-        ServiceType::RabbitMQ => (1, "2Gi".to_string(), "0.5".to_string(), "1Gi".to_string()),
-        ServiceType::Qdrant => (1, "5Gi".to_string(), "0.5".to_string(), "1Gi".to_string()),
+/// Parse a duration like "90s", "30m", "1h", "2d" (bare number = seconds).
+pub fn parse_duration(s: &str) -> Result<std::time::Duration, String> {
+    let s = s.trim();
+    let (num, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => (&s[..i], &s[i..]),
+        None => (s, "s"),
+    };
+    let n: u64 = num
+        .parse()
+        .map_err(|_| format!("invalid duration: {s} (expected e.g. 30m, 1h, 2d)"))?;
+    let secs = match unit {
+        "s" => n,
+        "m" => n * 60,
+        "h" => n * 3600,
+        "d" => n * 86400,
+        _ => return Err(format!("invalid duration unit in {s} (use s, m, h or d)")),
+    };
+    Ok(std::time::Duration::from_secs(secs))
+}
+
+/// fdb's own directory: $FDB_HOME or ~/.fdb.
+pub fn fdb_home() -> PathBuf {
+    if let Ok(home) = std::env::var("FDB_HOME") {
+        return PathBuf::from(home);
     }
+    expand_tilde("~/.fdb")
 }
 
-/// Load config from fdb.toml (current dir then ~/.fdb/fdb.toml), then apply CLI overrides.
-pub fn load_config(
-    service: ServiceType,
-    kubeconfig_override: Option<PathBuf>,
-    replicas_override: Option<u32>,
-    storage_override: Option<String>,
-    cpu_override: Option<String>,
-    memory_override: Option<String>,
-) -> Config {
+/// Load config from the merged fdb.toml layers, then apply CLI overrides.
+pub fn load_config(service: ServiceType, overrides: Overrides) -> Config {
     let mut kubeconfig = expand_tilde(DEFAULT_KUBECONFIG);
-    let (mut replicas, mut storage, mut cpu, mut memory) = defaults_for_service(service);
+    let defaults = service.spec().defaults();
+    let mut replicas = defaults.0;
+    let mut storage = defaults.1.to_string();
+    let mut cpu = defaults.2.to_string();
+    let mut memory = defaults.3.to_string();
+    let mut timeout = crate::cluster::DEFAULT_TIMEOUT;
+    let mut redis_acl_users = Vec::new();
+    let mut app_account = None;
+    let mut pitr = false;
+    let mut backup = None;
+    let mut jetstream = false;
+    let mut metrics = None;
+    let mut alerts = None;
+    let mut log_shipping = None;
 
     if let Some(toml_config) = load_fdb_toml() {
+        let engine = toml_config.engine(service).cloned();
+        let section = toml_config.metrics.unwrap_or_default();
+        let engine_override = engine.as_ref().and_then(|e| e.metrics);
+        if engine_override.unwrap_or(section.enabled) {
+            metrics = Some(MetricsConfig {
+                mode: section.mode,
+                labels: section.labels,
+                interval: section.interval.unwrap_or_else(|| "30s".to_string()),
+            });
+        }
+        if let Some(section) = toml_config.alerts
+            && section.enabled
+        {
+            alerts = Some(AlertsConfig {
+                labels: section.labels,
+                severity: section.severity.unwrap_or_else(|| "warning".to_string()),
+            });
+        }
+        if let Some(section) = toml_config.log_shipping
+            && section.enabled
+        {
+            log_shipping = Some(LogShippingConfig {
+                agent: section.agent,
+                url: section.url.unwrap_or_default(),
+                labels: section.labels,
+            });
+        }
+        if let Some(security) = toml_config.security
+            && security.app_account
+        {
+            app_account = Some(security.app_user.unwrap_or_else(|| DEFAULT_APP_USER.to_string()));
+        }
         if let Some(k8s) = toml_config.kubernetes {
             if let Some(k) = k8s.kubeconfig {
                 kubeconfig = expand_tilde(&k);
             }
         }
-        match service {
-            ServiceType::PostgreSQL => {
-                if let Some(pg) = toml_config.postgresql {
-                    if let Some(r) = pg.replicas {
-                        replicas = r;
-                    }
-                    if let Some(s) = pg.storage {
-                        storage = s;
-                    }
-                    if let Some(c) = pg.cpu {
-                        cpu = c;
-                    }
-                    if let Some(m) = pg.memory {
-                        memory = m;
-                    }
-                }
+        if let Some(e) = engine {
+            if let Some(v) = e.replicas {
+                replicas = v;
             }
-            ServiceType::Redis => {
-                if let Some(r) = toml_config.redis {
-                    if let Some(v) = r.replicas {
-                        replicas = v;
-                    }
-                    if let Some(s) = r.storage {
-                        storage = s;
-                    }
-                    if let Some(c) = r.cpu {
-                        cpu = c;
-                    }
-                    if let Some(m) = r.memory {
-                        memory = m;
-                    }
-                }
+            if let Some(s) = e.storage {
+                storage = s;
             }
-            ServiceType::RabbitMQ => {
-                if let Some(r) = toml_config.rabbitmq {
-                    if let Some(v) = r.replicas {
-                        replicas = v;
-                    }
-                    if let Some(s) = r.storage {
-                        storage = s;
-                    }
-                    if let Some(c) = r.cpu {
-                        cpu = c;
-                    }
-                    if let Some(m) = r.memory {
-                        memory = m;
-                    }
-                }
+            if let Some(c) = e.cpu {
+                cpu = c;
             }
-            ServiceType::Qdrant => {
-                if let Some(q) = toml_config.qdrant {
-                    if let Some(v) = q.replicas {
-                        replicas = v;
-                    }
-                    if let Some(s) = q.storage {
-                        storage = s;
-                    }
-                    if let Some(c) = q.cpu {
-                        cpu = c;
-                    }
-                    if let Some(m) = q.memory {
-                        memory = m;
-                    }
-                }
+            if let Some(m) = e.memory {
+                memory = m;
+            }
+            if let Some(t) = e.timeout {
+                timeout = t;
+            }
+            backup = e.backup;
+            match service {
+                ServiceType::PostgreSQL => pitr = e.pitr,
+                ServiceType::Redis => redis_acl_users = e.acl,
+                ServiceType::Nats => jetstream = e.jetstream,
+                _ => {}
             }
         }
     }
 
-    if let Some(k) = kubeconfig_override {
+    if let Some(k) = overrides.kubeconfig {
         kubeconfig = k;
     }
-    if let Some(r) = replicas_override {
+    if let Some(r) = overrides.replicas {
         replicas = r;
     }
-    if let Some(s) = storage_override {
+    if let Some(s) = overrides.storage {
         storage = s;
     }
-    if let Some(c) = cpu_override {
+    if let Some(c) = overrides.cpu {
         cpu = c;
     }
-    if let Some(m) = memory_override {
+    if let Some(m) = overrides.memory {
         memory = m;
     }
+    if let Some(t) = overrides.timeout {
+        timeout = t;
+    }
+    if let Some(u) = overrides.redis_acl_user {
+        redis_acl_users.retain(|existing| existing.name != u.name);
+        redis_acl_users.push(u);
+    }
+    jetstream |= overrides.jetstream;
 
     Config {
         kubeconfig,
@@ -231,6 +663,16 @@ pub fn load_config(
         storage,
         cpu,
         memory,
+        version: overrides.version,
+        timeout,
+        redis_acl_users,
+        app_account,
+        pitr,
+        backup,
+        jetstream,
+        metrics,
+        alerts,
+        log_shipping,
     }
 }
 
@@ -247,21 +689,520 @@ pub fn load_kubeconfig(kubeconfig_override: Option<PathBuf>) -> PathBuf {
     kubeconfig_override.unwrap_or(kubeconfig)
 }
 
-fn load_fdb_toml() -> Option<FdbToml> {
-    if let Ok(dir) = std::env::current_dir() {
-        let local = dir.join("fdb.toml");
-        if local.is_file() {
-            if let Ok(content) = std::fs::read_to_string(&local) {
-                if let Ok(cfg) = toml::from_str(&content) {
-                    return Some(cfg);
+/// Names of all `[clusters.<name>]` profiles, sorted.
+pub fn profile_names() -> Vec<String> {
+    load_fdb_toml().map(|t| t.clusters.into_keys().collect()).unwrap_or_default()
+}
+
+/// Look up `[clusters.<name>]`; unknown names get the closest defined profile suggested.
+pub fn load_profile(name: &str) -> Result<Profile, String> {
+    let profiles = load_fdb_toml().map(|t| t.clusters).unwrap_or_default();
+    let Some(section) = profiles.get(name) else {
+        let hint = match suggest::did_you_mean(name, profiles.keys().map(String::as_str)) {
+            Some(close) => format!("; did you mean `{close}`?"),
+            None if profiles.is_empty() => " (no [clusters.<name>] sections in fdb.toml)".to_string(),
+            None => String::new(),
+        };
+        return Err(format!("unknown profile \"{name}\"{hint}"));
+    };
+    if let Some(ns) = section.namespace.as_deref()
+        && ns != "default"
+    {
+        return Err(format!(
+            "[clusters.{name}] namespace = \"{ns}\": fdb manages clusters in the default namespace only"
+        ));
+    }
+    let skip_expose = match section.expose.as_deref() {
+        None => None,
+        Some("nodeport") => Some(false),
+        Some("none") => Some(true),
+        Some(other) => return Err(format!("[clusters.{name}] expose = \"{other}\": use nodeport or none")),
+    };
+    if let Some(spec) = section.auto_stop.as_deref() {
+        autostop::Schedule::parse(spec).map_err(|e| format!("[clusters.{name}] {e}"))?;
+    }
+    Ok(Profile {
+        profile: name.to_string(),
+        name: qualify(section.name.as_deref().unwrap_or(name)),
+        engine: section
+            .engine
+            .parse()
+            .map_err(|e| format!("[clusters.{name}] engine: {e}"))?,
+        replicas: section.replicas,
+        storage: section.storage.clone(),
+        cpu: section.cpu.clone(),
+        memory: section.memory.clone(),
+        version: section.version.clone(),
+        skip_expose,
+        auto_stop: section.auto_stop.clone(),
+    })
+}
+
+/// Whether fdb.toml marks the kube context as `production = true`.
+pub fn is_production_context(context: &str) -> bool {
+    load_fdb_toml()
+        .and_then(|t| t.contexts.get(context).map(|c| c.production))
+        .unwrap_or(false)
+}
+
+/// Load [vault] settings; address falls back to `VAULT_ADDR`.
+pub fn load_vault_config() -> Result<VaultConfig, String> {
+    let section = load_fdb_toml().and_then(|t| t.vault);
+    let (address, mount, path, secret_store) = match section {
+        Some(v) => (v.address, v.mount, v.path, v.secret_store),
+        None => (None, None, None, None),
+    };
+    let address = address
+        .or_else(|| std::env::var("VAULT_ADDR").ok())
+        .ok_or("vault address not set ([vault] address in fdb.toml or VAULT_ADDR)")?;
+    Ok(VaultConfig {
+        address: address.trim_end_matches('/').to_string(),
+        mount: mount.unwrap_or_else(|| "secret".to_string()),
+        path: path.unwrap_or_else(|| "fdb/{cluster}".to_string()),
+        secret_store: secret_store.unwrap_or_else(|| "vault-backend".to_string()),
+    })
+}
+
+/// Load `[security]` password settings; defaults to 32 hex characters.
+pub fn load_password_policy() -> Result<PasswordPolicy, String> {
+    let section = load_fdb_toml().and_then(|t| t.security);
+    let (length, charset) = match section {
+        Some(s) => (s.password_length, s.password_charset),
+        None => (None, None),
+    };
+    let length = length.unwrap_or(DEFAULT_PASSWORD_LENGTH);
+    if !(MIN_PASSWORD_LENGTH..=MAX_PASSWORD_LENGTH).contains(&length) {
+        return Err(format!(
+            "[security] password-length must be between {MIN_PASSWORD_LENGTH} and {MAX_PASSWORD_LENGTH}, got {length}"
+        ));
+    }
+    Ok(PasswordPolicy {
+        length,
+        charset: password_charset(charset.as_deref().unwrap_or("hex"))?,
+    })
+}
+
+/// Load `[cost]` rates; None when the section is absent. Missing rates count as 0.
+pub fn load_cost_rates() -> Option<CostRates> {
+    let cost = load_fdb_toml()?.cost?;
+    Some(CostRates {
+        cpu_per_core: cost.cpu_per_core.unwrap_or(0.0),
+        memory_per_gib: cost.memory_per_gib.unwrap_or(0.0),
+        storage_per_gib: cost.storage_per_gib.unwrap_or(0.0),
+        currency: cost.currency.unwrap_or_else(|| "$".to_string()),
+    })
+}
+
+/// Overlay `top` onto `base` key by key: tables merge recursively, any other value
+/// (including arrays such as `[[redis.acl]]`) replaces the one below it.
+fn merge(base: &mut toml::Table, top: toml::Table) {
+    for (key, value) in top {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(below)), toml::Value::Table(above)) => merge(below, above),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Keys of the `[<engine>.backup]` sections, for the engines whose KubeBlocks addon ships a
+/// backup policy.
+const BACKUP_KEYS: &[&str] = &["schedule", "retention", "method"];
+
+/// Keys each fdb.toml section accepts. Sections not listed here (`[contexts]`, `[clusters]`)
+/// take any name; their entries are checked against `<section>.*`.
+const KNOWN_KEYS: &[(&str, &[&str])] = &[
+    (
+        "",
+        &[
+            "strict", "kubernetes", "vault", "security", "cost", "tools", "hooks", "project", "mcp", "metrics", "alerts", "log-shipping", "contexts", "clusters",
+            "postgresql", "redis", "rabbitmq", "qdrant", "mongodb", "kafka", "elasticsearch", "opensearch", "milvus", "weaviate", "mariadb", "etcd", "pulsar", "nats", "minio", "cassandra", "scylladb", "cockroachdb", "tidb", "influxdb", "memcached", "neo4j",
+        ],
+    ),
+    ("kubernetes", &["kubeconfig"]),
+    ("vault", &["address", "mount", "path", "secret-store"]),
+    ("security", &["password-length", "password-charset", "app-account", "app-user"]),
+    ("cost", &["cpu-per-core", "memory-per-gib", "storage-per-gib", "currency"]),
+    ("tools", &["kubectl", "kbcli", "offline", "kubectl-file", "kbcli-file"]),
+    ("hooks", &["post-create", "pre-delete", "timeout"]),
+    ("project", &["prefix"]),
+    ("mcp", &["allow"]),
+    ("metrics", &["enabled", "mode", "labels", "interval"]),
+    ("alerts", &["enabled", "labels", "severity"]),
+    ("log-shipping", &["enabled", "agent", "url", "labels"]),
+    ("contexts.*", &["production"]),
+    (
+        "clusters.*",
+        &["name", "engine", "replicas", "storage", "cpu", "memory", "namespace", "version", "expose", "auto-stop"],
+    ),
+    ("postgresql", &["replicas", "storage", "cpu", "memory", "timeout", "pitr", "backup", "metrics"]),
+    ("postgresql.backup", BACKUP_KEYS),
+    ("redis", &["replicas", "storage", "cpu", "memory", "timeout", "acl", "backup", "metrics"]),
+    ("redis.backup", BACKUP_KEYS),
+    ("redis.acl", &["name", "commands", "keys"]),
+    ("rabbitmq", &["replicas", "storage", "cpu", "memory", "timeout", "metrics"]),
+    ("qdrant", &["replicas", "storage", "cpu", "memory", "timeout", "backup", "metrics"]),
+    ("qdrant.backup", BACKUP_KEYS),
+    ("mongodb", &["replicas", "storage", "cpu", "memory", "timeout", "backup", "metrics"]),
+    ("mongodb.backup", BACKUP_KEYS),
+    ("kafka", &["replicas", "storage", "cpu", "memory", "timeout", "metrics"]),
+    ("elasticsearch", &["replicas", "storage", "cpu", "memory", "timeout", "backup", "metrics"]),
+    ("elasticsearch.backup", BACKUP_KEYS),
+    ("opensearch", &["replicas", "storage", "cpu", "memory", "timeout", "metrics"]),
+    ("milvus", &["replicas", "storage", "cpu", "memory", "timeout", "metrics"]),
+    ("weaviate", &["replicas", "storage", "cpu", "memory", "timeout", "metrics"]),
+    ("mariadb", &["replicas", "storage", "cpu", "memory", "timeout", "backup", "metrics"]),
+    ("mariadb.backup", BACKUP_KEYS),
+    ("etcd", &["replicas", "storage", "cpu", "memory", "timeout", "backup", "metrics"]),
+    ("etcd.backup", BACKUP_KEYS),
+    ("pulsar", &["replicas", "storage", "cpu", "memory", "timeout", "metrics"]),
+    ("nats", &["replicas", "storage", "cpu", "memory", "timeout", "jetstream", "metrics"]),
+    ("minio", &["replicas", "storage", "cpu", "memory", "timeout", "metrics"]),
+    ("cassandra", &["replicas", "storage", "cpu", "memory", "timeout", "metrics"]),
+    ("scylladb", &["replicas", "storage", "cpu", "memory", "timeout", "metrics"]),
+    ("cockroachdb", &["replicas", "storage", "cpu", "memory", "timeout", "metrics"]),
+    ("tidb", &["replicas", "storage", "cpu", "memory", "timeout", "metrics"]),
+    ("influxdb", &["replicas", "storage", "cpu", "memory", "timeout", "metrics"]),
+    ("memcached", &["replicas", "storage", "cpu", "memory", "timeout", "metrics"]),
+    ("neo4j", &["replicas", "storage", "cpu", "memory", "timeout", "metrics"]),
+];
+
+/// Dotted paths of keys fdb does not know, with the closest known key when there is one.
+fn unknown_keys(table: &toml::Table, path: &str, pattern: &str, out: &mut Vec<String>) {
+    let known = KNOWN_KEYS.iter().find(|(p, _)| *p == pattern).map(|(_, keys)| *keys);
+    for (key, value) in table {
+        let full = if path.is_empty() { key.clone() } else { format!("{path}.{key}") };
+        let child = match known {
+            Some(keys) if !keys.contains(&key.as_str()) => {
+                match suggest::did_you_mean(key, keys.iter().copied()) {
+                    Some(close) => out.push(format!("unknown key `{full}` (did you mean `{close}`?)")),
+                    None => out.push(format!("unknown key `{full}`")),
+                }
+                continue;
+            }
+            Some(_) if pattern.is_empty() => key.clone(),
+            Some(_) => format!("{pattern}.{key}"),
+            None => format!("{pattern}.*"),
+        };
+        match value {
+            toml::Value::Table(t) => unknown_keys(t, &full, &child, out),
+            toml::Value::Array(items) => {
+                for item in items {
+                    if let toml::Value::Table(t) = item {
+                        unknown_keys(t, &full, &child, out);
+                    }
                 }
             }
+            _ => {}
+        }
+    }
+}
+
+/// Paths of the fdb.toml layers, lowest precedence first.
+fn layer_paths() -> Vec<PathBuf> {
+    let mut paths = vec![expand_tilde("~/.fdb/fdb.toml")];
+    if let Ok(dir) = std::env::current_dir() {
+        paths.push(dir.join("fdb.toml"));
+    }
+    paths
+}
+
+/// All layers merged, plus a description of every file that could not be read or parsed.
+fn read_layers() -> (toml::Table, Vec<String>) {
+    let mut merged = toml::Table::new();
+    let mut problems = Vec::new();
+    for path in layer_paths() {
+        if !path.is_file() {
+            continue;
+        }
+        let table = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|c| c.parse::<toml::Table>().map_err(|e| e.to_string()));
+        match table {
+            Ok(mut table) => {
+                let lookup = |var: &str| builtin_var(var).or_else(|| std::env::var(var).ok());
+                let mut undefined = Vec::new();
+                interpolate_table(&mut table, "", None, &lookup, &mut undefined);
+                problems.extend(undefined.into_iter().map(|u| format!("{}: {u}", path.display())));
+                let mut unknown = Vec::new();
+                unknown_keys(&table, "", "", &mut unknown);
+                problems.extend(unknown.into_iter().map(|u| format!("{}: {u}", path.display())));
+                merge(&mut merged, table);
+            }
+            Err(e) => problems.push(format!("{}: file ignored: {}", path.display(), e.trim())),
+        }
+    }
+    merge(&mut merged, env_layer(std::env::vars()));
+    (merged, problems)
+}
+
+/// `${namespace}` and `${git_branch}` (lowercase, runs of other characters as `-`, so it fits
+/// in a cluster name).
+fn builtin_var(var: &str) -> Option<String> {
+    match var {
+        "namespace" => Some("default".to_string()),
+        "git_branch" => {
+            let output = std::process::Command::new("git")
+                .args(["rev-parse", "--abbrev-ref", "HEAD"])
+                .stderr(std::process::Stdio::null())
+                .output()
+                .ok()
+                .filter(|o| o.status.success())?;
+            let branch = String::from_utf8_lossy(&output.stdout).trim().to_lowercase();
+            let slug: Vec<&str> = branch.split(|c: char| !c.is_ascii_alphanumeric()).filter(|s| !s.is_empty()).collect();
+            Some(slug.join("-"))
+        }
+        _ => None,
+    }
+}
+
+/// Expand `${VAR}` and `${VAR:-default}` in `s`; `$${` stays a literal `${`. `${cluster}` is
+/// the `[clusters.<name>]` section name when `cluster` is set and left alone otherwise, for
+/// hooks to fill in. Errors name the first variable that is not set.
+fn expand(s: &str, cluster: Option<&str>, lookup: &dyn Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut out = String::new();
+    let mut rest = s;
+    while let Some(i) = rest.find("${") {
+        if rest[..i].ends_with('$') {
+            out.push_str(&rest[..i - 1]);
+            out.push_str("${");
+            rest = &rest[i + 2..];
+            continue;
+        }
+        out.push_str(&rest[..i]);
+        let Some(end) = rest[i..].find('}') else {
+            return Err(format!("unclosed `${{` in \"{s}\""));
+        };
+        let inner = &rest[i + 2..i + end];
+        let (var, default) = match inner.split_once(":-") {
+            Some((var, default)) => (var, Some(default)),
+            None => (inner, None),
+        };
+        let value = match (var, cluster) {
+            ("cluster", Some(c)) => Some(c.to_string()),
+            ("cluster", None) => Some("${cluster}".to_string()),
+            _ => lookup(var).filter(|v| !v.is_empty()).or(default.map(str::to_string)),
+        };
+        out.push_str(&value.ok_or_else(|| format!("${{{var}}} is not set"))?);
+        rest = &rest[i + end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Expand variables in every string of `table` (see [`expand`]); values with undefined
+/// variables are kept as written and reported.
+fn interpolate_table(
+    table: &mut toml::Table,
+    path: &str,
+    cluster: Option<&str>,
+    lookup: &dyn Fn(&str) -> Option<String>,
+    problems: &mut Vec<String>,
+) {
+    for (key, value) in table.iter_mut() {
+        let full = if path.is_empty() { key.clone() } else { format!("{path}.{key}") };
+        let cluster = if path == "clusters" { Some(key.as_str()) } else { cluster };
+        interpolate_value(value, &full, cluster, lookup, problems);
+    }
+}
+
+fn interpolate_value(
+    value: &mut toml::Value,
+    path: &str,
+    cluster: Option<&str>,
+    lookup: &dyn Fn(&str) -> Option<String>,
+    problems: &mut Vec<String>,
+) {
+    match value {
+        toml::Value::String(s) => match expand(s, cluster, lookup) {
+            Ok(expanded) => *s = expanded,
+            Err(e) => problems.push(format!("`{path}`: {e}")),
+        },
+        toml::Value::Table(t) => interpolate_table(t, path, cluster, lookup, problems),
+        toml::Value::Array(items) => {
+            for item in items {
+                interpolate_value(item, path, cluster, lookup, problems);
+            }
         }
+        _ => {}
+    }
+}
+
+/// Report config problems that would otherwise be silently ignored: unreadable files, unknown
+/// keys (typos like `replcas`) and values of the wrong type. They are warnings unless fdb.toml
+/// sets `strict = true`, which turns them into an error.
+pub fn check() -> Result<(), String> {
+    let (merged, mut problems) = read_layers();
+    let strict = merged.get("strict").and_then(|v| v.as_bool()).unwrap_or(false);
+    if let Err(e) = toml::Value::Table(merged).try_into::<FdbToml>() {
+        problems.push(format!("fdb.toml: all settings ignored: {}", e.to_string().trim()));
+    }
+    if problems.is_empty() {
+        return Ok(());
+    }
+    if strict {
+        return Err(format!("invalid config (strict = true):\n  {}", problems.join("\n  ")));
     }
-    let global = expand_tilde("~/.fdb/fdb.toml");
-    if global.is_file() {
-        std::fs::read_to_string(&global).ok().and_then(|c| toml::from_str(&c).ok())
-    } else {
-        None
+    for p in problems {
+        eprintln!("warning: {p}");
+    }
+    Ok(())
+}
+
+/// Settings from the environment: `FDB_KUBECONFIG` and `FDB_<ENGINE>_<KEY>`
+/// (e.g. `FDB_POSTGRESQL_STORAGE=5Gi`, `FDB_REDIS_REPLICAS=3`).
+fn env_layer(vars: impl IntoIterator<Item = (String, String)>) -> toml::Table {
+    const KEYS: [&str; 5] = ["replicas", "storage", "cpu", "memory", "timeout"];
+    let mut layer = toml::Table::new();
+    for (name, value) in vars {
+        let Some(rest) = name.strip_prefix("FDB_") else {
+            continue;
+        };
+        let rest = rest.to_lowercase();
+        let (section, key) = if rest == "kubeconfig" {
+            ("kubernetes", rest.as_str())
+        } else {
+            match rest.split_once('_') {
+                Some((engine, key)) if ServiceType::ALL.iter().any(|s| s.kbcli_name() == engine) && KEYS.contains(&key) => {
+                    (engine, key)
+                }
+                _ => continue,
+            }
+        };
+        let value = match value.parse::<i64>() {
+            Ok(n) if key != "kubeconfig" => toml::Value::Integer(n),
+            _ => toml::Value::String(value),
+        };
+        let mut table = toml::Table::new();
+        table.insert(key.to_string(), value);
+        merge(&mut layer, toml::Table::from_iter([(section.to_string(), toml::Value::Table(table))]));
+    }
+    layer
+}
+
+/// fdb.toml layers merged key by key: `~/.fdb/fdb.toml`, then `./fdb.toml`, then `FDB_*`
+/// environment variables. CLI flags are applied on top by the callers.
+/// Load `[tools]`; nothing pinned and downloads allowed when absent. `~` in file paths is expanded.
+pub fn load_tools_config() -> ToolsConfig {
+    let mut tools: ToolsConfig = load_fdb_toml().and_then(|t| t.tools).unwrap_or_default();
+    for file in [&mut tools.kubectl_file, &mut tools.kbcli_file].into_iter().flatten() {
+        *file = expand_tilde(file).display().to_string();
+    }
+    tools
+}
+
+/// `[project] prefix`, if set and not empty.
+pub fn project_prefix() -> Option<String> {
+    load_fdb_toml()
+        .and_then(|t| t.project)
+        .and_then(|p| p.prefix)
+        .filter(|p| !p.is_empty())
+}
+
+/// Cluster name with the project prefix: "orders" -> "payments-orders". Names that already
+/// carry the prefix are returned as they are, so full names from `fdb list` work too.
+pub fn qualify(name: &str) -> String {
+    match project_prefix() {
+        Some(prefix) if !name.starts_with(&format!("{prefix}-")) => format!("{prefix}-{name}"),
+        _ => name.to_string(),
+    }
+}
+
+/// Destructive `fdb mcp` tools enabled by `[mcp] allow`; none by default.
+pub fn load_mcp_allow() -> Vec<String> {
+    load_fdb_toml().and_then(|t| t.mcp).unwrap_or_default().allow
+}
+
+/// Load `[hooks]`; no hooks when the section is missing.
+pub fn load_hooks_config() -> HooksConfig {
+    load_fdb_toml().and_then(|t| t.hooks).unwrap_or_default()
+}
+
+fn load_fdb_toml() -> Option<FdbToml> {
+    toml::Value::Table(read_layers().0).try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_layers_key_by_key() {
+        let mut merged: toml::Table =
+            "[kubernetes]\nkubeconfig = \"~/.kube/work\"\n[postgresql]\nreplicas = 3\nstorage = 10\n"
+                .parse()
+                .unwrap();
+        merge(&mut merged, "[postgresql]\nstorage = \"20Gi\"\n".parse().unwrap());
+        merge(
+            &mut merged,
+            env_layer([
+                ("FDB_POSTGRESQL_CPU".to_string(), "2".to_string()),
+                ("FDB_POSTGRESQL_TIMEOUT".to_string(), "900".to_string()),
+                ("FDB_HOME".to_string(), "/tmp/fdb".to_string()),
+            ]),
+        );
+        let config: FdbToml = toml::Value::Table(merged).try_into().unwrap();
+        assert_eq!(config.kubernetes.unwrap().kubeconfig.as_deref(), Some("~/.kube/work"));
+        let pg = config.postgresql.unwrap();
+        assert_eq!(pg.replicas, Some(3));
+        assert_eq!(pg.storage.as_deref(), Some("20Gi"));
+        assert_eq!(pg.cpu.as_deref(), Some("2"));
+        assert_eq!(pg.timeout, Some(std::time::Duration::from_secs(900)));
+        assert!(toml::from_str::<FdbToml>("[postgresql]\ntimeout = \"soon\"\n").is_err());
+    }
+
+    #[test]
+    fn interpolates_variables() {
+        let mut table: toml::Table = "[clusters.review]\nname = \"pr-${PR_NUMBER}-${cluster}\"\n\
+            version = \"${PG_VERSION:-16.4.0}\"\n[hooks]\npost-create = \"seed ${cluster} $${HOME} ${namespace}\"\n\
+            pre-delete = \"${MISSING}\"\n"
+            .parse()
+            .unwrap();
+        let lookup = |var: &str| match var {
+            "PR_NUMBER" => Some("42".to_string()),
+            other => builtin_var(other),
+        };
+        let mut problems = Vec::new();
+        interpolate_table(&mut table, "", None, &lookup, &mut problems);
+        assert_eq!(table["clusters"]["review"]["name"].as_str(), Some("pr-42-review"));
+        assert_eq!(table["clusters"]["review"]["version"].as_str(), Some("16.4.0"));
+        assert_eq!(table["hooks"]["post-create"].as_str(), Some("seed ${cluster} ${HOME} default"));
+        assert_eq!(problems, ["`hooks.pre-delete`: ${MISSING} is not set"]);
+    }
+
+    #[test]
+    fn parses_and_validates_backup_schedule() {
+        let config: FdbToml =
+            toml::from_str("[postgresql.backup]\nschedule = \"0 3 * * *\"\nretention = \"7d\"\n").unwrap();
+        let backup = config.postgresql.unwrap().backup.unwrap();
+        assert_eq!(backup.retention.as_deref(), Some("7d"));
+        assert!(backup.validate().is_ok());
+        let bad = |schedule: &str, retention: &str| BackupSchedule {
+            schedule: schedule.to_string(),
+            retention: Some(retention.to_string()),
+            method: None,
+        };
+        assert!(bad("0 3 * *", "7d").validate().unwrap_err().contains("cron"));
+        assert!(bad("0 3 * * *", "1w").validate().unwrap_err().contains("retention"));
+        assert!(bad("0 3 * * *", "0h").validate().is_err());
+    }
+
+    #[test]
+    fn reports_unknown_keys() {
+        let table: toml::Table = "strict = true\n[postgresql]\nreplcas = 3\n[contexts.prod]\nproduction = true\n\
+            [clusters.analytics]\nengine = \"redis\"\nsize = 1\n[[redis.acl]]\nname = \"app\"\nkey = \"a:*\"\n"
+            .parse()
+            .unwrap();
+        let mut unknown = Vec::new();
+        unknown_keys(&table, "", "", &mut unknown);
+        assert_eq!(
+            unknown,
+            [
+                "unknown key `clusters.analytics.size`",
+                "unknown key `postgresql.replcas` (did you mean `replicas`?)",
+                "unknown key `redis.acl.key` (did you mean `keys`?)",
+            ]
+        );
     }
 }
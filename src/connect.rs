@@ -0,0 +1,67 @@
+//! Interactive client shell on a cluster (`fdb connect`): the engine's own CLI (psql,
+//! redis-cli, mongosh, ...) run locally against a temporary port-forward, logged in with the
+//! cluster's credentials.
+
+use crate::accounts;
+use crate::credentials;
+use crate::exec::CommandExt;
+use crate::expose;
+use crate::portforward;
+use crate::service::ServiceType;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::process::Command;
+
+/// Run the client until it exits; the port-forward is stopped afterwards. Logs in as the app
+/// account when the cluster has one, unless `admin` asks for the admin user.
+pub fn run(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    admin: bool,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    let spec = service.spec();
+    if spec.shell("", None, 0).is_none() {
+        return Err(format!(
+            "fdb has no interactive client for {} clusters; use `fdb info {cluster_name} --uri` with your own client",
+            service.kbcli_name()
+        ));
+    }
+    let app = if admin { None } else { accounts::app_account(kubectl, cluster_name, kubeconfig)? };
+    let (user, password) = match app {
+        Some((user, password)) => (user, Some(password)),
+        None => (
+            credentials::get_user(kubectl, service, cluster_name, kubeconfig)?,
+            credentials::get_password(kubectl, service, cluster_name, kubeconfig)?,
+        ),
+    };
+
+    let component = expose::client_component(kubectl, service, cluster_name, kubeconfig);
+    let forward = portforward::start_port_forward(
+        kubectl,
+        &format!("svc/{cluster_name}-{component}"),
+        service.default_port(),
+        None,
+        kubeconfig,
+    )?;
+    let shell = spec.shell(&user, password.as_deref(), forward.local_port).expect("checked above");
+    println!("Connecting to \"{cluster_name}\" as {user} through 127.0.0.1:{}", forward.local_port);
+    let mut child = Command::new(shell.program)
+        .args(&shell.args)
+        .envs(shell.env.iter().map(|(k, v)| (k, v)))
+        .spawn_logged()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => format!(
+                "{} not found on PATH; install it, or use `fdb info {cluster_name} --uri` with another client",
+                shell.program
+            ),
+            _ => format!("start {}: {e}", shell.program),
+        })?;
+    let status = child.wait().map_err(|e| format!("{}: {e}", shell.program))?;
+    drop(forward);
+    match status.code() {
+        Some(0) | None => Ok(()),
+        Some(code) => Err(format!("{} exited with status {code}", shell.program)),
+    }
+}
@@ -0,0 +1,115 @@
+//! Pipe a logical dump from one cluster into another of the same engine (`fdb copy`).
+
+use crate::exec;
+use crate::load;
+use crate::pods;
+use crate::resources;
+use crate::service::ServiceType;
+use nanospinner::Spinner;
+use std::path::Path;
+
+/// Pod, container, dump command on the source and load command on the destination.
+type Plan = (String, String, &'static str, Vec<String>, Vec<String>);
+
+fn plan(
+    kubectl: &Path,
+    service: ServiceType,
+    source: &str,
+    dest: &str,
+    database: &str,
+    kubeconfig: &Path,
+) -> Result<Plan, String> {
+    let strings = |words: &[&str]| words.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+    match service {
+        ServiceType::PostgreSQL => Ok((
+            pods::primary_pod(kubectl, source, "postgresql", kubeconfig)?,
+            pods::primary_pod(kubectl, dest, "postgresql", kubeconfig)?,
+            "postgresql",
+            strings(&["pg_dump", "-U", "postgres", "-d", database, "--clean", "--if-exists"]),
+            strings(&["psql", "-U", "postgres", "-d", database, "-q", "-v", "ON_ERROR_STOP=1", "--single-transaction"]),
+        )),
+        ServiceType::RabbitMQ => Ok((
+            pods::component_pods(kubectl, source, "rabbitmq", kubeconfig)?[0].clone(),
+            pods::component_pods(kubectl, dest, "rabbitmq", kubeconfig)?[0].clone(),
+            "rabbitmq",
+            strings(&["rabbitmqctl", "export_definitions", "-", "--silent"]),
+            strings(&["rabbitmqctl", "import_definitions"]),
+        )),
+        _ => Err(format!(
+            "fdb copy does not support {}; only postgresql and rabbitmq",
+            service.kbcli_name()
+        )),
+    }
+}
+
+/// Stream a dump of `source` straight into `dest` through kubectl exec on both primaries;
+/// nothing is written locally. For PostgreSQL one database is copied (`pg_dump --clean`
+/// replaces its objects on the destination), asking first when the destination database
+/// already has tables unless `yes`.
+pub fn run(
+    kubectl: &Path,
+    service: ServiceType,
+    source: &str,
+    dest: &str,
+    database: Option<&str>,
+    yes: bool,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    if source == dest {
+        return Err("source and destination are the same cluster".to_string());
+    }
+    if database.is_some() && service != ServiceType::PostgreSQL {
+        return Err("--database is only supported for postgresql".to_string());
+    }
+    let database = database.unwrap_or("postgres");
+    let (source_pod, dest_pod, container, dump, restore) = plan(kubectl, service, source, dest, database, kubeconfig)?;
+
+    if service == ServiceType::PostgreSQL {
+        let tables = load::pg_table_count(kubectl, &dest_pod, database, kubeconfig)?;
+        if tables > 0
+            && !yes
+            && !load::confirm(&format!(
+                "Database \"{database}\" on \"{dest}\" has {tables} tables that will be replaced. Continue?"
+            ))?
+        {
+            return Err("aborted".to_string());
+        }
+    }
+
+    let dump: Vec<&str> = dump.iter().map(String::as_str).collect();
+    let restore: Vec<&str> = restore.iter().map(String::as_str).collect();
+    let (dump_cmd, mut dumper) = pods::spawn_exec(kubectl, &source_pod, container, &dump, kubeconfig)?;
+    let stdout = dumper.stdout.take().ok_or("kubectl exec: no stdout")?;
+
+    let message = format!("Copying \"{source}\" to \"{dest}\"");
+    let spinner = Spinner::new(&message).start();
+    let loaded = pods::exec_from(
+        kubectl,
+        &dest_pod,
+        container,
+        &restore,
+        stdout,
+        |sent| spinner.update(&format!("{message} {}", resources::format_bytes(sent as f64))),
+        kubeconfig,
+    );
+    let dumped = dumper
+        .wait_with_output()
+        .map_err(|e| format!("kubectl exec {source_pod}: {e}"))
+        .and_then(|output| {
+            if output.status.success() {
+                Ok(())
+            } else {
+                Err(exec::failure(&dump_cmd, &output))
+            }
+        });
+    match dumped.and(loaded) {
+        Ok(()) => {
+            spinner.success_with(&format!("Copied \"{source}\" to \"{dest}\""));
+            Ok(())
+        }
+        Err(e) => {
+            spinner.fail_with(&format!("{message} failed"));
+            Err(e)
+        }
+    }
+}
@@ -1,12 +1,16 @@
 //! Extract account password from Kubernetes secret for a cluster.
 
+use crate::exec::CommandExt;
+use crate::kubeblocks;
+use crate::redact;
 use crate::service::ServiceType;
+use std::io::{Read, Write};
 use std::path::Path;
 use std::process::{Command, Stdio};
 
 const NAMESPACE: &str = "default";
 
-/// Get account password for cluster. Returns None for services without password (e.g. Qdrant).
+/// when none matches, the error lists each secret checked and what was found there.
 pub fn get_password(
     kubectl: &Path,
     service: ServiceType,
@@ -16,43 +20,322 @@ pub fn get_password(
     if !service.has_password() {
         return Ok(None);
     }
+    let (_, _, encoded) = find_password(kubectl, service, cluster_name, kubeconfig)?;
+    let password = decode_base64(&encoded)?;
+    redact::register(&password);
+    Ok(Some(password))
+}
+
+/// Admin user of the cluster: the access key stored beside the secret key for MinIO, the
+/// engine's fixed admin user otherwise.
+pub fn get_user(kubectl: &Path, service: ServiceType, cluster_name: &str, kubeconfig: &Path) -> Result<String, String> {
+    let keys = service.spec().user_keys();
+    if keys.is_empty() {
+        return Ok(service.default_user().to_string());
+    }
+    for secret_name in kubeblocks::api(kubectl, kubeconfig).secret_names(service, cluster_name) {
+        let Some(data) = secret_data(kubectl, &secret_name, kubeconfig)? else {
+            continue;
+        };
+        if let Some(encoded) = keys.iter().find_map(|k| data.get(*k).and_then(|v| v.as_str())) {
+            return decode_base64(encoded);
+        }
+    }
+    Ok(service.default_user().to_string())
+}
+
+/// Secret name and key holding the account password, for manifests that reference it
+/// instead of embedding the value (seed Jobs).
+pub fn password_secret(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    kubeconfig: &Path,
+) -> Result<(String, String), String> {
+    let (secret, key, _) = find_password(kubectl, service, cluster_name, kubeconfig)?;
+    Ok((secret, key.to_string()))
+}
+
+/// First secret with a password field: its name, the key and the base64 value.
+fn find_password(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    kubeconfig: &Path,
+) -> Result<(String, &'static str, String), String> {
+    let mut checked = Vec::new();
+    for secret_name in kubeblocks::api(kubectl, kubeconfig).secret_names(service, cluster_name) {
+        let Some(data) = secret_data(kubectl, &secret_name, kubeconfig)? else {
+            checked.push(format!("{secret_name} (not found)"));
+            continue;
+        };
+        let found = service.spec().password_keys()
+            .iter()
+            .find_map(|k| data.get(*k).and_then(|v| v.as_str()).map(|v| (*k, v.to_string())));
+        if let Some((key, encoded)) = found {
+            return Ok((secret_name, key, encoded));
+        }
+        let keys: Vec<&str> = data.keys().map(String::as_str).collect();
+        checked.push(format!("{secret_name} (no password key; has: {})", keys.join(", ")));
+    }
+    Err(format!(
+        "no password secret found for \"{cluster_name}\"; checked in namespace {NAMESPACE}:\n  {}",
+        checked.join("\n  ")
+    ))
+}
 
-    let secret_name = service.secret_name(cluster_name);
+/// Base64-encoded `data` of a secret, or None when the secret does not exist.
+fn secret_data(
+    kubectl: &Path,
+    secret_name: &str,
+    kubeconfig: &Path,
+) -> Result<Option<serde_json::Map<String, serde_json::Value>>, String> {
+    let output = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["get", "secret", secret_name, "-n", NAMESPACE, "--ignore-not-found", "-o", "json"])
+        .output_checked()?;
+    if output.stdout.iter().all(u8::is_ascii_whitespace) {
+        return Ok(None);
+    }
+    let secret: serde_json::Value =
+        serde_json::from_slice(&output.stdout).map_err(|e| format!("parse secret {secret_name}: {e}"))?;
+    Ok(Some(secret.get("data").and_then(|d| d.as_object()).cloned().unwrap_or_default()))
+}
+
+/// Whether the secret exists, whatever it holds.
+pub fn secret_exists(kubectl: &Path, secret_name: &str, kubeconfig: &Path) -> Result<bool, String> {
+    Ok(secret_data(kubectl, secret_name, kubeconfig)?.is_some())
+}
+
+/// Decode base64 via the system `base64 -d`.
+fn decode_base64(encoded: &str) -> Result<String, String> {
+    let mut child = Command::new("base64")
+        .arg("-d")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn_logged()
+        .map_err(|e| format!("base64 -d failed: {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(encoded.as_bytes())
+            .map_err(|e| format!("base64 stdin: {e}"))?;
+    }
+    let output = child.wait_with_output().map_err(|e| format!("base64 -d failed: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("base64 decode failed: {stderr}"));
+    }
+    String::from_utf8(output.stdout).map_err(|e| format!("secret value not utf-8: {e}"))
+}
 
-    let mut kubectl_cmd = Command::new(kubectl)
+/// Read one field of a secret. Ok(None) when the secret or the field does not exist.
+pub fn read_secret_field(
+    kubectl: &Path,
+    secret_name: &str,
+    field: &str,
+    kubeconfig: &Path,
+) -> Result<Option<String>, String> {
+    let output = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
         .args([
             "get",
             "secret",
-            &secret_name,
+            secret_name,
             "-n",
             NAMESPACE,
+            "--ignore-not-found",
             "-o",
-            "jsonpath={.data.password}",
+            &format!("jsonpath={{.data.{}}}", field.replace('.', "\\.")),
         ])
+        .output_checked()?;
+    let encoded = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if encoded.is_empty() {
+        return Ok(None);
+    }
+    let value = decode_base64(&encoded)?;
+    redact::register(&value);
+    Ok(Some(value))
+}
+
+/// Set one field of a secret, creating the secret if needed.
+pub fn write_secret_field(
+    kubectl: &Path,
+    secret_name: &str,
+    field: &str,
+    value: &str,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    redact::register(value);
+    let exists = Command::new(kubectl)
         .arg("--kubeconfig")
         .arg(kubeconfig)
-        .stdout(Stdio::piped())
-        .spawn()
-        .map_err(|e| format!("kubectl failed: {e}"))?;
+        .args(["get", "secret", secret_name, "-n", NAMESPACE, "-o", "name"])
+        .output_logged()
+        .map_err(|e| format!("kubectl get secret: {e}"))?
+        .status
+        .success();
+
+    // The value goes through stdin: on kubectl's command line any local user could read it.
+    let mut cmd = Command::new(kubectl);
+    cmd.arg("--kubeconfig").arg(kubeconfig);
+    let input = if exists {
+        cmd.args(["patch", "secret", secret_name, "-n", NAMESPACE, "--type", "merge", "--patch-file", "/dev/stdin"]);
+        serde_json::json!({ "stringData": { field: value } })
+    } else {
+        cmd.args(["create", "-f", "-"]);
+        serde_json::json!({
+            "apiVersion": "v1",
+            "kind": "Secret",
+            "metadata": { "name": secret_name, "namespace": NAMESPACE },
+            "stringData": { field: value },
+        })
+    };
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn_logged()
+        .map_err(|e| format!("kubectl: {e}"))?;
+    let input = input.to_string();
+    crate::exec::echo_stdin(&input);
+    if let Some(mut stdin) = child.stdin.take()
+        && let Err(e) = stdin.write_all(input.as_bytes())
+        && e.kind() != std::io::ErrorKind::BrokenPipe
+    {
+        return Err(format!("kubectl stdin: {e}"));
+    }
+    let output = child.wait_with_output().map_err(|e| format!("kubectl: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "writing secret {secret_name} failed: {}",
+            redact::command_arg(String::from_utf8_lossy(&output.stderr).trim())
+        ));
+    }
+    Ok(())
+}
 
-    let kubectl_stdout = kubectl_cmd
-        .stdout
-        .take()
-        .ok_or("kubectl stdout not captured")?;
+/// Random hex token of `bytes` bytes from /dev/urandom.
+pub fn random_token(bytes: usize) -> Result<String, String> {
+    let mut buf = vec![0u8; bytes];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut f| f.read_exact(&mut buf))
+        .map_err(|e| format!("read /dev/urandom: {e}"))?;
+    let token: String = buf.iter().map(|b| format!("{b:02x}")).collect();
+    redact::register(&token);
+    Ok(token)
+}
 
-    let output = Command::new("base64")
-        .arg("-d")
-        .stdin(kubectl_stdout)
-        .output()
-        .map_err(|e| format!("base64 -d failed: {e}"))?;
+/// Password following the `[security]` policy from fdb.toml.
+pub fn generate_password() -> Result<String, String> {
+    let policy = crate::config::load_password_policy()?;
+    let charset = policy.charset.as_bytes();
+    // Rejection sampling keeps every character equally likely.
+    let limit = 256 - 256 % charset.len();
+    let mut password = String::with_capacity(policy.length);
+    let mut urandom = std::fs::File::open("/dev/urandom").map_err(|e| format!("open /dev/urandom: {e}"))?;
+    let mut buf = [0u8; 64];
+    while password.len() < policy.length {
+        urandom.read_exact(&mut buf).map_err(|e| format!("read /dev/urandom: {e}"))?;
+        for &b in buf.iter().filter(|&&b| (b as usize) < limit) {
+            if password.len() == policy.length {
+                break;
+            }
+            password.push(charset[b as usize % charset.len()] as char);
+        }
+    }
+    redact::register(&password);
+    Ok(password)
+}
 
-    let _ = kubectl_cmd.wait();
+/// Everything a client needs to connect to a cluster.
+#[derive(Debug, Clone)]
+pub struct ConnectionDetails {
+    pub user: String,
+    pub password: Option<String>,
+    /// External host and NodePort; None when the cluster could not be exposed.
+    pub endpoint: Option<(String, u16)>,
+    pub connection_string: Option<String>,
+    /// What `connection_string` is, e.g. "Connection string" or "Bootstrap servers".
+    pub label: &'static str,
+    /// What `password` is, e.g. "Password" or "Token".
+    pub password_label: &'static str,
+    /// Ready-to-paste CLI setup (`influx config create ...`), for engines that have one.
+    pub cli_command: Option<String>,
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("base64 decode failed: {stderr}"));
+impl ConnectionDetails {
+    /// Connection details for `user`: the admin user from [`get_user`] or an application account.
+    pub fn for_user(
+        service: ServiceType,
+        user: &str,
+        password: Option<String>,
+        endpoint: Option<(String, u16)>,
+    ) -> Self {
+        let user = user.to_string();
+        let connection_string = endpoint
+            .as_ref()
+            .map(|(host, port)| service.connection_string(&user, password.as_deref(), host, *port));
+        let cli_command = endpoint
+            .as_ref()
+            .and_then(|(host, port)| service.cli_command(password.as_deref(), host, *port));
+        ConnectionDetails {
+            user,
+            password,
+            endpoint,
+            connection_string,
+            label: service.connection_label(),
+            password_label: service.password_label(),
+            cli_command,
+        }
     }
 
-    let password = String::from_utf8(output.stdout).map_err(|e| format!("password not utf-8: {e}"))?;
-    Ok(Some(password))
+    /// Key/value pairs (user, password, host, port, uri) for machine-readable outputs.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![("user", self.user.clone())];
+        if let Some(ref p) = self.password {
+            fields.push(("password", p.clone()));
+        }
+        if let Some((ref host, port)) = self.endpoint {
+            fields.push(("host", host.clone()));
+            fields.push(("port", port.to_string()));
+        }
+        if let Some(ref uri) = self.connection_string {
+            fields.push(("uri", uri.clone()));
+        }
+        fields
+    }
+
+    /// Print the "Connection details:" block shown after create.
+    pub fn print(&self) {
+        println!("Connection details:");
+        match (&self.endpoint, &self.connection_string) {
+            (Some((host, port)), Some(connection_string)) => {
+                println!("  Host:              {host}");
+                println!("  Port:              {port}");
+                println!("  User:              {}", self.user);
+                if let Some(ref p) = self.password {
+                    println!("  {:<18} {}", format!("{}:", self.password_label), redact::secret(p));
+                }
+                println!("  {:<18} {}", format!("{}:", self.label), redact::url(connection_string));
+                if let Some(ref command) = self.cli_command {
+                    let password = self.password.as_deref().unwrap_or_default();
+                    let command = match password {
+                        "" => command.clone(),
+                        p => command.replace(p, &redact::secret(p)),
+                    };
+                    println!("  {:<18} {command}", "CLI:");
+                }
+            }
+            _ => {
+                println!("  User:     {}", self.user);
+                if let Some(ref p) = self.password {
+                    println!("  {:<9} {}", format!("{}:", self.password_label), redact::secret(p));
+                }
+                println!("  (Host/Port: enable NodePort or check kubeconfig)");
+            }
+        }
+    }
 }
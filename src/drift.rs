@@ -0,0 +1,437 @@
+//! Desired (`[clusters.<name>]` profiles) versus live cluster spec, for `fdb diff` and the
+//! OpsRequests `fdb apply` runs to converge them.
+
+use crate::cluster;
+use crate::config::{self, Overrides, Profile};
+use crate::error::{self, Error, ExitCode};
+use crate::exec::CommandExt;
+use crate::expose;
+use crate::load;
+use crate::progress::Progress;
+use crate::resources;
+use crate::service::ServiceType;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+const NAMESPACE: &str = "default";
+const GI: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Spec fields fdb manages. CPU in cores, memory and storage in Gi (kbcli units); None when
+/// unknown on the live side or left to kbcli on the desired side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spec {
+    pub replicas: Option<u32>,
+    pub cpu: Option<f64>,
+    pub memory: Option<f64>,
+    pub storage: Option<f64>,
+    pub version: Option<String>,
+    pub exposed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Replicas,
+    Cpu,
+    Memory,
+    Storage,
+    Version,
+    Expose,
+}
+
+impl Field {
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Replicas => "replicas",
+            Field::Cpu => "cpu",
+            Field::Memory => "memory",
+            Field::Storage => "storage",
+            Field::Version => "version",
+            Field::Expose => "expose",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub field: Field,
+    pub live: String,
+    pub desired: String,
+}
+
+/// What `live` reads from the Cluster resource.
+#[derive(Debug, Clone)]
+pub struct Live {
+    /// Cluster or first component definition.
+    pub definition: String,
+    /// Name of the first component, the one fdb creates and scales.
+    pub component: String,
+    pub spec: Spec,
+}
+
+/// How a profile compares with the cluster of the same name.
+#[derive(Debug)]
+pub enum Drift {
+    Missing,
+    EngineMismatch { live: String },
+    Changes { component: String, changes: Vec<Change> },
+}
+
+/// Desired spec of a profile after fdb.toml engine sections and defaults are applied.
+pub fn desired(profile: &Profile) -> Result<Spec, String> {
+    let config = config::load_config(
+        profile.engine,
+        Overrides {
+            replicas: profile.replicas,
+            storage: profile.storage.clone(),
+            cpu: profile.cpu.clone(),
+            memory: profile.memory.clone(),
+            version: profile.version.clone(),
+            ..Default::default()
+        },
+    );
+    let number = |s: String| s.parse::<f64>().ok();
+    Ok(Spec {
+        replicas: Some(config.replicas),
+        cpu: number(cluster::kbcli_cpu(&config.cpu)?),
+        memory: number(cluster::kbcli_gi(&config.memory, "memory")?),
+        storage: cluster::kbcli_storage(&config.storage)?.and_then(number),
+        version: config.version,
+        exposed: !profile.skip_expose.unwrap_or(false),
+    })
+}
+
+/// Engine and spec of the live cluster, None when it does not exist.
+pub fn live(
+    kubectl: &Path,
+    service: ServiceType,
+    name: &str,
+    kubeconfig: &Path,
+) -> Result<Option<Live>, String> {
+    let output = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["get", "cluster", name, "-n", NAMESPACE, "--ignore-not-found", "-o", "json"])
+        .output_checked()?;
+    if output.stdout.iter().all(u8::is_ascii_whitespace) {
+        return Ok(None);
+    }
+    let cluster: serde_json::Value =
+        serde_json::from_slice(&output.stdout).map_err(|e| format!("parse cluster \"{name}\": {e}"))?;
+    Ok(Some(parse_live(&cluster, |_| {
+        expose::existing_nodeport(kubectl, service, name, kubeconfig).is_ok()
+    })))
+}
+
+/// Definition, component and spec from a Cluster object; `exposed` is asked for the engine's
+/// service.
+fn parse_live(cluster: &serde_json::Value, exposed: impl FnOnce(&str) -> bool) -> Live {
+    let component = &cluster["spec"]["componentSpecs"][0];
+    let text = |pointer: &str| component.pointer(pointer).and_then(|v| v.as_str());
+    let definition = cluster["spec"]["clusterDef"]
+        .as_str()
+        .or(cluster["spec"]["clusterDefinitionRef"].as_str())
+        .filter(|d| !d.is_empty())
+        .or(text("/componentDef"))
+        .unwrap_or_default()
+        .to_string();
+    let spec = Spec {
+        replicas: component["replicas"].as_u64().map(|r| r as u32),
+        cpu: text("/resources/limits/cpu").and_then(resources::parse_cpu).map(|m| m / 1000.0),
+        memory: text("/resources/limits/memory").and_then(resources::parse_bytes).map(|b| b / GI),
+        storage: text("/volumeClaimTemplates/0/spec/resources/requests/storage")
+            .and_then(resources::parse_bytes)
+            .map(|b| b / GI),
+        version: text("/serviceVersion").map(str::to_string),
+        exposed: exposed(&definition),
+    };
+    Live {
+        component: text("/name").unwrap_or_default().to_string(),
+        definition,
+        spec,
+    }
+}
+
+fn show<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(T::to_string).unwrap_or_else(|| "?".to_string())
+}
+
+/// Fields where `live` differs from `desired`. Quantities within 1% count as equal (kbcli
+/// rounds), and fields the desired side leaves open are not compared.
+pub fn changes(live: &Spec, desired: &Spec) -> Vec<Change> {
+    let mut out = Vec::new();
+    let mut push = |field, live: String, desired: String| out.push(Change { field, live, desired });
+    if desired.replicas.is_some() && live.replicas != desired.replicas {
+        push(Field::Replicas, show(&live.replicas), show(&desired.replicas));
+    }
+    let quantities = [
+        (Field::Cpu, live.cpu, desired.cpu, ""),
+        (Field::Memory, live.memory, desired.memory, "Gi"),
+        (Field::Storage, live.storage, desired.storage, "Gi"),
+    ];
+    for (field, l, d, unit) in quantities {
+        let Some(d) = d else { continue };
+        if l.is_none_or(|l| (l - d).abs() > d * 0.01) {
+            let live_text = l.map(|l| format!("{}{unit}", (l * 100.0).round() / 100.0));
+            push(field, live_text.unwrap_or_else(|| "?".to_string()), format!("{d}{unit}"));
+        }
+    }
+    if desired.version.is_some() && live.version != desired.version {
+        push(Field::Version, show(&live.version), show(&desired.version));
+    }
+    if live.exposed != desired.exposed {
+        let expose = |e: bool| if e { "nodeport" } else { "none" }.to_string();
+        push(Field::Expose, expose(live.exposed), expose(desired.exposed));
+    }
+    out
+}
+
+/// Compare one profile with the cluster of the same name.
+pub fn drift(kubectl: &Path, profile: &Profile, kubeconfig: &Path) -> Result<Drift, String> {
+    let desired = desired(profile)?;
+    let Some(live) = live(kubectl, profile.engine, &profile.name, kubeconfig)? else {
+        return Ok(Drift::Missing);
+    };
+    if cluster::service_from_definition(&live.definition).ok() != Some(profile.engine) {
+        return Ok(Drift::EngineMismatch { live: live.definition });
+    }
+    Ok(Drift::Changes {
+        component: live.component,
+        changes: changes(&live.spec, &desired),
+    })
+}
+
+/// kbcli invocation that submits one OpsRequest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operation {
+    pub cluster: String,
+    pub args: Vec<String>,
+}
+
+impl std::fmt::Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "kbcli {}", self.args.join(" "))
+    }
+}
+
+/// OpsRequests that bring `component` of `cluster` to the desired side of `changes`: hscale
+/// for replicas, one vscale for CPU and memory, volume-expand for larger storage. Changes
+/// apply cannot make (shrinking storage, version, exposure) come back as explanations.
+pub fn operations(cluster: &str, component: &str, changes: &[Change]) -> (Vec<Operation>, Vec<String>) {
+    let mut ops = Vec::new();
+    let mut manual = Vec::new();
+    let op = |kind: &str, extra: &[&str]| {
+        let mut args: Vec<String> = ["cluster", kind, cluster, "-n", NAMESPACE, "--components", component]
+            .iter()
+            .chain(extra)
+            .map(|s| s.to_string())
+            .collect();
+        args.push("--auto-approve".to_string());
+        Operation {
+            cluster: cluster.to_string(),
+            args,
+        }
+    };
+    let desired = |field: Field| changes.iter().find(|c| c.field == field).map(|c| c.desired.as_str());
+    if let Some(replicas) = desired(Field::Replicas) {
+        ops.push(op("hscale", &["--replicas", replicas]));
+    }
+    let mut vscale = Vec::new();
+    if let Some(cpu) = desired(Field::Cpu) {
+        vscale.extend(["--cpu", cpu]);
+    }
+    if let Some(memory) = desired(Field::Memory) {
+        vscale.extend(["--memory", memory]);
+    }
+    if !vscale.is_empty() {
+        ops.push(op("vscale", &vscale));
+    }
+    for c in changes {
+        match c.field {
+            Field::Storage if grows(&c.live, &c.desired) => ops.push(op(
+                "volume-expand",
+                &["--volume-claim-templates", "data", "--storage", &c.desired],
+            )),
+            Field::Storage => manual.push(format!(
+                "{cluster}: storage {} -> {}: volumes cannot shrink; recreate the cluster",
+                c.live, c.desired
+            )),
+            Field::Version => manual.push(format!(
+                "{cluster}: version {} -> {}: not changed by apply",
+                c.live, c.desired
+            )),
+            Field::Expose => manual.push(format!(
+                "{cluster}: expose {} -> {}: not changed by apply",
+                c.live, c.desired
+            )),
+            Field::Replicas | Field::Cpu | Field::Memory => {}
+        }
+    }
+    (ops, manual)
+}
+
+/// Whether a storage change is an expansion; unknown live size counts as one.
+fn grows(live: &str, desired: &str) -> bool {
+    let gi = |s: &str| s.trim_end_matches("Gi").parse::<f64>().ok();
+    match (gi(live), gi(desired)) {
+        (Some(l), Some(d)) => d > l,
+        _ => true,
+    }
+}
+
+/// The named profile, or every profile in fdb.toml.
+fn profiles(name: Option<&str>) -> Result<Vec<Profile>, String> {
+    match name {
+        Some(name) => Ok(vec![config::load_profile(name)?]),
+        None => {
+            let names = config::profile_names();
+            if names.is_empty() {
+                return Err("no [clusters.<name>] sections in fdb.toml to compare against".to_string());
+            }
+            names.iter().map(|n| config::load_profile(n)).collect()
+        }
+    }
+}
+
+/// Print the drift of the named profile, or of every profile; Ok(true) when anything differs.
+pub fn print_diff(kubectl: &Path, name: Option<&str>, kubeconfig: &Path) -> Result<bool, String> {
+    let profiles = profiles(name)?;
+    let color = std::io::stdout().is_terminal();
+    let paint = |code: &str, text: String| if color { format!("\x1b[{code}m{text}\x1b[0m") } else { text };
+    let mut drifted = false;
+    for profile in &profiles {
+        let title = format!("{} ({})", profile.name, profile.engine.kbcli_name());
+        match drift(kubectl, profile, kubeconfig)? {
+            Drift::Missing => {
+                drifted = true;
+                println!("{}", paint("1", title));
+                println!("  {}", paint("32", format!("+ not created; run `fdb up {}`", profile.profile)));
+            }
+            Drift::EngineMismatch { live } => {
+                drifted = true;
+                println!("{}", paint("1", title));
+                println!("  {}", paint("31", format!("- engine: {live} (cannot be changed in place)")));
+            }
+            Drift::Changes { changes, .. } if changes.is_empty() => println!("{title}: up to date"),
+            Drift::Changes { changes, .. } => {
+                drifted = true;
+                println!("{}", paint("1", title));
+                for c in changes {
+                    println!("  {}", paint("31", format!("- {}: {}", c.field.name(), c.live)));
+                    println!("  {}", paint("32", format!("+ {}: {}", c.field.name(), c.desired)));
+                }
+            }
+        }
+    }
+    Ok(drifted)
+}
+
+/// Converge existing clusters to their profiles: list the OpsRequests, ask unless `yes`
+/// (production contexts always ask for the name), submit them and wait for Running again.
+pub fn apply(
+    kbcli: &Path,
+    kubectl: &Path,
+    name: Option<&str>,
+    kubeconfig: &Path,
+    yes: bool,
+    timeout: Duration,
+) -> Result<(), Error> {
+    let mut ops = Vec::new();
+    let mut manual = Vec::new();
+    for profile in profiles(name)? {
+        match drift(kubectl, &profile, kubeconfig)? {
+            Drift::Missing => manual.push(format!("{}: not created; run `fdb up {}`", profile.name, profile.profile)),
+            Drift::EngineMismatch { live } => manual.push(format!(
+                "{}: engine is {live}, profile wants {}; recreate the cluster",
+                profile.name,
+                profile.engine.kbcli_name()
+            )),
+            Drift::Changes { component, changes } => {
+                let (o, m) = operations(&profile.name, &component, &changes);
+                ops.extend(o);
+                manual.extend(m);
+            }
+        }
+    }
+    if !manual.is_empty() {
+        println!("Not applied:");
+        for m in &manual {
+            println!("  {m}");
+        }
+    }
+    if ops.is_empty() {
+        println!("Nothing to apply.");
+        return Ok(());
+    }
+    println!("Operations:");
+    for op in &ops {
+        println!("  {op}");
+    }
+    let mut clusters: Vec<&str> = ops.iter().map(|o| o.cluster.as_str()).collect();
+    clusters.dedup();
+    let mut confirmed = false;
+    for c in &clusters {
+        confirmed |= cluster::confirm_production(kubectl, c, "apply changes to", kubeconfig)?;
+    }
+    if !yes && !confirmed && !load::confirm(&format!("Run {} operation(s)?", ops.len()))? {
+        return Err("aborted".into());
+    }
+
+    for op in &ops {
+        Command::new(kbcli)
+            .arg("--kubeconfig")
+            .arg(kubeconfig)
+            .args(&op.args)
+            .output_checked()
+            .map_err(error::code(ExitCode::Kbcli))?;
+    }
+    let mut progress = Progress::new();
+    for c in &clusters {
+        cluster::wait_until_running(kbcli, kubectl, c, kubeconfig, timeout, &mut progress)?;
+    }
+    progress.finish();
+    println!("Applied {} operation(s) to {}.", ops.len(), clusters.join(", "));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_live_spec_with_desired() {
+        let cluster: serde_json::Value = serde_json::from_str(
+            r#"{"spec": {"clusterDef": "postgresql", "componentSpecs": [{"replicas": 1, "serviceVersion": "16.4.0",
+                "resources": {"limits": {"cpu": "500m", "memory": "819Mi"}},
+                "volumeClaimTemplates": [{"spec": {"resources": {"requests": {"storage": "2Gi"}}}}]}]}}"#,
+        )
+        .unwrap();
+        let live = parse_live(&cluster, |_| true);
+        assert_eq!(live.definition, "postgresql");
+        let live = live.spec;
+        let desired = Spec {
+            replicas: Some(3),
+            cpu: Some(0.5),
+            memory: Some(0.8),
+            storage: Some(10.0),
+            version: None,
+            exposed: true,
+        };
+        let drift = changes(&live, &desired);
+        let fields: Vec<Field> = drift.iter().map(|c| c.field).collect();
+        assert_eq!(fields, [Field::Replicas, Field::Storage]);
+        let (ops, manual) = operations("orders", "postgresql", &drift);
+        let ops: Vec<String> = ops.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            ops,
+            [
+                "kbcli cluster hscale orders -n default --components postgresql --replicas 3 --auto-approve",
+                "kbcli cluster volume-expand orders -n default --components postgresql --volume-claim-templates data \
+                 --storage 10Gi --auto-approve",
+            ]
+        );
+        assert!(manual.is_empty());
+        assert_eq!(changes(&live, &Spec { replicas: Some(1), storage: Some(2.0), ..desired }), []);
+    }
+}
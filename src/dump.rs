@@ -0,0 +1,108 @@
+//! Logical export of a cluster's data to a local file (`fdb dump`).
+
+use crate::pods;
+use crate::resources;
+use crate::service::ServiceType;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+/// Where the RDB snapshot is written inside the redis container before it is copied out.
+const REDIS_RDB: &str = "/tmp/fdb-dump.rdb";
+
+/// Primary pod of the component; single-pod clusters may carry no role label.
+fn source_pod(kubectl: &Path, cluster_name: &str, component: &str, kubeconfig: &Path) -> Result<String, String> {
+    pods::primary_pod(kubectl, cluster_name, component, kubeconfig).or_else(|_| {
+        pods::component_pods(kubectl, cluster_name, component, kubeconfig).map(|p| p[0].clone())
+    })
+}
+
+/// Run the dump tool on the primary and stream its stdout into `out`. Returns what was run,
+/// for the summary line.
+fn stream(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    database: Option<&str>,
+    out: Stdio,
+    kubeconfig: &Path,
+) -> Result<&'static str, String> {
+    match service {
+        ServiceType::PostgreSQL => {
+            let pod = source_pod(kubectl, cluster_name, "postgresql", kubeconfig)?;
+            let (tool, command) = match database {
+                Some(db) => ("pg_dump", vec!["pg_dump", "-U", "postgres", "-d", db]),
+                None => ("pg_dumpall", vec!["pg_dumpall", "-U", "postgres"]),
+            };
+            pods::exec_to(kubectl, &pod, "postgresql", &command, out, kubeconfig)?;
+            Ok(tool)
+        }
+        ServiceType::Redis => {
+            let pod = source_pod(kubectl, cluster_name, "redis", kubeconfig)?;
+            let rdb = ["sh", "-c", pods::REDIS_CLI_SCRIPT, "sh", "--rdb", REDIS_RDB];
+            pods::exec(kubectl, &pod, "redis", &rdb, kubeconfig)?;
+            let copied = pods::exec_to(kubectl, &pod, "redis", &["cat", REDIS_RDB], out, kubeconfig);
+            let _ = pods::exec(kubectl, &pod, "redis", &["rm", "-f", REDIS_RDB], kubeconfig);
+            copied?;
+            Ok("redis-cli --rdb")
+        }
+        ServiceType::RabbitMQ => {
+            let pod = source_pod(kubectl, cluster_name, "rabbitmq", kubeconfig)?;
+            let command = ["rabbitmqctl", "export_definitions", "-", "--silent"];
+            pods::exec_to(kubectl, &pod, "rabbitmq", &command, out, kubeconfig)?;
+            Ok("rabbitmqctl export_definitions")
+        }
+        ServiceType::MongoDB => {
+            let pod = source_pod(kubectl, cluster_name, "mongodb", kubeconfig)?;
+            let shell = pods::mongodb_admin_script("mongodump");
+            let mut command = vec!["sh", "-c", &shell, "sh", "--quiet", "--archive", "--gzip"];
+            if let Some(db) = database {
+                command.extend(["--db", db]);
+            }
+            pods::exec_to(kubectl, &pod, "mongodb", &command, out, kubeconfig)?;
+            Ok("mongodump --archive --gzip")
+        }
+        ServiceType::Qdrant => Err("fdb dump does not support qdrant; use Qdrant's snapshot API".to_string()),
+        ServiceType::Kafka => Err("fdb dump does not support kafka; mirror topics with MirrorMaker instead".to_string()),
+        _ => Err(format!("fdb dump does not support {}", service.kbcli_name())),
+    }
+}
+
+/// Dump the cluster's data to `out` (`-` for stdout): SQL from pg_dump (one database) or
+/// pg_dumpall, an RDB file for Redis, definitions JSON for RabbitMQ, a gzipped mongodump
+/// archive for MongoDB. The file only appears
+/// once the dump has succeeded.
+pub fn run(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    database: Option<&str>,
+    out: &Path,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    if database.is_some() && service != ServiceType::PostgreSQL {
+        return Err("--database is only supported for postgresql".to_string());
+    }
+    if out == Path::new("-") {
+        stream(kubectl, service, cluster_name, database, Stdio::inherit(), kubeconfig)?;
+        return Ok(());
+    }
+
+    let partial = PathBuf::from(format!("{}.partial", out.display()));
+    let file = fs::File::create(&partial).map_err(|e| format!("create {}: {e}", partial.display()))?;
+    let tool = match stream(kubectl, service, cluster_name, database, Stdio::from(file), kubeconfig) {
+        Ok(tool) => tool,
+        Err(e) => {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+    };
+    fs::rename(&partial, out).map_err(|e| format!("rename {} to {}: {e}", partial.display(), out.display()))?;
+    let size = fs::metadata(out).map(|m| m.len()).unwrap_or(0);
+    println!(
+        "Dumped \"{cluster_name}\" with {tool} to {} ({})",
+        out.display(),
+        resources::format_bytes(size as f64)
+    );
+    Ok(())
+}
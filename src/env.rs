@@ -0,0 +1,86 @@
+//! Connection details as environment variables (`fdb env`): `eval $(fdb env mydb)`, or
+//! `fdb env mydb --format dotenv > .env` for docker-compose style local development.
+
+use crate::credentials::ConnectionDetails;
+use crate::service::ServiceType;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvFormat {
+    /// `KEY=value`, quoted only where needed.
+    Dotenv,
+    /// `export KEY='value'` for `eval`.
+    Shell,
+    Json,
+}
+
+impl FromStr for EnvFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dotenv" | "env" => Ok(EnvFormat::Dotenv),
+            "shell" | "sh" => Ok(EnvFormat::Shell),
+            "json" => Ok(EnvFormat::Json),
+            _ => Err(format!("invalid --format: {s} (use dotenv, shell or json)")),
+        }
+    }
+}
+
+/// The engine's variables for the cluster's external endpoint.
+pub fn variables(
+    service: ServiceType,
+    details: &ConnectionDetails,
+    cluster_name: &str,
+) -> Result<Vec<(String, String)>, String> {
+    let (host, port) = details.endpoint.as_ref().ok_or_else(|| {
+        format!(
+            "\"{cluster_name}\" has no external endpoint; run `fdb expose {cluster_name} --type nodeport`, \
+             or `fdb port-forward {cluster_name}` for a local one"
+        )
+    })?;
+    Ok(service.env_vars(&details.user, details.password.as_deref(), host, *port))
+}
+
+pub fn render(vars: &[(String, String)], format: EnvFormat) -> String {
+    match format {
+        EnvFormat::Dotenv => vars.iter().map(|(k, v)| format!("{k}={}\n", dotenv_value(v))).collect(),
+        EnvFormat::Shell => vars.iter().map(|(k, v)| format!("export {k}={}\n", shell_quote(v))).collect(),
+        EnvFormat::Json => {
+            let map: serde_json::Map<String, serde_json::Value> =
+                vars.iter().map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone()))).collect();
+            format!("{}\n", serde_json::Value::Object(map))
+        }
+    }
+}
+
+fn shell_quote(v: &str) -> String {
+    format!("'{}'", v.replace('\'', r"'\''"))
+}
+
+/// Plain when the value is safe unquoted; single quotes (no interpolation in docker compose)
+/// otherwise.
+fn dotenv_value(v: &str) -> String {
+    if v.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:@?=&,+%".contains(c)) {
+        v.to_string()
+    } else if !v.contains('\'') {
+        format!("'{v}'")
+    } else {
+        format!("\"{}\"", v.replace('\\', "\\\\").replace('"', "\\\"").replace('$', "\\$"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_shell_and_dotenv() {
+        let vars = vec![
+            ("PGHOST".to_string(), "10.0.0.5".to_string()),
+            ("PGPASSWORD".to_string(), "it's $ecret".to_string()),
+        ];
+        assert_eq!(render(&vars, EnvFormat::Shell), "export PGHOST='10.0.0.5'\nexport PGPASSWORD='it'\\''s $ecret'\n");
+        assert_eq!(render(&vars, EnvFormat::Dotenv), "PGHOST=10.0.0.5\nPGPASSWORD=\"it's \\$ecret\"\n");
+    }
+}
@@ -0,0 +1,59 @@
+//! Failure type for `fdb` commands and the process exit codes it maps to.
+
+use std::fmt;
+
+/// Exit codes, documented in the README so wrappers can branch on the failure type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /// Anything not covered below.
+    Failure = 1,
+    /// Invalid command line.
+    Usage = 2,
+    /// kubectl/kbcli missing and could not be installed.
+    ToolsMissing = 3,
+    /// kbcli failed, or the cluster reached a Failed/Abnormal phase.
+    Kbcli = 4,
+    /// Timed out waiting for the cluster.
+    Timeout = 5,
+    /// Reading or creating credentials failed.
+    Credentials = 6,
+}
+
+/// Error message plus the exit code it should produce.
+#[derive(Debug, Clone)]
+pub struct Error {
+    pub code: ExitCode,
+    pub message: String,
+}
+
+impl Error {
+    pub fn new(code: ExitCode, message: impl Into<String>) -> Self {
+        Error {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// `map_err` adapter for String errors: `.map_err(error::code(ExitCode::Kbcli))`.
+pub fn code(code: ExitCode) -> impl Fn(String) -> Error {
+    move |message| Error::new(code, message)
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::new(ExitCode::Failure, message)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Error::new(ExitCode::Failure, message)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
@@ -0,0 +1,93 @@
+//! Single place where fdb runs external commands; with `--debug` every command is echoed
+//! to stderr after passing through the redaction filter.
+
+use crate::redact;
+use std::io;
+use std::process::{Child, Command, Output};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static DEBUG: AtomicBool = AtomicBool::new(false);
+
+pub fn set_debug(on: bool) {
+    DEBUG.store(on, Ordering::Relaxed);
+}
+
+/// Quote an argument for display when it contains whitespace or shell metacharacters.
+fn quote(arg: &str) -> String {
+    if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=,@%+".contains(c)) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// The command line with secrets masked, as shown in `--debug` echo and error messages.
+fn display(cmd: &Command) -> String {
+    let program = cmd.get_program().to_string_lossy();
+    let mut line = quote(program.rsplit('/').next().unwrap_or(&program));
+    for arg in cmd.get_args() {
+        line.push(' ');
+        line.push_str(&quote(&redact::command_arg(&arg.to_string_lossy())));
+    }
+    line
+}
+
+fn echo(cmd: &Command) {
+    if DEBUG.load(Ordering::Relaxed) {
+        eprintln!("+ {}", display(cmd));
+    }
+}
+
+/// Error for a command that ran but failed: the command, its exit status and trimmed stderr.
+pub fn failure(cmd: &Command, output: &Output) -> String {
+    let status = match output.status.code() {
+        Some(code) => format!("exit status {code}"),
+        None => "killed by signal".to_string(),
+    };
+    let stderr = redact::command_arg(String::from_utf8_lossy(&output.stderr).trim());
+    if stderr.is_empty() {
+        format!("`{}` failed ({status})", display(cmd))
+    } else {
+        format!("`{}` failed ({status}): {stderr}", display(cmd))
+    }
+}
+
+/// Echo a payload written to a command's stdin (manifests), redacted like arguments.
+pub fn echo_stdin(content: &str) {
+    if !DEBUG.load(Ordering::Relaxed) {
+        return;
+    }
+    for line in content.lines() {
+        eprintln!("  | {}", redact::command_arg(line));
+    }
+}
+
+/// `output`/`spawn` that go through the debug echo. Use these instead of the std methods.
+pub trait CommandExt {
+    fn output_logged(&mut self) -> io::Result<Output>;
+    fn spawn_logged(&mut self) -> io::Result<Child>;
+    /// `output_logged` that turns a failed start or a non-zero exit into a [`failure`] message.
+    fn output_checked(&mut self) -> Result<Output, String>;
+}
+
+impl CommandExt for Command {
+    fn output_logged(&mut self) -> io::Result<Output> {
+        echo(self);
+        self.output()
+    }
+
+    fn spawn_logged(&mut self) -> io::Result<Child> {
+        echo(self);
+        self.spawn()
+    }
+
+    fn output_checked(&mut self) -> Result<Output, String> {
+        let output = self
+            .output_logged()
+            .map_err(|e| format!("could not run `{}`: {e}", display(self)))?;
+        if !output.status.success() {
+            return Err(failure(self, &output));
+        }
+        Ok(output)
+    }
+}
@@ -1,9 +1,14 @@
-//! Expose cluster via NodePort and get connection host from kubeconfig.
+//! Expose cluster via NodePort (or, with `fdb expose`, a LoadBalancer or ClusterIP Service)
+//! and get connection host from kubeconfig.
 
+use crate::access;
+use crate::cluster;
+use crate::exec::CommandExt;
+use crate::kubeblocks;
 use crate::service::ServiceType;
-use std::io::Write;
+use std::collections::BTreeMap;
 use std::path::Path;
-use std::process::{Command, Stdio};
+use std::process::Command;
 
 const NAMESPACE: &str = "default";
 
@@ -20,13 +25,7 @@ pub fn server_host_from_kubeconfig(kubectl: &Path, kubeconfig: &Path) -> Result<
             "-o",
             "jsonpath={.clusters[0].cluster.server}",
         ])
-        .output()
-        .map_err(|e| format!("kubectl config view: {e}"))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("kubectl config view failed: {stderr}"));
-    }
+        .output_checked()?;
 
     let url = String::from_utf8(output.stdout)
         .map_err(|e| format!("kubectl output utf-8: {e}"))?
@@ -47,63 +46,201 @@ fn parse_url_host(url: &str) -> Option<String> {
     Some(host.to_string())
 }
 
-/// Create our own NodePort service (KubeBlocks-owned svc is reverted if patched). Return nodePort.
-fn ensure_external_nodeport_service(
+/// First of the engine's client components with pods in the cluster; the last one when none is found yet.
+pub fn client_component(kubectl: &Path, service: ServiceType, cluster_name: &str, kubeconfig: &Path) -> &'static str {
+    let candidates = service.spec().client_components();
+    let Some((&last, preferred)) = candidates.split_last() else {
+        return service.component_name();
+    };
+    for &component in preferred {
+        let selector = format!("app.kubernetes.io/instance={cluster_name},apps.kubeblocks.io/component-name={component}");
+        let found = Command::new(kubectl)
+            .arg("--kubeconfig")
+            .arg(kubeconfig)
+            .args(["get", "pods", "-n", NAMESPACE, "-l", &selector, "-o", "name"])
+            .output_logged()
+            .is_ok_and(|out| out.status.success() && !out.stdout.iter().all(u8::is_ascii_whitespace));
+        if found {
+            return component;
+        }
+    }
+    last
+}
+
+/// How `fdb expose` makes a cluster reachable: the type of fdb's external Service, or no
+/// Service at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExposeType {
+    NodePort,
+    LoadBalancer,
+    ClusterIp,
+    None,
+}
+
+impl std::str::FromStr for ExposeType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "nodeport" => Ok(ExposeType::NodePort),
+            "loadbalancer" => Ok(ExposeType::LoadBalancer),
+            "clusterip" => Ok(ExposeType::ClusterIp),
+            "none" => Ok(ExposeType::None),
+            _ => Err(format!("invalid --type: {s} (use nodeport, loadbalancer, clusterip or none)")),
+        }
+    }
+}
+
+impl ExposeType {
+    /// `spec.type` of the Service; None for `none`.
+    fn service_type(self) -> Option<&'static str> {
+        match self {
+            ExposeType::NodePort => Some("NodePort"),
+            ExposeType::LoadBalancer => Some("LoadBalancer"),
+            ExposeType::ClusterIp => Some("ClusterIP"),
+            ExposeType::None => None,
+        }
+    }
+}
+
+/// fdb's own Service in front of the cluster (KubeBlocks reverts changes to the Services it owns).
+pub fn external_service_name(service: ServiceType, cluster_name: &str) -> String {
+    format!("{cluster_name}-{}-external", service.kbcli_name())
+}
+
+/// Manifest of the external Service of type `service_type`, selecting the client component's
+/// pods (the primary where the engine has roles) on the engine's ports.
+fn external_service_yaml(
     kubectl: &Path,
     service: ServiceType,
     cluster_name: &str,
+    service_type: &str,
     kubeconfig: &Path,
-) -> Result<u16, String> {
+) -> String {
     let port = service.default_port();
-    let component = service.kbcli_name();
+    let component = client_component(kubectl, service, cluster_name, kubeconfig);
     let port_name = service.port_name();
-    let external_svc = format!("{cluster_name}-{component}-external");
-
-    let exists = Command::new(kubectl)
-        .arg("--kubeconfig")
-        .arg(kubeconfig)
-        .args(["get", "svc", &external_svc, "-n", NAMESPACE, "-o", "name"])
-        .output()
-        .map_err(|e| format!("kubectl get svc: {e}"))?;
-
-    if !exists.status.success()
-        || !String::from_utf8_lossy(&exists.stdout).trim().contains("service/")
-    {
-        let yaml = format!(
-            r#"apiVersion: v1
+    let external_svc = external_service_name(service, cluster_name);
+    let role = match kubeblocks::api(kubectl, kubeconfig).primary_role(service) {
+        Some(role) => format!("\n    kubeblocks.io/role: {role}"),
+        None => String::new(),
+    };
+    // pg_hba can only filter clients by address when kube-proxy keeps the source address, which it
+    // does for traffic to a node running the pod (`--allow-cidr`).
+    let filtered = service == ServiceType::PostgreSQL
+        && service_type != "ClusterIP"
+        && access::allowed_cidrs(kubectl, cluster_name, kubeconfig).is_ok_and(|c| !c.is_empty());
+    let traffic_policy = if filtered { "\n  externalTrafficPolicy: Local" } else { "" };
+    let extra_ports: String = service
+        .extra_ports()
+        .iter()
+        .map(|(name, port)| format!("\n  - port: {port}\n    targetPort: {port}\n    protocol: TCP\n    name: {name}"))
+        .collect();
+    format!(
+        r#"apiVersion: v1
 kind: Service
 metadata:
   name: {external_svc}
   namespace: {NAMESPACE}
 spec:
-  type: NodePort
+  type: {service_type}{traffic_policy}
   selector:
     app.kubernetes.io/instance: "{cluster_name}"
-    apps.kubeblocks.io/component-name: {component}
-    kubeblocks.io/role: primary
+    apps.kubeblocks.io/component-name: {component}{role}
   ports:
   - port: {port}
     targetPort: {port}
     protocol: TCP
-    name: {port_name}
+    name: {port_name}{extra_ports}
 "#
-        );
+    )
+}
 
-        let mut apply = Command::new(kubectl)
-            .arg("--kubeconfig")
-            .arg(kubeconfig)
-            .args(["apply", "-f", "-"])
-            .stdin(Stdio::piped())
-            .spawn()
-            .map_err(|e| format!("kubectl apply: {e}"))?;
+/// `spec.type` of the cluster's external Service; None when it has none.
+pub fn current_type(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    kubeconfig: &Path,
+) -> Result<Option<String>, String> {
+    let output = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["get", "svc", &external_service_name(service, cluster_name), "-n", NAMESPACE, "--ignore-not-found"])
+        .args(["-o", "jsonpath={.spec.type}"])
+        .output_checked()?;
+    let kind = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok((!kind.is_empty()).then_some(kind))
+}
 
-        if let Some(mut stdin) = apply.stdin.take() {
-            stdin.write_all(yaml.as_bytes()).map_err(|e| format!("stdin: {e}"))?;
-        }
-        let status = apply.wait().map_err(|e| format!("kubectl apply wait: {e}"))?;
-        if !status.success() {
-            return Err("kubectl apply -f - failed".to_string());
+/// How clients outside Kubernetes reach a cluster, for `fdb list`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    /// `--type` spelling of the Service type: nodeport, loadbalancer or clusterip.
+    pub kind: String,
+    /// `host:port`; `<pending>` while a LoadBalancer has no address.
+    pub host: String,
+}
+
+/// Endpoints of every external Service fdb created, by Service name, from one `kubectl get svc`.
+pub fn external_endpoints(kubectl: &Path, kubeconfig: &Path) -> Result<BTreeMap<String, Endpoint>, String> {
+    let output = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["get", "svc", "-n", NAMESPACE, "-o"])
+        .arg(
+            "jsonpath={range .items[*]}{.metadata.name}{\"\\t\"}{.spec.type}{\"\\t\"}{.spec.ports[0].port}{\"\\t\"}\
+             {.spec.ports[0].nodePort}{\"\\t\"}{.status.loadBalancer.ingress[0].ip}\
+             {.status.loadBalancer.ingress[0].hostname}{\"\\n\"}{end}",
+        )
+        .output_checked()?;
+    let mut node_host = None;
+    let mut endpoints = BTreeMap::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [name, kind, port, node_port, address] = fields[..] else {
+            continue;
+        };
+        if !name.ends_with("-external") {
+            continue;
         }
+        let (kind, host) = match kind {
+            "NodePort" => {
+                let host = node_host
+                    .get_or_insert_with(|| server_host_from_kubeconfig(kubectl, kubeconfig).unwrap_or("?".to_string()));
+                ("nodeport", format!("{host}:{node_port}"))
+            }
+            "LoadBalancer" if address.is_empty() => ("loadbalancer", "<pending>".to_string()),
+            "LoadBalancer" => ("loadbalancer", format!("{address}:{port}")),
+            _ => ("clusterip", format!("{name}.{NAMESPACE}.svc:{port}")),
+        };
+        endpoints.insert(name.to_string(), Endpoint { kind: kind.to_string(), host });
+    }
+    Ok(endpoints)
+}
+
+/// Create our own NodePort service (KubeBlocks-owned svc is reverted if patched). Return nodePort.
+fn ensure_external_nodeport_service(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    kubeconfig: &Path,
+) -> Result<u16, String> {
+    let port = service.default_port();
+    let external_svc = external_service_name(service, cluster_name);
+
+    let exists = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["get", "svc", &external_svc, "-n", NAMESPACE, "-o", "name"])
+        .output_logged()
+        .map_err(|e| format!("kubectl get svc: {e}"))?;
+
+    if !exists.status.success()
+        || !String::from_utf8_lossy(&exists.stdout).trim().contains("service/")
+    {
+        let yaml = external_service_yaml(kubectl, service, cluster_name, "NodePort", kubeconfig);
+        cluster::apply_manifest(kubectl, &yaml, kubeconfig)?;
         std::thread::sleep(std::time::Duration::from_millis(800));
     }
 
@@ -111,32 +248,8 @@ spec:
         if attempt > 0 {
             std::thread::sleep(std::time::Duration::from_millis(500));
         }
-        for jsonpath in [
-            &format!("{{.spec.ports[?(@.port=={port})].nodePort}}"),
-            "{.spec.ports[*].nodePort}",
-            "{.spec.ports[0].nodePort}",
-        ] {
-            let port_out = Command::new(kubectl)
-                .arg("--kubeconfig")
-                .arg(kubeconfig)
-                .args([
-                    "get", "svc", &external_svc, "-n", NAMESPACE,
-                    "-o", &format!("jsonpath={jsonpath}"),
-                ])
-                .output()
-                .map_err(|e| format!("kubectl get svc: {e}"))?;
-
-            if !port_out.status.success() {
-                continue;
-            }
-            let out = String::from_utf8_lossy(&port_out.stdout).trim().to_string();
-            for port_str in out.split_whitespace() {
-                if let Ok(p) = port_str.parse::<u16>() {
-                    if p != 0 {
-                        return Ok(p);
-                    }
-                }
-            }
+        if let Some(p) = read_node_port(kubectl, &external_svc, port, kubeconfig)? {
+            return Ok(p);
         }
     }
 
@@ -145,6 +258,123 @@ spec:
     ))
 }
 
+/// External address a LoadBalancer Service got, once the cloud provider assigned one.
+fn load_balancer_address(kubectl: &Path, external_svc: &str, kubeconfig: &Path) -> Result<Option<String>, String> {
+    let output = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["get", "svc", external_svc, "-n", NAMESPACE])
+        .args(["-o", "jsonpath={.status.loadBalancer.ingress[0].ip} {.status.loadBalancer.ingress[0].hostname}"])
+        .output_checked()?;
+    Ok(String::from_utf8_lossy(&output.stdout).split_whitespace().next().map(str::to_string))
+}
+
+/// Where clients reach the cluster after `set_exposure`.
+pub enum Exposure {
+    NodePort { host: Option<String>, port: u16 },
+    /// None while the cloud provider has not assigned an address yet.
+    LoadBalancer { address: Option<String>, port: u16 },
+    ClusterIp { host: String, port: u16 },
+    None,
+}
+
+/// Create, change the type of, or delete (`none`) the cluster's external Service.
+pub fn set_exposure(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    expose: ExposeType,
+    kubeconfig: &Path,
+) -> Result<Exposure, String> {
+    let external_svc = external_service_name(service, cluster_name);
+    let Some(service_type) = expose.service_type() else {
+        Command::new(kubectl)
+            .arg("--kubeconfig")
+            .arg(kubeconfig)
+            .args(["delete", "svc", &external_svc, "-n", NAMESPACE, "--ignore-not-found=true"])
+            .output_checked()?;
+        return Ok(Exposure::None);
+    };
+    // The API server drops the node ports when the type changes to ClusterIP, so apply can
+    // move between all three types.
+    let yaml = external_service_yaml(kubectl, service, cluster_name, service_type, kubeconfig);
+    cluster::apply_manifest(kubectl, &yaml, kubeconfig)?;
+    let port = service.default_port();
+    match expose {
+        ExposeType::NodePort => Ok(Exposure::NodePort {
+            host: server_host_from_kubeconfig(kubectl, kubeconfig).ok(),
+            port: ensure_external_nodeport_service(kubectl, service, cluster_name, kubeconfig)?,
+        }),
+        ExposeType::LoadBalancer => {
+            let mut address = None;
+            for attempt in 0..10 {
+                if attempt > 0 {
+                    std::thread::sleep(std::time::Duration::from_secs(3));
+                }
+                address = load_balancer_address(kubectl, &external_svc, kubeconfig)?;
+                if address.is_some() {
+                    break;
+                }
+            }
+            Ok(Exposure::LoadBalancer { address, port })
+        }
+        ExposeType::ClusterIp => Ok(Exposure::ClusterIp {
+            host: format!("{external_svc}.{NAMESPACE}.svc"),
+            port,
+        }),
+        ExposeType::None => Ok(Exposure::None),
+    }
+}
+
+/// Read the nodePort assigned to our external service, if any.
+fn read_node_port(
+    kubectl: &Path,
+    external_svc: &str,
+    port: u16,
+    kubeconfig: &Path,
+) -> Result<Option<u16>, String> {
+    for jsonpath in [
+        &format!("{{.spec.ports[?(@.port=={port})].nodePort}}"),
+        "{.spec.ports[*].nodePort}",
+        "{.spec.ports[0].nodePort}",
+    ] {
+        let port_out = Command::new(kubectl)
+            .arg("--kubeconfig")
+            .arg(kubeconfig)
+            .args([
+                "get", "svc", external_svc, "-n", NAMESPACE,
+                "-o", &format!("jsonpath={jsonpath}"),
+            ])
+            .output_logged()
+            .map_err(|e| format!("kubectl get svc: {e}"))?;
+
+        if !port_out.status.success() {
+            continue;
+        }
+        let out = String::from_utf8_lossy(&port_out.stdout).trim().to_string();
+        for port_str in out.split_whitespace() {
+            if let Ok(p) = port_str.parse::<u16>() {
+                if p != 0 {
+                    return Ok(Some(p));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// NodePort of an already exposed cluster; does not create the service.
+pub fn existing_nodeport(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    kubeconfig: &Path,
+) -> Result<u16, String> {
+    let external_svc = external_service_name(service, cluster_name);
+    read_node_port(kubectl, &external_svc, service.default_port(), kubeconfig)?
+        .ok_or_else(|| format!("service {external_svc} not found or has no nodePort"))
+}
+
 /// Ensure NodePort is available (our external service) and return the port.
 pub fn ensure_nodeport_and_get_port(
     kubectl: &Path,
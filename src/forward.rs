@@ -0,0 +1,44 @@
+//! Foreground port-forward to a cluster (`fdb port-forward`): forwards a local port to the
+//! engine's client Service and prints the connection details for it until Ctrl-C.
+
+use crate::accounts;
+use crate::credentials;
+use crate::expose;
+use crate::portforward;
+use crate::service::ServiceType;
+use std::path::Path;
+
+/// Forward `local_port` (a free one when None) to the cluster and block until kubectl exits.
+/// Prints the app account's details when the cluster has one, else the admin user's.
+pub fn run(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    local_port: Option<u16>,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    let (user, password) = match accounts::app_account(kubectl, cluster_name, kubeconfig)? {
+        Some((user, password)) => (user, Some(password)),
+        None => (
+            credentials::get_user(kubectl, service, cluster_name, kubeconfig)?,
+            credentials::get_password(kubectl, service, cluster_name, kubeconfig)?,
+        ),
+    };
+    let component = expose::client_component(kubectl, service, cluster_name, kubeconfig);
+    let mut forward = portforward::start_port_forward(
+        kubectl,
+        &format!("svc/{cluster_name}-{component}"),
+        service.default_port(),
+        local_port,
+        kubeconfig,
+    )?;
+
+    println!("Forwarding 127.0.0.1:{} to \"{cluster_name}\"", forward.local_port);
+    println!();
+    let endpoint = Some(("127.0.0.1".to_string(), forward.local_port));
+    credentials::ConnectionDetails::for_user(service, &user, password, endpoint).print();
+    println!();
+    println!("Port-forward running; press Ctrl-C to stop.");
+    // Ctrl-C reaches kubectl too (same process group), so the forward never outlives fdb.
+    forward.wait()
+}
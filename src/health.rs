@@ -0,0 +1,329 @@
+//! Protocol-level checks that a new cluster accepts connections, not just that pods are Running.
+
+use crate::portforward;
+use crate::service::ServiceType;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::time::Duration;
+
+const ATTEMPTS: u32 = 5;
+const RETRY_DELAY: Duration = Duration::from_secs(3);
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn connect(port: u16) -> Result<TcpStream, String> {
+    let stream = TcpStream::connect(("127.0.0.1", port)).map_err(|e| format!("connect: {e}"))?;
+    let _ = stream.set_read_timeout(Some(IO_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(IO_TIMEOUT));
+    Ok(stream)
+}
+
+/// `SELECT 1` as the admin user.
+fn check_postgres(port: u16, user: &str, password: &str) -> Result<(), String> {
+    let mut client = postgres::Config::new()
+        .host("127.0.0.1")
+        .port(port)
+        .user(user)
+        .password(password)
+        .dbname("postgres")
+        .connect_timeout(IO_TIMEOUT)
+        .connect(postgres::NoTls)
+        .map_err(|e| e.to_string())?;
+    client.simple_query("SELECT 1").map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Read one CRLF-terminated line: RESP ("+PONG\r\n", "-ERR ...\r\n") or a NATS greeting.
+fn read_resp_line(stream: &mut TcpStream) -> Result<String, String> {
+    let mut line = Vec::new();
+    let mut byte = [0u8; 1];
+    while !line.ends_with(b"\r\n") {
+        stream.read_exact(&mut byte).map_err(|e| format!("read: {e}"))?;
+        line.push(byte[0]);
+    }
+    Ok(String::from_utf8_lossy(&line).trim_end().to_string())
+}
+
+/// `AUTH` (when the cluster has a password) then `PING`, expecting `+PONG`.
+fn check_redis(port: u16, password: Option<&str>) -> Result<(), String> {
+    let mut stream = connect(port)?;
+    if let Some(password) = password.filter(|p| !p.is_empty()) {
+        let auth = format!("*2\r\n$4\r\nAUTH\r\n${}\r\n{password}\r\n", password.len());
+        stream.write_all(auth.as_bytes()).map_err(|e| format!("write: {e}"))?;
+        let reply = read_resp_line(&mut stream)?;
+        if !reply.starts_with("+OK") {
+            return Err(format!("AUTH: {reply}"));
+        }
+    }
+    stream.write_all(b"*1\r\n$4\r\nPING\r\n").map_err(|e| format!("write: {e}"))?;
+    let reply = read_resp_line(&mut stream)?;
+    if reply != "+PONG" {
+        return Err(format!("PING: {reply}"));
+    }
+    Ok(())
+}
+
+/// Bolt handshake: magic preamble and four proposed versions (5.x down to 3.0); the server
+/// answers with the version it picked, all zeroes when it supports none.
+fn check_bolt(port: u16) -> Result<(), String> {
+    let mut stream = connect(port)?;
+    // Each proposal is (unused, range, minor, major): 5.4 and the four minors below it, etc.
+    let handshake = [
+        0x60, 0x60, 0xb0, 0x17, // magic
+        0, 4, 4, 5, // 5.0-5.4
+        0, 4, 4, 4, // 4.0-4.4
+        0, 0, 0, 3, // 3.0
+        0, 0, 0, 0, // no further proposal
+    ];
+    stream.write_all(&handshake).map_err(|e| format!("write: {e}"))?;
+    let mut version = [0u8; 4];
+    stream.read_exact(&mut version).map_err(|e| format!("read: {e}"))?;
+    if version == [0; 4] {
+        return Err("server supports none of the proposed Bolt versions".to_string());
+    }
+    Ok(())
+}
+
+/// Text protocol `version`, expecting `VERSION <x>`.
+fn check_memcached(port: u16) -> Result<(), String> {
+    let mut stream = connect(port)?;
+    stream.write_all(b"version\r\n").map_err(|e| format!("write: {e}"))?;
+    let reply = read_resp_line(&mut stream)?;
+    if !reply.starts_with("VERSION ") {
+        return Err(format!("version: {reply}"));
+    }
+    Ok(())
+}
+
+/// Send the AMQP 0-9-1 protocol header and expect a Connection.Start method frame.
+fn check_amqp(port: u16) -> Result<(), String> {
+    let mut stream = connect(port)?;
+    stream.write_all(b"AMQP\x00\x00\x09\x01").map_err(|e| format!("write: {e}"))?;
+    let mut frame = [0u8; 11];
+    stream.read_exact(&mut frame).map_err(|e| format!("read: {e}"))?;
+    // type 1 (method), channel 0, 4-byte size, class 10 (connection), method 10 (start)
+    if frame[0] != 1 || frame[7..11] != [0, 10, 0, 10] {
+        return Err("unexpected reply to AMQP protocol header".to_string());
+    }
+    Ok(())
+}
+
+/// `hello` on the admin database as an OP_MSG (allowed before authentication), expecting
+/// `ok: 1` in the reply.
+fn check_mongodb(port: u16) -> Result<(), String> {
+    let mut stream = connect(port)?;
+    // BSON {hello: 1, $db: "admin"}
+    let mut doc = Vec::new();
+    doc.extend(b"\x10hello\x00");
+    doc.extend(1i32.to_le_bytes());
+    doc.extend(b"\x02$db\x00");
+    doc.extend(6i32.to_le_bytes());
+    doc.extend(b"admin\x00\x00");
+    let doc = [&((doc.len() + 4) as i32).to_le_bytes()[..], &doc].concat();
+    // header (length, request id, response to, opcode 2013), flags, section kind 0
+    let length = (16 + 4 + 1 + doc.len()) as i32;
+    let mut message = Vec::new();
+    for field in [length, 1, 0, 2013, 0] {
+        message.extend(field.to_le_bytes());
+    }
+    message.push(0);
+    message.extend(doc);
+    stream.write_all(&message).map_err(|e| format!("write: {e}"))?;
+
+    let mut header = [0u8; 16];
+    stream.read_exact(&mut header).map_err(|e| format!("read: {e}"))?;
+    let length = i32::from_le_bytes(header[0..4].try_into().unwrap_or_default());
+    if i32::from_le_bytes(header[12..16].try_into().unwrap_or_default()) != 2013 || !(16..1 << 24).contains(&length) {
+        return Err("unexpected reply to hello".to_string());
+    }
+    let mut body = vec![0u8; length as usize - 16];
+    stream.read_exact(&mut body).map_err(|e| format!("read: {e}"))?;
+    // `ok` is a double: type 0x01, name, 1.0
+    let ok = [&b"\x01ok\x00"[..], &1f64.to_le_bytes()].concat();
+    if !body.windows(ok.len()).any(|w| w == ok) {
+        return Err("hello did not return ok: 1".to_string());
+    }
+    Ok(())
+}
+
+/// Read the server's initial handshake packet and expect protocol version 10; an error
+/// packet (0xff, e.g. "Host is blocked") fails with its message.
+fn check_mysql(port: u16) -> Result<(), String> {
+    let mut stream = connect(port)?;
+    let mut header = [0u8; 4];
+    stream.read_exact(&mut header).map_err(|e| format!("read: {e}"))?;
+    let length = u32::from_le_bytes([header[0], header[1], header[2], 0]) as usize;
+    let mut payload = vec![0u8; length];
+    stream.read_exact(&mut payload).map_err(|e| format!("read: {e}"))?;
+    match payload.first() {
+        Some(10) => Ok(()),
+        Some(0xff) => Err(String::from_utf8_lossy(payload.get(3..).unwrap_or_default()).into_owned()),
+        _ => Err("unexpected MySQL handshake".to_string()),
+    }
+}
+
+/// The server greets every connection with an `INFO {...}` line.
+fn check_nats(port: u16) -> Result<(), String> {
+    let mut stream = connect(port)?;
+    let line = read_resp_line(&mut stream)?;
+    if !line.starts_with("INFO ") {
+        return Err(format!("unexpected greeting: {line}"));
+    }
+    Ok(())
+}
+
+/// PostgreSQL SSLRequest, answered with `S` or `N` before any authentication; used where the
+/// server may insist on TLS (CockroachDB).
+fn check_ssl_request(port: u16) -> Result<(), String> {
+    let mut stream = connect(port)?;
+    // length, SSLRequest code
+    stream.write_all(&[0, 0, 0, 8, 0x04, 0xd2, 0x16, 0x2f]).map_err(|e| format!("write: {e}"))?;
+    let mut reply = [0u8; 1];
+    stream.read_exact(&mut reply).map_err(|e| format!("read: {e}"))?;
+    match reply[0] {
+        b'S' | b'N' => Ok(()),
+        other => Err(format!("unexpected reply to SSLRequest: {other:#x}")),
+    }
+}
+
+/// CQL native protocol v4 OPTIONS frame, expecting SUPPORTED; it needs no authentication.
+fn check_cql(port: u16) -> Result<(), String> {
+    let mut stream = connect(port)?;
+    // version, flags, stream id, opcode OPTIONS, body length
+    stream.write_all(&[0x04, 0, 0, 0, 0x05, 0, 0, 0, 0]).map_err(|e| format!("write: {e}"))?;
+    let mut header = [0u8; 9];
+    stream.read_exact(&mut header).map_err(|e| format!("read: {e}"))?;
+    match (header[0], header[4]) {
+        (0x84, 0x06) => Ok(()),
+        (_, 0x00) => Err("server replied with an ERROR frame".to_string()),
+        (version, opcode) => Err(format!("unexpected reply (version {version:#x}, opcode {opcode:#x})")),
+    }
+}
+
+/// ApiVersions v0 request to the broker, expecting our correlation id and error code 0.
+fn check_kafka(port: u16) -> Result<(), String> {
+    let mut stream = connect(port)?;
+    const CLIENT_ID: &[u8] = b"fdb";
+    let mut request = Vec::new();
+    request.extend(18i16.to_be_bytes()); // api key: ApiVersions
+    request.extend(0i16.to_be_bytes()); // api version
+    request.extend(1i32.to_be_bytes()); // correlation id
+    request.extend((CLIENT_ID.len() as i16).to_be_bytes());
+    request.extend(CLIENT_ID);
+    let message = [&(request.len() as i32).to_be_bytes()[..], &request].concat();
+    stream.write_all(&message).map_err(|e| format!("write: {e}"))?;
+
+    // size, correlation id, error code
+    let mut reply = [0u8; 10];
+    stream.read_exact(&mut reply).map_err(|e| format!("read: {e}"))?;
+    if reply[4..8] != 1i32.to_be_bytes() {
+        return Err("unexpected reply to ApiVersions".to_string());
+    }
+    match i16::from_be_bytes([reply[8], reply[9]]) {
+        0 => Ok(()),
+        code => Err(format!("ApiVersions error code {code}")),
+    }
+}
+
+/// `GET <path>` on an HTTP API (Qdrant `/readyz`, Weaviate `/v1/.well-known/ready`, etcd
+/// `/health`, MinIO `/minio/health/live`, InfluxDB `/health`).
+fn check_http(port: u16, path: &str) -> Result<(), String> {
+    ureq::get(&format!("http://127.0.0.1:{port}{path}"))
+        .timeout(IO_TIMEOUT)
+        .call()
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Readiness check an engine declares ([`crate::services::Service::health_check`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthCheck {
+    /// `SELECT 1` as the admin user.
+    Postgres,
+    /// `AUTH` when there is a password, then `PING`.
+    Redis,
+    Memcached,
+    Bolt,
+    Amqp,
+    /// `GET` on the path of an HTTP API.
+    Http(&'static str),
+    MongoDB,
+    Kafka,
+    MySql,
+    SslRequest,
+    Nats,
+    Cql,
+    /// Plain TCP connect, for engines without a protocol check.
+    Connect,
+}
+
+impl HealthCheck {
+    /// What the check does, for the final output.
+    fn describe(self) -> String {
+        match self {
+            HealthCheck::Postgres => "SELECT 1".to_string(),
+            HealthCheck::Redis => "PING".to_string(),
+            HealthCheck::Memcached => "version".to_string(),
+            HealthCheck::Bolt => "Bolt handshake".to_string(),
+            HealthCheck::Amqp => "AMQP handshake".to_string(),
+            HealthCheck::Http(path) => format!("GET {path}"),
+            HealthCheck::MongoDB => "hello".to_string(),
+            HealthCheck::Kafka => "ApiVersions".to_string(),
+            HealthCheck::MySql => "MySQL handshake".to_string(),
+            HealthCheck::SslRequest => "SSLRequest".to_string(),
+            HealthCheck::Nats => "INFO".to_string(),
+            HealthCheck::Cql => "CQL OPTIONS".to_string(),
+            HealthCheck::Connect => "TCP connect".to_string(),
+        }
+    }
+
+    fn run(self, port: u16, user: &str, password: Option<&str>) -> Result<(), String> {
+        match self {
+            HealthCheck::Postgres => check_postgres(port, user, password.unwrap_or_default()),
+            HealthCheck::Redis => check_redis(port, password),
+            HealthCheck::Memcached => check_memcached(port),
+            HealthCheck::Bolt => check_bolt(port),
+            HealthCheck::Amqp => check_amqp(port),
+            HealthCheck::Http(path) => check_http(port, path),
+            HealthCheck::MongoDB => check_mongodb(port),
+            HealthCheck::Kafka => check_kafka(port),
+            HealthCheck::MySql => check_mysql(port),
+            HealthCheck::SslRequest => check_ssl_request(port),
+            HealthCheck::Nats => check_nats(port),
+            HealthCheck::Cql => check_cql(port),
+            HealthCheck::Connect => connect(port).map(|_| ()),
+        }
+    }
+}
+
+/// Run the engine's check through a temporary port-forward, retrying briefly while the
+/// service finishes starting. Ok carries a short description of what succeeded.
+pub fn check(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    password: Option<&str>,
+    kubeconfig: &Path,
+) -> Result<String, String> {
+    let forward = portforward::start_port_forward(
+        kubectl,
+        &format!("svc/{cluster_name}-{}", service.component_name()),
+        service.default_port(),
+        None,
+        kubeconfig,
+    )?;
+    let port = forward.local_port;
+    let probe = service.spec().health_check();
+    let mut last_error = String::new();
+    for attempt in 1..=ATTEMPTS {
+        let result = probe.run(port, service.default_user(), password);
+        match result {
+            Ok(()) => return Ok(probe.describe()),
+            Err(e) => last_error = e,
+        }
+        if attempt < ATTEMPTS {
+            std::thread::sleep(RETRY_DELAY);
+        }
+    }
+    Err(format!("{} failed: {last_error}", probe.describe()))
+}
@@ -0,0 +1,101 @@
+//! Audit log of mutating fdb invocations in ~/.fdb/history.jsonl (`fdb history`).
+
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+/// One line of history.jsonl.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// RFC 3339 local time.
+    pub timestamp: String,
+    pub user: String,
+    /// Command name, e.g. "create" or "delete".
+    pub action: String,
+    /// Cluster name the command acted on.
+    pub target: String,
+    /// Command-line arguments after `fdb`.
+    pub args: Vec<String>,
+    /// "ok" or the error message.
+    pub result: String,
+}
+
+fn history_path() -> PathBuf {
+    crate::config::fdb_home().join("history.jsonl")
+}
+
+fn current_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// Append an entry for a finished command. Failures only warn: the audit log must never block work.
+pub fn record(action: &str, target: &str, result: Result<(), &str>) {
+    let entry = HistoryEntry {
+        timestamp: chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+        user: current_user(),
+        action: action.to_string(),
+        target: target.to_string(),
+        args: std::env::args().skip(1).collect(),
+        result: match result {
+            Ok(()) => "ok".to_string(),
+            Err(e) => e.to_string(),
+        },
+    };
+    if let Err(e) = append(&entry) {
+        eprintln!("warning: could not write {}: {e}", history_path().display());
+    }
+}
+
+fn append(entry: &HistoryEntry) -> Result<(), String> {
+    let path = history_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path).map_err(|e| e.to_string())?;
+    writeln!(file, "{line}").map_err(|e| e.to_string())
+}
+
+/// Read all entries, optionally only those for one cluster. Malformed lines are skipped.
+pub fn load(target: Option<&str>) -> Result<Vec<HistoryEntry>, String> {
+    let path = history_path();
+    let file = match std::fs::File::open(&path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("open {}: {e}", path.display())),
+    };
+    Ok(std::io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|l| serde_json::from_str::<HistoryEntry>(&l).ok())
+        .filter(|e| target.is_none_or(|t| e.target == t))
+        .collect())
+}
+
+/// Print history as a table, oldest first.
+pub fn print(entries: &[HistoryEntry]) {
+    if entries.is_empty() {
+        println!("No history.");
+        return;
+    }
+    println!("{:<25} {:<12} {:<10} {:<20} RESULT", "TIME", "USER", "ACTION", "TARGET");
+    for e in entries {
+        println!(
+            "{:<25} {:<12} {:<10} {:<20} {}",
+            e.timestamp,
+            e.user,
+            e.action,
+            e.target,
+            e.result.lines().next().unwrap_or("")
+        );
+    }
+}
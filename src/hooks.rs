@@ -0,0 +1,112 @@
+//! User commands from `[hooks]` in fdb.toml, run around create and delete.
+
+use crate::cluster;
+use crate::config;
+use crate::credentials::ConnectionDetails;
+use crate::service::ServiceType;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+/// Output lines repeated in the error when a hook fails.
+const ERROR_TAIL: usize = 10;
+
+/// Environment every hook gets: FDB_CLUSTER, FDB_ENGINE, FDB_NAMESPACE, FDB_KUBECONFIG.
+fn base_env(service: ServiceType, cluster_name: &str, kubeconfig: &Path) -> Vec<(String, String)> {
+    vec![
+        ("FDB_CLUSTER".to_string(), cluster_name.to_string()),
+        ("FDB_ENGINE".to_string(), service.kbcli_name().to_string()),
+        ("FDB_NAMESPACE".to_string(), "default".to_string()),
+        ("FDB_KUBECONFIG".to_string(), kubeconfig.display().to_string()),
+    ]
+}
+
+/// Run `[hooks] post-create` if set; connection details come in as FDB_USER, FDB_PASSWORD,
+/// FDB_HOST, FDB_PORT and FDB_URI (those that are known).
+pub fn post_create(
+    service: ServiceType,
+    cluster_name: &str,
+    details: &ConnectionDetails,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    let hooks = config::load_hooks_config();
+    let Some(command) = hooks.post_create.as_deref() else {
+        return Ok(());
+    };
+    let mut env = base_env(service, cluster_name, kubeconfig);
+    env.extend(details.fields().into_iter().map(|(k, v)| (format!("FDB_{}", k.to_uppercase()), v)));
+    run("post-create", &command.replace("${cluster}", cluster_name), &env, timeout(&hooks)?)
+        .map_err(|e| format!("{e}; cluster \"{cluster_name}\" was created"))
+}
+
+/// Run `[hooks] pre-delete` if set; an error means the delete must not go ahead.
+pub fn pre_delete(kbcli: &Path, cluster_name: &str, kubeconfig: &Path) -> Result<(), String> {
+    let hooks = config::load_hooks_config();
+    let Some(command) = hooks.pre_delete.as_deref() else {
+        return Ok(());
+    };
+    let service = cluster::cluster_service_type(kbcli, cluster_name, kubeconfig)?;
+    let command = command.replace("${cluster}", cluster_name);
+    run("pre-delete", &command, &base_env(service, cluster_name, kubeconfig), timeout(&hooks)?)
+        .map_err(|e| format!("{e}; cluster \"{cluster_name}\" was not deleted"))
+}
+
+fn timeout(hooks: &config::HooksConfig) -> Result<Duration, String> {
+    match hooks.timeout.as_deref() {
+        Some(t) => config::parse_duration(t).map_err(|e| format!("[hooks] timeout: {e}")),
+        None => Ok(DEFAULT_TIMEOUT),
+    }
+}
+
+/// Run `command` with `sh -c`, killing it after `timeout`. Its combined output is captured
+/// and printed prefixed with the hook name once it exits.
+fn run(hook: &str, command: &str, env: &[(String, String)], timeout: Duration) -> Result<(), String> {
+    println!("Running {hook} hook: {command}");
+    let mut cmd = Command::new("sh");
+    // eval after the redirect, so even syntax errors land in the captured output.
+    cmd.args(["-c", "exec 2>&1; eval \"$1\"", "sh", command])
+        .envs(env.iter().map(|(k, v)| (k, v)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped());
+    // Own process group, so a timeout also stops whatever the script started.
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+    let mut child = cmd.spawn().map_err(|e| format!("{hook} hook: start sh: {e}"))?;
+    let mut stdout = child.stdout.take().expect("piped stdout");
+    let reader = std::thread::spawn(move || {
+        let mut out = Vec::new();
+        let _ = stdout.read_to_end(&mut out);
+        out
+    });
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| format!("{hook} hook: {e}"))? {
+            break Some(status);
+        }
+        if start.elapsed() >= timeout {
+            #[cfg(unix)]
+            let _ = Command::new("kill").args(["-KILL", "--", &format!("-{}", child.id())]).status();
+            let _ = child.kill();
+            let _ = child.wait();
+            break None;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    };
+    let output = String::from_utf8_lossy(&reader.join().unwrap_or_default()).into_owned();
+    for line in output.lines() {
+        println!("  {hook} | {line}");
+    }
+
+    let tail = || {
+        let lines: Vec<&str> = output.lines().collect();
+        lines[lines.len().saturating_sub(ERROR_TAIL)..].join("\n")
+    };
+    match status {
+        None => Err(format!("{hook} hook timed out after {}s:\n{}", timeout.as_secs(), tail())),
+        Some(s) if !s.success() => Err(format!("{hook} hook failed ({s}):\n{}", tail())),
+        Some(_) => Ok(()),
+    }
+}
@@ -0,0 +1,122 @@
+//! Idle detection (`fdb pause-idle`): every check samples client connections from the engine
+//! (pg_stat_activity, CLIENT LIST, list_connections) and stamps the cluster with the last time
+//! anyone was connected; clusters without a stamp count from their creation time.
+
+use crate::accounts;
+use crate::cluster::{self, ClusterInfo};
+use crate::error::{self, Error, ExitCode};
+use crate::load;
+use crate::service::ServiceType;
+use chrono::{DateTime, Utc};
+use std::path::Path;
+use std::time::Duration;
+
+/// RFC 3339 time a client connection was last seen.
+const LAST_ACTIVE_ANNOTATION: &str = "fdb.io/last-active";
+
+/// Sample connections now and return the last time the cluster had clients, if ever seen.
+fn record(kubectl: &Path, service: ServiceType, name: &str, kubeconfig: &Path) -> Result<Option<DateTime<Utc>>, String> {
+    if accounts::client_connections(kubectl, service, name, kubeconfig)?.is_some_and(|n| n > 0) {
+        let now = Utc::now();
+        cluster::set_cluster_annotation(kubectl, name, LAST_ACTIVE_ANNOTATION, Some(&now.to_rfc3339()), kubeconfig)?;
+        return Ok(Some(now));
+    }
+    let stamp = cluster::cluster_annotation(kubectl, name, LAST_ACTIVE_ANNOTATION, kubeconfig)?;
+    Ok(stamp
+        .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+        .map(|t| t.with_timezone(&Utc)))
+}
+
+/// Running clusters whose engine reports connections (see `accounts::client_connections`).
+fn sampled_clusters(kbcli: &Path, name: Option<&str>, kubeconfig: &Path) -> Result<Vec<(ClusterInfo, ServiceType)>, String> {
+    let clusters = cluster::list_cluster_infos(kbcli, name, kubeconfig)?;
+    Ok(clusters
+        .into_iter()
+        .filter(|c| c.status == "Running")
+        .filter_map(|c| {
+            let service = cluster::service_from_definition(&c.definition).ok()?;
+            let sampled = matches!(
+                service,
+                ServiceType::PostgreSQL | ServiceType::Redis | ServiceType::RabbitMQ | ServiceType::MongoDB
+            );
+            sampled.then_some((c, service))
+        })
+        .collect())
+}
+
+/// Stamp running clusters that have clients now; `fdb gc` calls this so the history builds
+/// up between `fdb pause-idle` runs. Failures are warnings.
+pub fn sample(kbcli: &Path, kubectl: &Path, name: Option<&str>, kubeconfig: &Path) -> Result<(), String> {
+    for (info, service) in sampled_clusters(kbcli, name, kubeconfig)? {
+        if let Err(e) = record(kubectl, service, &info.name, kubeconfig) {
+            eprintln!("warning: could not check connections on \"{}\": {e}", info.name);
+        }
+    }
+    Ok(())
+}
+
+/// A cluster nobody has connected to for a while.
+struct Idle {
+    name: String,
+    /// Last seen connection; None when never seen (idle since creation).
+    last_active: Option<DateTime<Utc>>,
+    idle_for: chrono::TimeDelta,
+}
+
+fn find_idle(kbcli: &Path, kubectl: &Path, after: Duration, kubeconfig: &Path) -> Result<Vec<Idle>, String> {
+    let after = chrono::TimeDelta::from_std(after).map_err(|e| format!("--after: {e}"))?;
+    let now = Utc::now();
+    let mut idle = Vec::new();
+    for (info, service) in sampled_clusters(kbcli, None, kubeconfig)? {
+        let last_active = match record(kubectl, service, &info.name, kubeconfig) {
+            Ok(t) => t,
+            Err(e) => {
+                eprintln!("warning: skipping \"{}\": could not check connections: {e}", info.name);
+                continue;
+            }
+        };
+        let Some(since) = last_active.or_else(|| cluster::created_at(&info)) else {
+            continue;
+        };
+        if now - since >= after {
+            idle.push(Idle {
+                name: info.name,
+                last_active,
+                idle_for: now - since,
+            });
+        }
+    }
+    Ok(idle)
+}
+
+/// List running clusters without client connections for at least `after`, then stop them
+/// after confirmation (`yes` skips it, except on production contexts).
+pub fn pause_idle(kbcli: &Path, kubectl: &Path, after: Duration, yes: bool, kubeconfig: &Path) -> Result<(), Error> {
+    let idle = find_idle(kbcli, kubectl, after, kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    let after = chrono::TimeDelta::from_std(after).map(cluster::format_age).unwrap_or_default();
+    if idle.is_empty() {
+        println!("No running clusters idle for {after} or more.");
+        return Ok(());
+    }
+    println!("Idle for {after} or more:");
+    let width = idle.iter().map(|i| i.name.len()).max().unwrap_or(0);
+    for i in &idle {
+        let seen = match i.last_active {
+            Some(t) => format!("last connection {} ago ({})", cluster::format_age(i.idle_for), t.format("%Y-%m-%d %H:%M UTC")),
+            None => format!("no connection seen since creation {} ago", cluster::format_age(i.idle_for)),
+        };
+        println!("  {:width$}  {seen}", i.name);
+    }
+    let mut confirmed = false;
+    for i in &idle {
+        confirmed |= cluster::confirm_production(kubectl, &i.name, "stop", kubeconfig)?;
+    }
+    if !yes && !confirmed && !load::confirm(&format!("Stop {} cluster(s)?", idle.len()))? {
+        return Err("aborted".into());
+    }
+    for i in &idle {
+        cluster::set_running(kbcli, &i.name, false, kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+        println!("Stopped \"{}\"; `fdb start {}` brings it back.", i.name, i.name);
+    }
+    Ok(())
+}
@@ -0,0 +1,145 @@
+//! Differences between KubeBlocks releases. The installed operator is detected once per run
+//! from the Cluster CRD and each caller asks the matching adapter for names and flags instead
+//! of hard-coding one release's layout.
+
+use crate::exec::CommandExt;
+use crate::service::ServiceType;
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// Cluster API served by the operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Api {
+    /// KubeBlocks 0.x (`apps.kubeblocks.io/v1alpha1`): ClusterVersion objects, and
+    /// `<cluster>-conn-credential` secrets before 0.9.
+    V1Alpha1,
+    /// KubeBlocks 1.x (`apps.kubeblocks.io/v1`): service versions and per-account secrets.
+    V1,
+    /// Not detected (no access to CRDs); callers try every layout.
+    Unknown,
+}
+
+static DETECTED: OnceLock<Api> = OnceLock::new();
+
+/// Served versions of the Cluster CRD, e.g. "v1 v1alpha1".
+fn served_versions(kubectl: &Path, kubeconfig: &Path) -> Result<String, String> {
+    let out = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["get", "crd", "clusters.apps.kubeblocks.io"])
+        .args(["-o", "jsonpath={.spec.versions[?(@.served==true)].name}"])
+        .output_checked()?;
+    Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+fn from_served(versions: &str) -> Api {
+    let versions: Vec<&str> = versions.split_whitespace().collect();
+    if versions.contains(&"v1") {
+        Api::V1
+    } else if versions.contains(&"v1alpha1") {
+        Api::V1Alpha1
+    } else {
+        Api::Unknown
+    }
+}
+
+/// The operator's API, detected on first use.
+pub fn api(kubectl: &Path, kubeconfig: &Path) -> Api {
+    *DETECTED.get_or_init(|| served_versions(kubectl, kubeconfig).map(|v| from_served(&v)).unwrap_or(Api::Unknown))
+}
+
+impl Api {
+    /// Secrets that may hold the admin password, in lookup order.
+    pub fn secret_names(self, service: ServiceType, cluster_name: &str) -> Vec<String> {
+        let all = service.secret_names(cluster_name);
+        match self {
+            // 1.x only creates per-account secrets.
+            Api::V1 => all.into_iter().filter(|s| !s.ends_with("-conn-credential")).collect(),
+            Api::V1Alpha1 | Api::Unknown => all,
+        }
+    }
+
+    /// `--version` for `kbcli cluster create`: 1.x takes the service version ("14.8.0"),
+    /// 0.x a ClusterVersion name ("postgresql-14.8.0"). Either spelling is accepted.
+    pub fn service_version(self, service: ServiceType, version: &str) -> String {
+        let prefix = format!("{}-", service.kbcli_name());
+        match self {
+            Api::V1 => version.strip_prefix(&prefix).unwrap_or(version).to_string(),
+            Api::V1Alpha1 if !version.starts_with(&prefix) => format!("{prefix}{version}"),
+            Api::V1Alpha1 | Api::Unknown => version.to_string(),
+        }
+    }
+
+    /// Versions the operator can run the engine at, in the spelling `service_version` produces:
+    /// the releases of the main component's ComponentVersion on 1.x, the engine's ClusterVersion
+    /// names on 0.x.
+    pub fn available_versions(
+        self,
+        kubectl: &Path,
+        service: ServiceType,
+        kubeconfig: &Path,
+    ) -> Result<Vec<String>, String> {
+        let query = |args: &[&str]| -> Result<Vec<String>, String> {
+            let out = Command::new(kubectl).arg("--kubeconfig").arg(kubeconfig).args(args).output_checked()?;
+            Ok(String::from_utf8_lossy(&out.stdout).split_whitespace().map(str::to_string).collect())
+        };
+        let component = service.spec().component_name().to_string();
+        let releases = "jsonpath={.spec.releases[*].serviceVersion}";
+        let v1 = || query(&["get", "componentversion", &component, "-o", releases]);
+        let selector = format!("clusterdefinition.kubeblocks.io/name={}", service.kbcli_name());
+        let names = "jsonpath={.items[*].metadata.name}";
+        let v1alpha1 = || query(&["get", "clusterversion", "-l", &selector, "-o", names]);
+        match self {
+            Api::V1 => v1(),
+            Api::V1Alpha1 => v1alpha1(),
+            Api::Unknown => v1().or_else(|_| v1alpha1()),
+        }
+    }
+
+    /// `kbcli` arguments of the OpsRequest moving `component` of the cluster to `version`
+    /// (as `service_version` spells it).
+    pub fn upgrade_args(self, cluster_name: &str, component: &str, version: &str) -> Vec<String> {
+        let mut args: Vec<String> = ["cluster", "upgrade", cluster_name, "-n", "default"].map(String::from).into();
+        match self {
+            Api::V1 | Api::Unknown => {
+                args.extend(["--components", component, "--service-version", version].map(String::from));
+            }
+            Api::V1Alpha1 => args.extend(["--cluster-version", version].map(String::from)),
+        }
+        args.push("--auto-approve".to_string());
+        args
+    }
+
+    /// Role label value of the pod accepting writes, for Service selectors. 1.x RabbitMQ and
+    /// Qdrant components have no roles, so selecting on one would leave the Service empty;
+    /// neither do the other engines' pods.
+    pub fn primary_role(self, service: ServiceType) -> Option<&'static str> {
+        match (self, service) {
+            (_, ServiceType::PostgreSQL | ServiceType::Redis | ServiceType::MongoDB) => Some("primary"),
+            (Api::V1, ServiceType::RabbitMQ | ServiceType::Qdrant) => None,
+            (Api::V1Alpha1 | Api::Unknown, ServiceType::RabbitMQ | ServiceType::Qdrant) => Some("primary"),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adapts_versions_and_secrets_to_the_operator() {
+        assert_eq!(from_served("v1 v1alpha1"), Api::V1);
+        assert_eq!(from_served("v1alpha1"), Api::V1Alpha1);
+        assert_eq!(from_served(""), Api::Unknown);
+        let pg = ServiceType::PostgreSQL;
+        assert_eq!(Api::V1.service_version(pg, "postgresql-14.8.0"), "14.8.0");
+        assert_eq!(Api::V1Alpha1.service_version(pg, "14.8.0"), "postgresql-14.8.0");
+        assert_eq!(Api::V1.secret_names(pg, "orders"), ["orders-postgresql-account-postgres"]);
+        assert_eq!(Api::Unknown.secret_names(pg, "orders").len(), 2);
+        let upgrade = |api: Api, version: &str| api.upgrade_args("orders", "postgresql", version).join(" ");
+        assert!(upgrade(Api::V1, "16.4.0").contains("--components postgresql --service-version 16.4.0"));
+        assert!(upgrade(Api::V1Alpha1, "postgresql-14.8.0").contains("--cluster-version postgresql-14.8.0"));
+    }
+}
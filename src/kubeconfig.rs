@@ -0,0 +1,156 @@
+//! Scoped kubeconfig for CI consumers of one cluster (`fdb kubeconfig export`).
+
+use crate::exec::{self, CommandExt};
+use crate::redact;
+use crate::service::ServiceType;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+const NAMESPACE: &str = "default";
+
+fn kubectl_output(kubectl: &Path, kubeconfig: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(args)
+        .output_checked()?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Name of the kubeconfig's current context.
+pub fn current_context(kubectl: &Path, kubeconfig: &Path) -> Result<String, String> {
+    kubectl_output(kubectl, kubeconfig, &["config", "current-context"])
+}
+
+/// ServiceAccount, Role and RoleBinding allowing only: read the cluster's account secrets,
+/// find its pods/services, and port-forward to them.
+fn rbac_manifest(service: ServiceType, cluster_name: &str, account: &str) -> String {
+    let secrets = service
+        .secret_names(cluster_name)
+        .iter()
+        .map(|s| format!("\"{s}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        r#"apiVersion: v1
+kind: ServiceAccount
+metadata:
+  name: {account}
+  namespace: {NAMESPACE}
+---
+apiVersion: rbac.authorization.k8s.io/v1
+kind: Role
+metadata:
+  name: {account}
+  namespace: {NAMESPACE}
+rules:
+- apiGroups: [""]
+  resources: ["secrets"]
+  resourceNames: [{secrets}]
+  verbs: ["get"]
+- apiGroups: [""]
+  resources: ["pods", "services"]
+  verbs: ["get", "list"]
+- apiGroups: [""]
+  resources: ["pods/portforward"]
+  verbs: ["create"]
+---
+apiVersion: rbac.authorization.k8s.io/v1
+kind: RoleBinding
+metadata:
+  name: {account}
+  namespace: {NAMESPACE}
+roleRef:
+  apiGroup: rbac.authorization.k8s.io
+  kind: Role
+  name: {account}
+subjects:
+- kind: ServiceAccount
+  name: {account}
+  namespace: {NAMESPACE}
+"#
+    )
+}
+
+/// Create the scoped ServiceAccount (idempotent) and return a kubeconfig using a token valid for `ttl`.
+pub fn export_scoped_kubeconfig(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    ttl: std::time::Duration,
+    kubeconfig: &Path,
+) -> Result<String, String> {
+    let account = format!("fdb-{cluster_name}-reader");
+
+    let mut apply = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["apply", "-f", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn_logged()
+        .map_err(|e| format!("kubectl apply: {e}"))?;
+    let manifest = rbac_manifest(service, cluster_name, &account);
+    exec::echo_stdin(&manifest);
+    if let Some(mut stdin) = apply.stdin.take() {
+        stdin
+            .write_all(manifest.as_bytes())
+            .map_err(|e| format!("stdin: {e}"))?;
+    }
+    let status = apply.wait().map_err(|e| format!("kubectl apply wait: {e}"))?;
+    if !status.success() {
+        return Err(format!("`kubectl apply -f -` failed ({status})"));
+    }
+
+    let token = kubectl_output(
+        kubectl,
+        kubeconfig,
+        &["create", "token", &account, "-n", NAMESPACE, "--duration", &format!("{}s", ttl.as_secs())],
+    )?;
+    redact::register(&token);
+    let server = kubectl_output(
+        kubectl,
+        kubeconfig,
+        &["config", "view", "--minify", "-o", "jsonpath={.clusters[0].cluster.server}"],
+    )?;
+    let ca_data = kubectl_output(
+        kubectl,
+        kubeconfig,
+        &[
+            "config",
+            "view",
+            "--minify",
+            "--raw",
+            "-o",
+            "jsonpath={.clusters[0].cluster.certificate-authority-data}",
+        ],
+    )?;
+    let ca_line = if ca_data.is_empty() {
+        "    insecure-skip-tls-verify: true".to_string()
+    } else {
+        format!("    certificate-authority-data: {ca_data}")
+    };
+
+    Ok(format!(
+        r#"apiVersion: v1
+kind: Config
+clusters:
+- name: fdb-{cluster_name}
+  cluster:
+    server: {server}
+{ca_line}
+users:
+- name: {account}
+  user:
+    token: {token}
+contexts:
+- name: fdb-{cluster_name}
+  context:
+    cluster: fdb-{cluster_name}
+    user: {account}
+    namespace: {NAMESPACE}
+current-context: fdb-{cluster_name}
+"#
+    ))
+}
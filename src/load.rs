@@ -0,0 +1,125 @@
+//! Import a local dump into a cluster (`fdb load`).
+
+use crate::pods;
+use crate::resources;
+use crate::service::ServiceType;
+use nanospinner::Spinner;
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// First line `pg_dumpall` writes; such dumps recreate roles that usually exist already.
+pub const PG_DUMPALL_HEADER: &str = "-- PostgreSQL database cluster dump";
+
+/// Number of user tables in the database, to warn before loading over existing data.
+pub fn pg_table_count(kubectl: &Path, pod: &str, database: &str, kubeconfig: &Path) -> Result<u64, String> {
+    let sql = "SELECT count(*) FROM pg_tables WHERE schemaname NOT IN ('pg_catalog', 'information_schema')";
+    let out = pods::exec(
+        kubectl,
+        pod,
+        "postgresql",
+        &["psql", "-U", "postgres", "-d", database, "-tAc", sql],
+        kubeconfig,
+    )?;
+    out.trim().parse().map_err(|_| format!("unexpected table count from {pod}: {}", out.trim()))
+}
+
+/// Ask a yes/no question; the prompt goes to stderr so stdout keeps only results (`-o json|yaml`).
+pub fn confirm(question: &str) -> Result<bool, String> {
+    eprint!("{question} [y/N]: ");
+    let _ = io::stderr().flush();
+    let mut line = String::new();
+    io::stdin().read_line(&mut line).map_err(|e| format!("read stdin: {e}"))?;
+    Ok(matches!(line.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Stream `file` into the cluster's primary: through psql for PostgreSQL (`fdb dump` SQL,
+/// pg_dump or pg_dumpall output), through `rabbitmqctl import_definitions` for RabbitMQ.
+/// Loading into a PostgreSQL database that already has tables asks first unless `yes`.
+pub fn run(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    file: &Path,
+    database: Option<&str>,
+    yes: bool,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    if database.is_some() && service != ServiceType::PostgreSQL {
+        return Err("--database is only supported for postgresql".to_string());
+    }
+    let size = fs::metadata(file).map_err(|e| format!("{}: {e}", file.display()))?.len();
+    let input = fs::File::open(file).map_err(|e| format!("open {}: {e}", file.display()))?;
+
+    let (pod, container, command): (String, &str, Vec<String>) = match service {
+        ServiceType::PostgreSQL => {
+            let pod = pods::primary_pod(kubectl, cluster_name, "postgresql", kubeconfig)?;
+            let database = database.unwrap_or("postgres");
+            let tables = pg_table_count(kubectl, &pod, database, kubeconfig)?;
+            if tables > 0
+                && !yes
+                && !confirm(&format!(
+                    "Database \"{database}\" on \"{cluster_name}\" already has {tables} tables. Load anyway?"
+                ))?
+            {
+                return Err("aborted".to_string());
+            }
+            let mut head = Vec::new();
+            let _ = (&input).take(256).read_to_end(&mut head);
+            (&input).seek(SeekFrom::Start(0)).map_err(|e| format!("read {}: {e}", file.display()))?;
+            let cluster_dump = String::from_utf8_lossy(&head).trim_start().starts_with(PG_DUMPALL_HEADER);
+            let mut command = vec!["psql", "-U", "postgres", "-d", database, "-q"];
+            if !cluster_dump {
+                command.extend(["-v", "ON_ERROR_STOP=1", "--single-transaction"]);
+            }
+            (pod, "postgresql", command.into_iter().map(str::to_string).collect())
+        }
+        ServiceType::RabbitMQ => {
+            let pod = pods::component_pods(kubectl, cluster_name, "rabbitmq", kubeconfig)?[0].clone();
+            let command = vec!["rabbitmqctl".to_string(), "import_definitions".to_string()];
+            (pod, "rabbitmq", command)
+        }
+        ServiceType::MongoDB => {
+            let pod = pods::primary_pod(kubectl, cluster_name, "mongodb", kubeconfig)?;
+            let shell = pods::mongodb_admin_script("mongorestore");
+            let command = ["sh", "-c", &shell, "sh", "--quiet", "--archive", "--gzip"];
+            (pod, "mongodb", command.into_iter().map(str::to_string).collect())
+        }
+        _ => {
+            return Err(format!(
+                "fdb load does not support {}; restore it from a backup instead",
+                service.kbcli_name()
+            ));
+        }
+    };
+
+    let command: Vec<&str> = command.iter().map(String::as_str).collect();
+    let total = resources::format_bytes(size as f64);
+    let spinner = Spinner::new(&format!("Loading {} into \"{cluster_name}\"", file.display())).start();
+    let result = pods::exec_from(
+        kubectl,
+        &pod,
+        container,
+        &command,
+        input,
+        |sent| {
+            let pct = (sent * 100).checked_div(size).unwrap_or(100);
+            spinner.update(&format!(
+                "Loading {} into \"{cluster_name}\" {} / {total} ({pct}%)",
+                file.display(),
+                resources::format_bytes(sent as f64)
+            ));
+        },
+        kubeconfig,
+    );
+    match result {
+        Ok(()) => {
+            spinner.success_with(&format!("Loaded {} ({total}) into \"{cluster_name}\"", file.display()));
+            Ok(())
+        }
+        Err(e) => {
+            spinner.fail_with(&format!("Loading {} failed", file.display()));
+            Err(e)
+        }
+    }
+}
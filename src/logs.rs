@@ -0,0 +1,46 @@
+//! Stream a cluster's pod logs (`fdb logs`) without looking up KubeBlocks pod names.
+
+use crate::exec::CommandExt;
+use std::path::Path;
+use std::process::Command;
+
+const NAMESPACE: &str = "default";
+/// Concurrent streams kubectl opens with `--follow`; one per pod of the larger clusters.
+const MAX_LOG_REQUESTS: &str = "20";
+
+/// What `fdb logs` prints.
+#[derive(Debug, Clone, Default)]
+pub struct LogOptions {
+    pub follow: bool,
+    /// Lines per pod; kubectl's default for label selectors (10) when unset.
+    pub tail: Option<u32>,
+    /// Only the pods of this component.
+    pub component: Option<String>,
+}
+
+/// `kubectl logs` over every pod of the cluster, each line prefixed with its pod and
+/// container; runs until kubectl exits (Ctrl-C with `--follow`).
+pub fn stream(kubectl: &Path, cluster_name: &str, opts: &LogOptions, kubeconfig: &Path) -> Result<(), String> {
+    let mut selector = format!("app.kubernetes.io/instance={cluster_name}");
+    if let Some(component) = &opts.component {
+        selector.push_str(&format!(",apps.kubeblocks.io/component-name={component}"));
+    }
+    let mut cmd = Command::new(kubectl);
+    cmd.arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["logs", "-n", NAMESPACE, "-l", &selector, "--prefix", "--max-log-requests", MAX_LOG_REQUESTS]);
+    if let Some(tail) = opts.tail {
+        cmd.args(["--tail", &tail.to_string()]);
+    }
+    if opts.follow {
+        cmd.arg("--follow");
+    }
+    let status = cmd
+        .spawn_logged()
+        .and_then(|mut child| child.wait())
+        .map_err(|e| format!("kubectl logs: {e}"))?;
+    if !status.success() {
+        return Err(format!("kubectl logs for \"{cluster_name}\" failed ({status})"));
+    }
+    Ok(())
+}
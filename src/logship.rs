@@ -0,0 +1,288 @@
+//! Log shipping (`[log-shipping]` in fdb.toml): one promtail or fluent-bit DaemonSet,
+//! `fdb-log-shipper`, tails the containers of the KubeBlocks pods in the fdb namespace and
+//! ships them to Loki or Elasticsearch labelled with the cluster, engine and component.
+//! `fdb create` applies it, so the first cluster deploys it and later ones update its config.
+
+use crate::cluster;
+use crate::config::{LogAgent, LogShippingConfig};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+const NAMESPACE: &str = "default";
+pub const NAME: &str = "fdb-log-shipper";
+const PROMTAIL_IMAGE: &str = "grafana/promtail:3.0.0";
+const FLUENT_BIT_IMAGE: &str = "fluent/fluent-bit:3.1";
+
+/// promtail config: pods of this node managed by KubeBlocks, with fdb labels.
+fn promtail_config(url: &str, labels: &[(&String, &String)]) -> String {
+    let external: String = labels.iter().map(|(k, v)| format!("\n      {k}: \"{v}\"")).collect();
+    let external = if external.is_empty() { String::new() } else { format!("\n    external_labels:{external}") };
+    format!(
+        r#"server:
+  http_listen_port: 3101
+positions:
+  filename: /var/lib/{NAME}/positions.yaml
+clients:
+  - url: {url}{external}
+scrape_configs:
+  - job_name: fdb
+    kubernetes_sd_configs:
+      - role: pod
+        namespaces:
+          names: [{NAMESPACE}]
+        selectors:
+          - role: pod
+            label: app.kubernetes.io/managed-by=kubeblocks
+    relabel_configs:
+      - source_labels: [__meta_kubernetes_pod_node_name]
+        target_label: __host__
+      - source_labels: [__meta_kubernetes_pod_label_app_kubernetes_io_instance]
+        target_label: fdb_cluster
+      - source_labels: [__meta_kubernetes_pod_label_app_kubernetes_io_name]
+        target_label: fdb_engine
+      - source_labels: [__meta_kubernetes_pod_label_apps_kubeblocks_io_component_name]
+        target_label: fdb_component
+      - source_labels: [__meta_kubernetes_namespace]
+        target_label: namespace
+      - source_labels: [__meta_kubernetes_pod_name]
+        target_label: pod
+      - source_labels: [__meta_kubernetes_pod_container_name]
+        target_label: container
+      - source_labels: [__meta_kubernetes_pod_uid, __meta_kubernetes_pod_container_name]
+        separator: /
+        target_label: __path__
+        replacement: /var/log/pods/*$1/*.log
+    pipeline_stages:
+      - cri: {{}}
+"#
+    )
+}
+
+/// Host, port and TLS of an Elasticsearch/OpenSearch URL (`https://es.example.com:9200`).
+fn es_endpoint(url: &str) -> Result<(String, u16, bool), String> {
+    let (tls, rest) = if let Some(rest) = url.strip_prefix("https://") {
+        (true, rest)
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        (false, rest)
+    } else {
+        return Err(format!("log-shipping url \"{url}\" must start with http:// or https://"));
+    };
+    let host = rest.split('/').next().unwrap_or_default();
+    let (host, port) = match host.rsplit_once(':') {
+        Some((h, p)) => (h, p.parse().map_err(|_| format!("log-shipping url \"{url}\": bad port \"{p}\""))?),
+        None => (host, 9200),
+    };
+    if host.is_empty() {
+        return Err(format!("log-shipping url \"{url}\" has no host"));
+    }
+    Ok((host.to_string(), port, tls))
+}
+
+/// fluent-bit config: container logs of the namespace, kept when the pod is managed by
+/// KubeBlocks, enriched with pod metadata and the configured fields.
+fn fluent_bit_config(url: &str, labels: &[(&String, &String)]) -> Result<String, String> {
+    let (host, port, tls) = es_endpoint(url)?;
+    let tls = if tls { "On" } else { "Off" };
+    let fields: String = labels.iter().map(|(k, v)| format!("\n    Add {k} {v}")).collect();
+    Ok(format!(
+        r#"[SERVICE]
+    Flush 5
+    Parsers_File /fluent-bit/etc/parsers.conf
+[INPUT]
+    Name tail
+    Path /var/log/containers/*_{NAMESPACE}_*.log
+    multiline.parser cri, docker
+    Tag kube.*
+    DB /var/lib/{NAME}/tail.db
+[FILTER]
+    Name kubernetes
+    Match kube.*
+    Labels On
+    Annotations Off
+[FILTER]
+    Name grep
+    Match kube.*
+    Regex $kubernetes['labels']['app.kubernetes.io/managed-by'] ^kubeblocks$
+[FILTER]
+    Name modify
+    Match kube.*
+    Copy kubernetes.labels.app.kubernetes.io/instance fdb_cluster{fields}
+[OUTPUT]
+    Name es
+    Match kube.*
+    Host {host}
+    Port {port}
+    tls {tls}
+    Index fdb-logs
+    Suppress_Type_Name On
+    Replace_Dots On
+"#
+    ))
+}
+
+fn indent(text: &str, width: usize) -> String {
+    text.lines().map(|l| format!("{:width$}{l}", "")).collect::<Vec<_>>().join("\n")
+}
+
+/// ServiceAccount, Role, ConfigMap and DaemonSet for the agent. The pod template carries a
+/// hash of the config, so a changed config rolls the pods.
+fn manifest(shipping: &LogShippingConfig) -> Result<String, String> {
+    if shipping.url.is_empty() {
+        return Err("[log-shipping] needs a url".to_string());
+    }
+    let labels: Vec<_> = shipping.labels.iter().collect();
+    let (image, file, config, args, mount) = match shipping.agent {
+        LogAgent::Promtail => (
+            PROMTAIL_IMAGE,
+            "promtail.yaml",
+            promtail_config(&shipping.url, &labels),
+            "[\"-config.file=/etc/fdb-log-shipper/promtail.yaml\"]",
+            "/var/log/pods",
+        ),
+        LogAgent::FluentBit => (
+            FLUENT_BIT_IMAGE,
+            "fluent-bit.conf",
+            fluent_bit_config(&shipping.url, &labels)?,
+            "[\"/fluent-bit/bin/fluent-bit\", \"-c\", \"/etc/fdb-log-shipper/fluent-bit.conf\"]",
+            "/var/log",
+        ),
+    };
+    let hash: String = Sha256::digest(config.as_bytes()).iter().take(8).map(|b| format!("{b:02x}")).collect();
+    let command = match shipping.agent {
+        LogAgent::Promtail => format!("args: {args}"),
+        LogAgent::FluentBit => format!("command: {args}"),
+    };
+    Ok(format!(
+        r#"apiVersion: v1
+kind: ServiceAccount
+metadata:
+  name: {NAME}
+  namespace: {NAMESPACE}
+  labels:
+    app.kubernetes.io/managed-by: fdb
+---
+apiVersion: rbac.authorization.k8s.io/v1
+kind: Role
+metadata:
+  name: {NAME}
+  namespace: {NAMESPACE}
+  labels:
+    app.kubernetes.io/managed-by: fdb
+rules:
+  - apiGroups: [""]
+    resources: ["pods"]
+    verbs: ["get", "list", "watch"]
+---
+apiVersion: rbac.authorization.k8s.io/v1
+kind: RoleBinding
+metadata:
+  name: {NAME}
+  namespace: {NAMESPACE}
+  labels:
+    app.kubernetes.io/managed-by: fdb
+roleRef:
+  apiGroup: rbac.authorization.k8s.io
+  kind: Role
+  name: {NAME}
+subjects:
+  - kind: ServiceAccount
+    name: {NAME}
+    namespace: {NAMESPACE}
+---
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: {NAME}
+  namespace: {NAMESPACE}
+  labels:
+    app.kubernetes.io/managed-by: fdb
+data:
+  {file}: |
+{}
+---
+apiVersion: apps/v1
+kind: DaemonSet
+metadata:
+  name: {NAME}
+  namespace: {NAMESPACE}
+  labels:
+    app.kubernetes.io/managed-by: fdb
+spec:
+  selector:
+    matchLabels:
+      app.kubernetes.io/name: {NAME}
+  template:
+    metadata:
+      labels:
+        app.kubernetes.io/name: {NAME}
+      annotations:
+        fdb.io/config-hash: "{hash}"
+    spec:
+      serviceAccountName: {NAME}
+      tolerations:
+        - operator: Exists
+      containers:
+        - name: agent
+          image: {image}
+          {command}
+          env:
+            - name: HOSTNAME
+              valueFrom:
+                fieldRef:
+                  fieldPath: spec.nodeName
+          volumeMounts:
+            - name: config
+              mountPath: /etc/fdb-log-shipper
+            - name: logs
+              mountPath: {mount}
+              readOnly: true
+            - name: state
+              mountPath: /var/lib/{NAME}
+      volumes:
+        - name: config
+          configMap:
+            name: {NAME}
+        - name: logs
+          hostPath:
+            path: {mount}
+        - name: state
+          hostPath:
+            path: /var/lib/{NAME}
+            type: DirectoryOrCreate
+"#,
+        indent(&config, 4)
+    ))
+}
+
+/// Deploy the agent, or update the existing one to the current config. Returns a line for the
+/// create summary.
+pub fn ensure(kubectl: &Path, shipping: &LogShippingConfig, kubeconfig: &Path) -> Result<String, String> {
+    cluster::apply_manifest(kubectl, &manifest(shipping)?, kubeconfig)?;
+    let agent = match shipping.agent {
+        LogAgent::Promtail => "promtail",
+        LogAgent::FluentBit => "fluent-bit",
+    };
+    Ok(format!("{agent} DaemonSet {NAME} -> {}", shipping.url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fluent_bit_keeps_kubeblocks_pods_and_parses_es_url() {
+        let shipping = LogShippingConfig {
+            agent: LogAgent::FluentBit,
+            url: "https://es.internal:9243".to_string(),
+            labels: [("team".to_string(), "payments".to_string())].into(),
+        };
+        let yaml = manifest(&shipping).unwrap();
+        assert!(yaml.contains("    Host es.internal\n"), "{yaml}");
+        assert!(yaml.contains("    Port 9243\n"));
+        assert!(yaml.contains("    tls On\n"));
+        assert!(yaml.contains("app.kubernetes.io/managed-by'] ^kubeblocks$"));
+        assert!(yaml.contains("    Add team payments"));
+        assert_eq!(es_endpoint("http://es").unwrap(), ("es".to_string(), 9200, false));
+        assert!(es_endpoint("es:9200").is_err());
+    }
+}
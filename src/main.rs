@@ -1,72 +1,771 @@
 //! fdb — CLI for quick database cluster deployment via kbcli/kubectl.
 
+mod access;
+mod accounts;
+mod autostop;
+mod backup;
+mod benchmark;
+mod bundle;
+mod certs;
 mod cluster;
 mod config;
+mod connect;
+mod copy;
 mod credentials;
+mod drift;
+mod dump;
+mod env;
+mod error;
+mod exec;
 mod expose;
+mod forward;
+mod health;
+mod hooks;
+mod idle;
+mod history;
+mod kubeblocks;
+mod kubeconfig;
+mod load;
+mod logs;
+mod logship;
+mod mcp;
+mod metrics;
+mod names;
+mod output;
+mod pods;
+mod portforward;
+mod progress;
+mod publish;
+mod query;
+mod redact;
+mod registry;
+mod report;
+mod resources;
+mod scale;
+mod seed;
+mod serve;
 mod service;
+mod services;
+mod shellenv;
+mod sops;
+mod sql;
+mod status;
+mod suggest;
 mod tools;
+mod top;
+mod ui;
+mod upgrade;
+mod webui;
+mod wizard;
 
-use config::{load_config, load_kubeconfig};
+use config::{load_config, load_kubeconfig, Overrides};
+use error::{Error, ExitCode};
+use publish::PublishTarget;
+use serde::{Deserialize, Serialize};
 use service::ServiceType;
-use std::path::PathBuf;
+use sops::SopsFormat;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 
 fn main() {
     if let Err(e) = run() {
         eprintln!("fdb: {e}");
-        std::process::exit(1);
+        if output::structured() {
+            let _ = output::print(&serde_json::json!({"error": e.message, "code": e.code as i32}));
+        }
+        std::process::exit(e.code as i32);
     }
 }
 
+/// Flags accepted by every command.
+#[derive(Debug, Default)]
+struct GlobalOptions {
+    redact: bool,
+    show_secrets: bool,
+    /// Echo every external command (secrets masked) to stderr.
+    debug: bool,
+    /// Never download tools.
+    offline: bool,
+    /// `-o json|yaml|table`; `--json` is `-o json`.
+    output: output::Format,
+}
+
 #[derive(Debug)]
 enum CliCommand {
     Create {
         service: ServiceType,
         name: String,
+        /// No name was given; `name` was generated and may be replaced if it is taken.
+        generated_name: bool,
+        overrides: Overrides,
+        setup: Box<PostCreate>,
+        protect: bool,
+        wait: bool,
+    },
+    Wait {
+        names: Vec<String>,
+        condition: cluster::WaitFor,
         kubeconfig: Option<PathBuf>,
-        replicas: Option<u32>,
-        storage: Option<String>,
-        cpu: Option<String>,
-        memory: Option<String>,
+        timeout: std::time::Duration,
     },
     Delete {
         name: String,
         kubeconfig: Option<PathBuf>,
         yes: bool,
     },
+    /// `fdb delete --all` or `fdb delete 'test-*'`; `--all` is the pattern `*`.
+    DeleteMany {
+        pattern: String,
+        kubeconfig: Option<PathBuf>,
+        yes: bool,
+    },
     List {
         kubeconfig: Option<PathBuf>,
+        watch: bool,
+        filter: cluster::ListFilter,
+        view: cluster::ListView,
+    },
+    Info {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        sops: bool,
+        age: Option<String>,
+        format: SopsFormat,
+        out: Option<PathBuf>,
+        admin: bool,
+        /// Print only the connection string (for scripts and `fdb shellenv`).
+        uri: bool,
+    },
+    Env {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        format: env::EnvFormat,
+        admin: bool,
+    },
+    CredentialsAdd {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        opts: accounts::AddOptions,
+    },
+    CredentialsTemp {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        ttl: std::time::Duration,
+        opts: accounts::AddOptions,
+    },
+    CredsRotate {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        admin: bool,
+    },
+    Gc {
+        name: Option<String>,
+        kubeconfig: Option<PathBuf>,
+    },
+    Start {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        timeout: std::time::Duration,
+    },
+    Stop {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        timeout: std::time::Duration,
+    },
+    Restart {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        timeout: std::time::Duration,
+    },
+    Upgrade {
+        name: String,
+        version: String,
+        kubeconfig: Option<PathBuf>,
+        timeout: std::time::Duration,
+    },
+    Expose {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        /// None shows the current exposure.
+        expose_type: Option<expose::ExposeType>,
+    },
+    PauseIdle {
+        after: std::time::Duration,
+        kubeconfig: Option<PathBuf>,
+        yes: bool,
+    },
+    KubeconfigExport {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        ttl: std::time::Duration,
+        out: Option<PathBuf>,
+    },
+    History {
+        name: Option<String>,
+    },
+    Diff {
+        name: Option<String>,
+        kubeconfig: Option<PathBuf>,
+    },
+    Apply {
+        name: Option<String>,
+        kubeconfig: Option<PathBuf>,
+        yes: bool,
+        timeout: std::time::Duration,
+    },
+    Dump {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        database: Option<String>,
+        out: PathBuf,
+    },
+    Load {
+        name: String,
+        file: PathBuf,
+        kubeconfig: Option<PathBuf>,
+        database: Option<String>,
+        yes: bool,
+        /// `--job`: run in the cluster instead of streaming from here.
+        job: Option<seed::Job>,
+    },
+    Copy {
+        source: String,
+        dest: String,
+        kubeconfig: Option<PathBuf>,
+        database: Option<String>,
+        yes: bool,
+    },
+    Clone {
+        source: String,
+        target: String,
+        method: backup::CloneMethod,
+        kubeconfig: Option<PathBuf>,
+        timeout: std::time::Duration,
+    },
+    Backup {
+        name: String,
+        method: Option<String>,
+        kubeconfig: Option<PathBuf>,
+        timeout: std::time::Duration,
+    },
+    BackupList {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        json: bool,
+    },
+    Restore {
+        backup: String,
+        new_name: String,
+        kubeconfig: Option<PathBuf>,
+        timeout: std::time::Duration,
+    },
+    RestoreAt {
+        name: String,
+        at: chrono::DateTime<chrono::Utc>,
+        into: String,
+        kubeconfig: Option<PathBuf>,
+        timeout: std::time::Duration,
+    },
+    ToolsUpgrade {
+        check_only: bool,
+    },
+    BundleExport {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        age: Option<String>,
+        out: Option<PathBuf>,
+        admin: bool,
+    },
+    BundleImport {
+        file: PathBuf,
+    },
+    BundleList,
+    Serve {
+        listen: String,
+        token: Option<String>,
+        kubeconfig: Option<PathBuf>,
+    },
+    Mcp {
+        kubeconfig: Option<PathBuf>,
+    },
+    Sql {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+    },
+    ExecSql {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        database: Option<String>,
+        /// SQL text; `-` reads it from stdin.
+        sql: String,
+        json: bool,
+    },
+    ExecRedis {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        args: Vec<String>,
+        json: bool,
+    },
+    Ui {
+        kubeconfig: Option<PathBuf>,
+    },
+    Report {
+        kubeconfig: Option<PathBuf>,
     },
+    Top {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+    },
+    Shellenv {
+        shell: shellenv::Shell,
+    },
+    Open {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        no_browser: bool,
+    },
+    Connect {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        admin: bool,
+    },
+    PortForward {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        local_port: Option<u16>,
+    },
+    Status {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        json: bool,
+    },
+    Logs {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        opts: logs::LogOptions,
+    },
+    Scale {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        target: scale::Target,
+        timeout: std::time::Duration,
+    },
+    Benchmark {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        opts: benchmark::BenchOptions,
+    },
+    Protect {
+        name: String,
+        kubeconfig: Option<PathBuf>,
+        protected: bool,
+    },
+}
+
+impl CliCommand {
+    /// Cluster names given on the command line, for `[project] prefix`.
+    fn cluster_names_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            CliCommand::Create { name, .. }
+            | CliCommand::Delete { name, .. }
+            | CliCommand::DeleteMany { pattern: name, .. }
+            | CliCommand::Info { name, .. }
+            | CliCommand::Env { name, .. }
+            | CliCommand::CredentialsAdd { name, .. }
+            | CliCommand::CredentialsTemp { name, .. }
+            | CliCommand::CredsRotate { name, .. }
+            | CliCommand::KubeconfigExport { name, .. }
+            | CliCommand::Dump { name, .. }
+            | CliCommand::Load { name, .. }
+            | CliCommand::Sql { name, .. }
+            | CliCommand::ExecSql { name, .. }
+            | CliCommand::ExecRedis { name, .. }
+            | CliCommand::Top { name, .. }
+            | CliCommand::Open { name, .. }
+            | CliCommand::Connect { name, .. }
+            | CliCommand::PortForward { name, .. }
+            | CliCommand::Status { name, .. }
+            | CliCommand::Logs { name, .. }
+            | CliCommand::Scale { name, .. }
+            | CliCommand::Backup { name, .. }
+            | CliCommand::BackupList { name, .. }
+            | CliCommand::Benchmark { name, .. }
+            | CliCommand::BundleExport { name, .. }
+            | CliCommand::Start { name, .. }
+            | CliCommand::Stop { name, .. }
+            | CliCommand::Restart { name, .. }
+            | CliCommand::Upgrade { name, .. }
+            | CliCommand::Expose { name, .. }
+            | CliCommand::Protect { name, .. } => vec![name],
+            CliCommand::Gc { name, .. } | CliCommand::History { name } => name.iter_mut().collect(),
+            CliCommand::Wait { names, .. } => names.iter_mut().collect(),
+            CliCommand::Copy { source, dest, .. } => vec![source, dest],
+            CliCommand::Clone { source, target, .. } => vec![source, target],
+            CliCommand::Restore { new_name, .. } => vec![new_name],
+            CliCommand::RestoreAt { name, into, .. } => vec![name, into],
+            // Profile names; their cluster names are qualified when the profile is loaded.
+            CliCommand::Diff { .. } | CliCommand::Apply { .. } => Vec::new(),
+            CliCommand::PauseIdle { .. } => Vec::new(),
+            CliCommand::List { .. }
+            | CliCommand::ToolsUpgrade { .. }
+            | CliCommand::BundleImport { .. }
+            | CliCommand::BundleList
+            | CliCommand::Serve { .. }
+            | CliCommand::Mcp { .. }
+            | CliCommand::Shellenv { .. }
+            | CliCommand::Ui { .. }
+            | CliCommand::Report { .. } => Vec::new(),
+        }
+    }
+
+    /// (action, target) recorded in the history log; None for read-only commands.
+    fn audited(&self) -> Option<(&'static str, &str)> {
+        match self {
+            CliCommand::Create { name, .. } => Some(("create", name)),
+            CliCommand::Delete { name, .. } => Some(("delete", name)),
+            CliCommand::DeleteMany { pattern, .. } => Some(("delete", pattern)),
+            CliCommand::Load { name, .. } => Some(("load", name)),
+            CliCommand::Copy { dest, .. } => Some(("copy", dest)),
+            CliCommand::Clone { target, .. } => Some(("clone", target)),
+            CliCommand::Backup { name, .. } => Some(("backup", name)),
+            CliCommand::Restore { new_name, .. } => Some(("restore", new_name)),
+            CliCommand::RestoreAt { into, .. } => Some(("restore", into)),
+            CliCommand::CredentialsAdd { name, .. } => Some(("credentials", name)),
+            CliCommand::CredentialsTemp { name, .. } => Some(("credentials", name)),
+            CliCommand::CredsRotate { name, .. } => Some(("credentials", name)),
+            CliCommand::Gc { name, .. } => Some(("gc", name.as_deref().unwrap_or("*"))),
+            CliCommand::Start { name, .. } => Some(("start", name)),
+            CliCommand::Stop { name, .. } => Some(("stop", name)),
+            CliCommand::Restart { name, .. } => Some(("restart", name)),
+            CliCommand::Upgrade { name, .. } => Some(("upgrade", name)),
+            CliCommand::Expose { name, expose_type: Some(_), .. } => Some(("expose", name)),
+            CliCommand::Scale { name, .. } => Some(("scale", name)),
+            CliCommand::PauseIdle { .. } => Some(("pause-idle", "*")),
+            CliCommand::Apply { name, .. } => Some(("apply", name.as_deref().unwrap_or("*"))),
+            CliCommand::KubeconfigExport { name, .. } => Some(("kubeconfig", name)),
+            CliCommand::BundleExport { name, .. } => Some(("bundle", name)),
+            CliCommand::Protect { name, protected: true, .. } => Some(("protect", name)),
+            CliCommand::Protect { name, protected: false, .. } => Some(("unprotect", name)),
+            CliCommand::List { .. }
+            | CliCommand::Info { .. }
+            | CliCommand::Env { .. }
+            | CliCommand::History { .. }
+            | CliCommand::Diff { .. }
+            | CliCommand::ToolsUpgrade { .. }
+            | CliCommand::BundleImport { .. }
+            | CliCommand::BundleList
+            | CliCommand::Serve { .. }
+            | CliCommand::Mcp { .. }
+            | CliCommand::Dump { .. }
+            | CliCommand::Sql { .. }
+            | CliCommand::ExecSql { .. }
+            | CliCommand::ExecRedis { .. }
+            | CliCommand::Top { .. }
+            | CliCommand::Open { .. }
+            | CliCommand::Connect { .. }
+            | CliCommand::PortForward { .. }
+            | CliCommand::Status { .. }
+            | CliCommand::Logs { .. }
+            | CliCommand::BackupList { .. }
+            | CliCommand::Expose { expose_type: None, .. }
+            | CliCommand::Shellenv { .. }
+            | CliCommand::Report { .. }
+            | CliCommand::Ui { .. }
+            | CliCommand::Benchmark { .. }
+            | CliCommand::Wait { .. } => None,
+        }
+    }
 }
 
-fn run() -> Result<(), String> {
-    let cmd = parse_args()?;
+fn run() -> Result<(), Error> {
+    let (mut cmd, globals) = parse_args().map_err(error::code(ExitCode::Usage))?;
+    redact::init(globals.redact, globals.show_secrets);
+    exec::set_debug(globals.debug);
+    output::set_format(globals.output);
+    tools::set_offline(globals.offline);
+    config::check()?;
+    for name in cmd.cluster_names_mut() {
+        *name = config::qualify(name);
+    }
+    if let CliCommand::Create { name, .. } = &cmd {
+        names::validate(name).map_err(error::code(ExitCode::Usage))?;
+    }
 
-    match cmd {
+    let audited = cmd
+        .audited()
+        .map(|(action, target)| (action, target.to_string()));
+    let result = match cmd {
         CliCommand::Create {
             service,
             name,
+            generated_name,
+            overrides,
+            setup,
+            protect,
+            wait,
+        } => run_create(service, name, generated_name, overrides, *setup, protect, wait),
+        CliCommand::Wait {
+            names,
+            condition,
             kubeconfig,
-            replicas,
-            storage,
-            cpu,
-            memory,
-        } => run_create(service, &name, kubeconfig, replicas, storage, cpu, memory),
+            timeout,
+        } => run_wait(&names, condition, kubeconfig, timeout),
         CliCommand::Delete { name, kubeconfig, yes } => run_delete(&name, kubeconfig, yes),
-        CliCommand::List { kubeconfig } => run_list(kubeconfig),
+        CliCommand::DeleteMany { pattern, kubeconfig, yes } => run_delete_many(&pattern, kubeconfig, yes),
+        CliCommand::List {
+            kubeconfig,
+            watch,
+            filter,
+            view,
+        } => run_list(kubeconfig, watch, &filter, &view),
+        CliCommand::Info {
+            name,
+            kubeconfig,
+            admin,
+            uri: true,
+            ..
+        } => run_info_uri(&name, kubeconfig, admin),
+        CliCommand::Info {
+            name,
+            kubeconfig,
+            sops,
+            age,
+            format,
+            out,
+            admin,
+            uri: false,
+        } => run_info(&name, kubeconfig, sops, age, format, out, admin),
+        CliCommand::CredentialsAdd {
+            name,
+            kubeconfig,
+            opts,
+        } => run_credentials_add(&name, kubeconfig, &opts),
+        CliCommand::CredentialsTemp {
+            name,
+            kubeconfig,
+            ttl,
+            opts,
+        } => run_credentials_temp(&name, kubeconfig, ttl, &opts),
+        CliCommand::CredsRotate { name, kubeconfig, admin } => run_creds_rotate(&name, kubeconfig, admin),
+        CliCommand::Gc { name, kubeconfig } => run_gc(name.as_deref(), kubeconfig),
+        CliCommand::Start {
+            name,
+            kubeconfig,
+            timeout,
+        } => run_start(&name, kubeconfig, timeout),
+        CliCommand::Stop {
+            name,
+            kubeconfig,
+            timeout,
+        } => run_stop(&name, kubeconfig, timeout),
+        CliCommand::Restart {
+            name,
+            kubeconfig,
+            timeout,
+        } => run_restart(&name, kubeconfig, timeout),
+        CliCommand::Upgrade {
+            name,
+            version,
+            kubeconfig,
+            timeout,
+        } => run_upgrade(&name, &version, kubeconfig, timeout),
+        CliCommand::Expose {
+            name,
+            kubeconfig,
+            expose_type,
+        } => run_expose(&name, kubeconfig, expose_type),
+        CliCommand::PauseIdle { after, kubeconfig, yes } => run_pause_idle(after, kubeconfig, yes),
+        CliCommand::KubeconfigExport {
+            name,
+            kubeconfig,
+            ttl,
+            out,
+        } => run_kubeconfig_export(&name, kubeconfig, ttl, out),
+        CliCommand::History { name } => run_history(name.as_deref()),
+        CliCommand::Diff { name, kubeconfig } => run_diff(name.as_deref(), kubeconfig),
+        CliCommand::BundleExport {
+            name,
+            kubeconfig,
+            age,
+            out,
+            admin,
+        } => run_bundle_export(&name, kubeconfig, age, out, admin),
+        CliCommand::BundleImport { file } => run_bundle_import(&file),
+        CliCommand::Serve {
+            listen,
+            token,
+            kubeconfig,
+        } => run_serve(&listen, token, kubeconfig),
+        CliCommand::Mcp { kubeconfig } => run_mcp(kubeconfig),
+        CliCommand::BundleList => {
+            run_bundle_list();
+            Ok(())
+        }
+        CliCommand::Apply {
+            name,
+            kubeconfig,
+            yes,
+            timeout,
+        } => run_apply(name.as_deref(), kubeconfig, yes, timeout),
+        CliCommand::Dump {
+            name,
+            kubeconfig,
+            database,
+            out,
+        } => run_dump(&name, kubeconfig, database.as_deref(), &out),
+        CliCommand::Load {
+            name,
+            file,
+            kubeconfig,
+            database,
+            yes,
+            job,
+        } => run_load(&name, &file, kubeconfig, database.as_deref(), yes, job),
+        CliCommand::Copy {
+            source,
+            dest,
+            kubeconfig,
+            database,
+            yes,
+        } => run_copy(&source, &dest, kubeconfig, database.as_deref(), yes),
+        CliCommand::Clone {
+            source,
+            target,
+            method,
+            kubeconfig,
+            timeout,
+        } => run_clone(&source, &target, method, kubeconfig, timeout),
+        CliCommand::Backup {
+            name,
+            method,
+            kubeconfig,
+            timeout,
+        } => run_backup(&name, method.as_deref(), kubeconfig, timeout),
+        CliCommand::BackupList { name, kubeconfig, json } => run_backup_list(&name, kubeconfig, json),
+        CliCommand::Restore {
+            backup,
+            new_name,
+            kubeconfig,
+            timeout,
+        } => run_restore(&backup, &new_name, kubeconfig, timeout),
+        CliCommand::RestoreAt {
+            name,
+            at,
+            into,
+            kubeconfig,
+            timeout,
+        } => run_restore_at(&name, at, &into, kubeconfig, timeout),
+        CliCommand::ToolsUpgrade { check_only } => tools::upgrade(check_only).map_err(error::code(ExitCode::ToolsMissing)),
+        CliCommand::Sql { name, kubeconfig } => run_sql(&name, kubeconfig),
+        CliCommand::ExecSql {
+            name,
+            kubeconfig,
+            database,
+            sql,
+            json,
+        } => run_exec_sql(&name, kubeconfig, database.as_deref(), &sql, json),
+        CliCommand::ExecRedis {
+            name,
+            kubeconfig,
+            args,
+            json,
+        } => run_exec_redis(&name, kubeconfig, &args, json),
+        CliCommand::Top { name, kubeconfig } => run_top(&name, kubeconfig),
+        CliCommand::Open {
+            name,
+            kubeconfig,
+            no_browser,
+        } => run_open(&name, kubeconfig, no_browser),
+        CliCommand::Connect { name, kubeconfig, admin } => run_connect(&name, kubeconfig, admin),
+        CliCommand::Env { name, kubeconfig, format, admin } => run_env(&name, kubeconfig, format, admin),
+        CliCommand::PortForward { name, kubeconfig, local_port } => run_port_forward(&name, kubeconfig, local_port),
+        CliCommand::Status { name, kubeconfig, json } => run_status(&name, kubeconfig, json),
+        CliCommand::Logs { name, kubeconfig, opts } => run_logs(&name, kubeconfig, &opts),
+        CliCommand::Scale {
+            name,
+            kubeconfig,
+            target,
+            timeout,
+        } => run_scale(&name, kubeconfig, &target, timeout),
+        CliCommand::Shellenv { shell } => {
+            print!("{}", shellenv::script(shell));
+            Ok(())
+        }
+        CliCommand::Report { kubeconfig } => run_report(kubeconfig),
+        CliCommand::Ui { kubeconfig } => run_ui(kubeconfig),
+        CliCommand::Benchmark { name, kubeconfig, opts } => run_benchmark(&name, kubeconfig, opts),
+        CliCommand::Protect {
+            name,
+            kubeconfig,
+            protected,
+        } => run_protect(&name, kubeconfig, protected),
+    };
+    if let Some((action, target)) = audited {
+        history::record(action, &target, result.as_ref().map(|_| ()).map_err(|e| e.message.as_str()));
     }
+    result
+}
+
+/// Subcommands, for did-you-mean suggestions.
+const COMMANDS: &[&str] = &[
+    "create", "up", "wait", "delete", "list", "info", "describe", "creds", "env", "status", "logs", "scale", "credentials", "kubeconfig", "gc", "protect",
+    "unprotect", "start", "stop", "restart", "upgrade", "expose", "pause-idle", "history", "diff", "apply", "tools", "dump", "load", "copy", "clone", "backup", "restore", "bundle", "serve", "mcp", "sql", "exec-sql", "exec-redis", "open", "connect", "port-forward", "shellenv", "top", "ui", "report", "benchmark",
+];
+
+fn parse_args() -> Result<(CliCommand, GlobalOptions), String> {
+    parse_args_from(std::env::args_os().skip(1).collect())
 }
 
-fn parse_args() -> Result<CliCommand, String> {
+fn parse_args_from(args: Vec<std::ffi::OsString>) -> Result<(CliCommand, GlobalOptions), String> {
+    let mut globals = GlobalOptions::default();
     let mut kubeconfig: Option<PathBuf> = None;
     let mut replicas: Option<u32> = None;
     let mut storage: Option<String> = None;
     let mut cpu: Option<String> = None;
     let mut memory: Option<String> = None;
+    let mut publish: Option<PublishTarget> = None;
+    let mut acl_user: Option<String> = None;
+    let mut allow_cidrs: Vec<String> = Vec::new();
     let mut yes = false;
+    let mut protect = false;
+    let mut jetstream = false;
+    let mut wait = true;
+    let mut skip_expose: Option<bool> = None;
+    let mut profile: Option<String> = None;
+    let mut timeout: Option<std::time::Duration> = None;
+    let mut admin = false;
+    let mut watch = false;
+    let mut check_only = false;
+    let mut database: Option<String> = None;
+    let mut method: Option<String> = None;
+    let mut listen: Option<String> = None;
+    let mut job = false;
+    let mut no_browser = false;
+    let mut uri = false;
+    let mut after: Option<std::time::Duration> = None;
+    let mut image: Option<String> = None;
+    let mut token: Option<String> = None;
+    let mut at: Option<String> = None;
+    let mut into: Option<String> = None;
+    let mut as_name: Option<String> = None;
+    let mut target_version: Option<String> = None;
+    let mut expose_type: Option<expose::ExposeType> = None;
+    let mut wait_for: Option<cluster::WaitFor> = None;
+    let mut local_port: Option<u16> = None;
+    let mut all = false;
+    let mut list_filter = cluster::ListFilter::default();
+    let mut list_view = cluster::ListView::default();
+    let mut account = accounts::AddOptions::default();
+    let mut sops = false;
+    let mut age: Option<String> = None;
+    let mut format: Option<String> = None;
+    let mut out: Option<PathBuf> = None;
+    let mut ttl: Option<std::time::Duration> = None;
+    let mut bench = benchmark::BenchOptions::default();
+    let mut log_opts = logs::LogOptions::default();
     let mut positional: Vec<String> = Vec::new();
 
-    let mut parser = lexopt::Parser::from_env();
+    let mut parser = lexopt::Parser::from_args(args);
     while let Some(arg) = parser.next().map_err(|e| e.to_string())? {
         match arg {
             lexopt::Arg::Long("kubeconfig") => {
@@ -74,6 +773,57 @@ fn parse_args() -> Result<CliCommand, String> {
                 kubeconfig = Some(PathBuf::from(val.to_string_lossy().into_owned()));
             }
             lexopt::Arg::Short('y') | lexopt::Arg::Long("yes") => yes = true,
+            lexopt::Arg::Long("protect") => protect = true,
+            lexopt::Arg::Long("profile") => {
+                profile = Some(parser.value().map_err(|e| e.to_string())?.to_string_lossy().into_owned());
+            }
+            lexopt::Arg::Long("expose") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                skip_expose = Some(match val.to_string_lossy().as_ref() {
+                    "nodeport" => false,
+                    "none" => true,
+                    other => return Err(format!("invalid --expose: {other} (use nodeport or none)")),
+                });
+            }
+            lexopt::Arg::Long("wait") => {
+                wait = match parser.optional_value() {
+                    None => true,
+                    Some(v) => match v.to_string_lossy().as_ref() {
+                        "true" => true,
+                        "false" => false,
+                        other => return Err(format!("invalid --wait: {other} (use --wait=false)")),
+                    },
+                };
+            }
+            lexopt::Arg::Long("no-wait") => wait = false,
+            lexopt::Arg::Long("timeout") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                timeout = Some(config::parse_duration(&val.to_string_lossy())?);
+            }
+            lexopt::Arg::Long("admin") => admin = true,
+            lexopt::Arg::Long("all") => all = true,
+            lexopt::Arg::Long("watch") | lexopt::Arg::Short('w') => watch = true,
+            lexopt::Arg::Long("check") => check_only = true,
+            lexopt::Arg::Long("engine") | lexopt::Arg::Long("service") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                list_filter.engine = Some(val.to_string_lossy().parse()?);
+            }
+            lexopt::Arg::Long("status") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                list_filter.status = Some(val.to_string_lossy().into_owned());
+            }
+            lexopt::Arg::Long("columns") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                list_view.set_columns(&val.to_string_lossy())?;
+            }
+            lexopt::Arg::Long("sort") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                list_view.set_sort(&val.to_string_lossy())?;
+            }
+            lexopt::Arg::Long("label") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                list_filter.add_label(&val.to_string_lossy())?;
+            }
             lexopt::Arg::Long("replicas") => {
                 let val = parser.value().map_err(|e| e.to_string())?;
                 let s = val.to_string_lossy();
@@ -91,178 +841,1914 @@ fn parse_args() -> Result<CliCommand, String> {
                 let val = parser.value().map_err(|e| e.to_string())?;
                 memory = Some(val.to_string_lossy().into_owned());
             }
-            lexopt::Arg::Value(val) => {
-                positional.push(val.to_string_lossy().into_owned());
+            lexopt::Arg::Long("acl-user") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                acl_user = Some(val.to_string_lossy().into_owned());
             }
-            _ => return Err(format!("unexpected argument: {arg:?}")),
-        }
-    }
-
-    if positional.is_empty() {
-        return Err(usage());
-    }
-
-    match positional[0].as_str() {
-        "create" => {
-            if positional.len() != 3 {
-                return Err("usage: fdb create <postgresql|redis|rabbitmq|qdrant> <name> [--kubeconfig PATH] [--replicas N] [--storage SIZE] [--cpu CPU] [--memory MEM]".to_string());
+            lexopt::Arg::Long("acl-commands") | lexopt::Arg::Long("commands") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                account.commands = Some(val.to_string_lossy().into_owned());
             }
-            let service = positional[1].parse::<ServiceType>()?;
-            let name = positional[2].clone();
-            Ok(CliCommand::Create {
-                service,
-                name,
-                kubeconfig,
-                replicas,
-                storage,
-                cpu,
-                memory,
-            })
-        }
-        "delete" => {
-            if positional.len() != 2 {
-                return Err("usage: fdb delete <name> [--kubeconfig PATH] [-y|--yes]".to_string());
+            lexopt::Arg::Long("acl-keys") | lexopt::Arg::Long("keys") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                account.keys = Some(val.to_string_lossy().into_owned());
             }
-            let name = positional[1].clone();
-            Ok(CliCommand::Delete {
-                name,
-                kubeconfig,
-                yes,
-            })
-        }
-        "list" => {
-            if positional.len() != 1 {
-                return Err("usage: fdb list [--kubeconfig PATH]".to_string());
+            lexopt::Arg::Long("jetstream") => jetstream = true,
+            lexopt::Arg::Long("allow-cidr") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                allow_cidrs.push(access::parse_cidr(&val.to_string_lossy())?);
             }
-            Ok(CliCommand::List { kubeconfig })
-        }
-        _ => Err(usage()),
-    }
-}
-
-fn usage() -> String {
-    "usage: fdb create <postgresql|redis|rabbitmq|qdrant> <name> [options]
-       fdb delete <name> [-y|--yes] [--kubeconfig PATH]
-       fdb list [--kubeconfig PATH]"
-        .to_string()
-}
-
-fn run_create(
-    service: ServiceType,
-    cluster_name: &str,
-    kubeconfig_override: Option<PathBuf>,
-    replicas_override: Option<u32>,
-    storage_override: Option<String>,
-    cpu_override: Option<String>,
-    memory_override: Option<String>,
-) -> Result<(), String> {
-    let config = load_config(
-        service,
-        kubeconfig_override,
-        replicas_override,
-        storage_override,
-        cpu_override,
-        memory_override,
-    );
-
-    tools::ensure_tools()?;
-    let kubectl = tools::resolve_kubectl()?;
-    let kbcli = tools::resolve_kbcli()?;
-
-    let started = chrono::Local::now();
-    let kubeconfig_display = config.kubeconfig.display().to_string();
-    println!(
-        "Creating {} cluster \"{cluster_name}\" (replicas={}, storage={} Gi, cpu={}, memory={} Gi)",
-        service.kbcli_name(),
-        config.replicas,
-        config.storage.trim_end_matches("Gi").trim_end_matches("gi").trim(),
-        config.cpu,
-        config.memory.trim_end_matches("Gi").trim_end_matches("gi").trim()
-    );
-    println!("  kubeconfig: {kubeconfig_display}");
-    println!("  started: {}", started.format("%Y-%m-%d %H:%M:%S"));
-    println!();
-
-    cluster::create_cluster(
-        &kbcli,
+            lexopt::Arg::Long("publish") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                publish = Some(val.to_string_lossy().parse()?);
+            }
+            lexopt::Arg::Long("redact") => globals.redact = true,
+            lexopt::Arg::Long("show-secrets") => globals.show_secrets = true,
+            lexopt::Arg::Long("debug") => globals.debug = true,
+            lexopt::Arg::Long("offline") => globals.offline = true,
+            lexopt::Arg::Long("readonly") => account.readonly = true,
+            lexopt::Arg::Long("cert") => account.cert = true,
+            lexopt::Arg::Long("ca-secret") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                account.ca_secret = Some(val.to_string_lossy().into_owned());
+            }
+            lexopt::Arg::Long("after") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                after = Some(config::parse_duration(&val.to_string_lossy())?);
+            }
+            lexopt::Arg::Long("ttl") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                ttl = Some(config::parse_duration(&val.to_string_lossy())?);
+            }
+            lexopt::Arg::Long("duration") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                bench.duration = config::parse_duration(&val.to_string_lossy())?;
+            }
+            lexopt::Arg::Long("clients") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                let s = val.to_string_lossy();
+                bench.clients = s.parse().ok().filter(|&c| c > 0).ok_or(format!("invalid --clients: {s}"))?;
+            }
+            lexopt::Arg::Long("user") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                account.user = Some(val.to_string_lossy().into_owned());
+            }
+            lexopt::Arg::Long("vhost") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                account.vhost = Some(val.to_string_lossy().into_owned());
+            }
+            lexopt::Arg::Long("permissions") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                account.permissions = Some(val.to_string_lossy().into_owned());
+            }
+            lexopt::Arg::Long("sops") => sops = true,
+            lexopt::Arg::Long("age") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                age = Some(val.to_string_lossy().into_owned());
+            }
+            lexopt::Arg::Long("format") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                format = Some(val.to_string_lossy().into_owned());
+            }
+            lexopt::Arg::Long("follow") | lexopt::Arg::Short('f') => log_opts.follow = true,
+            lexopt::Arg::Long("tail") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                let s = val.to_string_lossy();
+                log_opts.tail = Some(s.parse().map_err(|_| format!("invalid --tail: {s}"))?);
+            }
+            lexopt::Arg::Long("component") => {
+                log_opts.component = Some(parser.value().map_err(|e| e.to_string())?.to_string_lossy().into_owned());
+            }
+            lexopt::Arg::Long("job") => job = true,
+            lexopt::Arg::Long("json") => globals.output = output::Format::Json,
+            lexopt::Arg::Short('o') | lexopt::Arg::Long("output") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                globals.output = val.to_string_lossy().parse()?;
+            }
+            lexopt::Arg::Long("no-browser") => no_browser = true,
+            lexopt::Arg::Long("uri") => uri = true,
+            lexopt::Arg::Long("image") => {
+                image = Some(parser.value().map_err(|e| e.to_string())?.to_string_lossy().into_owned());
+            }
+            lexopt::Arg::Long("listen") => {
+                listen = Some(parser.value().map_err(|e| e.to_string())?.to_string_lossy().into_owned());
+            }
+            lexopt::Arg::Long("token") => {
+                token = Some(parser.value().map_err(|e| e.to_string())?.to_string_lossy().into_owned());
+            }
+            lexopt::Arg::Long("at") => {
+                at = Some(parser.value().map_err(|e| e.to_string())?.to_string_lossy().into_owned());
+            }
+            lexopt::Arg::Long("into") => {
+                into = Some(parser.value().map_err(|e| e.to_string())?.to_string_lossy().into_owned());
+            }
+            lexopt::Arg::Long("local-port") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                let s = val.to_string_lossy();
+                local_port = Some(s.parse().map_err(|_| format!("invalid --local-port: {s}"))?);
+            }
+            lexopt::Arg::Long("type") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                expose_type = Some(val.to_string_lossy().parse()?);
+            }
+            lexopt::Arg::Long("for") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                wait_for = Some(val.to_string_lossy().parse()?);
+            }
+            lexopt::Arg::Long("version") => {
+                target_version = Some(parser.value().map_err(|e| e.to_string())?.to_string_lossy().into_owned());
+            }
+            lexopt::Arg::Long("as") => {
+                as_name = Some(parser.value().map_err(|e| e.to_string())?.to_string_lossy().into_owned());
+            }
+            lexopt::Arg::Long("method") => {
+                method = Some(parser.value().map_err(|e| e.to_string())?.to_string_lossy().into_owned());
+            }
+            lexopt::Arg::Long("database") | lexopt::Arg::Short('d') => {
+                database = Some(parser.value().map_err(|e| e.to_string())?.to_string_lossy().into_owned());
+            }
+            lexopt::Arg::Long("out") => {
+                let val = parser.value().map_err(|e| e.to_string())?;
+                out = Some(PathBuf::from(val.to_string_lossy().into_owned()));
+            }
+            lexopt::Arg::Value(val) => {
+                positional.push(val.to_string_lossy().into_owned());
+            }
+            _ => return Err(format!("unexpected argument: {arg:?}")),
+        }
+    }
+
+    if positional.is_empty() {
+        return Err(usage());
+    }
+    let json = globals.output != output::Format::Table;
+
+    let cmd = match positional[0].as_str() {
+        "create" if positional.len() == 1 && profile.is_none() && std::io::stdin().is_terminal() => {
+            let args = wizard::run(kubeconfig.as_deref())?;
+            let (cmd, _) = parse_args_from(args.into_iter().map(Into::into).collect())?;
+            return Ok((cmd, globals));
+        }
+        "create" | "up" => {
+            const CREATE_USAGE: &str = "usage: fdb create <engine> [<name>] [--profile NAME] [--kubeconfig PATH] [--replicas N] [--storage SIZE] [--cpu CPU] [--memory MEM] [--publish vault|external-secret] [--acl-user NAME [--acl-commands RULES] [--acl-keys PATTERNS]] [--allow-cidr CIDR ...] [--expose nodeport|none] [--jetstream] [--protect] [--no-wait] [--timeout 5m]\n       fdb create --profile NAME [<name>] [options]\n       fdb up <profile> [<name>] [options]";
+            let mut args = positional[1..].to_vec();
+            if positional[0] == "up" {
+                if args.is_empty() {
+                    return Err(CREATE_USAGE.to_string());
+                }
+                profile = Some(args.remove(0));
+            }
+            let profile = profile.as_deref().map(config::load_profile).transpose()?;
+            let (service, name) = match &profile {
+                Some(p) if args.len() <= 1 => (p.engine, args.first().cloned().or_else(|| Some(p.name.clone()))),
+                None if (1..=2).contains(&args.len()) => (args[0].parse::<ServiceType>()?, args.get(1).cloned()),
+                _ => return Err(CREATE_USAGE.to_string()),
+            };
+            let generated_name = name.is_none();
+            let name = match name {
+                Some(name) => {
+                    names::validate(&name)?;
+                    name
+                }
+                None => names::generate(&[])?,
+            };
+            let mut version = None;
+            if let Some(p) = profile {
+                replicas = replicas.or(p.replicas);
+                storage = storage.or(p.storage);
+                cpu = cpu.or(p.cpu);
+                memory = memory.or(p.memory);
+                skip_expose = skip_expose.or(p.skip_expose);
+                version = p.version;
+            }
+            if acl_user.is_some() && service != ServiceType::Redis {
+                return Err("--acl-user is only supported for redis".to_string());
+            }
+            if !allow_cidrs.is_empty() && service != ServiceType::PostgreSQL {
+                return Err("--allow-cidr is only supported for postgresql".to_string());
+            }
+            if jetstream && service != ServiceType::Nats {
+                return Err("--jetstream is only supported for nats".to_string());
+            }
+            let redis_acl_user = acl_user.map(|name| config::RedisAclUser {
+                name,
+                commands: account.commands.clone().unwrap_or_else(config::default_acl_commands),
+                keys: account.keys.clone().unwrap_or_else(config::default_acl_keys),
+            });
+            CliCommand::Create {
+                service,
+                name,
+                generated_name,
+                overrides: Overrides {
+                    kubeconfig,
+                    replicas,
+                    storage,
+                    cpu,
+                    memory,
+                    version,
+                    timeout,
+                    redis_acl_user,
+                    jetstream,
+                },
+                setup: Box::new(PostCreate {
+                    publish,
+                    allow_cidrs,
+                    skip_expose: skip_expose.unwrap_or(false),
+                    ..Default::default()
+                }),
+                protect,
+                wait,
+            }
+        }
+        "wait" => {
+            if positional.len() < 2 {
+                return Err("usage: fdb wait <name>... [--for running|stopped|deleted] [--timeout 5m] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Wait {
+                names: positional[1..].to_vec(),
+                condition: wait_for.unwrap_or_default(),
+                kubeconfig,
+                timeout: timeout.unwrap_or(cluster::DEFAULT_TIMEOUT),
+            }
+        }
+        "delete" => match (positional.len(), all) {
+            (1, true) => CliCommand::DeleteMany {
+                pattern: "*".to_string(),
+                kubeconfig,
+                yes,
+            },
+            (2, false) if positional[1].contains(['*', '?']) => CliCommand::DeleteMany {
+                pattern: positional[1].clone(),
+                kubeconfig,
+                yes,
+            },
+            (2, false) => CliCommand::Delete {
+                name: positional[1].clone(),
+                kubeconfig,
+                yes,
+            },
+            _ => return Err("usage: fdb delete <name>|<pattern>|--all [--kubeconfig PATH] [-y|--yes]".to_string()),
+        },
+        "list" => {
+            if positional.len() != 1 {
+                return Err("usage: fdb list [--watch] [--engine|--service TYPE] [--status PHASE] [--label KEY=VALUE] [--columns LIST] [--sort name|age|status] [--kubeconfig PATH]".to_string());
+            }
+            if watch && json {
+                return Err("--watch redraws a table; it cannot be combined with -o json|yaml".to_string());
+            }
+            CliCommand::List {
+                kubeconfig,
+                watch,
+                filter: list_filter,
+                view: list_view,
+            }
+        }
+        "creds" if positional.len() == 3 && positional[1] == "rotate" => CliCommand::CredsRotate {
+            name: positional[2].clone(),
+            kubeconfig,
+            admin,
+        },
+        "info" | "describe" | "creds" => {
+            if positional.len() != 2 {
+                return Err("usage: fdb info <name> [--kubeconfig PATH] [--admin] [--uri | --sops --age RECIPIENT [--format yaml|env] [--out PATH]]".to_string());
+            }
+            let name = positional[1].clone();
+            CliCommand::Info {
+                name,
+                kubeconfig,
+                sops,
+                age,
+                format: format.as_deref().map(str::parse).transpose()?.unwrap_or(SopsFormat::Yaml),
+                out,
+                admin,
+                uri,
+            }
+        }
+        "env" => {
+            if positional.len() != 2 {
+                return Err("usage: fdb env <name> [--format dotenv|shell|json] [--admin] [--kubeconfig PATH]".to_string());
+            }
+            let default = match globals.output {
+                output::Format::Json => env::EnvFormat::Json,
+                _ => env::EnvFormat::Shell,
+            };
+            CliCommand::Env {
+                name: positional[1].clone(),
+                kubeconfig,
+                format: format.as_deref().map(str::parse).transpose()?.unwrap_or(default),
+                admin,
+            }
+        }
+        "credentials" => match positional.get(1).map(String::as_str) {
+            Some("add") if positional.len() == 3 => CliCommand::CredentialsAdd {
+                name: positional[2].clone(),
+                kubeconfig,
+                opts: account,
+            },
+            Some("temp") if positional.len() == 3 => CliCommand::CredentialsTemp {
+                name: positional[2].clone(),
+                kubeconfig,
+                ttl: ttl.unwrap_or(std::time::Duration::from_secs(3600)),
+                opts: account,
+            },
+            _ => {
+                return Err("usage: fdb credentials add <name> [--readonly] [--cert [--ca-secret NAME]] [--user NAME [--vhost VHOST] [--permissions REGEX] [--commands RULES] [--keys PATTERNS]] [--kubeconfig PATH]
+       fdb credentials temp <name> [--ttl 1h] [--readonly] [--kubeconfig PATH]".to_string());
+            }
+        },
+        "kubeconfig" => {
+            if positional.len() != 3 || positional[1] != "export" {
+                return Err("usage: fdb kubeconfig export <name> [--ttl 720h] [--out PATH] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::KubeconfigExport {
+                name: positional[2].clone(),
+                kubeconfig,
+                ttl: ttl.unwrap_or(std::time::Duration::from_secs(720 * 3600)),
+                out,
+            }
+        }
+        "gc" => {
+            if positional.len() > 2 {
+                return Err("usage: fdb gc [name] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Gc {
+                name: positional.get(1).cloned(),
+                kubeconfig,
+            }
+        }
+        "start" => {
+            if positional.len() != 2 {
+                return Err("usage: fdb start <name> [--timeout 5m] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Start {
+                name: positional[1].clone(),
+                kubeconfig,
+                timeout: timeout.unwrap_or(cluster::DEFAULT_TIMEOUT),
+            }
+        }
+        "stop" => {
+            if positional.len() != 2 {
+                return Err("usage: fdb stop <name> [--timeout 5m] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Stop {
+                name: positional[1].clone(),
+                kubeconfig,
+                timeout: timeout.unwrap_or(cluster::DEFAULT_TIMEOUT),
+            }
+        }
+        "expose" => {
+            if positional.len() != 2 {
+                return Err("usage: fdb expose <name> [--type nodeport|loadbalancer|clusterip|none] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Expose {
+                name: positional[1].clone(),
+                kubeconfig,
+                expose_type,
+            }
+        }
+        "upgrade" => {
+            let (2, Some(version)) = (positional.len(), target_version) else {
+                return Err("usage: fdb upgrade <name> --version VERSION [--timeout 5m] [--kubeconfig PATH]".to_string());
+            };
+            CliCommand::Upgrade {
+                name: positional[1].clone(),
+                version,
+                kubeconfig,
+                timeout: timeout.unwrap_or(cluster::DEFAULT_TIMEOUT),
+            }
+        }
+        "restart" => {
+            if positional.len() != 2 {
+                return Err("usage: fdb restart <name> [--timeout 5m] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Restart {
+                name: positional[1].clone(),
+                kubeconfig,
+                timeout: timeout.unwrap_or(cluster::DEFAULT_TIMEOUT),
+            }
+        }
+        "pause-idle" => {
+            if positional.len() != 1 {
+                return Err("usage: fdb pause-idle [--after 48h] [-y|--yes] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::PauseIdle {
+                after: after.unwrap_or(std::time::Duration::from_secs(48 * 3600)),
+                kubeconfig,
+                yes,
+            }
+        }
+        "protect" | "unprotect" => {
+            if positional.len() != 2 {
+                return Err(format!("usage: fdb {} <name> [--kubeconfig PATH]", positional[0]));
+            }
+            CliCommand::Protect {
+                name: positional[1].clone(),
+                kubeconfig,
+                protected: positional[0] == "protect",
+            }
+        }
+        "dump" => {
+            let (2, Some(out)) = (positional.len(), out) else {
+                return Err("usage: fdb dump <name> --out FILE|- [--database NAME] [--kubeconfig PATH]".to_string());
+            };
+            CliCommand::Dump {
+                name: positional[1].clone(),
+                kubeconfig,
+                database,
+                out,
+            }
+        }
+        "load" => {
+            const LOAD_USAGE: &str = "usage: fdb load <name> <file> [--database NAME] [-y|--yes] [--kubeconfig PATH]\n       fdb load <name> <file|dir> --job [--image IMAGE] [--timeout 30m] [--database NAME] [-y|--yes]";
+            if positional.len() != 3 {
+                return Err(LOAD_USAGE.to_string());
+            }
+            if !job && image.is_some() {
+                return Err("--image needs --job".to_string());
+            }
+            CliCommand::Load {
+                name: positional[1].clone(),
+                file: PathBuf::from(&positional[2]),
+                kubeconfig,
+                database,
+                yes,
+                job: job.then(|| seed::Job {
+                    image: image.unwrap_or_else(|| seed::DEFAULT_IMAGE.to_string()),
+                    timeout: timeout.unwrap_or(seed::DEFAULT_TIMEOUT),
+                }),
+            }
+        }
+        "copy" => {
+            if positional.len() != 3 {
+                return Err("usage: fdb copy <source> <dest> [--database NAME] [-y|--yes] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Copy {
+                source: positional[1].clone(),
+                dest: positional[2].clone(),
+                kubeconfig,
+                database,
+                yes,
+            }
+        }
+        "clone" => {
+            if positional.len() != 3 {
+                return Err("usage: fdb clone <source> <target> [--method snapshot|backup|empty] [--timeout 10m] [--kubeconfig PATH]".to_string());
+            }
+            names::validate(&positional[2])?;
+            CliCommand::Clone {
+                source: positional[1].clone(),
+                target: positional[2].clone(),
+                method: method.as_deref().map(str::parse).transpose()?.unwrap_or(backup::CloneMethod::Snapshot),
+                kubeconfig,
+                timeout: timeout.unwrap_or(cluster::DEFAULT_TIMEOUT),
+            }
+        }
+        "backup" => {
+            const BACKUP_USAGE: &str = "usage: fdb backup <name> [--method METHOD] [--timeout 10m] [--kubeconfig PATH]\n       fdb backup list <name> [-o json|yaml] [--kubeconfig PATH]";
+            match (positional.get(1).map(String::as_str), positional.len()) {
+                (Some("list"), 3) => CliCommand::BackupList {
+                    name: positional[2].clone(),
+                    kubeconfig,
+                    json,
+                },
+                (Some(_), 2) => CliCommand::Backup {
+                    name: positional[1].clone(),
+                    method,
+                    kubeconfig,
+                    timeout: timeout.unwrap_or(cluster::DEFAULT_TIMEOUT),
+                },
+                _ => return Err(BACKUP_USAGE.to_string()),
+            }
+        }
+        "restore" => {
+            const RESTORE_USAGE: &str = "usage: fdb restore <backup> --as NEW [--timeout 10m] [--kubeconfig PATH]\n       fdb restore <name> --at TIME --into NEW [--timeout 10m] [--kubeconfig PATH]";
+            let timeout = timeout.unwrap_or(cluster::DEFAULT_TIMEOUT);
+            match (positional.len(), at, into, as_name) {
+                (2, None, None, Some(new_name)) => {
+                    names::validate(&new_name)?;
+                    CliCommand::Restore {
+                        backup: positional[1].clone(),
+                        new_name,
+                        kubeconfig,
+                        timeout,
+                    }
+                }
+                (2, Some(at), Some(into), None) => {
+                    let at = chrono::DateTime::parse_from_rfc3339(&at)
+                        .map_err(|_| format!("invalid --at: {at} (expected RFC 3339, e.g. 2024-05-01T12:00:00Z)"))?
+                        .with_timezone(&chrono::Utc);
+                    names::validate(&into)?;
+                    CliCommand::RestoreAt {
+                        name: positional[1].clone(),
+                        at,
+                        into,
+                        kubeconfig,
+                        timeout,
+                    }
+                }
+                _ => return Err(RESTORE_USAGE.to_string()),
+            }
+        }
+        "tools" => {
+            if positional.len() != 2 || positional[1] != "upgrade" {
+                return Err("usage: fdb tools upgrade [--check]".to_string());
+            }
+            CliCommand::ToolsUpgrade { check_only }
+        }
+        "bundle" => {
+            const BUNDLE_USAGE: &str = "usage: fdb bundle export <name> [--age RECIPIENT] [--out PATH] [--admin] [--kubeconfig PATH]\n       fdb bundle import <file>\n       fdb bundle list";
+            match (positional.get(1).map(String::as_str), positional.len()) {
+                (Some("export"), 3) => CliCommand::BundleExport {
+                    name: positional[2].clone(),
+                    kubeconfig,
+                    age,
+                    out,
+                    admin,
+                },
+                (Some("import"), 3) => CliCommand::BundleImport {
+                    file: PathBuf::from(&positional[2]),
+                },
+                (Some("list"), 2) => CliCommand::BundleList,
+                _ => return Err(BUNDLE_USAGE.to_string()),
+            }
+        }
+        "serve" => {
+            if positional.len() != 1 {
+                return Err("usage: fdb serve [--listen 127.0.0.1:7654] [--token TOKEN] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Serve {
+                listen: listen.unwrap_or_else(|| "127.0.0.1:7654".to_string()),
+                token,
+                kubeconfig,
+            }
+        }
+        "mcp" => {
+            if positional.len() != 1 {
+                return Err("usage: fdb mcp [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Mcp { kubeconfig }
+        }
+        "history" => {
+            if positional.len() > 2 {
+                return Err("usage: fdb history [name]".to_string());
+            }
+            CliCommand::History {
+                name: positional.get(1).cloned(),
+            }
+        }
+        "diff" => {
+            if positional.len() > 2 {
+                return Err("usage: fdb diff [name] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Diff {
+                name: positional.get(1).cloned(),
+                kubeconfig,
+            }
+        }
+        "apply" => {
+            if positional.len() > 2 {
+                return Err("usage: fdb apply [name] [-y|--yes] [--timeout 5m] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Apply {
+                name: positional.get(1).cloned(),
+                kubeconfig,
+                yes,
+                timeout: timeout.unwrap_or(cluster::DEFAULT_TIMEOUT),
+            }
+        }
+        "sql" => {
+            if positional.len() != 2 {
+                return Err("usage: fdb sql <name> [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Sql {
+                name: positional[1].clone(),
+                kubeconfig,
+            }
+        }
+        "exec-sql" => {
+            if positional.len() != 3 {
+                return Err("usage: fdb exec-sql <name> <sql|-> [--database NAME] [--json] [--kubeconfig PATH]".to_string());
+            }
+            if globals.output == output::Format::Yaml {
+                return Err("fdb exec-sql prints rows as JSON only; use -o json".to_string());
+            }
+            CliCommand::ExecSql {
+                name: positional[1].clone(),
+                kubeconfig,
+                database,
+                sql: positional[2].clone(),
+                json,
+            }
+        }
+        "exec-redis" => {
+            if positional.len() < 3 {
+                return Err("usage: fdb exec-redis <name> <command> [--json] [--kubeconfig PATH]".to_string());
+            }
+            if globals.output == output::Format::Yaml {
+                return Err("fdb exec-redis prints replies as JSON only; use -o json".to_string());
+            }
+            // One argument is a whole command line ("GET key"); several are the words already split.
+            let args = match &positional[2..] {
+                [line] => query::split_command(line)?,
+                words => words.to_vec(),
+            };
+            CliCommand::ExecRedis {
+                name: positional[1].clone(),
+                kubeconfig,
+                args,
+                json,
+            }
+        }
+        "shellenv" => match &positional[1..] {
+            [] => CliCommand::Shellenv {
+                shell: shellenv::Shell::detect(),
+            },
+            [shell] => CliCommand::Shellenv { shell: shell.parse()? },
+            _ => return Err("usage: fdb shellenv [bash|zsh|fish]".to_string()),
+        },
+        "open" => {
+            if positional.len() != 2 {
+                return Err("usage: fdb open <name> [--no-browser] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Open {
+                name: positional[1].clone(),
+                kubeconfig,
+                no_browser,
+            }
+        }
+        "status" => {
+            if positional.len() != 2 {
+                return Err("usage: fdb status <name> [-o json|yaml] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Status {
+                name: positional[1].clone(),
+                kubeconfig,
+                json,
+            }
+        }
+        "scale" => {
+            if positional.len() != 2 {
+                return Err("usage: fdb scale <name> [--replicas N] [--cpu C] [--memory M] [--timeout 5m] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Scale {
+                name: positional[1].clone(),
+                kubeconfig,
+                target: scale::Target { replicas, cpu, memory },
+                timeout: timeout.unwrap_or(cluster::DEFAULT_TIMEOUT),
+            }
+        }
+        "logs" => {
+            if positional.len() != 2 {
+                return Err("usage: fdb logs <name> [-f|--follow] [--tail N] [--component NAME] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Logs {
+                name: positional[1].clone(),
+                kubeconfig,
+                opts: log_opts,
+            }
+        }
+        "connect" => {
+            if positional.len() != 2 {
+                return Err("usage: fdb connect <name> [--admin] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Connect {
+                name: positional[1].clone(),
+                kubeconfig,
+                admin,
+            }
+        }
+        "port-forward" => {
+            if positional.len() != 2 {
+                return Err("usage: fdb port-forward <name> [--local-port N] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::PortForward {
+                name: positional[1].clone(),
+                kubeconfig,
+                local_port,
+            }
+        }
+        "top" => {
+            if positional.len() != 2 {
+                return Err("usage: fdb top <name> [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Top {
+                name: positional[1].clone(),
+                kubeconfig,
+            }
+        }
+        "ui" => {
+            if positional.len() != 1 {
+                return Err("usage: fdb ui [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Ui { kubeconfig }
+        }
+        "report" => {
+            if positional.len() != 1 {
+                return Err("usage: fdb report [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Report { kubeconfig }
+        }
+        "benchmark" => {
+            if positional.len() != 2 {
+                return Err("usage: fdb benchmark <name> [--duration 10s] [--clients 4] [--kubeconfig PATH]".to_string());
+            }
+            CliCommand::Benchmark {
+                name: positional[1].clone(),
+                kubeconfig,
+                opts: bench,
+            }
+        }
+        other => {
+            return Err(match suggest::did_you_mean(other, COMMANDS.iter().copied()) {
+                Some(close) => format!("unknown command `{other}`; did you mean `{close}`?\n\n{}", usage()),
+                None => usage(),
+            });
+        }
+    };
+    Ok((cmd, globals))
+}
+
+fn usage() -> String {
+    "usage: fdb create [<engine> [<name>] [options]]
+       fdb create --profile NAME [<name>] [options]   (or: fdb up <profile> [<name>])
+       fdb wait <name>... [--for running|stopped|deleted] [--timeout 5m] [--kubeconfig PATH]
+       fdb delete <name>|<pattern>|--all [-y|--yes] [--kubeconfig PATH]
+       fdb list [--watch] [--engine|--service TYPE] [--status PHASE] [--label KEY=VALUE] [--columns LIST] [--sort name|age|status] [--kubeconfig PATH]
+       fdb info <name> [--admin] [--uri | --sops --age RECIPIENT] [--kubeconfig PATH]
+       fdb env <name> [--format dotenv|shell|json] [--admin] [--kubeconfig PATH]
+       fdb status <name> [-o json|yaml] [--kubeconfig PATH]
+       fdb scale <name> [--replicas N] [--cpu C] [--memory M] [--timeout 5m] [--kubeconfig PATH]
+       fdb logs <name> [-f|--follow] [--tail N] [--component NAME] [--kubeconfig PATH]
+       fdb credentials add <name> [--readonly | --user NAME ...] [--kubeconfig PATH]
+       fdb credentials temp <name> [--ttl 1h] [--readonly] [--kubeconfig PATH]
+       fdb creds <name> [--admin] [--kubeconfig PATH]
+       fdb creds rotate <name> [--admin] [--kubeconfig PATH]
+       fdb gc [name] [--kubeconfig PATH]
+       fdb start <name> [--timeout 5m] [--kubeconfig PATH]
+       fdb stop <name> [--timeout 5m] [--kubeconfig PATH]
+       fdb restart <name> [--timeout 5m] [--kubeconfig PATH]
+       fdb upgrade <name> --version VERSION [--timeout 5m] [--kubeconfig PATH]
+       fdb expose <name> [--type nodeport|loadbalancer|clusterip|none] [--kubeconfig PATH]
+       fdb pause-idle [--after 48h] [-y|--yes] [--kubeconfig PATH]
+       fdb kubeconfig export <name> [--ttl 720h] [--out PATH]
+       fdb protect|unprotect <name> [--kubeconfig PATH]
+       fdb bundle export <name> [--age RECIPIENT] [--out PATH] [--admin] [--kubeconfig PATH]
+       fdb bundle import <file> | fdb bundle list
+       fdb serve [--listen 127.0.0.1:7654] [--token TOKEN] [--kubeconfig PATH]
+       fdb mcp [--kubeconfig PATH]
+       fdb history [name]
+       fdb diff [name] [--kubeconfig PATH]
+       fdb apply [name] [-y|--yes] [--timeout 5m] [--kubeconfig PATH]
+       fdb tools upgrade [--check]
+       fdb dump <name> --out FILE|- [--database NAME] [--kubeconfig PATH]
+       fdb load <name> <file> [--database NAME] [-y|--yes] [--kubeconfig PATH]
+       fdb load <name> <file|dir> --job [--image IMAGE] [--timeout 30m] [--database NAME] [-y|--yes]
+       fdb copy <source> <dest> [--database NAME] [-y|--yes] [--kubeconfig PATH]
+       fdb clone <source> <target> [--method snapshot|backup|empty] [--timeout 10m] [--kubeconfig PATH]
+       fdb restore <name> --at TIME --into NEW [--timeout 10m] [--kubeconfig PATH]
+       fdb sql <name> [--kubeconfig PATH]
+       fdb exec-sql <name> <sql|-> [--database NAME] [--json] [--kubeconfig PATH]
+       fdb exec-redis <name> <command> [--json] [--kubeconfig PATH]
+       fdb open <name> [--no-browser] [--kubeconfig PATH]
+       fdb connect <name> [--admin] [--kubeconfig PATH]
+       fdb port-forward <name> [--local-port N] [--kubeconfig PATH]
+       fdb shellenv [bash|zsh|fish]
+       fdb top <name> [--kubeconfig PATH]
+       fdb ui [--kubeconfig PATH]
+       fdb report [--kubeconfig PATH]
+       fdb benchmark <name> [--duration 10s] [--clients 4] [--kubeconfig PATH]
+
+global options: -o json|yaml|table, --redact (default in CI), --show-secrets, --debug"
+        .to_string()
+}
+
+/// Steps of `fdb create` that need a Running cluster. With `--wait=false` they are saved on
+/// the cluster so `fdb wait` can finish them.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct PostCreate {
+    publish: Option<PublishTarget>,
+    allow_cidrs: Vec<String>,
+    /// `--expose none`: no NodePort; reach the cluster with port-forward.
+    skip_expose: bool,
+    redis_acl_users: Vec<config::RedisAclUser>,
+    app_account: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    metrics: Option<config::MetricsConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    alerts: Option<config::AlertsConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    log_shipping: Option<config::LogShippingConfig>,
+}
+
+/// Engine-specific `kbcli cluster create` flags from the config.
+fn engine_flags(service: ServiceType, config: &config::Config) -> &'static [&'static str] {
+    match service {
+        // JetStream's file store lives on the cluster's volume, sized by `--storage`.
+        ServiceType::Nats if config.jetstream => &["--jetstream-enabled=true"],
+        _ => &[],
+    }
+}
+
+fn run_create(
+    service: ServiceType,
+    mut cluster_name: String,
+    generated_name: bool,
+    overrides: Overrides,
+    mut setup: PostCreate,
+    protect: bool,
+    wait: bool,
+) -> Result<(), Error> {
+    let config = load_config(service, overrides);
+    let storage = cluster::kbcli_storage(&config.storage)?;
+    let memory = cluster::kbcli_gi(&config.memory, "memory")?;
+    let cpu = cluster::kbcli_cpu(&config.cpu)?;
+    if let Some(schedule) = &config.backup {
+        schedule.validate()?;
+    }
+
+    let mut progress = progress::Progress::new();
+    let (kubectl, kbcli) = progress.quiet("tools", tools::prepare)?;
+    if generated_name {
+        let taken = cluster::cluster_names(&kbcli, &config.kubeconfig).unwrap_or_default();
+        if taken.contains(&cluster_name) {
+            cluster_name = config::qualify(&names::generate(&taken)?);
+        }
+        output::say!("No name given; using \"{cluster_name}\".");
+    }
+    let cluster_name = cluster_name.as_str();
+
+    let started = chrono::Local::now();
+    let kubeconfig_display = config.kubeconfig.display().to_string();
+    output::say!(
+        "Creating {} cluster \"{cluster_name}\" (replicas={}, storage={}, cpu={cpu}, memory={memory} Gi)",
+        service.kbcli_name(),
+        config.replicas,
+        storage.as_ref().map_or("none".to_string(), |s| format!("{s} Gi")),
+    );
+    output::say!("  kubeconfig: {kubeconfig_display}");
+    output::say!("  started: {}", started.format("%Y-%m-%d %H:%M:%S"));
+    output::say!();
+
+    progress.begin("create accepted", "Submitting cluster");
+    let version = config
+        .version
+        .as_deref()
+        .map(|v| kubeblocks::api(&kubectl, &config.kubeconfig).service_version(service, v));
+    cluster::create_cluster(
+        &kbcli,
         service,
         cluster_name,
         &config.kubeconfig,
         config.replicas,
-        &config.storage,
-        &config.cpu,
-        &config.memory,
-    )?;
+        storage.as_deref(),
+        &cpu,
+        &memory,
+        version.as_deref(),
+        engine_flags(service, &config),
+    ).map_err(error::code(ExitCode::Kbcli))?;
+
+    if protect {
+        cluster::set_protected(&kubectl, cluster_name, true, &config.kubeconfig)?;
+    }
+    if config.pitr && service == ServiceType::PostgreSQL {
+        backup::enable_pitr(&kbcli, cluster_name, &config.kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    }
+    if let Some(schedule) = &config.backup {
+        backup::enable_schedule(&kbcli, cluster_name, schedule, &config.kubeconfig)
+            .map_err(error::code(ExitCode::Kbcli))?;
+    }
+    if config.metrics.is_some() {
+        metrics::enable_exporter(&kbcli, service, cluster_name, &config.kubeconfig)
+            .map_err(error::code(ExitCode::Kbcli))?;
+    }
+
+    setup.redis_acl_users = config.redis_acl_users.clone();
+    setup.app_account = config.app_account.clone();
+    setup.metrics = config.metrics.clone();
+    setup.alerts = config.alerts.clone();
+    setup.log_shipping = config.log_shipping.clone();
+    if !wait {
+        let json = serde_json::to_string(&setup).map_err(|e| format!("serialize setup: {e}"))?;
+        cluster::set_pending_setup(&kubectl, cluster_name, Some(&json), &config.kubeconfig)?;
+        progress.finish();
+        if output::structured() {
+            output::print(&CreateResult {
+                name: cluster_name,
+                engine: service.kbcli_name(),
+                status: "Creating",
+                connection: None,
+                health: None,
+                warnings: Vec::new(),
+            })?;
+            return Ok(());
+        }
+        println!();
+        println!("Cluster \"{cluster_name}\" accepted; not waiting for it to be Running.");
+        println!("Run `fdb wait {cluster_name}` to wait, finish setup and show connection details.");
+        return Ok(());
+    }
+
+    cluster::wait_until_running(&kbcli, &kubectl, cluster_name, &config.kubeconfig, config.timeout, &mut progress)?;
+    finish_create(&kubectl, service, cluster_name, &config.kubeconfig, &setup, &mut progress)
+}
+
+/// `fdb create -o json|yaml`: what the human summary shows, for CI pipelines.
+#[derive(Serialize)]
+struct CreateResult<'a> {
+    name: &'a str,
+    engine: &'a str,
+    /// "Running", or "Creating" with `--wait=false`.
+    status: &'a str,
+    /// user, password, host, port and uri (secrets masked under `--redact`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    connection: Option<std::collections::BTreeMap<&'static str, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    health: Option<String>,
+    warnings: Vec<String>,
+}
+
+/// Everything after the cluster is Running: network policy, NodePort, credentials, health check,
+/// publishing and Redis ACL users; prints connection details and the time summary.
+fn finish_create(
+    kubectl: &std::path::Path,
+    service: ServiceType,
+    cluster_name: &str,
+    kubeconfig: &std::path::Path,
+    setup: &PostCreate,
+    progress: &mut progress::Progress,
+) -> Result<(), Error> {
+    progress.begin("exposing", "Exposing");
+    if !setup.allow_cidrs.is_empty() {
+        access::apply_allowed_cidrs(kubectl, cluster_name, &setup.allow_cidrs, kubeconfig)?;
+    }
+    // Warnings wait until the spinner line is done.
+    let (endpoint, expose_warning) = if setup.skip_expose {
+        (None, None)
+    } else {
+        match (
+            expose::server_host_from_kubeconfig(kubectl, kubeconfig),
+            expose::ensure_nodeport_and_get_port(kubectl, service, cluster_name, kubeconfig),
+        ) {
+            (Ok(h), Ok(p)) => (Some((h, p)), None),
+            (Err(e), _) => (None, Some(format!("could not get server host from kubeconfig: {e}"))),
+            (_, Err(e)) => (None, Some(format!("could not expose NodePort: {e}"))),
+        }
+    };
+    let (scrape, metrics_warning) = match &setup.metrics {
+        None => (None, None),
+        Some(m) => match metrics::wire(kubectl, service, cluster_name, m, kubeconfig) {
+            Ok(line) => (Some(line), None),
+            Err(e) => (None, Some(format!("could not set up metrics scraping: {e}"))),
+        },
+    };
+    let (rules, alerts_warning) = match &setup.alerts {
+        None => (None, None),
+        Some(a) => match metrics::install_alerts(kubectl, service, cluster_name, a, kubeconfig) {
+            Ok(line) => (Some(line), None),
+            Err(e) => (None, Some(format!("could not install alerting rules: {e}"))),
+        },
+    };
+    let (shipper, logs_warning) = match &setup.log_shipping {
+        None => (None, None),
+        Some(l) => match logship::ensure(kubectl, l, kubeconfig) {
+            Ok(line) => (Some(line), None),
+            Err(e) => (None, Some(format!("could not set up log shipping: {e}"))),
+        },
+    };
+
+    progress.begin("fetching credentials", "Fetching credentials");
+    let password = credentials::get_password(kubectl, service, cluster_name, kubeconfig)
+        .map_err(error::code(ExitCode::Credentials))?;
+    let app_password = match setup.app_account {
+        Some(ref user) => accounts::create_app_account(kubectl, service, cluster_name, user, kubeconfig)
+            .map_err(error::code(ExitCode::Credentials))?,
+        None => None,
+    };
+
+    progress.begin("verifying", "Verifying connectivity");
+    let health = health::check(kubectl, service, cluster_name, password.as_deref(), kubeconfig);
+    match health {
+        Ok(_) => progress.finish(),
+        Err(_) => progress.fail("health check failed"),
+    }
+    let warnings: Vec<String> =
+        expose_warning.into_iter().chain(metrics_warning).chain(alerts_warning).chain(logs_warning).collect();
+    for w in &warnings {
+        eprintln!("warning: {w}");
+    }
+
+    let app_details = setup.app_account.is_some() && app_password.is_some();
+    let details = match (&setup.app_account, app_password) {
+        (Some(user), Some(p)) => credentials::ConnectionDetails::for_user(service, user, Some(p), endpoint),
+        _ => {
+            let user = credentials::get_user(kubectl, service, cluster_name, kubeconfig)
+                .map_err(error::code(ExitCode::Credentials))?;
+            credentials::ConnectionDetails::for_user(service, &user, password, endpoint)
+        }
+    };
+
+    if output::structured() {
+        let connection = details
+            .fields()
+            .into_iter()
+            .map(|(key, value)| match key {
+                "password" => (key, redact::secret(&value)),
+                "uri" => (key, redact::url(&value)),
+                _ => (key, value),
+            })
+            .collect();
+        output::print(&CreateResult {
+            name: cluster_name,
+            engine: service.kbcli_name(),
+            status: "Running",
+            connection: Some(connection),
+            health: Some(match health {
+                Ok(check) => format!("ok ({check})"),
+                Err(e) => format!("FAILED ({e})"),
+            }),
+            warnings,
+        })?;
+    } else {
+        println!();
+        println!("Cluster \"{cluster_name}\" is running.");
+        println!();
+        details.print();
+        if app_details {
+            println!("  (admin credentials: fdb info {cluster_name} --admin)");
+        }
+        match health {
+            Ok(check) => println!("  Health check:      ok ({check})"),
+            Err(e) => println!("  Health check:      FAILED ({e})"),
+        }
+        if let Some(scrape) = scrape {
+            println!("  Metrics:           {scrape}");
+        }
+        if let Some(rules) = rules {
+            println!("  Alerts:            {rules}");
+        }
+        if let Some(shipper) = shipper {
+            println!("  Logs:              {shipper}");
+        }
+    }
+
+    if let Some(target) = setup.publish {
+        publish::publish(target, cluster_name, &details)?;
+    }
+
+    accounts::provision_redis_users(kubectl, cluster_name, &setup.redis_acl_users, kubeconfig)?;
+
+    output::say!();
+    progress.print_summary();
+    hooks::post_create(service, cluster_name, &details, kubeconfig)?;
+    Ok(())
+}
+
+/// Wait for each cluster to reach `condition`. For Running, also finish the setup
+/// `fdb create --wait=false` left behind.
+fn run_wait(
+    names: &[String],
+    condition: cluster::WaitFor,
+    kubeconfig_override: Option<PathBuf>,
+    timeout: std::time::Duration,
+) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    for name in names {
+        let mut progress = progress::Progress::new();
+        match condition {
+            cluster::WaitFor::Running => {
+                let service =
+                    cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+                cluster::wait_until_running(&kbcli, &kubectl, name, &kubeconfig, timeout, &mut progress)?;
+                match cluster::pending_setup(&kubectl, name, &kubeconfig)? {
+                    Some(json) => {
+                        let setup: PostCreate = serde_json::from_str(&json)
+                            .map_err(|e| format!("parse pending setup of \"{name}\": {e}"))?;
+                        finish_create(&kubectl, service, name, &kubeconfig, &setup, &mut progress)?;
+                        cluster::set_pending_setup(&kubectl, name, None, &kubeconfig)?;
+                    }
+                    None => println!("Cluster \"{name}\" is running."),
+                }
+            }
+            cluster::WaitFor::Stopped => {
+                cluster::wait_until_stopped(&kbcli, name, &kubeconfig, timeout, &mut progress)?;
+                println!("Cluster \"{name}\" is stopped.");
+            }
+            cluster::WaitFor::Deleted => {
+                cluster::wait_until_deleted(&kubectl, name, &kubeconfig, timeout, &mut progress)?;
+                println!("Cluster \"{name}\" is deleted.");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_delete(name: &str, kubeconfig_override: Option<PathBuf>, yes: bool) -> Result<(), Error> {
+    if output::structured() && !yes {
+        return Err(Error::new(ExitCode::Usage, "-o json|yaml cannot prompt; add --yes"));
+    }
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    cluster::delete_cluster(&kbcli, &kubectl, name, &kubeconfig, yes).map_err(error::code(ExitCode::Kbcli))?;
+    if output::structured() {
+        output::print(&serde_json::json!({ "deleted": [name] }))?;
+    } else {
+        println!("Cluster \"{name}\" deleted.");
+    }
+    Ok(())
+}
 
-    cluster::wait_until_running(&kbcli, cluster_name, &config.kubeconfig)?;
+fn run_delete_many(pattern: &str, kubeconfig_override: Option<PathBuf>, yes: bool) -> Result<(), Error> {
+    if output::structured() && !yes {
+        return Err(Error::new(ExitCode::Usage, "-o json|yaml cannot prompt; add --yes"));
+    }
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let deleted = cluster::delete_clusters(&kbcli, &kubectl, pattern, &kubeconfig, yes)
+        .map_err(error::code(ExitCode::Kbcli))?;
+    if output::structured() {
+        output::print(&serde_json::json!({ "deleted": deleted }))?;
+    }
+    Ok(())
+}
 
-    let password = credentials::get_password(
-        &kubectl,
-        service,
-        cluster_name,
-        &config.kubeconfig,
-    )?;
+fn run_list(
+    kubeconfig_override: Option<PathBuf>,
+    watch: bool,
+    filter: &cluster::ListFilter,
+    view: &cluster::ListView,
+) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    if watch {
+        cluster::watch_clusters(&kbcli, &kubectl, filter, view, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    } else {
+        cluster::list_clusters(&kbcli, &kubectl, filter, view, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    }
+    Ok(())
+}
 
-    let user = service.default_user();
+/// `fdb info --uri`: the connection string alone, unmasked, for `$(...)`.
+fn run_info_uri(name: &str, kubeconfig_override: Option<PathBuf>, admin: bool) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    let details = accounts::connection_details(&kubectl, service, name, &kubeconfig, admin)?;
+    let uri = details
+        .connection_string
+        .ok_or_else(|| format!("\"{name}\" has no external endpoint (NodePort); see `fdb info {name}`"))?;
+    println!("{uri}");
+    Ok(())
+}
 
-    let (host, port) = match (
-        expose::server_host_from_kubeconfig(&kubectl, &config.kubeconfig),
-        expose::ensure_nodeport_and_get_port(&kubectl, service, cluster_name, &config.kubeconfig),
-    ) {
-        (Ok(h), Ok(p)) => (h, p),
-        (Err(e), _) => {
-            eprintln!("warning: could not get server host from kubeconfig: {e}");
-            (String::new(), 0)
-        }
-        (_, Err(e)) => {
-            eprintln!("warning: could not expose NodePort: {e}");
-            (String::new(), 0)
-        }
+fn run_env(name: &str, kubeconfig_override: Option<PathBuf>, format: env::EnvFormat, admin: bool) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    let details = accounts::connection_details(&kubectl, service, name, &kubeconfig, admin)?;
+    print!("{}", env::render(&env::variables(service, &details, name)?, format));
+    Ok(())
+}
+
+fn run_info(
+    name: &str,
+    kubeconfig_override: Option<PathBuf>,
+    sops: bool,
+    age: Option<String>,
+    format: SopsFormat,
+    out: Option<PathBuf>,
+    admin: bool,
+) -> Result<(), Error> {
+    let age = if sops {
+        let recipient = age
+            .or_else(|| std::env::var("SOPS_AGE_RECIPIENTS").ok())
+            .ok_or("--sops requires --age RECIPIENT (or SOPS_AGE_RECIPIENTS)")?;
+        Some(recipient)
+    } else {
+        None
+    };
+
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+
+    let service = match cluster::cluster_service_type(&kbcli, name, &kubeconfig) {
+        Ok(service) => service,
+        // Clusters shared with `fdb bundle import` may live where this kubeconfig can't reach.
+        Err(e) => match bundle::load(name)? {
+            Some(b) if age.is_none() => {
+                b.print();
+                return Ok(());
+            }
+            _ => return Err(Error::new(ExitCode::Kbcli, e)),
+        },
     };
+    let details = accounts::connection_details(&kubectl, service, name, &kubeconfig, admin)?;
+
+    if let Some(recipient) = age {
+        let out = out.unwrap_or_else(|| format.default_path(name));
+        sops::write_encrypted(&details, &recipient, format, &out)?;
+        println!("Wrote encrypted connection details to {}", out.display());
+        return Ok(());
+    }
 
+    println!("Cluster \"{name}\" ({})", service.kbcli_name());
     println!();
-    println!("Cluster \"{cluster_name}\" is running.");
+    details.print();
+    if details.user != service.default_user() {
+        println!("  (admin credentials: fdb info {name} --admin)");
+    }
+
+    if service == ServiceType::PostgreSQL {
+        let cidrs = access::allowed_cidrs(&kubectl, name, &kubeconfig)?;
+        println!();
+        if cidrs.is_empty() {
+            println!("Allowed client networks: any (no --allow-cidr applied)");
+        } else {
+            println!("Allowed client networks: {}", cidrs.join(", "));
+            println!("pg_hba rules:");
+            let pod_cidrs = access::pod_cidrs(&kubectl, &kubeconfig);
+            for rule in access::pg_hba_rules(&cidrs, &pod_cidrs) {
+                println!("  {rule}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_bundle_export(
+    name: &str,
+    kubeconfig_override: Option<PathBuf>,
+    age: Option<String>,
+    out: Option<PathBuf>,
+    admin: bool,
+) -> Result<(), Error> {
+    let age = age.or_else(|| std::env::var("SOPS_AGE_RECIPIENTS").ok());
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    let details = accounts::connection_details(&kubectl, service, name, &kubeconfig, admin)?;
+    let context = kubeconfig::current_context(&kubectl, &kubeconfig)?;
+    let spec = drift::live(&kubectl, service, name, &kubeconfig)?
+        .map(|live| bundle::BundleSpec {
+            replicas: live.spec.replicas,
+            cpu: live.spec.cpu,
+            memory_gi: live.spec.memory,
+            storage_gi: live.spec.storage,
+            version: live.spec.version,
+        })
+        .unwrap_or_default();
+    let out = out.unwrap_or_else(|| PathBuf::from(format!("{name}.fdb.json")));
+    let b = bundle::Bundle::new(name, service.kbcli_name(), &context, spec, &details);
+    bundle::export(b, age.as_deref(), &out)?;
+    match age {
+        Some(_) => println!("Wrote bundle with encrypted credentials to {}", out.display()),
+        None => println!("Wrote bundle without credentials to {} (use --age to include them)", out.display()),
+    }
+    Ok(())
+}
+
+fn run_bundle_import(file: &Path) -> Result<(), Error> {
+    let b = bundle::import(file).map_err(error::code(ExitCode::Credentials))?;
+    b.print();
     println!();
-    println!("Connection details:");
-    if !host.is_empty() && port != 0 {
-        let connection_string = service.connection_string(
-            user,
-            password.as_deref(),
-            &host,
-            port,
-        );
-        println!("  Host:              {host}");
-        println!("  Port:              {port}");
-        println!("  User:              {user}");
-        if let Some(ref p) = password {
-            println!("  Password:          {p}");
-        }
-        println!("  Connection string: {connection_string}");
+    println!("Registered \"{}\"; `fdb info {}` shows it when the cluster is not reachable.", b.name, b.name);
+    Ok(())
+}
+
+fn run_serve(listen: &str, token: Option<String>, kubeconfig_override: Option<PathBuf>) -> Result<(), Error> {
+    let token = match token.or_else(|| std::env::var("FDB_API_TOKEN").ok()) {
+        Some(t) if t.trim().is_empty() => {
+            return Err(Error::new(ExitCode::Usage, "--token (or FDB_API_TOKEN) must not be empty"));
+        }
+        Some(t) => t,
+        // A generated token printed masked would lock everyone out.
+        None if redact::enabled() => {
+            return Err(Error::new(
+                ExitCode::Usage,
+                "secrets are redacted (--redact or CI), so a generated token can't be shown; pass --token or set \
+                 FDB_API_TOKEN",
+            ));
+        }
+        None => {
+            let t = credentials::random_token(16)?;
+            println!("API token (pass as `Authorization: Bearer <token>`): {t}");
+            t
+        }
+    };
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let api = serve::Api {
+        kubectl,
+        kbcli,
+        kubeconfig,
+        token,
+        create: api_create,
+    };
+    serve::run(listen, &api)?;
+    Ok(())
+}
+
+fn run_mcp(kubeconfig_override: Option<PathBuf>) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let server = mcp::Server {
+        kubectl,
+        kbcli,
+        kubeconfig,
+        allow: config::load_mcp_allow(),
+    };
+    eprintln!("fdb mcp: serving on stdio");
+    mcp::run(&server)?;
+    Ok(())
+}
+
+/// `POST /clusters`: parse the request like the equivalent `fdb create` command line, then
+/// run the create (with setup and audit entry) on a background thread.
+fn api_create(request: &serve::CreateRequest, kubeconfig: &Path) -> Result<String, Error> {
+    let mut args: Vec<String> = match (&request.profile, &request.engine) {
+        (Some(profile), None) => vec!["up".into(), profile.clone()],
+        (None, Some(engine)) => vec!["create".into(), engine.clone()],
+        _ => return Err(Error::new(ExitCode::Usage, "give either \"engine\" or \"profile\"")),
+    };
+    args.extend(request.name.clone());
+    let flags = [
+        ("--replicas", request.replicas.map(|r| r.to_string())),
+        ("--storage", request.storage.clone()),
+        ("--cpu", request.cpu.clone()),
+        ("--memory", request.memory.clone()),
+        ("--expose", request.expose.clone()),
+    ];
+    for (flag, value) in flags {
+        if let Some(v) = value {
+            args.extend([flag.to_string(), v]);
+        }
+    }
+    args.extend(["--kubeconfig".to_string(), kubeconfig.display().to_string()]);
+    let (cmd, _) = parse_args_from(args.into_iter().map(Into::into).collect()).map_err(error::code(ExitCode::Usage))?;
+    let CliCommand::Create {
+        service,
+        name,
+        generated_name,
+        overrides,
+        setup,
+        protect,
+        wait,
+    } = cmd
+    else {
+        return Err(Error::new(ExitCode::Usage, "not a create request"));
+    };
+    let mut name = config::qualify(&name);
+    names::validate(&name).map_err(error::code(ExitCode::Usage))?;
+    // Settle a generated name now, so the response names the cluster that gets created.
+    if generated_name {
+        let (_, kbcli) = tools::prepare()?;
+        let taken = cluster::cluster_names(&kbcli, kubeconfig).unwrap_or_default();
+        if taken.contains(&name) {
+            name = config::qualify(&names::generate(&taken)?);
+        }
+    }
+    let target = name.clone();
+    std::thread::spawn(move || {
+        let result = run_create(service, name.clone(), false, overrides, *setup, protect, wait);
+        if let Err(ref e) = result {
+            eprintln!("create \"{name}\": {e}");
+        }
+        history::record("create", &name, result.as_ref().map(|_| ()).map_err(|e| e.message.as_str()));
+    });
+    Ok(target)
+}
+
+fn run_bundle_list() {
+    let bundles = bundle::list();
+    if bundles.is_empty() {
+        println!("No bundles imported.");
+        return;
+    }
+    println!("{:<30} {:<12} {:<25} EXPORTED", "NAME", "ENGINE", "CONTEXT");
+    for b in bundles {
+        println!("{:<30} {:<12} {:<25} {}", b.name, b.engine, b.context, b.exported_at);
+    }
+}
+
+fn run_credentials_add(
+    name: &str,
+    kubeconfig_override: Option<PathBuf>,
+    opts: &accounts::AddOptions,
+) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    accounts::add_credentials(&kubectl, service, name, opts, &kubeconfig).map_err(error::code(ExitCode::Credentials))
+}
+
+fn run_sql(name: &str, kubeconfig_override: Option<PathBuf>) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    if service != ServiceType::PostgreSQL {
+        return Err(format!("fdb sql only supports PostgreSQL clusters; \"{name}\" is {}", service.kbcli_name()).into());
+    }
+    sql::run_prompt(&kubectl, name, &kubeconfig)?;
+    Ok(())
+}
+
+fn run_exec_sql(
+    name: &str,
+    kubeconfig_override: Option<PathBuf>,
+    database: Option<&str>,
+    sql: &str,
+    json: bool,
+) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    if service != ServiceType::PostgreSQL {
+        return Err(format!("fdb exec-sql only supports PostgreSQL clusters; \"{name}\" is {}", service.kbcli_name()).into());
+    }
+    let sql = if sql == "-" {
+        std::io::read_to_string(std::io::stdin()).map_err(|e| format!("read stdin: {e}"))?
+    } else {
+        sql.to_string()
+    };
+    query::exec_sql(&kubectl, name, database, &sql, json, &kubeconfig)?;
+    Ok(())
+}
+
+fn run_exec_redis(name: &str, kubeconfig_override: Option<PathBuf>, args: &[String], json: bool) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    if service != ServiceType::Redis {
+        return Err(format!("fdb exec-redis only supports Redis clusters; \"{name}\" is {}", service.kbcli_name()).into());
+    }
+    query::exec_redis(&kubectl, name, args, json, &kubeconfig)?;
+    Ok(())
+}
+
+fn run_dump(name: &str, kubeconfig_override: Option<PathBuf>, database: Option<&str>, out: &Path) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    dump::run(&kubectl, service, name, database, out, &kubeconfig)?;
+    Ok(())
+}
+
+fn run_load(
+    name: &str,
+    file: &Path,
+    kubeconfig_override: Option<PathBuf>,
+    database: Option<&str>,
+    yes: bool,
+    job: Option<seed::Job>,
+) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    match job {
+        Some(_) if service != ServiceType::PostgreSQL => {
+            let message = format!("--job is only supported for postgresql, not {}", service.kbcli_name());
+            return Err(Error::new(ExitCode::Usage, message));
+        }
+        Some(job) => seed::run(&kubectl, name, file, database, yes, &job, &kubeconfig)?,
+        None => load::run(&kubectl, service, name, file, database, yes, &kubeconfig)?,
+    }
+    Ok(())
+}
+
+fn run_copy(
+    source: &str,
+    dest: &str,
+    kubeconfig_override: Option<PathBuf>,
+    database: Option<&str>,
+    yes: bool,
+) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, source, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    let dest_service = cluster::cluster_service_type(&kbcli, dest, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    if service != dest_service {
+        return Err(format!(
+            "\"{source}\" is {} but \"{dest}\" is {}; fdb copy needs the same engine",
+            service.kbcli_name(),
+            dest_service.kbcli_name()
+        )
+        .into());
+    }
+    copy::run(&kubectl, service, source, dest, database, yes, &kubeconfig)?;
+    Ok(())
+}
+
+fn run_clone(
+    source: &str,
+    target: &str,
+    method: backup::CloneMethod,
+    kubeconfig_override: Option<PathBuf>,
+    timeout: std::time::Duration,
+) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override.clone());
+    let (kubectl, kbcli) = tools::prepare()?;
+    cluster::ensure_exists(&kbcli, source, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    match method {
+        backup::CloneMethod::Snapshot => {
+            backup::clone_from_snapshot(&kbcli, &kubectl, source, target, &kubeconfig, timeout)
+        }
+        backup::CloneMethod::Backup => {
+            let latest = backup::latest_backup(&kubectl, source, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+            println!("Restoring backup \"{latest}\" of \"{source}\" into \"{target}\"");
+            backup::restore_backup(&kbcli, &kubectl, &latest, target, &kubeconfig, timeout)
+        }
+        backup::CloneMethod::Empty => {
+            let service =
+                cluster::cluster_service_type(&kbcli, source, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+            let spec = drift::live(&kubectl, service, source, &kubeconfig)?
+                .ok_or_else(|| format!("cluster \"{source}\" not found"))?
+                .spec;
+            // drift reports quantities in cores and Gi; create takes them back as overrides.
+            let overrides = Overrides {
+                kubeconfig: kubeconfig_override,
+                replicas: spec.replicas,
+                cpu: spec.cpu.map(|c| c.to_string()),
+                memory: spec.memory.map(|m| format!("{m}Gi")),
+                storage: spec.storage.map(|s| format!("{s}Gi")),
+                version: spec.version,
+                ..Default::default()
+            };
+            let setup = PostCreate {
+                skip_expose: !spec.exposed,
+                ..Default::default()
+            };
+            run_create(service, target.to_string(), false, overrides, setup, false, true)
+        }
+    }
+}
+
+fn run_backup(
+    name: &str,
+    method: Option<&str>,
+    kubeconfig_override: Option<PathBuf>,
+    timeout: std::time::Duration,
+) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    cluster::ensure_exists(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    backup::backup(&kbcli, &kubectl, name, method, &kubeconfig, timeout)
+}
+
+fn run_backup_list(name: &str, kubeconfig_override: Option<PathBuf>, json: bool) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, _) = tools::prepare()?;
+    let backups = backup::list_backups(&kubectl, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    if json {
+        output::print(&backups)?;
+    } else {
+        backup::print_backups(name, &backups);
+    }
+    Ok(())
+}
+
+fn run_restore(
+    backup: &str,
+    new_name: &str,
+    kubeconfig_override: Option<PathBuf>,
+    timeout: std::time::Duration,
+) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    backup::restore_backup(&kbcli, &kubectl, backup, new_name, &kubeconfig, timeout)
+}
+
+fn run_restore_at(
+    name: &str,
+    at: chrono::DateTime<chrono::Utc>,
+    into: &str,
+    kubeconfig_override: Option<PathBuf>,
+    timeout: std::time::Duration,
+) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    if service != ServiceType::PostgreSQL {
+        return Err(format!("point-in-time restore is only supported for PostgreSQL; \"{name}\" is {}", service.kbcli_name()).into());
+    }
+    backup::restore_to_time(&kbcli, &kubectl, name, into, at, &kubeconfig, timeout)
+}
+
+fn run_top(name: &str, kubeconfig_override: Option<PathBuf>) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, _) = tools::prepare()?;
+    top::show(&kubectl, name, &kubeconfig)?;
+    Ok(())
+}
+
+fn run_open(name: &str, kubeconfig_override: Option<PathBuf>, no_browser: bool) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    webui::open(&kubectl, service, name, no_browser, &kubeconfig)?;
+    Ok(())
+}
+
+fn run_status(name: &str, kubeconfig_override: Option<PathBuf>, json: bool) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let info = cluster::cluster_info(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    let service = cluster::service_from_definition(&info.definition).map_err(error::code(ExitCode::Kbcli))?;
+    let status = status::collect(&kubectl, service, &info, &kubeconfig);
+    if json {
+        output::print(&status)?;
+    } else {
+        status.print();
+    }
+    Ok(())
+}
+
+fn run_scale(
+    name: &str,
+    kubeconfig_override: Option<PathBuf>,
+    target: &scale::Target,
+    timeout: std::time::Duration,
+) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    scale::run(&kbcli, &kubectl, service, name, target, &kubeconfig, timeout)
+}
+
+fn run_logs(name: &str, kubeconfig_override: Option<PathBuf>, opts: &logs::LogOptions) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    cluster::ensure_exists(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    logs::stream(&kubectl, name, opts, &kubeconfig)?;
+    Ok(())
+}
+
+fn run_connect(name: &str, kubeconfig_override: Option<PathBuf>, admin: bool) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    connect::run(&kubectl, service, name, admin, &kubeconfig)?;
+    Ok(())
+}
+
+fn run_port_forward(name: &str, kubeconfig_override: Option<PathBuf>, local_port: Option<u16>) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    forward::run(&kubectl, service, name, local_port, &kubeconfig)?;
+    Ok(())
+}
+
+fn run_ui(kubeconfig_override: Option<PathBuf>) -> Result<(), Error> {
+    if !std::io::stdout().is_terminal() {
+        return Err(Error::new(ExitCode::Usage, "fdb ui needs a terminal"));
+    }
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let context = kubeconfig::current_context(&kubectl, &kubeconfig).unwrap_or_else(|_| "?".to_string());
+    ui::run(&kubectl, &kbcli, &context, &kubeconfig)?;
+    Ok(())
+}
+
+fn run_report(kubeconfig_override: Option<PathBuf>) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let clusters = cluster::list_cluster_infos(&kbcli, None, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    let context = kubeconfig::current_context(&kubectl, &kubeconfig).unwrap_or_else(|_| "?".to_string());
+    report::print(&kubectl, &context, &clusters, config::load_cost_rates().as_ref(), &kubeconfig)?;
+    Ok(())
+}
+
+fn run_benchmark(name: &str, kubeconfig_override: Option<PathBuf>, opts: benchmark::BenchOptions) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    benchmark::run(&kubectl, service, name, opts, &kubeconfig)?;
+    Ok(())
+}
+
+fn run_history(name: Option<&str>) -> Result<(), Error> {
+    let entries = history::load(name)?;
+    history::print(&entries);
+    Ok(())
+}
+
+fn run_diff(name: Option<&str>, kubeconfig_override: Option<PathBuf>) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, _) = tools::prepare()?;
+    if drift::print_diff(&kubectl, name, &kubeconfig)? {
+        println!("\nRun `fdb apply` to converge existing clusters.");
+    } else {
+        println!("No drift.");
+    }
+    Ok(())
+}
+
+fn run_apply(
+    name: Option<&str>,
+    kubeconfig_override: Option<PathBuf>,
+    yes: bool,
+    timeout: std::time::Duration,
+) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    drift::apply(&kbcli, &kubectl, name, &kubeconfig, yes, timeout)
+}
+
+fn run_protect(name: &str, kubeconfig_override: Option<PathBuf>, protected: bool) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, _) = tools::prepare()?;
+    if !protected {
+        cluster::confirm_production(&kubectl, name, "unprotect", &kubeconfig)?;
+    }
+    cluster::set_protected(&kubectl, name, protected, &kubeconfig)?;
+    if protected {
+        println!("Cluster \"{name}\" is protected from deletion.");
     } else {
-        println!("  User:     {user}");
-        if let Some(ref p) = password {
-            println!("  Password: {p}");
+        println!("Cluster \"{name}\" is no longer protected.");
+    }
+    Ok(())
+}
+
+fn run_credentials_temp(
+    name: &str,
+    kubeconfig_override: Option<PathBuf>,
+    ttl: std::time::Duration,
+    opts: &accounts::AddOptions,
+) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    accounts::add_temp_credentials(&kubectl, service, name, ttl, opts, &kubeconfig)
+        .map_err(error::code(ExitCode::Credentials))
+}
+
+fn run_creds_rotate(name: &str, kubeconfig_override: Option<PathBuf>, admin: bool) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    let account = if admin { "admin" } else { "app" };
+    cluster::confirm_production(&kubectl, name, &format!("rotate the {account} password of"), &kubeconfig)?;
+    let (user, _) = accounts::rotate_password(&kubectl, service, name, admin, &kubeconfig)
+        .map_err(error::code(ExitCode::Credentials))?;
+    println!("Rotated the password of \"{user}\" on \"{name}\"; clients using the old one must reconnect.");
+    println!();
+    accounts::connection_details(&kubectl, service, name, &kubeconfig, admin)?.print();
+    Ok(())
+}
+
+fn run_gc(name: Option<&str>, kubeconfig_override: Option<PathBuf>) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let names = match name {
+        Some(n) => vec![n.to_string()],
+        None => cluster::cluster_names(&kbcli, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?,
+    };
+    let mut revoked = 0;
+    for cluster_name in &names {
+        let service = match cluster::cluster_service_type(&kbcli, cluster_name, &kubeconfig) {
+            Ok(s) => s,
+            Err(_) => continue,
+        };
+        for user in accounts::gc_expired(&kubectl, service, cluster_name, &kubeconfig)? {
+            println!("Revoked expired user {user} on \"{cluster_name}\"");
+            revoked += 1;
         }
-        println!("  (Host/Port: enable NodePort or check kubeconfig)");
     }
+    if revoked == 0 {
+        println!("No expired temporary users.");
+    }
+    autostop::enforce(&kbcli, &kubectl, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    idle::sample(&kbcli, &kubectl, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    Ok(())
+}
+
+fn run_pause_idle(after: std::time::Duration, kubeconfig_override: Option<PathBuf>, yes: bool) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    idle::pause_idle(&kbcli, &kubectl, after, yes, &kubeconfig)
+}
+
+fn run_start(name: &str, kubeconfig_override: Option<PathBuf>, timeout: std::time::Duration) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    cluster::ensure_exists(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    let awake_until = autostop::wake(&kbcli, &kubectl, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    let mut progress = progress::Progress::new();
+    cluster::wait_until_running(&kbcli, &kubectl, name, &kubeconfig, timeout, &mut progress)?;
+    progress.finish();
+    match awake_until {
+        Some(until) => println!("Cluster \"{name}\" is running; auto-stop resumes {until}."),
+        None => println!("Cluster \"{name}\" is running."),
+    }
+    Ok(())
+}
+
+fn run_stop(name: &str, kubeconfig_override: Option<PathBuf>, timeout: std::time::Duration) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let info = cluster::cluster_info(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    if info.status == "Stopped" {
+        println!("Cluster \"{name}\" is already stopped.");
+        return Ok(());
+    }
+    cluster::confirm_production(&kubectl, name, "stop", &kubeconfig)?;
+    cluster::set_running(&kbcli, name, false, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    let mut progress = progress::Progress::new();
+    cluster::wait_until_stopped(&kbcli, name, &kubeconfig, timeout, &mut progress)?;
+    println!("Cluster \"{name}\" is stopped; its volumes are kept. Run `fdb start {name}` to bring it back.");
+    Ok(())
+}
 
+fn run_restart(name: &str, kubeconfig_override: Option<PathBuf>, timeout: std::time::Duration) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    cluster::ensure_exists(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    cluster::confirm_production(&kubectl, name, "restart", &kubeconfig)?;
+    let ops = cluster::restart(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    let mut progress = progress::Progress::new();
+    cluster::wait_for_ops(&kubectl, ops.as_slice(), &kubeconfig, timeout, &mut progress)?;
+    cluster::wait_until_running(&kbcli, &kubectl, name, &kubeconfig, timeout, &mut progress)?;
+    progress.finish();
+    println!("Cluster \"{name}\" restarted and is running.");
     Ok(())
 }
 
-fn run_delete(name: &str, kubeconfig_override: Option<PathBuf>, yes: bool) -> Result<(), String> {
+fn run_upgrade(
+    name: &str,
+    version: &str,
+    kubeconfig_override: Option<PathBuf>,
+    timeout: std::time::Duration,
+) -> Result<(), Error> {
+    let kubeconfig = load_kubeconfig(kubeconfig_override);
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    upgrade::run(&kbcli, &kubectl, service, name, version, &kubeconfig, timeout)
+}
+
+fn run_expose(
+    name: &str,
+    kubeconfig_override: Option<PathBuf>,
+    expose_type: Option<expose::ExposeType>,
+) -> Result<(), Error> {
     let kubeconfig = load_kubeconfig(kubeconfig_override);
-    tools::ensure_tools()?;
-    let kubectl = tools::resolve_kubectl()?;
-    let kbcli = tools::resolve_kbcli()?;
-    cluster::delete_cluster(&kbcli, &kubectl, name, &kubeconfig, yes)?;
-    println!("Cluster \"{name}\" deleted.");
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    let svc = expose::external_service_name(service, name);
+    let Some(expose_type) = expose_type else {
+        match expose::current_type(&kubectl, service, name, &kubeconfig)? {
+            Some(kind) => println!("\"{name}\" is exposed through {kind} Service {svc}."),
+            None => println!("\"{name}\" is not exposed; reach it with `fdb connect {name}` or `fdb expose {name} --type nodeport`."),
+        }
+        return Ok(());
+    };
+    match expose::set_exposure(&kubectl, service, name, expose_type, &kubeconfig)? {
+        expose::Exposure::NodePort { host, port } => {
+            let host = host.unwrap_or_else(|| "<node>".to_string());
+            println!("Exposed \"{name}\" on NodePort {host}:{port}.");
+        }
+        expose::Exposure::LoadBalancer { address: Some(address), port } => {
+            println!("Exposed \"{name}\" through a LoadBalancer at {address}:{port}.");
+        }
+        expose::Exposure::LoadBalancer { address: None, port } => {
+            println!("Requested a LoadBalancer for \"{name}\" (port {port}); no external address yet.");
+            println!("Watch for it with `kubectl get svc {svc} -n default -w`.");
+        }
+        expose::Exposure::ClusterIp { host, port } => {
+            println!("\"{name}\" is reachable inside the Kubernetes cluster at {host}:{port}.");
+        }
+        expose::Exposure::None => {
+            println!("Removed the external Service of \"{name}\"; reach it with `fdb connect {name}`.");
+        }
+    }
     Ok(())
 }
 
-fn run_list(kubeconfig_override: Option<PathBuf>) -> Result<(), String> {
+fn run_kubeconfig_export(
+    name: &str,
+    kubeconfig_override: Option<PathBuf>,
+    ttl: std::time::Duration,
+    out: Option<PathBuf>,
+) -> Result<(), Error> {
     let kubeconfig = load_kubeconfig(kubeconfig_override);
-    tools::ensure_tools()?;
-    let kbcli = tools::resolve_kbcli()?;
-    cluster::list_clusters(&kbcli, &kubeconfig)?;
+    let (kubectl, kbcli) = tools::prepare()?;
+    let service = cluster::cluster_service_type(&kbcli, name, &kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    let scoped = kubeconfig::export_scoped_kubeconfig(&kubectl, service, name, ttl, &kubeconfig)?;
+
+    match out {
+        Some(path) => {
+            std::fs::write(&path, &scoped).map_err(|e| format!("write {}: {e}", path.display()))?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+            }
+            eprintln!("Wrote scoped kubeconfig for \"{name}\" to {}", path.display());
+        }
+        None if redact::enabled() => {
+            return Err("refusing to print a kubeconfig token under --redact; use --out PATH".into());
+        }
+        None => print!("{scoped}"),
+    }
     Ok(())
 }
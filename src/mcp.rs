@@ -0,0 +1,241 @@
+//! Model Context Protocol server on stdin/stdout (`fdb mcp`), so coding agents can list,
+//! inspect, connect to, create and (when allowed) delete clusters.
+//!
+//! stdout carries only JSON-RPC messages. Commands that print progress (create, delete) run
+//! as a child `fdb` with their output captured; logs go to stderr.
+
+use crate::accounts;
+use crate::cluster;
+use crate::config;
+use crate::service::ServiceType;
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+/// Tools that are only listed when `[mcp] allow` names them.
+const DESTRUCTIVE: &[&str] = &["delete"];
+
+pub struct Server {
+    pub kubectl: PathBuf,
+    pub kbcli: PathBuf,
+    pub kubeconfig: PathBuf,
+    /// Destructive tools enabled in fdb.toml.
+    pub allow: Vec<String>,
+}
+
+/// Serve requests from stdin until it closes.
+pub fn run(server: &Server) -> Result<(), String> {
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    for line in stdin.lock().lines() {
+        let line = line.map_err(|e| format!("read stdin: {e}"))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => server.handle(&message),
+            Err(e) => Some(json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": { "code": -32700, "message": format!("parse error: {e}") },
+            })),
+        };
+        if let Some(response) = response {
+            writeln!(stdout, "{response}").map_err(|e| format!("write stdout: {e}"))?;
+            stdout.flush().map_err(|e| format!("write stdout: {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+fn schema(properties: Value, required: &[&str]) -> Value {
+    json!({ "type": "object", "properties": properties, "required": required })
+}
+
+fn name_schema() -> Value {
+    schema(json!({ "name": { "type": "string", "description": "Cluster name" } }), &["name"])
+}
+
+impl Server {
+    /// Response to one JSON-RPC message; None for notifications.
+    fn handle(&self, message: &Value) -> Option<Value> {
+        let id = message.get("id")?.clone();
+        let method = message["method"].as_str().unwrap_or_default();
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "fdb", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": self.tools() })),
+            "tools/call" => {
+                let name = message["params"]["name"].as_str().unwrap_or_default();
+                let args = &message["params"]["arguments"];
+                eprintln!("fdb mcp: {name} {args}");
+                Ok(match self.call(name, args) {
+                    Ok(text) => json!({ "content": [{ "type": "text", "text": text }] }),
+                    Err(e) => json!({ "content": [{ "type": "text", "text": e }], "isError": true }),
+                })
+            }
+            _ => Err(json!({ "code": -32601, "message": format!("method not found: {method}") })),
+        };
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+        })
+    }
+
+    fn allowed(&self, tool: &str) -> bool {
+        !DESTRUCTIVE.contains(&tool) || self.allow.iter().any(|a| a == tool)
+    }
+
+    fn tools(&self) -> Vec<Value> {
+        let tools = [
+            json!({
+                "name": "list",
+                "description": "List database clusters with engine and status.",
+                "inputSchema": schema(json!({}), &[]),
+            }),
+            json!({
+                "name": "info",
+                "description": "Engine, status and endpoint of a cluster (no password).",
+                "inputSchema": name_schema(),
+            }),
+            json!({
+                "name": "connect",
+                "description": "Connection string with credentials for a cluster, for use in app config or a client.",
+                "inputSchema": name_schema(),
+            }),
+            json!({
+                "name": "create",
+                "description": "Start creating a cluster; poll `list` until it is Running, then call `connect`.",
+                "inputSchema": schema(
+                    json!({
+                        "engine": { "type": "string", "enum": ServiceType::supported_names() },
+                        "name": { "type": "string", "description": "Cluster name; generated when omitted" },
+                        "replicas": { "type": "integer" },
+                        "storage": { "type": "string", "description": "e.g. 5Gi" },
+                        "cpu": { "type": "string", "description": "cores, e.g. 0.5" },
+                        "memory": { "type": "string", "description": "e.g. 1Gi" },
+                    }),
+                    &["engine"],
+                ),
+            }),
+            json!({
+                "name": "delete",
+                "description": "Delete a cluster and its data. Protected clusters and production contexts are refused.",
+                "inputSchema": name_schema(),
+            }),
+        ];
+        tools
+            .into_iter()
+            .filter(|t| self.allowed(t["name"].as_str().unwrap_or_default()))
+            .collect()
+    }
+
+    fn call(&self, tool: &str, args: &Value) -> Result<String, String> {
+        if !self.allowed(tool) {
+            return Err(format!("tool `{tool}` is disabled; enable it with `[mcp] allow = [\"{tool}\"]` in fdb.toml"));
+        }
+        let name = || {
+            args["name"]
+                .as_str()
+                .map(config::qualify)
+                .ok_or_else(|| "missing argument `name`".to_string())
+        };
+        match tool {
+            "list" => self.list(),
+            "info" => self.info(&name()?, false),
+            "connect" => self.info(&name()?, true),
+            "create" => self.create(args),
+            "delete" => self.delete(&name()?),
+            _ => Err(format!("unknown tool `{tool}`")),
+        }
+    }
+
+    fn list(&self) -> Result<String, String> {
+        let clusters = cluster::list_cluster_infos(&self.kbcli, None, &self.kubeconfig)?;
+        if clusters.is_empty() {
+            return Ok("No clusters.".to_string());
+        }
+        Ok(clusters
+            .iter()
+            .map(|c| format!("{} ({}) {}", c.name, c.definition, c.status))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    fn info(&self, name: &str, with_secrets: bool) -> Result<String, String> {
+        let service = cluster::cluster_service_type(&self.kbcli, name, &self.kubeconfig)?;
+        let details = accounts::connection_details(&self.kubectl, service, name, &self.kubeconfig, false)
+            .map_err(|e| e.message)?;
+        let mut lines = vec![format!("name: {name}"), format!("engine: {}", service.kbcli_name())];
+        for (key, value) in details.fields() {
+            if with_secrets || !matches!(key, "password" | "uri") {
+                lines.push(format!("{key}: {value}"));
+            }
+        }
+        Ok(lines.join("\n"))
+    }
+
+    /// Run this fdb binary with `args`; stdout and stderr are captured, stdin is closed.
+    fn fdb(&self, args: &[String]) -> std::io::Result<std::process::Output> {
+        Command::new(std::env::current_exe()?)
+            .args(args)
+            .arg("--kubeconfig")
+            .arg(&self.kubeconfig)
+            .stdin(Stdio::null())
+            .output()
+    }
+
+    fn create(&self, args: &Value) -> Result<String, String> {
+        let engine = args["engine"].as_str().ok_or("missing argument `engine`")?;
+        let mut cli = vec!["create".to_string(), engine.to_string()];
+        if let Some(name) = args["name"].as_str() {
+            crate::names::validate(&config::qualify(name))?;
+            cli.push(name.to_string());
+        }
+        for key in ["replicas", "storage", "cpu", "memory"] {
+            match &args[key] {
+                Value::String(v) => cli.extend([format!("--{key}"), v.clone()]),
+                Value::Number(n) => cli.extend([format!("--{key}"), n.to_string()]),
+                _ => {}
+            }
+        }
+        let started = format!("Creating {engine} cluster ({}); poll `list` until it is Running.", cli[2..].join(" "));
+        // The create prints the admin password and URI; keep them out of the host's logs.
+        cli.push("--redact".to_string());
+        let child = Server {
+            kubectl: self.kubectl.clone(),
+            kbcli: self.kbcli.clone(),
+            kubeconfig: self.kubeconfig.clone(),
+            allow: Vec::new(),
+        };
+        std::thread::spawn(move || match child.fdb(&cli) {
+            Ok(out) => eprintln!(
+                "fdb mcp: {} exited with {}\n{}",
+                cli.join(" "),
+                out.status,
+                String::from_utf8_lossy(&out.stderr)
+            ),
+            Err(e) => eprintln!("fdb mcp: {}: {e}", cli.join(" ")),
+        });
+        Ok(started)
+    }
+
+    fn delete(&self, name: &str) -> Result<String, String> {
+        let context = crate::kubeconfig::current_context(&self.kubectl, &self.kubeconfig)?;
+        if config::is_production_context(&context) {
+            return Err(format!("context \"{context}\" is marked production; delete with the fdb CLI"));
+        }
+        let out = self
+            .fdb(&["delete".to_string(), name.to_string(), "--yes".to_string()])
+            .map_err(|e| format!("run fdb delete: {e}"))?;
+        let text = format!("{}{}", String::from_utf8_lossy(&out.stdout), String::from_utf8_lossy(&out.stderr));
+        if out.status.success() { Ok(text.trim().to_string()) } else { Err(text.trim().to_string()) }
+    }
+}
+
@@ -0,0 +1,266 @@
+//! Prometheus metrics for new clusters (`[metrics]` in fdb.toml): turn on the KubeBlocks
+//! exporter sidecar where the engine needs one, then make the pods discoverable with either a
+//! headless Service carrying `prometheus.io/*` annotations or a PodMonitor. `[alerts]` adds a
+//! PrometheusRule for the cluster.
+
+use crate::cluster;
+use crate::config::{AlertsConfig, MetricsConfig, MetricsMode};
+use crate::exec::CommandExt;
+use crate::service::ServiceType;
+use std::path::Path;
+use std::process::Command;
+
+const NAMESPACE: &str = "default";
+
+/// Where the engine serves metrics: (component, container port, path).
+fn endpoint(service: ServiceType) -> (&'static str, u16, &'static str) {
+    service.spec().metrics_endpoint()
+}
+
+/// Name of the Service or PodMonitor fdb creates for the cluster.
+pub fn resource_name(cluster_name: &str) -> String {
+    format!("{cluster_name}-metrics")
+}
+
+/// Ask KubeBlocks to add the exporter sidecar (PostgreSQL, Redis, MongoDB, Elasticsearch,
+/// OpenSearch, MariaDB, NATS, Memcached); other engines export metrics themselves.
+pub fn enable_exporter(kbcli: &Path, service: ServiceType, name: &str, kubeconfig: &Path) -> Result<(), String> {
+    if !service.spec().exporter_sidecar() {
+        return Ok(());
+    }
+    Command::new(kbcli)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["cluster", "update", name, "-n", NAMESPACE, "--disable-exporter=false", "--auto-approve"])
+        .output_checked()?;
+    Ok(())
+}
+
+fn manifest(service: ServiceType, cluster_name: &str, metrics: &MetricsConfig) -> String {
+    let (component, port, path) = endpoint(service);
+    let name = resource_name(cluster_name);
+    let engine = service.kbcli_name();
+    match metrics.mode {
+        MetricsMode::Annotations => format!(
+            r#"apiVersion: v1
+kind: Service
+metadata:
+  name: {name}
+  namespace: {NAMESPACE}
+  labels:
+    app.kubernetes.io/managed-by: fdb
+    fdb.io/cluster: "{cluster_name}"
+    fdb.io/engine: {engine}
+  annotations:
+    prometheus.io/scrape: "true"
+    prometheus.io/port: "{port}"
+    prometheus.io/path: {path}
+spec:
+  clusterIP: None
+  selector:
+    app.kubernetes.io/instance: "{cluster_name}"
+    apps.kubeblocks.io/component-name: {component}
+  ports:
+  - name: metrics
+    port: {port}
+    targetPort: {port}
+"#
+        ),
+        MetricsMode::PodMonitor => {
+            let labels: String = metrics
+                .labels
+                .iter()
+                .map(|(k, v)| format!("\n    {k}: \"{v}\""))
+                .collect();
+            format!(
+                r#"apiVersion: monitoring.coreos.com/v1
+kind: PodMonitor
+metadata:
+  name: {name}
+  namespace: {NAMESPACE}
+  labels:
+    app.kubernetes.io/managed-by: fdb{labels}
+spec:
+  selector:
+    matchLabels:
+      app.kubernetes.io/instance: "{cluster_name}"
+      apps.kubeblocks.io/component-name: {component}
+  podMetricsEndpoints:
+  - targetPort: {port}
+    path: {path}
+    interval: {}
+    relabelings:
+    - targetLabel: fdb_cluster
+      replacement: "{cluster_name}"
+    - targetLabel: fdb_engine
+      replacement: {engine}
+"#,
+                metrics.interval
+            )
+        }
+    }
+}
+
+/// Create the scrape Service or PodMonitor. Returns a line for the create summary.
+pub fn wire(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    metrics: &MetricsConfig,
+    kubeconfig: &Path,
+) -> Result<String, String> {
+    cluster::apply_manifest(kubectl, &manifest(service, cluster_name, metrics), kubeconfig).map_err(|e| {
+        if metrics.mode == MetricsMode::PodMonitor {
+            format!("{e}; is prometheus-operator installed (PodMonitor CRD)?")
+        } else {
+            e
+        }
+    })?;
+    let (_, port, path) = endpoint(service);
+    Ok(match metrics.mode {
+        MetricsMode::Annotations => format!("Service {} (port {port}{path})", resource_name(cluster_name)),
+        MetricsMode::PodMonitor => format!("PodMonitor {} (port {port}{path})", resource_name(cluster_name)),
+    })
+}
+
+fn rules_name(cluster_name: &str) -> String {
+    format!("{cluster_name}-alerts")
+}
+
+/// (alert, PromQL, for, summary) for the cluster: no ready pod, a volume over 80% full and,
+/// for engines whose exporter reports it, replication lag over 30s.
+fn alert_rules(service: ServiceType, cluster_name: &str) -> Vec<(&'static str, String, &'static str, String)> {
+    let (component, _, _) = endpoint(service);
+    let pods = format!("{cluster_name}-{component}-[0-9]+");
+    let pvcs = format!(r#"namespace="{NAMESPACE}", persistentvolumeclaim=~"data-{pods}""#);
+    let mut rules = vec![
+        (
+            "FdbClusterDown",
+            format!(r#"max(kube_pod_status_ready{{namespace="{NAMESPACE}", condition="true", pod=~"{pods}"}}) < 1"#),
+            "5m",
+            format!("No ready {} pod in cluster {cluster_name}", service.kbcli_name()),
+        ),
+        (
+            "FdbDiskAlmostFull",
+            format!("max(kubelet_volume_stats_used_bytes{{{pvcs}}} / kubelet_volume_stats_capacity_bytes{{{pvcs}}}) > 0.8"),
+            "10m",
+            format!("A data volume of cluster {cluster_name} is more than 80% full"),
+        ),
+    ];
+    let lag_metric = match service {
+        ServiceType::PostgreSQL => Some("pg_replication_lag_seconds"),
+        ServiceType::Redis => Some("redis_connected_slave_lag_seconds"),
+        ServiceType::MongoDB => Some("mongodb_mongod_replset_member_replication_lag"),
+        _ => None,
+    };
+    if let Some(metric) = lag_metric {
+        rules.push((
+            "FdbReplicationLag",
+            format!(r#"max({metric}{{pod=~"{pods}"}}) > 30"#),
+            "5m",
+            format!("Replicas of cluster {cluster_name} are more than 30s behind"),
+        ));
+    }
+    rules
+}
+
+fn rules_manifest(service: ServiceType, cluster_name: &str, alerts: &AlertsConfig) -> String {
+    let labels: String = alerts.labels.iter().map(|(k, v)| format!("\n    {k}: \"{v}\"")).collect();
+    let rules: String = alert_rules(service, cluster_name)
+        .into_iter()
+        .map(|(alert, expr, wait, summary)| {
+            format!(
+                r#"
+    - alert: {alert}
+      expr: |-
+        {expr}
+      for: {wait}
+      labels:
+        severity: "{}"
+        fdb_cluster: "{cluster_name}"
+      annotations:
+        summary: "{summary}""#,
+                alerts.severity
+            )
+        })
+        .collect();
+    format!(
+        r#"apiVersion: monitoring.coreos.com/v1
+kind: PrometheusRule
+metadata:
+  name: {}
+  namespace: {NAMESPACE}
+  labels:
+    app.kubernetes.io/managed-by: fdb{labels}
+spec:
+  groups:
+  - name: fdb-{cluster_name}
+    rules:{rules}
+"#,
+        rules_name(cluster_name)
+    )
+}
+
+/// Create the cluster's PrometheusRule. Returns a line for the create summary.
+pub fn install_alerts(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    alerts: &AlertsConfig,
+    kubeconfig: &Path,
+) -> Result<String, String> {
+    cluster::apply_manifest(kubectl, &rules_manifest(service, cluster_name, alerts), kubeconfig)
+        .map_err(|e| format!("{e}; is prometheus-operator installed (PrometheusRule CRD)?"))?;
+    let names: Vec<&str> = alert_rules(service, cluster_name).iter().map(|r| r.0).collect();
+    Ok(format!("PrometheusRule {} ({})", rules_name(cluster_name), names.join(", ")))
+}
+
+/// Remove what [`wire`] and [`install_alerts`] created; missing resources (or CRDs) are fine.
+pub fn unwire(kubectl: &Path, cluster_name: &str, kubeconfig: &Path) {
+    let resources = [
+        ("svc", resource_name(cluster_name)),
+        ("podmonitor", resource_name(cluster_name)),
+        ("prometheusrule", rules_name(cluster_name)),
+    ];
+    for (kind, name) in resources {
+        let _ = Command::new(kubectl)
+            .arg("--kubeconfig")
+            .arg(kubeconfig)
+            .args(["delete", kind, &name, "-n", NAMESPACE, "--ignore-not-found"])
+            .output_logged();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pod_monitor_selects_cluster_pods_with_configured_labels() {
+        let metrics = MetricsConfig {
+            mode: MetricsMode::PodMonitor,
+            labels: [("release".to_string(), "prometheus".to_string())].into(),
+            interval: "15s".to_string(),
+        };
+        let yaml = manifest(ServiceType::PostgreSQL, "orders", &metrics);
+        assert!(yaml.contains("kind: PodMonitor"));
+        assert!(yaml.contains("    release: \"prometheus\"\n"));
+        assert!(yaml.contains("app.kubernetes.io/instance: \"orders\""));
+        assert!(yaml.contains("targetPort: 9187"));
+        assert!(yaml.contains("interval: 15s"));
+    }
+
+    #[test]
+    fn alert_rules_are_scoped_to_cluster_pods_and_volumes() {
+        let alerts = AlertsConfig {
+            labels: Default::default(),
+            severity: "page".to_string(),
+        };
+        let yaml = rules_manifest(ServiceType::Redis, "cache", &alerts);
+        assert!(yaml.contains(r#"pod=~"cache-redis-[0-9]+""#), "{yaml}");
+        assert!(yaml.contains(r#"persistentvolumeclaim=~"data-cache-redis-[0-9]+""#), "{yaml}");
+        assert!(yaml.contains("- alert: FdbReplicationLag"));
+        assert!(yaml.contains("severity: \"page\""));
+        assert!(!rules_manifest(ServiceType::Qdrant, "vec", &alerts).contains("FdbReplicationLag"));
+    }
+}
@@ -0,0 +1,91 @@
+//! Cluster names: validation against Kubernetes DNS-label rules and friendly generated names.
+
+use std::io::Read;
+
+/// Longest accepted name. KubeBlocks derives pod and service names from it
+/// (`<name>-postgresql-headless`, `<name>-rabbitmq-0`), which must stay within 63 characters.
+const MAX_LEN: usize = 40;
+
+const ADJECTIVES: [&str; 32] = [
+    "amber", "bold", "brave", "bright", "calm", "clever", "cosmic", "crisp", "eager", "fancy", "gentle", "happy",
+    "jolly", "keen", "lively", "lucky", "mellow", "merry", "nimble", "proud", "quick", "quiet", "rapid", "shiny",
+    "silent", "snappy", "steady", "sunny", "swift", "tidy", "witty", "zesty",
+];
+
+const NOUNS: [&str; 32] = [
+    "badger", "beaver", "bison", "comet", "crane", "falcon", "ferret", "finch", "fox", "gecko", "heron", "koala",
+    "lemur", "lynx", "marten", "meadow", "moose", "otter", "panda", "pebble", "puffin", "quokka", "raven", "river",
+    "robin", "salmon", "sparrow", "tiger", "tundra", "walrus", "willow", "yak",
+];
+
+/// Check a user-supplied cluster name up front, so kbcli doesn't reject it halfway through.
+pub fn validate(name: &str) -> Result<(), String> {
+    let problem = if name.is_empty() {
+        Some("must not be empty".to_string())
+    } else if name.len() > MAX_LEN {
+        Some(format!("is {} characters long (at most {MAX_LEN})", name.len()))
+    } else if let Some(c) = name.chars().find(|c| !matches!(c, 'a'..='z' | '0'..='9' | '-')) {
+        Some(format!("contains '{c}' (use lowercase letters, digits and '-')"))
+    } else if !name.starts_with(|c: char| c.is_ascii_lowercase()) {
+        Some("must start with a lowercase letter".to_string())
+    } else if name.ends_with('-') {
+        Some("must not end with '-'".to_string())
+    } else {
+        None
+    };
+    match problem {
+        Some(p) => Err(format!("invalid cluster name \"{name}\": {p}")),
+        None => Ok(()),
+    }
+}
+
+/// Random adjective-noun name (`swift-otter`) not in `taken`; falls back to a numeric
+/// suffix when many tries collide.
+pub fn generate(taken: &[String]) -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut f| f.read_exact(&mut bytes))
+        .map_err(|e| format!("read /dev/urandom: {e}"))?;
+    let candidates = bytes.chunks(2).map(|pair| {
+        format!(
+            "{}-{}",
+            ADJECTIVES[pair[0] as usize % ADJECTIVES.len()],
+            NOUNS[pair[1] as usize % NOUNS.len()]
+        )
+    });
+    let mut last = String::new();
+    for name in candidates {
+        if !taken.contains(&name) {
+            return Ok(name);
+        }
+        last = name;
+    }
+    (2..)
+        .map(|n| format!("{last}-{n}"))
+        .find(|name| !taken.contains(name))
+        .ok_or_else(|| "could not generate a cluster name".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_dns_labels_and_generates_unique_names() {
+        assert!(validate("mydb-pg").is_ok());
+        assert!(validate("MyDB").unwrap_err().contains("'M'"));
+        assert!(validate("1db").unwrap_err().contains("start with a lowercase letter"));
+        assert!(validate("db-").unwrap_err().contains("end with"));
+        assert!(validate("my_db").unwrap_err().contains("'_'"));
+        assert!(validate(&"a".repeat(41)).unwrap_err().contains("at most 40"));
+
+        let name = generate(&[]).unwrap();
+        assert!(validate(&name).is_ok());
+        let all: Vec<String> = ADJECTIVES
+            .iter()
+            .flat_map(|a| NOUNS.iter().map(move |n| format!("{a}-{n}")))
+            .collect();
+        let fallback = generate(&all).unwrap();
+        assert!(fallback.ends_with("-2") && validate(&fallback).is_ok());
+    }
+}
@@ -0,0 +1,358 @@
+//! Find a cluster's pods and run commands inside them via kubectl.
+
+use crate::exec::{self, CommandExt};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+const NAMESPACE: &str = "default";
+
+/// Names of the pods of one cluster component (KubeBlocks instance/component labels).
+pub fn component_pods(
+    kubectl: &Path,
+    cluster_name: &str,
+    component: &str,
+    kubeconfig: &Path,
+) -> Result<Vec<String>, String> {
+    let selector = format!(
+        "app.kubernetes.io/instance={cluster_name},apps.kubeblocks.io/component-name={component}"
+    );
+    let output = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args([
+            "get",
+            "pods",
+            "-n",
+            NAMESPACE,
+            "-l",
+            &selector,
+            "-o",
+            "jsonpath={.items[*].metadata.name}",
+        ])
+        .output_checked()?;
+    let pods: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    if pods.is_empty() {
+        return Err(format!("no {component} pods found for cluster \"{cluster_name}\""));
+    }
+    Ok(pods)
+}
+
+/// Name of the component's primary pod (`kubeblocks.io/role=primary`).
+pub fn primary_pod(
+    kubectl: &Path,
+    cluster_name: &str,
+    component: &str,
+    kubeconfig: &Path,
+) -> Result<String, String> {
+    let selector = format!(
+        "app.kubernetes.io/instance={cluster_name},apps.kubeblocks.io/component-name={component},kubeblocks.io/role=primary"
+    );
+    let output = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args([
+            "get",
+            "pods",
+            "-n",
+            NAMESPACE,
+            "-l",
+            &selector,
+            "-o",
+            "jsonpath={.items[0].metadata.name}",
+        ])
+        .output_logged()
+        .map_err(|e| format!("kubectl get pods: {e}"))?;
+    let pod = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || pod.is_empty() {
+        return Err(format!("no primary {component} pod found for cluster \"{cluster_name}\""));
+    }
+    Ok(pod)
+}
+
+/// `sh -c` script that runs a MongoDB tool (mongosh, mongodump, mongorestore) as the root user.
+/// The credentials come from the container's env, which KubeBlocks fills from the account
+/// secret, so the password never appears on the kubectl command line. Arguments after the
+/// script (`sh -c <script> sh <args>...`) are passed through to the tool.
+pub fn mongodb_admin_script(tool: &str) -> String {
+    format!(
+        "exec {tool} --authenticationDatabase admin -u \"$MONGODB_ROOT_USER\" -p \"$MONGODB_ROOT_PASSWORD\" \"$@\""
+    )
+}
+
+/// `sh -c` script that runs redis-cli as the default user with the password from the container's
+/// env (`REDIS_DEFAULT_PASSWORD`, filled from the account secret) passed as `REDISCLI_AUTH`, so
+/// it never appears on a command line. Arguments after the script are passed through.
+pub const REDIS_CLI_SCRIPT: &str = r#"[ -n "$REDIS_DEFAULT_PASSWORD" ] && export REDISCLI_AUTH="$REDIS_DEFAULT_PASSWORD"
+exec redis-cli --no-auth-warning "$@""#;
+
+/// Run a command in a pod's container and return its stdout.
+pub fn exec(
+    kubectl: &Path,
+    pod: &str,
+    container: &str,
+    command: &[&str],
+    kubeconfig: &Path,
+) -> Result<String, String> {
+    let output = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["exec", pod, "-n", NAMESPACE, "-c", container, "--"])
+        .args(command)
+        .output_checked()?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Run a command in a pod's container with `input` on its stdin and return its stdout. For
+/// input carrying secrets (SQL that sets passwords): on kubectl's command line any local user
+/// could read them, and the API server would log them.
+pub fn exec_with_input(
+    kubectl: &Path,
+    pod: &str,
+    container: &str,
+    command: &[&str],
+    input: &str,
+    kubeconfig: &Path,
+) -> Result<String, String> {
+    let mut cmd = Command::new(kubectl);
+    cmd.arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["exec", "-i", pod, "-n", NAMESPACE, "-c", container, "--"])
+        .args(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = cmd.spawn_logged().map_err(|e| format!("kubectl exec {pod}: {e}"))?;
+    exec::echo_stdin(input);
+    if let Some(mut stdin) = child.stdin.take()
+        && let Err(e) = stdin.write_all(input.as_bytes())
+        && e.kind() != std::io::ErrorKind::BrokenPipe
+    {
+        return Err(format!("write to {pod}: {e}"));
+    }
+    let output = child.wait_with_output().map_err(|e| format!("kubectl exec {pod}: {e}"))?;
+    if !output.status.success() {
+        return Err(exec::failure(&cmd, &output));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Run a command in a pod's container with its stdout going to `out` (a file for dumps), so
+/// large output is streamed instead of held in memory.
+pub fn exec_to(
+    kubectl: &Path,
+    pod: &str,
+    container: &str,
+    command: &[&str],
+    out: Stdio,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["exec", pod, "-n", NAMESPACE, "-c", container, "--"])
+        .args(command)
+        .stdout(out)
+        .output_checked()?;
+    Ok(())
+}
+
+/// Start a command in a pod's container with stdout and stderr piped, e.g. the dump side of
+/// `fdb copy`. The caller reads stdout and then checks the exit status.
+pub fn spawn_exec(
+    kubectl: &Path,
+    pod: &str,
+    container: &str,
+    command: &[&str],
+    kubeconfig: &Path,
+) -> Result<(Command, std::process::Child), String> {
+    let mut cmd = Command::new(kubectl);
+    cmd.arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["exec", pod, "-n", NAMESPACE, "-c", container, "--"])
+        .args(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let child = cmd.spawn_logged().map_err(|e| format!("kubectl exec {pod}: {e}"))?;
+    Ok((cmd, child))
+}
+
+/// Run a command in a pod's container with `input` streamed to its stdin; `progress` is called
+/// with the number of bytes sent so far. stdout is discarded, stderr is kept for the error.
+pub fn exec_from(
+    kubectl: &Path,
+    pod: &str,
+    container: &str,
+    command: &[&str],
+    mut input: impl Read,
+    mut progress: impl FnMut(u64),
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    let mut cmd = Command::new(kubectl);
+    cmd.arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["exec", "-i", pod, "-n", NAMESPACE, "-c", container, "--"])
+        .args(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    let mut child = cmd.spawn_logged().map_err(|e| format!("kubectl exec {pod}: {e}"))?;
+    // Drain stderr concurrently so a chatty command can't block on a full pipe.
+    let mut stderr = child.stderr.take().ok_or("kubectl exec: no stderr")?;
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stderr.read_to_end(&mut buf);
+        buf
+    });
+    let mut stdin = child.stdin.take().ok_or("kubectl exec: no stdin")?;
+    let mut buf = [0u8; 65536];
+    let mut sent = 0u64;
+    let written = loop {
+        let n = match input.read(&mut buf) {
+            Ok(0) => break Ok(()),
+            Ok(n) => n,
+            Err(e) => break Err(format!("read input: {e}")),
+        };
+        if let Err(e) = stdin.write_all(&buf[..n]) {
+            break Err(format!("write to {pod}: {e}"));
+        }
+        sent += n as u64;
+        progress(sent);
+    };
+    drop(stdin);
+    let status = child.wait().map_err(|e| format!("kubectl exec {pod}: {e}"))?;
+    let output = std::process::Output {
+        status,
+        stdout: Vec::new(),
+        stderr: stderr_reader.join().unwrap_or_default(),
+    };
+    if !output.status.success() {
+        return Err(exec::failure(&cmd, &output));
+    }
+    written
+}
+
+/// Scheduling/readiness of one pod, for progress output while a cluster starts.
+#[derive(Debug, Clone)]
+pub struct PodState {
+    pub name: String,
+    /// What kubectl would show: Ready, a container's waiting reason (ContainerCreating,
+    /// CrashLoopBackOff, ...) or the pod phase (Pending, Running, ...).
+    pub status: String,
+    /// `PodScheduled` condition is True.
+    pub scheduled: bool,
+    /// `Ready` condition is True.
+    pub ready: bool,
+}
+
+fn condition_true(pod: &serde_json::Value, kind: &str) -> bool {
+    pod.pointer("/status/conditions")
+        .and_then(|c| c.as_array())
+        .into_iter()
+        .flatten()
+        .any(|c| c["type"] == kind && c["status"] == "True")
+}
+
+fn pod_state(pod: &serde_json::Value) -> PodState {
+    let ready = condition_true(pod, "Ready");
+    let waiting = pod
+        .pointer("/status/containerStatuses")
+        .and_then(|c| c.as_array())
+        .into_iter()
+        .flatten()
+        .find_map(|c| c.pointer("/state/waiting/reason").and_then(|r| r.as_str()));
+    let status = if ready {
+        "Ready"
+    } else {
+        waiting.or(pod["status"]["phase"].as_str()).unwrap_or("Unknown")
+    };
+    PodState {
+        name: pod["metadata"]["name"].as_str().unwrap_or_default().to_string(),
+        status: status.to_string(),
+        scheduled: condition_true(pod, "PodScheduled"),
+        ready,
+    }
+}
+
+/// State of every pod of the cluster (`app.kubernetes.io/instance` label).
+pub fn cluster_pod_states(kubectl: &Path, cluster_name: &str, kubeconfig: &Path) -> Result<Vec<PodState>, String> {
+    let output = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args([
+            "get",
+            "pods",
+            "-n",
+            NAMESPACE,
+            "-l",
+            &format!("app.kubernetes.io/instance={cluster_name}"),
+            "-o",
+            "json",
+        ])
+        .output_checked()?;
+    let list: serde_json::Value =
+        serde_json::from_slice(&output.stdout).map_err(|e| format!("parse pods of \"{cluster_name}\": {e}"))?;
+    Ok(list["items"].as_array().into_iter().flatten().map(pod_state).collect())
+}
+
+/// Message of the pod's most recent event, e.g. "FailedScheduling: 0/3 nodes are available:
+/// 3 Insufficient memory."; None when there are no events.
+pub fn latest_event(kubectl: &Path, pod: &str, kubeconfig: &Path) -> Result<Option<String>, String> {
+    let output = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args([
+            "get",
+            "events",
+            "-n",
+            NAMESPACE,
+            "--field-selector",
+            &format!("involvedObject.kind=Pod,involvedObject.name={pod}"),
+            "-o",
+            "json",
+        ])
+        .output_checked()?;
+    let list: serde_json::Value =
+        serde_json::from_slice(&output.stdout).map_err(|e| format!("parse events of {pod}: {e}"))?;
+    let timestamp = |e: &serde_json::Value| {
+        ["lastTimestamp", "eventTime", "firstTimestamp"]
+            .iter()
+            .find_map(|k| e[*k].as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    Ok(list["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .max_by_key(|e| timestamp(e))
+        .map(|e| {
+            format!(
+                "{}: {}",
+                e["reason"].as_str().unwrap_or_default(),
+                e["message"].as_str().unwrap_or_default().trim()
+            )
+        }))
+}
+
+/// One line about the pods of a starting cluster: "1/3 ready; pg-postgresql-1 ContainerCreating;
+/// pg-postgresql-2 Pending (FailedScheduling: 0/3 nodes are available: ...)". The latest event is
+/// looked up for pods that are not scheduled yet.
+pub fn describe_states(kubectl: &Path, pods: &[PodState], kubeconfig: &Path) -> String {
+    let ready = pods.iter().filter(|p| p.ready).count();
+    let mut parts = vec![format!("{ready}/{} ready", pods.len())];
+    for pod in pods.iter().filter(|p| !p.ready) {
+        let mut part = format!("{} {}", pod.name, pod.status);
+        if !pod.scheduled
+            && let Ok(Some(event)) = latest_event(kubectl, &pod.name, kubeconfig)
+        {
+            part.push_str(&format!(" ({event})"));
+        }
+        parts.push(part);
+    }
+    parts.join("; ")
+}
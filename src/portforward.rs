@@ -1,62 +1,92 @@
-//! Background kubectl port-forward to expose PostgreSQL locally.
+//! Background kubectl port-forward to reach a cluster service locally.
 
+use crate::exec::CommandExt;
+use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
 use std::process::{Child, Command, Stdio};
 
-const REMOTE_PORT: u16 = 5432;
+const NAMESPACE: &str = "default";
 
-/// Start `kubectl port-forward svc/<name>-postgresql :5432` in background.
-/// Returns (child process, local port). Caller must not kill the child so port-forward stays alive.
+/// A running `kubectl port-forward`; the forward is stopped when this is dropped.
+pub struct PortForward {
+    child: Child,
+    pub local_port: u16,
+}
+
+impl PortForward {
+    /// Block until kubectl exits (Ctrl-C, or the pod going away).
+    pub fn wait(&mut self) -> Result<(), String> {
+        self.child.wait().map(|_| ()).map_err(|e| format!("kubectl port-forward: {e}"))
+    }
+}
+
+impl Drop for PortForward {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Start `kubectl port-forward <target> [local]:<remote_port>` in background, e.g. target `svc/mydb-postgresql`.
+/// With `local_port` None, kubectl picks a free port. Returns once the forward is listening.
 pub fn start_port_forward(
     kubectl: &Path,
-    cluster_name: &str,
+    target: &str,
+    remote_port: u16,
+    local_port: Option<u16>,
     kubeconfig: &Path,
-) -> Result<(Child, u16), String> {
-    let svc = format!("{cluster_name}-postgresql");
-
+) -> Result<PortForward, String> {
+    let local = local_port.map(|p| p.to_string()).unwrap_or_default();
     let mut child = Command::new(kubectl)
         .args([
             "port-forward",
-            &format!("svc/{svc}"),
-            &format!(":{REMOTE_PORT}"),
+            target,
+            &format!("{local}:{remote_port}"),
+            "-n",
+            NAMESPACE,
         ])
         .arg("--kubeconfig")
         .arg(kubeconfig)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
-        .spawn()
+        .spawn_logged()
         .map_err(|e| format!("kubectl port-forward failed: {e}"))?;
 
-    // kubectl prints "Forwarding from 127.0.0.1:XXXXX -> 5432" to stderr
-    let stderr = child
-        .stderr
+    // kubectl prints "Forwarding from 127.0.0.1:XXXXX -> 5432" to stdout
+    let stdout = child
+        .stdout
         .take()
-        .ok_or("port-forward stderr not captured")?;
-
-    use std::io::Read;
-    let mut buf = [0u8; 256];
-    let mut port_str = String::new();
-    let mut reader = std::io::BufReader::new(stderr);
-    let mut total = 0;
-    for _ in 0..50 {
-        std::thread::sleep(std::time::Duration::from_millis(50));
-        let n = reader.read(&mut buf).map_err(|e| e.to_string())?;
+        .ok_or("port-forward stdout not captured")?;
+    let mut reader = BufReader::new(stdout);
+    let mut line = String::new();
+    for _ in 0..10 {
+        line.clear();
+        let n = reader.read_line(&mut line).map_err(|e| e.to_string())?;
         if n == 0 {
-            continue;
-        }
-        total += n;
-        let s = String::from_utf8_lossy(&buf[..n]);
-        port_str.push_str(&s);
-        if let Some(port) = parse_forwarding_port(&port_str) {
-            return Ok((child, port));
-        }
-        if total > 512 {
             break;
         }
+        if let Some(port) = parse_forwarding_port(&line) {
+            // Keep draining so kubectl never blocks on a full pipe ("Handling connection for ...").
+            std::thread::spawn(move || {
+                let _ = std::io::copy(&mut reader, &mut std::io::sink());
+            });
+            return Ok(PortForward {
+                child,
+                local_port: port,
+            });
+        }
     }
 
     let _ = child.kill();
-    Err("could not determine local port from kubectl port-forward output".to_string())
+    let mut stderr = String::new();
+    if let Some(mut e) = child.stderr.take() {
+        let _ = e.read_to_string(&mut stderr);
+    }
+    let _ = child.wait();
+    Err(format!(
+        "kubectl port-forward {target} failed: {}",
+        stderr.trim()
+    ))
 }
 
 fn parse_forwarding_port(output: &str) -> Option<u16> {
@@ -0,0 +1,116 @@
+//! Named phases with a spinner and elapsed time each, plus a summary of where the time went.
+//! With `-o json|yaml` the phases are only timed, so stdout stays parseable.
+
+use crate::output;
+use nanospinner::{Spinner, SpinnerHandle};
+use std::time::{Duration, Instant};
+
+struct Phase {
+    key: &'static str,
+    message: String,
+    started: Instant,
+    spinner: Option<SpinnerHandle>,
+}
+
+/// Progress of a multi-step command (`fdb create`). One phase runs at a time; starting the
+/// next one completes the current one.
+pub struct Progress {
+    started: Instant,
+    done: Vec<(&'static str, Duration)>,
+    current: Option<Phase>,
+}
+
+/// "850ms", "12.3s", "2m05s".
+pub fn format_elapsed(d: Duration) -> String {
+    let ms = d.as_millis();
+    if ms < 1000 {
+        format!("{ms}ms")
+    } else if ms < 60_000 {
+        format!("{:.1}s", d.as_secs_f64())
+    } else {
+        format!("{}m{:02}s", d.as_secs() / 60, d.as_secs() % 60)
+    }
+}
+
+impl Progress {
+    pub fn new() -> Self {
+        Progress {
+            started: Instant::now(),
+            done: Vec::new(),
+            current: None,
+        }
+    }
+
+    /// Complete the current phase (if any) and start `key` with a spinner showing `message`.
+    pub fn begin(&mut self, key: &'static str, message: &str) {
+        self.finish();
+        self.current = Some(Phase {
+            key,
+            message: message.to_string(),
+            started: Instant::now(),
+            spinner: (!output::structured()).then(|| Spinner::new(&format!("{message}...")).start()),
+        });
+    }
+
+    /// Show extra detail next to the current phase's message.
+    pub fn update(&self, detail: &str) {
+        if let Some(ref phase) = self.current
+            && let Some(ref spinner) = phase.spinner
+        {
+            let elapsed = format_elapsed(phase.started.elapsed());
+            spinner.update(&format!("{}... {detail} [{elapsed}]", phase.message));
+        }
+    }
+
+    /// Mark the current phase successful.
+    pub fn finish(&mut self) {
+        if let Some(phase) = self.current.take() {
+            let elapsed = phase.started.elapsed();
+            if let Some(spinner) = phase.spinner {
+                spinner.success_with(&format!("{} ({})", phase.message, format_elapsed(elapsed)));
+            }
+            self.done.push((phase.key, elapsed));
+        }
+    }
+
+    /// Mark the current phase failed with a short reason.
+    pub fn fail(&mut self, reason: &str) {
+        if let Some(phase) = self.current.take() {
+            let elapsed = phase.started.elapsed();
+            if let Some(spinner) = phase.spinner {
+                spinner.fail_with(&format!("{}: {reason} ({})", phase.message, format_elapsed(elapsed)));
+            }
+            self.done.push((phase.key, elapsed));
+        }
+    }
+
+    /// Time a step that prints its own output (no spinner), e.g. tool downloads.
+    pub fn quiet<T>(&mut self, key: &'static str, f: impl FnOnce() -> T) -> T {
+        self.finish();
+        let started = Instant::now();
+        let result = f();
+        self.done.push((key, started.elapsed()));
+        result
+    }
+
+    /// Print each phase's share of the total.
+    pub fn print_summary(&mut self) {
+        self.finish();
+        if output::structured() {
+            return;
+        }
+        let total = self.started.elapsed();
+        println!("Time spent ({} total):", format_elapsed(total));
+        for (key, elapsed) in &self.done {
+            let share = elapsed.as_secs_f64() / total.as_secs_f64().max(f64::EPSILON) * 100.0;
+            println!("  {key:<22} {:>8}  {share:>3.0}%", format_elapsed(*elapsed));
+        }
+    }
+}
+
+impl Drop for Progress {
+    /// A phase still running when the command bails out with `?` failed.
+    fn drop(&mut self) {
+        self.fail("failed");
+    }
+}
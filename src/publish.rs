@@ -0,0 +1,124 @@
+//! Publish generated credentials to HashiCorp Vault or as an ExternalSecret manifest.
+
+use crate::config::VaultConfig;
+use crate::credentials::ConnectionDetails;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Where `fdb create --publish` sends credentials.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PublishTarget {
+    /// Write to Vault KV v2.
+    Vault,
+    /// Write to Vault and emit an ExternalSecret manifest that pulls the same keys.
+    ExternalSecret,
+}
+
+impl FromStr for PublishTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &*s.to_lowercase() {
+            "vault" => Ok(PublishTarget::Vault),
+            "external-secret" | "externalsecret" | "eso" => Ok(PublishTarget::ExternalSecret),
+            _ => Err(format!(
+                "unknown publish target: {s} (supported: vault, external-secret)"
+            )),
+        }
+    }
+}
+
+/// Vault path for a cluster: [vault] path with `{cluster}` substituted.
+fn secret_path(vault: &VaultConfig, cluster_name: &str) -> String {
+    vault
+        .path
+        .replace("{cluster}", cluster_name)
+        .trim_matches('/')
+        .to_string()
+}
+
+/// Vault token from VAULT_TOKEN, then ~/.vault-token (written by `vault login`).
+fn vault_token() -> Result<String, String> {
+    if let Ok(t) = std::env::var("VAULT_TOKEN")
+        && !t.trim().is_empty()
+    {
+        return Ok(t.trim().to_string());
+    }
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    std::fs::read_to_string(PathBuf::from(home).join(".vault-token"))
+        .map(|t| t.trim().to_string())
+        .map_err(|_| "vault token not found (set VAULT_TOKEN or run `vault login`)".to_string())
+}
+
+/// Write credentials to Vault KV v2 at <mount>/<path>. Returns the logical path written.
+pub fn publish_vault(
+    vault: &VaultConfig,
+    cluster_name: &str,
+    details: &ConnectionDetails,
+) -> Result<String, String> {
+    let token = vault_token()?;
+    let path = secret_path(vault, cluster_name);
+    let url = format!("{}/v1/{}/data/{path}", vault.address, vault.mount);
+    let data: serde_json::Map<String, serde_json::Value> = details
+        .fields()
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.into()))
+        .collect();
+    let body = serde_json::json!({ "data": data }).to_string();
+
+    ureq::post(&url)
+        .set("X-Vault-Token", &token)
+        .set("Content-Type", "application/json")
+        .send_string(&body)
+        .map_err(|e| match e {
+            ureq::Error::Status(code, resp) => {
+                let text = resp.into_string().unwrap_or_default();
+                format!("vault write {path} failed ({code}): {}", text.trim())
+            }
+            e => format!("vault write {path}: {e}"),
+        })?;
+    Ok(format!("{}/{path}", vault.mount))
+}
+
+/// ExternalSecret (external-secrets.io) pulling the published keys into a Secret named <cluster>-credentials.
+pub fn external_secret_manifest(vault: &VaultConfig, cluster_name: &str) -> String {
+    let path = secret_path(vault, cluster_name);
+    let store = &vault.secret_store;
+    format!(
+        r#"apiVersion: external-secrets.io/v1beta1
+kind: ExternalSecret
+metadata:
+  name: {cluster_name}-credentials
+spec:
+  refreshInterval: 1h
+  secretStoreRef:
+    name: {store}
+    kind: SecretStore
+  target:
+    name: {cluster_name}-credentials
+  dataFrom:
+  - extract:
+      key: {path}
+"#
+    )
+}
+
+/// Publish credentials to the requested target, printing where they went.
+pub fn publish(
+    target: PublishTarget,
+    cluster_name: &str,
+    details: &ConnectionDetails,
+) -> Result<(), String> {
+    let vault = crate::config::load_vault_config()?;
+    let written = publish_vault(&vault, cluster_name, details)?;
+    println!("  Published to Vault: {written}");
+    if target == PublishTarget::ExternalSecret {
+        let file = PathBuf::from(format!("{cluster_name}-externalsecret.yaml"));
+        std::fs::write(&file, external_secret_manifest(&vault, cluster_name))
+            .map_err(|e| format!("write {}: {e}", file.display()))?;
+        println!("  ExternalSecret:     {} (kubectl apply -f)", file.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,268 @@
+//! One-shot queries for scripts (`fdb exec-sql`, `fdb exec-redis`): connect through a temporary
+//! port-forward, run one command, print the result as a table (or JSON) and exit.
+
+use crate::credentials;
+use crate::portforward;
+use crate::service::ServiceType;
+use crate::sql;
+use postgres::{NoTls, SimpleQueryMessage};
+use serde_json::{Map, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::path::Path;
+
+/// Run `statements` on the PostgreSQL primary as `postgres`. Each result set prints as a table,
+/// or with `json` as one JSON array of row objects per line; statements without rows print
+/// their row count. A failing statement is an error.
+pub fn exec_sql(
+    kubectl: &Path,
+    cluster_name: &str,
+    database: Option<&str>,
+    statements: &str,
+    json: bool,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    let service = ServiceType::PostgreSQL;
+    let password = credentials::get_password(kubectl, service, cluster_name, kubeconfig)?.unwrap_or_default();
+    let forward = portforward::start_port_forward(
+        kubectl,
+        &format!("svc/{cluster_name}-postgresql"),
+        service.default_port(),
+        None,
+        kubeconfig,
+    )?;
+    let mut client = postgres::Config::new()
+        .host("127.0.0.1")
+        .port(forward.local_port)
+        .user(service.default_user())
+        .password(&password)
+        .dbname(database.unwrap_or("postgres"))
+        .connect(NoTls)
+        .map_err(|e| format!("connect to \"{cluster_name}\": {e}"))?;
+    let messages = client.simple_query(statements).map_err(|e| match e.as_db_error() {
+        Some(db) => format!("ERROR:  {}", db.message()),
+        None => e.to_string(),
+    })?;
+
+    let mut columns: Vec<String> = Vec::new();
+    let mut rows: Vec<Vec<Option<String>>> = Vec::new();
+    for message in messages {
+        match message {
+            SimpleQueryMessage::RowDescription(cols) => {
+                columns = cols.iter().map(|c| c.name().to_string()).collect();
+            }
+            SimpleQueryMessage::Row(row) => {
+                if columns.is_empty() {
+                    columns = row.columns().iter().map(|c| c.name().to_string()).collect();
+                }
+                rows.push((0..row.len()).map(|i| row.get(i).map(str::to_string)).collect());
+            }
+            SimpleQueryMessage::CommandComplete(n) => {
+                match (columns.is_empty(), json) {
+                    (true, false) => println!("OK ({n})"),
+                    (true, true) => println!("{}", serde_json::json!({ "rows_affected": n })),
+                    (false, false) => {
+                        let rows: Vec<Vec<String>> =
+                            rows.iter().map(|r| r.iter().map(|v| v.clone().unwrap_or_default()).collect()).collect();
+                        sql::print_table(&columns, &rows);
+                    }
+                    (false, true) => println!("{}", rows_json(&columns, &rows)),
+                }
+                columns.clear();
+                rows.clear();
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Rows as `[{"column": "value" | null, ...}]`; values stay text, as the server sent them.
+fn rows_json(columns: &[String], rows: &[Vec<Option<String>>]) -> Value {
+    Value::Array(
+        rows.iter()
+            .map(|row| {
+                let object: Map<String, Value> = columns
+                    .iter()
+                    .zip(row)
+                    .map(|(c, v)| (c.clone(), v.clone().map(Value::String).unwrap_or(Value::Null)))
+                    .collect();
+                Value::Object(object)
+            })
+            .collect(),
+    )
+}
+
+/// Split a command line into arguments, honouring "double" and 'single' quotes.
+pub fn split_command(line: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quote: Option<char> = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.get_or_insert_default().push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                current.get_or_insert_default();
+            }
+            (None, c) if c.is_whitespace() => args.extend(current.take()),
+            (None, c) => current.get_or_insert_default().push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err(format!("unterminated quote in \"{line}\""));
+    }
+    args.extend(current.take());
+    if args.is_empty() {
+        return Err("empty command".to_string());
+    }
+    Ok(args)
+}
+
+/// A RESP reply.
+#[derive(Debug, PartialEq)]
+enum Reply {
+    Status(String),
+    Error(String),
+    Integer(i64),
+    Bulk(Option<Vec<u8>>),
+    Array(Option<Vec<Reply>>),
+}
+
+fn read_line(reader: &mut impl BufRead) -> Result<String, String> {
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|e| format!("read: {e}"))?;
+    if !line.ends_with("\r\n") {
+        return Err("connection closed".to_string());
+    }
+    line.truncate(line.len() - 2);
+    Ok(line)
+}
+
+fn read_reply(reader: &mut impl BufRead) -> Result<Reply, String> {
+    let line = read_line(reader)?;
+    let (kind, rest) = line.split_at_checked(1).ok_or("empty reply")?;
+    let length = || rest.parse::<i64>().map_err(|_| format!("bad reply \"{line}\""));
+    Ok(match kind {
+        "+" => Reply::Status(rest.to_string()),
+        "-" => Reply::Error(rest.to_string()),
+        ":" => Reply::Integer(length()?),
+        "$" => match usize::try_from(length()?) {
+            Err(_) => Reply::Bulk(None),
+            Ok(n) => {
+                let mut data = vec![0u8; n + 2];
+                std::io::Read::read_exact(reader, &mut data).map_err(|e| format!("read: {e}"))?;
+                data.truncate(n);
+                Reply::Bulk(Some(data))
+            }
+        },
+        "*" => match usize::try_from(length()?) {
+            Err(_) => Reply::Array(None),
+            Ok(n) => Reply::Array(Some((0..n).map(|_| read_reply(reader)).collect::<Result<_, _>>()?)),
+        },
+        _ => return Err(format!("unexpected reply \"{line}\"")),
+    })
+}
+
+fn send_command(stream: &mut TcpStream, args: &[String]) -> Result<(), String> {
+    let mut out = format!("*{}\r\n", args.len()).into_bytes();
+    for a in args {
+        out.extend(format!("${}\r\n", a.len()).bytes());
+        out.extend(a.bytes());
+        out.extend(b"\r\n");
+    }
+    stream.write_all(&out).map_err(|e| format!("write: {e}"))
+}
+
+/// redis-cli style: `(nil)`, `(integer) 3`, numbered array items.
+fn format_reply(reply: &Reply, indent: usize, out: &mut String) {
+    match reply {
+        Reply::Status(s) | Reply::Error(s) => out.push_str(s),
+        Reply::Integer(n) => out.push_str(&format!("(integer) {n}")),
+        Reply::Bulk(None) | Reply::Array(None) => out.push_str("(nil)"),
+        Reply::Bulk(Some(data)) => out.push_str(&format!("\"{}\"", String::from_utf8_lossy(data).escape_debug())),
+        Reply::Array(Some(items)) if items.is_empty() => out.push_str("(empty array)"),
+        Reply::Array(Some(items)) => {
+            let width = items.len().to_string().len();
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push('\n');
+                    out.push_str(&" ".repeat(indent));
+                }
+                out.push_str(&format!("{:>width$}) ", i + 1));
+                format_reply(item, indent + width + 2, out);
+            }
+        }
+    }
+}
+
+fn reply_json(reply: &Reply) -> Value {
+    match reply {
+        Reply::Status(s) | Reply::Error(s) => Value::String(s.clone()),
+        Reply::Integer(n) => Value::from(*n),
+        Reply::Bulk(None) | Reply::Array(None) => Value::Null,
+        Reply::Bulk(Some(data)) => Value::String(String::from_utf8_lossy(data).into_owned()),
+        Reply::Array(Some(items)) => Value::Array(items.iter().map(reply_json).collect()),
+    }
+}
+
+/// Run one Redis command (`args`, e.g. `["GET", "key"]`) on the cluster. An error reply is an
+/// error.
+pub fn exec_redis(
+    kubectl: &Path,
+    cluster_name: &str,
+    args: &[String],
+    json: bool,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    let service = ServiceType::Redis;
+    let password = credentials::get_password(kubectl, service, cluster_name, kubeconfig)?;
+    let forward = portforward::start_port_forward(
+        kubectl,
+        &format!("svc/{cluster_name}-redis"),
+        service.default_port(),
+        None,
+        kubeconfig,
+    )?;
+    let mut stream = TcpStream::connect(("127.0.0.1", forward.local_port))
+        .map_err(|e| format!("connect to \"{cluster_name}\": {e}"))?;
+    let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+    if let Some(password) = password.filter(|p| !p.is_empty()) {
+        send_command(&mut stream, &["AUTH".to_string(), password])?;
+        if let Reply::Error(e) = read_reply(&mut reader)? {
+            return Err(format!("AUTH: {e}"));
+        }
+    }
+    send_command(&mut stream, args)?;
+    let reply = read_reply(&mut reader)?;
+    if let Reply::Error(e) = reply {
+        return Err(e);
+    }
+    if json {
+        println!("{}", reply_json(&reply));
+    } else {
+        let mut out = String::new();
+        format_reply(&reply, 0, &mut out);
+        println!("{out}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_replies_and_splits_quoted_commands() {
+        let mut input: &[u8] = b"*3\r\n$5\r\nhello\r\n$-1\r\n*1\r\n:42\r\n";
+        let reply = read_reply(&mut input).unwrap();
+        let mut out = String::new();
+        format_reply(&reply, 0, &mut out);
+        assert_eq!(out, "1) \"hello\"\n2) (nil)\n3) 1) (integer) 42");
+        assert_eq!(reply_json(&reply).to_string(), r#"["hello",null,[42]]"#);
+        assert_eq!(split_command(r#"SET greeting "hello world""#).unwrap(), ["SET", "greeting", "hello world"]);
+        assert_eq!(split_command("GET ''").unwrap(), ["GET", ""]);
+        assert!(split_command("GET \"key").is_err());
+    }
+}
@@ -0,0 +1,140 @@
+//! Secret redaction for everything fdb prints (`--redact`, on by default in CI).
+//!
+//! Command echo (`--debug`) always goes through [`command_arg`]: secrets fdb has seen are
+//! registered here and masked wherever they appear, unless `--show-secrets` is given.
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const MASK: &str = "***";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static SHOW_SECRETS: AtomicBool = AtomicBool::new(false);
+static KNOWN_SECRETS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Key names whose values are masked in `key=value` / `"key": "value"` arguments.
+const SENSITIVE_KEYS: &[&str] = &["password", "passwd", "secret", "token", "apikey", "api-key", "api_key", ".key"];
+
+/// True when running under CI (the `CI` variable most providers set).
+fn is_ci() -> bool {
+    match std::env::var("CI") {
+        Ok(v) => !v.is_empty() && v != "0" && !v.eq_ignore_ascii_case("false"),
+        Err(_) => false,
+    }
+}
+
+/// Decide once at startup: `--show-secrets` wins, then `--redact` or CI.
+pub fn init(redact: bool, show_secrets: bool) {
+    let on = !show_secrets && (redact || is_ci());
+    ENABLED.store(on, Ordering::Relaxed);
+    SHOW_SECRETS.store(show_secrets, Ordering::Relaxed);
+}
+
+/// Remember a secret value so it is masked in any echoed command or manifest.
+/// Very short values are ignored to avoid masking unrelated text.
+pub fn register(value: &str) {
+    let value = value.trim();
+    if value.len() < 4 {
+        return;
+    }
+    if let Ok(mut known) = KNOWN_SECRETS.lock()
+        && !known.iter().any(|k| k == value)
+    {
+        known.push(value.to_string());
+    }
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Password, token or key as it should be printed.
+pub fn secret(value: &str) -> String {
+    if enabled() {
+        MASK.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Mask the password in a URL's userinfo: `postgresql://u:p@h` -> `postgresql://u:***@h`.
+pub fn url(value: &str) -> String {
+    if !enabled() {
+        return value.to_string();
+    }
+    mask_url_password(value)
+}
+
+fn mask_url_password(value: &str) -> String {
+    let Some(scheme_end) = value.find("://") else {
+        return value.to_string();
+    };
+    let rest = &value[scheme_end + 3..];
+    let authority_end = rest.find('/').unwrap_or(rest.len());
+    let Some(at) = rest[..authority_end].rfind('@') else {
+        return value.to_string();
+    };
+    let userinfo = &rest[..at];
+    let masked = match userinfo.find(':') {
+        Some(colon) => format!("{}:{MASK}", &userinfo[..colon]),
+        None => MASK.to_string(),
+    };
+    format!("{}{masked}{}", &value[..scheme_end + 3], &rest[at..])
+}
+
+fn is_sensitive_key(key: &str) -> bool {
+    let key = key.trim_matches(|c: char| c == '"' || c == '\'' || c == '-').to_lowercase();
+    SENSITIVE_KEYS.iter().any(|s| key.ends_with(s))
+}
+
+/// Mask `password=...` style values and `"password": "..."` JSON/YAML values in one line.
+fn mask_key_values(line: &str) -> String {
+    // --from-literal=password=xyz, PGPASSWORD=xyz
+    if let Some(eq) = line.rfind('=')
+        && !line[eq + 1..].is_empty()
+        && is_sensitive_key(line[..eq].rsplit(['=', ' ', ',']).next().unwrap_or(""))
+    {
+        return format!("{}={MASK}", &line[..eq]);
+    }
+    // "password":"xyz" / password: xyz
+    let mut out = String::new();
+    let mut rest = line;
+    while let Some(colon) = rest.find(':') {
+        let (head, tail) = rest.split_at(colon);
+        let key = head.rsplit(['{', ',', ' ']).next().unwrap_or("");
+        out.push_str(head);
+        out.push(':');
+        let tail = &tail[1..];
+        if !is_sensitive_key(key) || tail.trim_start().starts_with('{') {
+            rest = tail;
+            continue;
+        }
+        let value = tail.trim_start();
+        out.push_str(&tail[..tail.len() - value.len()]);
+        let end = if let Some(quoted) = value.strip_prefix('"') {
+            quoted.find('"').map(|i| i + 2).unwrap_or(value.len())
+        } else {
+            value.find([',', '}', ' ']).unwrap_or(value.len())
+        };
+        out.push_str(if value.starts_with('"') { "\"***\"" } else { MASK });
+        rest = &value[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// A command argument or manifest line as it may be echoed: registered secrets, URL passwords
+/// and values of password/token/key fields are masked. Only `--show-secrets` disables this.
+pub fn command_arg(value: &str) -> String {
+    if SHOW_SECRETS.load(Ordering::Relaxed) {
+        return value.to_string();
+    }
+    let mut out = value.to_string();
+    if let Ok(known) = KNOWN_SECRETS.lock() {
+        for secret in known.iter() {
+            out = out.replace(secret.as_str(), MASK);
+        }
+    }
+    let out = mask_key_values(&out);
+    out.split(' ').map(mask_url_password).collect::<Vec<_>>().join(" ")
+}
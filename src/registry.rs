@@ -0,0 +1,175 @@
+//! Custom service definitions from ~/.fdb/services.d/*.toml, for KubeBlocks addons fdb has no
+//! built-in support for. Each file describes one addon; `fdb create <addon>` then works like
+//! for a built-in engine, with the definition supplying the names, port and templates.
+
+use crate::service::ServiceType;
+use crate::services::Service;
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// One services.d file.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Definition {
+    /// kbcli addon (`kbcli cluster create <addon>`), also the engine name on fdb's command line.
+    pub addon: String,
+    pub port: u16,
+    #[serde(default = "default_port_name")]
+    pub port_name: String,
+    /// Component the NodePort Service selects; the addon name when unset.
+    pub component: Option<String>,
+    #[serde(default)]
+    pub user: String,
+    /// Secret holding the password, `{cluster}` standing for the cluster name; no password
+    /// when unset.
+    pub secret: Option<String>,
+    /// `{user}`, `{password}`, `{host}` and `{port}` are filled in; `{host}:{port}` when unset.
+    pub connection: Option<String>,
+    /// Where Prometheus scrapes the pods; the client port and `/metrics` when unset.
+    pub metrics_port: Option<u16>,
+    #[serde(default = "default_metrics_path")]
+    pub metrics_path: String,
+    /// Sizes `fdb create` starts from; fdb's generic defaults when unset.
+    pub replicas: Option<u32>,
+    #[serde(default, deserialize_with = "crate::config::deser_string_or_number")]
+    pub storage: Option<String>,
+    #[serde(default, deserialize_with = "crate::config::deser_string_or_number")]
+    pub cpu: Option<String>,
+    #[serde(default, deserialize_with = "crate::config::deser_string_or_number")]
+    pub memory: Option<String>,
+}
+
+fn default_port_name() -> String {
+    "client".to_string()
+}
+
+fn default_metrics_path() -> String {
+    "/metrics".to_string()
+}
+
+impl Service for Definition {
+    fn kbcli_name(&self) -> &str {
+        &self.addon
+    }
+
+    fn component_name(&self) -> &str {
+        self.component.as_deref().unwrap_or(&self.addon)
+    }
+
+    fn default_port(&self) -> u16 {
+        self.port
+    }
+
+    fn port_name(&self) -> &str {
+        &self.port_name
+    }
+
+    fn default_user(&self) -> &str {
+        &self.user
+    }
+
+    fn account_secret(&self, cluster_name: &str) -> String {
+        self.secret.as_deref().unwrap_or_default().replace("{cluster}", cluster_name)
+    }
+
+    fn has_password(&self) -> bool {
+        self.secret.is_some()
+    }
+
+    fn connection_string(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> String {
+        self.connection
+            .as_deref()
+            .unwrap_or("{host}:{port}")
+            .replace("{user}", user)
+            .replace("{password}", password.unwrap_or(""))
+            .replace("{host}", host)
+            .replace("{port}", &port.to_string())
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (
+            self.replicas.unwrap_or(1),
+            self.storage.as_deref().unwrap_or("10Gi"),
+            self.cpu.as_deref().unwrap_or("0.5"),
+            self.memory.as_deref().unwrap_or("1Gi"),
+        )
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        (self.component_name(), self.metrics_port.unwrap_or(self.port), &self.metrics_path)
+    }
+}
+
+static LOADED: OnceLock<Vec<&'static Definition>> = OnceLock::new();
+
+/// Parse every `*.toml` in `dir`, in file name order. Broken files and definitions that would
+/// shadow a built-in engine or an earlier file are skipped with a warning.
+fn load_dir(dir: &Path) -> Vec<&'static Definition> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "toml"))
+        .collect();
+    paths.sort();
+    let mut definitions: Vec<&'static Definition> = Vec::new();
+    for path in paths {
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| toml::from_str::<Definition>(&s).map_err(|e| e.to_string()));
+        let definition = match parsed {
+            Ok(d) => d,
+            Err(e) => {
+                eprintln!("warning: skipping {}: {e}", path.display());
+                continue;
+            }
+        };
+        let addon = definition.addon.as_str();
+        let builtin = ServiceType::ALL.iter().any(|s| s.kbcli_name() == addon || s.spec().aliases().contains(&addon));
+        if builtin || definitions.iter().any(|d| d.addon == addon) {
+            eprintln!("warning: skipping {}: \"{addon}\" is already defined", path.display());
+            continue;
+        }
+        definitions.push(Box::leak(Box::new(definition)));
+    }
+    definitions
+}
+
+/// Definitions in ~/.fdb/services.d, read on first use.
+pub fn definitions() -> &'static [&'static Definition] {
+    LOADED.get_or_init(|| load_dir(&crate::config::fdb_home().join("services.d")))
+}
+
+/// The definition for `addon`, if one was loaded.
+pub fn find(addon: &str) -> Option<&'static Definition> {
+    definitions().iter().copied().find(|d| d.addon == addon)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_definitions_and_fills_templates() {
+        let dir = std::env::temp_dir().join(format!("fdb-registry-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("clickhouse.toml"),
+            "addon = \"clickhouse\"\nport = 8123\nuser = \"admin\"\nsecret = \"{cluster}-clickhouse-account-admin\"\n\
+             connection = \"http://{user}:{password}@{host}:{port}\"\nstorage = 20\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("redis.toml"), "addon = \"redis\"\nport = 6379\n").unwrap();
+        std::fs::write(dir.join("broken.toml"), "addon = \"x\"\n").unwrap();
+
+        let loaded = load_dir(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded.len(), 1);
+        let clickhouse = loaded[0];
+        assert_eq!(clickhouse.account_secret("events"), "events-clickhouse-account-admin");
+        assert_eq!(clickhouse.connection_string("admin", Some("pw"), "10.0.0.1", 30123), "http://admin:pw@10.0.0.1:30123");
+        assert_eq!(clickhouse.defaults(), (1, "20", "0.5", "1Gi"));
+    }
+}
@@ -0,0 +1,98 @@
+//! Requested CPU/memory/storage across clusters, with an optional cost estimate (`fdb report`).
+
+use crate::cluster::ClusterInfo;
+use crate::config::CostRates;
+use crate::resources;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Requests of one cluster; CPU in cores, memory and storage in GiB.
+#[derive(Debug, Clone, Default)]
+struct Usage {
+    cpu: f64,
+    memory: f64,
+    storage: f64,
+}
+
+impl Usage {
+    fn add(&mut self, other: &Usage) {
+        self.cpu += other.cpu;
+        self.memory += other.memory;
+        self.storage += other.storage;
+    }
+
+    fn monthly_cost(&self, rates: &CostRates) -> f64 {
+        self.cpu * rates.cpu_per_core + self.memory * rates.memory_per_gib + self.storage * rates.storage_per_gib
+    }
+}
+
+fn cluster_usage(kubectl: &Path, cluster_name: &str, kubeconfig: &Path) -> Result<Usage, String> {
+    let containers = resources::cluster_containers(kubectl, cluster_name, kubeconfig)?;
+    Ok(Usage {
+        cpu: containers.iter().filter_map(|c| c.cpu_request).sum::<f64>() / 1000.0,
+        memory: containers.iter().filter_map(|c| c.memory_request).sum::<f64>() / GIB,
+        storage: resources::cluster_storage(kubectl, cluster_name, kubeconfig)? / GIB,
+    })
+}
+
+fn row(label: &str, usage: &Usage, rates: Option<&CostRates>) -> String {
+    let mut line = format!(
+        "{label:<36} {:>8.2} {:>12.2} {:>12.2}",
+        usage.cpu, usage.memory, usage.storage
+    );
+    if let Some(rates) = rates {
+        line.push_str(&format!(" {:>12}", format!("{}{:.2}", rates.currency, usage.monthly_cost(rates))));
+    }
+    line
+}
+
+/// Print requested resources per cluster and totals per context/namespace; with `[cost]`
+/// rates in fdb.toml, also the estimated monthly cost.
+pub fn print(
+    kubectl: &Path,
+    context: &str,
+    clusters: &[ClusterInfo],
+    rates: Option<&CostRates>,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    if clusters.is_empty() {
+        println!("No clusters found.");
+        return Ok(());
+    }
+    let mut header = format!("{:<36} {:>8} {:>12} {:>12}", "CLUSTER", "CPU", "MEMORY(GiB)", "STORAGE(GiB)");
+    if rates.is_some() {
+        header.push_str(&format!(" {:>12}", "COST/MONTH"));
+    }
+
+    let mut totals: BTreeMap<&str, Usage> = BTreeMap::new();
+    println!("{header}");
+    for c in clusters {
+        let usage = match cluster_usage(kubectl, &c.name, kubeconfig) {
+            Ok(u) => u,
+            Err(e) => {
+                eprintln!("warning: skipping \"{}\": {e}", c.name);
+                continue;
+            }
+        };
+        println!("{}", row(&c.name, &usage, rates));
+        totals.entry(c.namespace.as_str()).or_default().add(&usage);
+    }
+
+    println!();
+    println!("{}", header.replacen("CLUSTER", "TOTAL (CONTEXT/NAMESPACE)", 1));
+    let mut grand = Usage::default();
+    for (namespace, usage) in &totals {
+        println!("{}", row(&format!("{context}/{namespace}"), usage, rates));
+        grand.add(usage);
+    }
+    if totals.len() > 1 {
+        println!("{}", row("all", &grand, rates));
+    }
+    if rates.is_none() {
+        println!();
+        println!("Add a [cost] section to fdb.toml to estimate monthly cost.");
+    }
+    Ok(())
+}
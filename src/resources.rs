@@ -0,0 +1,213 @@
+//! Kubernetes resource quantities and the requests/limits of a cluster's pods.
+
+use crate::exec::CommandExt;
+use serde::Deserialize;
+use std::path::Path;
+use std::process::Command;
+
+const NAMESPACE: &str = "default";
+
+/// CPU quantity in millicores: "500m" -> 500, "2" -> 2000, "250000n" -> 0.25.
+pub fn parse_cpu(s: &str) -> Option<f64> {
+    let s = s.trim();
+    let (num, factor) = if let Some(n) = s.strip_suffix('n') {
+        (n, 1e-6)
+    } else if let Some(u) = s.strip_suffix('u') {
+        (u, 1e-3)
+    } else if let Some(m) = s.strip_suffix('m') {
+        (m, 1.0)
+    } else {
+        (s, 1000.0)
+    };
+    num.parse::<f64>().ok().map(|n| n * factor)
+}
+
+/// Memory/storage quantity in bytes: "512Mi", "2Gi", "1G", "1048576".
+pub fn parse_bytes(s: &str) -> Option<f64> {
+    let s = s.trim();
+    let split = s.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let factor = match unit {
+        "" => 1.0,
+        "Ki" => 1024.0,
+        "Mi" => 1024.0f64.powi(2),
+        "Gi" => 1024.0f64.powi(3),
+        "Ti" => 1024.0f64.powi(4),
+        "k" => 1e3,
+        "M" => 1e6,
+        "G" => 1e9,
+        "T" => 1e12,
+        _ => return None,
+    };
+    num.parse::<f64>().ok().map(|n| n * factor)
+}
+
+/// Millicores for display: "250m", "1.5".
+pub fn format_cpu(millis: f64) -> String {
+    if millis >= 1000.0 {
+        format!("{}", (millis / 10.0).round() / 100.0)
+    } else {
+        format!("{}m", millis.round())
+    }
+}
+
+/// Bytes for display in binary units: "512Mi", "1.5Gi".
+pub fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 4] = ["Ki", "Mi", "Gi", "Ti"];
+    let mut value = bytes / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    let rounded = (value * 10.0).round() / 10.0;
+    format!("{rounded}{}", UNITS[unit])
+}
+
+#[derive(Deserialize)]
+struct PodList {
+    #[serde(default)]
+    items: Vec<Pod>,
+}
+
+#[derive(Deserialize)]
+struct Pod {
+    metadata: PodMetadata,
+    spec: PodSpec,
+}
+
+#[derive(Deserialize)]
+struct PodMetadata {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PodSpec {
+    #[serde(default)]
+    node_name: Option<String>,
+    #[serde(default)]
+    containers: Vec<Container>,
+}
+
+#[derive(Deserialize)]
+struct Container {
+    name: String,
+    #[serde(default)]
+    resources: ContainerResources,
+}
+
+#[derive(Deserialize, Default)]
+struct ContainerResources {
+    #[serde(default)]
+    requests: std::collections::HashMap<String, String>,
+    #[serde(default)]
+    limits: std::collections::HashMap<String, String>,
+}
+
+/// Configured resources of one container; CPU in millicores, memory in bytes.
+#[derive(Debug, Clone)]
+pub struct ContainerSpec {
+    pub pod: String,
+    pub node: Option<String>,
+    pub container: String,
+    pub cpu_request: Option<f64>,
+    pub cpu_limit: Option<f64>,
+    pub memory_request: Option<f64>,
+    pub memory_limit: Option<f64>,
+}
+
+/// Containers of every pod belonging to the cluster (`app.kubernetes.io/instance` label).
+pub fn cluster_containers(kubectl: &Path, cluster_name: &str, kubeconfig: &Path) -> Result<Vec<ContainerSpec>, String> {
+    let output = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args([
+            "get",
+            "pods",
+            "-n",
+            NAMESPACE,
+            "-l",
+            &format!("app.kubernetes.io/instance={cluster_name}"),
+            "-o",
+            "json",
+        ])
+        .output_checked()?;
+    let pods: PodList =
+        serde_json::from_slice(&output.stdout).map_err(|e| format!("parse pods of \"{cluster_name}\": {e}"))?;
+    let mut containers = Vec::new();
+    for pod in pods.items {
+        for c in pod.spec.containers {
+            containers.push(ContainerSpec {
+                pod: pod.metadata.name.clone(),
+                node: pod.spec.node_name.clone(),
+                container: c.name,
+                cpu_request: c.resources.requests.get("cpu").and_then(|q| parse_cpu(q)),
+                cpu_limit: c.resources.limits.get("cpu").and_then(|q| parse_cpu(q)),
+                memory_request: c.resources.requests.get("memory").and_then(|q| parse_bytes(q)),
+                memory_limit: c.resources.limits.get("memory").and_then(|q| parse_bytes(q)),
+            });
+        }
+    }
+    Ok(containers)
+}
+
+#[derive(Deserialize)]
+struct PvcList {
+    #[serde(default)]
+    items: Vec<serde_json::Value>,
+}
+
+/// The cluster's PVCs with their requested storage in bytes, sorted by name.
+pub fn cluster_pvcs(kubectl: &Path, cluster_name: &str, kubeconfig: &Path) -> Result<Vec<(String, f64)>, String> {
+    let output = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args([
+            "get",
+            "pvc",
+            "-n",
+            NAMESPACE,
+            "-l",
+            &format!("app.kubernetes.io/instance={cluster_name}"),
+            "-o",
+            "json",
+        ])
+        .output_checked()?;
+    let pvcs: PvcList =
+        serde_json::from_slice(&output.stdout).map_err(|e| format!("parse PVCs of \"{cluster_name}\": {e}"))?;
+    let mut sizes: Vec<(String, f64)> = pvcs
+        .items
+        .iter()
+        .map(|p| {
+            let name = p.pointer("/metadata/name").and_then(|n| n.as_str()).unwrap_or_default();
+            let size = p.pointer("/spec/resources/requests/storage").and_then(|s| s.as_str()).and_then(parse_bytes);
+            (name.to_string(), size.unwrap_or_default())
+        })
+        .collect();
+    sizes.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(sizes)
+}
+
+/// Total requested storage (bytes) of the cluster's PVCs.
+pub fn cluster_storage(kubectl: &Path, cluster_name: &str, kubeconfig: &Path) -> Result<f64, String> {
+    Ok(cluster_pvcs(kubectl, cluster_name, kubeconfig)?.iter().map(|(_, size)| size).sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_formats_quantities() {
+        assert_eq!(parse_cpu("500m"), Some(500.0));
+        assert_eq!(parse_cpu("2"), Some(2000.0));
+        assert_eq!(parse_cpu("250000n"), Some(0.25));
+        assert_eq!(parse_bytes("512Mi"), Some(512.0 * 1024.0 * 1024.0));
+        assert_eq!(parse_bytes("1G"), Some(1e9));
+        assert_eq!(parse_bytes("1GB"), None);
+        assert_eq!(format_cpu(250.0), "250m");
+        assert_eq!(format_cpu(1500.0), "1.5");
+        assert_eq!(format_bytes(1536.0 * 1024.0 * 1024.0), "1.5Gi");
+    }
+}
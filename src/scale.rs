@@ -0,0 +1,102 @@
+//! Scale a running cluster (`fdb scale`): the same hscale/vscale OpsRequests `fdb apply`
+//! submits, for one cluster and the values given on the command line.
+
+use crate::cluster;
+use crate::drift::{self, Spec};
+use crate::error::{self, Error, ExitCode};
+use crate::exec::CommandExt;
+use crate::pods;
+use crate::progress::Progress;
+use crate::service::ServiceType;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+/// New size; unset fields keep their current value.
+#[derive(Debug, Clone, Default)]
+pub struct Target {
+    pub replicas: Option<u32>,
+    pub cpu: Option<String>,
+    pub memory: Option<String>,
+}
+
+impl Target {
+    fn is_empty(&self) -> bool {
+        self.replicas.is_none() && self.cpu.is_none() && self.memory.is_none()
+    }
+
+    /// `live` with this target's fields applied, in kbcli units.
+    fn apply_to(&self, live: &Spec) -> Result<Spec, String> {
+        let number = |s: String| s.parse::<f64>().ok();
+        let mut desired = live.clone();
+        if let Some(r) = self.replicas {
+            desired.replicas = Some(r);
+        }
+        if let Some(c) = &self.cpu {
+            desired.cpu = number(cluster::kbcli_cpu(c)?);
+        }
+        if let Some(m) = &self.memory {
+            desired.memory = number(cluster::kbcli_gi(m, "memory")?);
+        }
+        Ok(desired)
+    }
+}
+
+/// "3 replicas, 1 CPU, 2Gi memory" from a live spec.
+fn topology(spec: &Spec) -> String {
+    let show = |v: Option<f64>, unit: &str| {
+        v.map_or("?".to_string(), |v| format!("{}{unit}", (v * 100.0).round() / 100.0))
+    };
+    let replicas = spec.replicas.map_or("?".to_string(), |r| r.to_string());
+    format!("{replicas} replicas, {} CPU, {} memory", show(spec.cpu, ""), show(spec.memory, "Gi"))
+}
+
+/// Submit the OpsRequests that bring the cluster's main component to `target`, wait for them
+/// and for the cluster to be Running, then print the new topology.
+pub fn run(
+    kbcli: &Path,
+    kubectl: &Path,
+    service: ServiceType,
+    name: &str,
+    target: &Target,
+    kubeconfig: &Path,
+    timeout: Duration,
+) -> Result<(), Error> {
+    if target.is_empty() {
+        return Err(Error::new(ExitCode::Usage, "nothing to scale; pass --replicas, --cpu or --memory"));
+    }
+    let live = drift::live(kubectl, service, name, kubeconfig)?.ok_or_else(|| format!("cluster \"{name}\" not found"))?;
+    let desired = target.apply_to(&live.spec)?;
+    let changes = drift::changes(&live.spec, &desired);
+    let (ops, _) = drift::operations(name, &live.component, &changes);
+    if ops.is_empty() {
+        println!("\"{name}\" already has {}.", topology(&live.spec));
+        return Ok(());
+    }
+    cluster::confirm_production(kubectl, name, "scale", kubeconfig)?;
+
+    let mut submitted = Vec::new();
+    for op in &ops {
+        println!("Running {op}");
+        let output = Command::new(kbcli)
+            .arg("--kubeconfig")
+            .arg(kubeconfig)
+            .args(&op.args)
+            .output_checked()
+            .map_err(error::code(ExitCode::Kbcli))?;
+        submitted.extend(cluster::ops_request_name(&String::from_utf8_lossy(&output.stdout)));
+    }
+    let mut progress = Progress::new();
+    cluster::wait_for_ops(kubectl, &submitted, kubeconfig, timeout, &mut progress)?;
+    cluster::wait_until_running(kbcli, kubectl, name, kubeconfig, timeout, &mut progress)?;
+    progress.finish();
+
+    let now = drift::live(kubectl, service, name, kubeconfig)?.map_or(desired, |l| l.spec);
+    println!("Scaled \"{name}\" ({}) to {}.", live.component, topology(&now));
+    if let Ok(pods) = pods::cluster_pod_states(kubectl, name, kubeconfig) {
+        for pod in pods {
+            println!("  {:<40} {}", pod.name, pod.status);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,293 @@
+//! Seed a PostgreSQL cluster from inside Kubernetes (`fdb load --job`): the SQL files go into
+//! a ConfigMap and a Job in the cluster namespace runs them with psql against the primary
+//! service, so nothing streams through the workstation.
+
+use crate::cluster;
+use crate::credentials;
+use crate::exec::CommandExt;
+use crate::load;
+use crate::pods;
+use crate::resources;
+use crate::service::ServiceType;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use nanospinner::Spinner;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+const NAMESPACE: &str = "default";
+pub const DEFAULT_IMAGE: &str = "postgres:16-alpine";
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+/// ConfigMaps hold at most 1 MiB; leave room for metadata.
+const MAX_CONFIGMAP_BYTES: u64 = 1000 * 1024;
+
+/// `--job` options.
+#[derive(Debug)]
+pub struct Job {
+    pub image: String,
+    pub timeout: Duration,
+}
+
+/// Seed files in run order: `path` itself, or the `.sql`, `.sql.gz` and `.sh` files of a
+/// directory sorted by name (`01-schema.sql`, `02-data.sql.gz`, ...).
+fn seed_files(path: &Path) -> Result<Vec<PathBuf>, String> {
+    if !path.is_dir() {
+        fs::metadata(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(path)
+        .map_err(|e| format!("{}: {e}", path.display()))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            let name = p.file_name().unwrap_or_default().to_string_lossy();
+            p.is_file() && [".sql", ".sql.gz", ".sh"].iter().any(|ext| name.ends_with(ext))
+        })
+        .collect();
+    files.sort();
+    if files.is_empty() {
+        return Err(format!("{} has no .sql, .sql.gz or .sh files", path.display()));
+    }
+    Ok(files)
+}
+
+/// ConfigMap key for the `index`th file: ordered, gzipped, and tagged with how to run it
+/// (`.sh`, `.dumpall.sql` for pg_dumpall output, `.sql` otherwise).
+fn key(index: usize, file: &Path, head: &[u8]) -> String {
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    let stem = name.trim_end_matches(".gz").trim_end_matches(".sql").trim_end_matches(".sh");
+    let stem: String = stem
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let kind = if name.ends_with(".sh") {
+        "sh"
+    } else if String::from_utf8_lossy(head).trim_start().starts_with(load::PG_DUMPALL_HEADER) {
+        "dumpall.sql"
+    } else {
+        "sql"
+    };
+    format!("{index:03}-{stem}.{kind}.gz")
+}
+
+/// Gzip the seed files into `dir` under their ConfigMap keys; returns the total size.
+fn stage(files: &[PathBuf], dir: &Path) -> Result<u64, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("create {}: {e}", dir.display()))?;
+    let mut total = 0;
+    for (i, file) in files.iter().enumerate() {
+        let data = fs::read(file).map_err(|e| format!("read {}: {e}", file.display()))?;
+        let plain = if file.extension().is_some_and(|e| e == "gz") {
+            let mut out = Vec::new();
+            flate2::read::GzDecoder::new(&data[..])
+                .read_to_end(&mut out)
+                .map_err(|e| format!("gunzip {}: {e}", file.display()))?;
+            out
+        } else {
+            data
+        };
+        let mut gz = GzEncoder::new(Vec::new(), Compression::best());
+        gz.write_all(&plain).map_err(|e| format!("gzip {}: {e}", file.display()))?;
+        let gz = gz.finish().map_err(|e| format!("gzip {}: {e}", file.display()))?;
+        total += gz.len() as u64;
+        let dest = dir.join(key(i + 1, file, &plain[..plain.len().min(256)]));
+        fs::write(&dest, gz).map_err(|e| format!("write {}: {e}", dest.display()))?;
+    }
+    Ok(total)
+}
+
+/// Shell run by the Job: each file in key order, stopping at the first failure.
+const SCRIPT: &str = r#"set -e
+for f in /seed/*; do
+  echo "==> ${f#/seed/}"
+  case "$f" in
+    *.sh.gz) gunzip -c "$f" | sh ;;
+    *.dumpall.sql.gz) gunzip -c "$f" | psql -q ;;
+    *) gunzip -c "$f" | psql -q -v ON_ERROR_STOP=1 --single-transaction ;;
+  esac
+done
+"#;
+
+fn manifest(job_name: &str, cluster_name: &str, database: &str, image: &str, secret: &(String, String)) -> String {
+    let script = SCRIPT.lines().map(|l| format!("              {l}")).collect::<Vec<_>>().join("\n");
+    format!(
+        r#"apiVersion: batch/v1
+kind: Job
+metadata:
+  name: {job_name}
+  namespace: {NAMESPACE}
+  labels:
+    app.kubernetes.io/managed-by: fdb
+spec:
+  backoffLimit: 0
+  ttlSecondsAfterFinished: 3600
+  template:
+    spec:
+      restartPolicy: Never
+      containers:
+        - name: seed
+          image: {image}
+          command: ["sh", "-c"]
+          args:
+            - |
+{script}
+          env:
+            - name: PGHOST
+              value: {cluster_name}-postgresql
+            - name: PGPORT
+              value: "5432"
+            - name: PGUSER
+              value: postgres
+            - name: PGDATABASE
+              value: "{database}"
+            - name: PGPASSWORD
+              valueFrom:
+                secretKeyRef:
+                  name: {}
+                  key: {}
+          volumeMounts:
+            - name: seed
+              mountPath: /seed
+      volumes:
+        - name: seed
+          configMap:
+            name: {job_name}
+"#,
+        secret.0, secret.1
+    )
+}
+
+fn kubectl(kubectl: &Path, kubeconfig: &Path) -> Command {
+    let mut cmd = Command::new(kubectl);
+    cmd.arg("--kubeconfig").arg(kubeconfig);
+    cmd
+}
+
+fn cleanup(kubectl_path: &Path, job_name: &str, kubeconfig: &Path) {
+    for kind in ["job", "configmap"] {
+        let _ = kubectl(kubectl_path, kubeconfig)
+            .args(["delete", kind, job_name, "-n", NAMESPACE, "--ignore-not-found"])
+            .output_logged();
+    }
+}
+
+/// `succeeded`/`failed` pod counts of the Job.
+fn job_state(kubectl_path: &Path, job_name: &str, kubeconfig: &Path) -> Result<(u32, u32), String> {
+    let out = kubectl(kubectl_path, kubeconfig)
+        .args(["get", "job", job_name, "-n", NAMESPACE])
+        .args(["-o", "jsonpath={.status.succeeded},{.status.failed}"])
+        .output_checked()?;
+    let out = String::from_utf8_lossy(&out.stdout);
+    let (succeeded, failed) = out.trim().split_once(',').unwrap_or(("", ""));
+    Ok((succeeded.parse().unwrap_or(0), failed.parse().unwrap_or(0)))
+}
+
+fn job_logs(kubectl_path: &Path, job_name: &str, kubeconfig: &Path) -> String {
+    kubectl(kubectl_path, kubeconfig)
+        .args(["logs", &format!("job/{job_name}"), "-n", NAMESPACE, "--tail=20"])
+        .output_logged()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Run the seed files at `path` in a Job (PostgreSQL clusters only). Asks before loading into
+/// a database that already has tables unless `yes`. The Job and ConfigMap are removed
+/// afterwards; on failure the error carries the tail of the Job log.
+pub fn run(
+    kubectl_path: &Path,
+    cluster_name: &str,
+    path: &Path,
+    database: Option<&str>,
+    yes: bool,
+    job: &Job,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    let files = seed_files(path)?;
+    let database = database.unwrap_or("postgres");
+    let pod = pods::primary_pod(kubectl_path, cluster_name, "postgresql", kubeconfig)?;
+    let tables = load::pg_table_count(kubectl_path, &pod, database, kubeconfig)?;
+    if tables > 0
+        && !yes
+        && !load::confirm(&format!(
+            "Database \"{database}\" on \"{cluster_name}\" already has {tables} tables. Load anyway?"
+        ))?
+    {
+        return Err("aborted".to_string());
+    }
+    let secret = credentials::password_secret(kubectl_path, ServiceType::PostgreSQL, cluster_name, kubeconfig)?;
+
+    let staging = std::env::temp_dir().join(format!("fdb-seed-{}", std::process::id()));
+    let staged = stage(&files, &staging);
+    let staged = staged.and_then(|size| {
+        if size > MAX_CONFIGMAP_BYTES {
+            return Err(format!(
+                "seed files are {} compressed; a ConfigMap holds at most 1 MiB, so load them without --job",
+                resources::format_bytes(size as f64)
+            ));
+        }
+        Ok(size)
+    });
+    let job_name = format!("fdb-seed-{cluster_name}");
+    cleanup(kubectl_path, &job_name, kubeconfig);
+    let created = staged.and_then(|_| {
+        kubectl(kubectl_path, kubeconfig)
+            .args(["create", "configmap", &job_name, "-n", NAMESPACE])
+            .arg(format!("--from-file={}", staging.display()))
+            .output_checked()
+            .map(|_| ())
+    });
+    let _ = fs::remove_dir_all(&staging);
+    created?;
+
+    let yaml = manifest(&job_name, cluster_name, database, &job.image, &secret);
+    if let Err(e) = cluster::apply_manifest(kubectl_path, &yaml, kubeconfig) {
+        cleanup(kubectl_path, &job_name, kubeconfig);
+        return Err(e);
+    }
+
+    let what = format!("{} file(s) from {}", files.len(), path.display());
+    let spinner = Spinner::new(&format!("Seeding \"{cluster_name}\" with {what} in Job {job_name}")).start();
+    let start = Instant::now();
+    let result = loop {
+        match job_state(kubectl_path, &job_name, kubeconfig) {
+            Ok((s, _)) if s > 0 => break Ok(()),
+            Ok((_, f)) if f > 0 => {
+                break Err(format!("seed Job failed:\n{}", job_logs(kubectl_path, &job_name, kubeconfig)));
+            }
+            Ok(_) => {}
+            Err(e) => break Err(e),
+        }
+        if start.elapsed() >= job.timeout {
+            break Err(format!(
+                "seed Job did not finish within {}s:\n{}",
+                job.timeout.as_secs(),
+                job_logs(kubectl_path, &job_name, kubeconfig)
+            ));
+        }
+        std::thread::sleep(Duration::from_secs(2));
+    };
+    cleanup(kubectl_path, &job_name, kubeconfig);
+    match result {
+        Ok(()) => {
+            spinner.success_with(&format!("Seeded \"{cluster_name}\" with {what}"));
+            Ok(())
+        }
+        Err(e) => {
+            spinner.fail_with(&format!("Seeding \"{cluster_name}\" failed"));
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_keep_order_and_run_mode() {
+        assert_eq!(key(1, Path::new("db/01 schema.sql"), b"CREATE TABLE"), "001-01_schema.sql.gz");
+        assert_eq!(key(2, Path::new("data.sql.gz"), b"\n-- PostgreSQL database cluster dump"), "002-data.dumpall.sql.gz");
+        assert_eq!(key(3, Path::new("grants.sh"), b"#!/bin/sh"), "003-grants.sh.gz");
+    }
+}
@@ -0,0 +1,238 @@
+//! Local HTTP API (`fdb serve`): list, inspect, create and delete clusters as JSON, for IDE
+//! plugins and internal dashboards. Every request except `GET /healthz` needs
+//! `Authorization: Bearer <token>`.
+
+use crate::accounts;
+use crate::cluster;
+use crate::config;
+use crate::error::{Error, ExitCode};
+use crate::history;
+use serde::Deserialize;
+use serde_json::json;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const MAX_BODY: usize = 1 << 20;
+
+/// Body of `POST /clusters`. Either `engine` or `profile` is required; the rest mirrors the
+/// `fdb create` flags.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateRequest {
+    pub engine: Option<String>,
+    pub profile: Option<String>,
+    pub name: Option<String>,
+    pub replicas: Option<u32>,
+    pub storage: Option<String>,
+    pub cpu: Option<String>,
+    pub memory: Option<String>,
+    pub expose: Option<String>,
+}
+
+/// Validates a create request and starts it in the background; returns the cluster name.
+pub type CreateFn = fn(&CreateRequest, &Path) -> Result<String, Error>;
+
+pub struct Api {
+    pub kubectl: PathBuf,
+    pub kbcli: PathBuf,
+    pub kubeconfig: PathBuf,
+    pub token: String,
+    pub create: CreateFn,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+type Response = (u16, serde_json::Value);
+
+/// Accept connections on `addr` until the process is stopped; requests are handled one at a
+/// time, creates continue in the background.
+pub fn run(addr: &str, api: &Api) -> Result<(), String> {
+    if api.token.is_empty() {
+        return Err("the API token must not be empty".to_string());
+    }
+    let listener = TcpListener::bind(addr).map_err(|e| format!("listen on {addr}: {e}"))?;
+    println!("fdb API listening on http://{addr}");
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else { continue };
+        let _ = stream.set_read_timeout(Some(Duration::from_secs(10)));
+        let (status, body) = match read_request(&mut stream) {
+            Ok(request) => {
+                let response = handle(api, &request);
+                println!("{} {} -> {}", request.method, request.path, response.0);
+                response
+            }
+            Err(e) => (400, json!({ "error": e })),
+        };
+        let _ = write_response(&mut stream, status, &body);
+    }
+    Ok(())
+}
+
+fn read_request(stream: &mut TcpStream) -> Result<Request, String> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|e| format!("read request: {e}"))?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err("malformed request line".to_string());
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+    let mut length = 0;
+    let mut authorization = None;
+    loop {
+        line.clear();
+        reader.read_line(&mut line).map_err(|e| format!("read headers: {e}"))?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let Some((key, value)) = header.split_once(':') else { continue };
+        match key.trim().to_ascii_lowercase().as_str() {
+            "content-length" => length = value.trim().parse().map_err(|_| "invalid Content-Length")?,
+            "authorization" => authorization = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+    if length > MAX_BODY {
+        return Err("request body too large".to_string());
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(|e| format!("read body: {e}"))?;
+    Ok(Request {
+        method,
+        path,
+        authorization,
+        body,
+    })
+}
+
+fn write_response(stream: &mut TcpStream, status: u16, body: &serde_json::Value) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        502 => "Bad Gateway",
+        _ => "Internal Server Error",
+    };
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+fn error_response(e: &Error) -> Response {
+    let status = match e.code {
+        ExitCode::Usage => 400,
+        _ if e.message.contains("not found") => 404,
+        ExitCode::Kbcli => 502,
+        _ => 500,
+    };
+    (status, json!({ "error": e.message }))
+}
+
+/// Byte equality that takes the same time wherever the first difference is, so the token
+/// cannot be guessed byte by byte from response times.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn handle(api: &Api, request: &Request) -> Response {
+    let path = request.path.split('?').next().unwrap_or_default();
+    if path == "/healthz" {
+        return (200, json!({ "ok": true }));
+    }
+    let presented = request.authorization.as_deref().and_then(|a| a.strip_prefix("Bearer "));
+    if !presented.is_some_and(|t| constant_time_eq(t.as_bytes(), api.token.as_bytes())) {
+        return (401, json!({ "error": "missing or wrong bearer token" }));
+    }
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let result = match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["clusters"]) => list(api),
+        ("POST", ["clusters"]) => create(api, &request.body),
+        ("GET", ["clusters", name]) => info(api, &config::qualify(name)),
+        ("DELETE", ["clusters", name]) => delete(api, &config::qualify(name)),
+        (_, ["clusters"] | ["clusters", _]) => return (405, json!({ "error": "method not allowed" })),
+        _ => return (404, json!({ "error": format!("no route for {path}") })),
+    };
+    result.unwrap_or_else(|e| error_response(&e))
+}
+
+fn list(api: &Api) -> Result<Response, Error> {
+    let clusters = cluster::list_cluster_infos(&api.kbcli, None, &api.kubeconfig)
+        .map_err(|e| Error::new(ExitCode::Kbcli, e))?;
+    let items: Vec<serde_json::Value> = clusters
+        .iter()
+        .map(|c| {
+            json!({
+                "name": c.name,
+                "engine": cluster::service_from_definition(&c.definition).map(|s| s.kbcli_name()).ok(),
+                "status": c.status,
+                "created": c.created,
+                "termination_policy": c.termination_policy,
+                "labels": c.labels,
+            })
+        })
+        .collect();
+    Ok((200, json!({ "clusters": items })))
+}
+
+fn info(api: &Api, name: &str) -> Result<Response, Error> {
+    let service = cluster::cluster_service_type(&api.kbcli, name, &api.kubeconfig)
+        .map_err(|e| Error::new(ExitCode::Kbcli, e))?;
+    let details = accounts::connection_details(&api.kubectl, service, name, &api.kubeconfig, false)?;
+    let mut body = json!({ "name": name, "engine": service.kbcli_name() });
+    for (key, value) in details.fields() {
+        body[key] = json!(value);
+    }
+    Ok((200, body))
+}
+
+fn create(api: &Api, body: &[u8]) -> Result<Response, Error> {
+    let request: CreateRequest =
+        serde_json::from_slice(body).map_err(|e| Error::new(ExitCode::Usage, format!("invalid create request: {e}")))?;
+    let name = (api.create)(&request, &api.kubeconfig)?;
+    Ok((202, json!({ "name": name, "status": "Creating" })))
+}
+
+/// Like `fdb delete --yes`; protected clusters and production contexts (which need the name
+/// typed on a terminal) are refused.
+fn delete(api: &Api, name: &str) -> Result<Response, Error> {
+    let context = crate::kubeconfig::current_context(&api.kubectl, &api.kubeconfig)?;
+    if config::is_production_context(&context) {
+        return Ok((403, json!({ "error": format!("context \"{context}\" is production; delete with the CLI") })));
+    }
+    if cluster::is_protected(&api.kubectl, name, &api.kubeconfig)? {
+        return Ok((409, json!({ "error": format!("cluster \"{name}\" is protected") })));
+    }
+    let result = cluster::delete_cluster(&api.kbcli, &api.kubectl, name, &api.kubeconfig, true);
+    history::record("delete", name, result.as_ref().map(|_| ()).map_err(String::as_str));
+    result.map_err(|e| Error::new(ExitCode::Kbcli, e))?;
+    Ok((200, json!({ "deleted": name })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_tokens_exactly() {
+        assert!(constant_time_eq(b"s3cret", b"s3cret"));
+        assert!(!constant_time_eq(b"s3cret", b"s3creT"));
+        assert!(!constant_time_eq(b"s3cret", b"s3cret2"));
+        assert!(!constant_time_eq(b"", b"s3cret"));
+    }
+}
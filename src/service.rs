@@ -1,5 +1,8 @@
-//! Service type (postgresql, redis, rabbitmq, qdrant) for kbcli and connection details.
+//! Service types (the KubeBlocks engines fdb creates) for kbcli and connection details. The
+//! per-engine knowledge lives in [`crate::services`]; these methods forward to it.
 
+use crate::registry::{self, Definition};
+use crate::services::{self, Service};
 use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -8,55 +11,129 @@ pub enum ServiceType {
     Redis,
     RabbitMQ,
     Qdrant,
+    MongoDB,
+    Kafka,
+    Elasticsearch,
+    OpenSearch,
+    Milvus,
+    Weaviate,
+    MariaDB,
+    Etcd,
+    Pulsar,
+    Nats,
+    MinIO,
+    Cassandra,
+    ScyllaDB,
+    CockroachDB,
+    TiDB,
+    InfluxDB,
+    Memcached,
+    Neo4j,
+    /// An addon described in ~/.fdb/services.d (see [`registry`]).
+    Custom(&'static Definition),
 }
 
 impl ServiceType {
+    /// Every built-in engine, in the order fdb lists them.
+    pub const ALL: [ServiceType; 22] = [
+        ServiceType::PostgreSQL,
+        ServiceType::Redis,
+        ServiceType::RabbitMQ,
+        ServiceType::Qdrant,
+        ServiceType::MongoDB,
+        ServiceType::Kafka,
+        ServiceType::Elasticsearch,
+        ServiceType::OpenSearch,
+        ServiceType::Milvus,
+        ServiceType::Weaviate,
+        ServiceType::MariaDB,
+        ServiceType::Etcd,
+        ServiceType::Pulsar,
+        ServiceType::Nats,
+        ServiceType::MinIO,
+        ServiceType::Cassandra,
+        ServiceType::ScyllaDB,
+        ServiceType::CockroachDB,
+        ServiceType::TiDB,
+        ServiceType::InfluxDB,
+        ServiceType::Memcached,
+        ServiceType::Neo4j,
+    ];
+
+    /// kbcli names of the built-in engines, then of the custom ones from ~/.fdb/services.d.
+    pub fn supported_names() -> Vec<&'static str> {
+        let custom = registry::definitions().iter().map(|d| d.addon.as_str());
+        ServiceType::ALL.iter().map(|t| t.kbcli_name()).chain(custom).collect()
+    }
+
+    /// The engine's definition.
+    pub fn spec(&self) -> &'static dyn Service {
+        match *self {
+            ServiceType::PostgreSQL => &services::PostgreSQL,
+            ServiceType::Redis => &services::Redis,
+            ServiceType::RabbitMQ => &services::RabbitMQ,
+            ServiceType::Qdrant => &services::Qdrant,
+            ServiceType::MongoDB => &services::MongoDB,
+            ServiceType::Kafka => &services::Kafka,
+            ServiceType::Elasticsearch => &services::Elasticsearch,
+            ServiceType::OpenSearch => &services::OpenSearch,
+            ServiceType::Milvus => &services::Milvus,
+            ServiceType::Weaviate => &services::Weaviate,
+            ServiceType::MariaDB => &services::MariaDB,
+            ServiceType::Etcd => &services::Etcd,
+            ServiceType::Pulsar => &services::Pulsar,
+            ServiceType::Nats => &services::Nats,
+            ServiceType::MinIO => &services::MinIO,
+            ServiceType::Cassandra => &services::Cassandra,
+            ServiceType::ScyllaDB => &services::ScyllaDB,
+            ServiceType::CockroachDB => &services::CockroachDB,
+            ServiceType::TiDB => &services::TiDB,
+            ServiceType::InfluxDB => &services::InfluxDB,
+            ServiceType::Memcached => &services::Memcached,
+            ServiceType::Neo4j => &services::Neo4j,
+            ServiceType::Custom(d) => d,
+        }
+    }
+
     /// Name used in kbcli: cluster create <name>.
     pub fn kbcli_name(&self) -> &'static str {
-        match self {
-            ServiceType::PostgreSQL => "postgresql",
-            ServiceType::Redis => "redis",
-            ServiceType::RabbitMQ => "rabbitmq",
-            ServiceType::Qdrant => "qdrant",
-        }
+        self.spec().kbcli_name()
+    }
+
+    /// Component serving clients; KubeBlocks names its Service `<cluster>-<component>`.
+    pub fn component_name(&self) -> &'static str {
+        self.spec().component_name()
     }
 
     /// Default port for the service.
     pub fn default_port(&self) -> u16 {
-        match self {
-            ServiceType::PostgreSQL => 5432,
-            ServiceType::Redis => 6379,
-            ServiceType::RabbitMQ => 5672,
-            ServiceType::Qdrant => 6333,
-        }
+        self.spec().default_port()
     }
 
-    /// Kubernetes secret name for account password (e.g. <cluster_name>-postgresql-account-postgres).
-    pub fn secret_name(&self, cluster_name: &str) -> String {
+    /// Ports exposed next to `default_port` on the NodePort Service: (name, port).
+    pub fn extra_ports(&self) -> &'static [(&'static str, u16)] {
+        self.spec().extra_ports()
+    }
+
+    /// Kubernetes secrets that may hold the admin password, in lookup order: the KubeBlocks 1.x
+    /// per-account secret (e.g. <cluster_name>-postgresql-account-postgres), then the 0.x
+    /// `<cluster_name>-conn-credential` secret. Custom engines only have the one they name.
+    pub fn secret_names(&self, cluster_name: &str) -> Vec<String> {
+        let account = self.spec().account_secret(cluster_name);
         match self {
-            ServiceType::PostgreSQL => format!("{cluster_name}-postgresql-account-postgres"),
-            ServiceType::Redis => format!("{cluster_name}-redis-account-default"),
-            ServiceType::RabbitMQ => format!("{cluster_name}-rabbitmq-account-root"),
-            ServiceType::Qdrant => format!("{cluster_name}-qdrant-account-root"),
+            ServiceType::Custom(_) => vec![account],
+            _ => vec![account, format!("{cluster_name}-conn-credential")],
         }
     }
 
     /// Default user for connection string.
     pub fn default_user(&self) -> &'static str {
-        match self {
-            ServiceType::PostgreSQL => "postgres",
-            ServiceType::Redis => "default",
-            ServiceType::RabbitMQ => "root",
-            ServiceType::Qdrant => "root",
-        }
+        self.spec().default_user()
     }
 
     /// Whether this service typically has a password in K8s secret.
     pub fn has_password(&self) -> bool {
-        match self {
-            ServiceType::PostgreSQL | ServiceType::Redis | ServiceType::RabbitMQ => true,
-            ServiceType::Qdrant => false,
-        }
+        self.spec().has_password()
     }
 
     /// Build connection string for display.
@@ -67,35 +144,33 @@ impl ServiceType {
         host: &str,
         port: u16,
     ) -> String {
-        match self {
-            ServiceType::PostgreSQL => {
-                let pass = password.unwrap_or("");
-                format!("postgresql://{user}:{pass}@{host}:{port}/postgres")
-            }
-            ServiceType::Redis => {
-                let pass = password.unwrap_or("");
-                if pass.is_empty() {
-                    format!("redis://{host}:{port}")
-                } else {
-                    format!("redis://:{pass}@{host}:{port}")
-                }
-            }
-            ServiceType::RabbitMQ => {
-                let pass = password.unwrap_or("");
-                format!("amqp://{user}:{pass}@{host}:{port}/")
-            }
-            ServiceType::Qdrant => format!("http://{host}:{port}"),
-        }
+        self.spec().connection_string(user, password, host, port)
+    }
+
+    /// Label for `connection_string` in connection details.
+    pub fn connection_label(&self) -> &'static str {
+        self.spec().connection_label()
+    }
+
+    /// What the `password` is called in the output: InfluxDB authenticates with an API token.
+    pub fn password_label(&self) -> &'static str {
+        self.spec().password_label()
+    }
+
+    /// Command that points the engine's CLI at the cluster, for engines whose CLI keeps its
+    /// own connection profiles.
+    pub fn cli_command(&self, password: Option<&str>, host: &str, port: u16) -> Option<String> {
+        self.spec().cli_command(password, host, port)
+    }
+
+    /// Environment variables clients of this engine read (`fdb env`).
+    pub fn env_vars(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> Vec<(String, String)> {
+        self.spec().env_vars(user, password, host, port)
     }
 
     /// Display name for port in Service YAML.
     pub fn port_name(&self) -> &'static str {
-        match self {
-            ServiceType::PostgreSQL => "postgresql",
-            ServiceType::Redis => "redis",
-            ServiceType::RabbitMQ => "rabbitmq",
-            ServiceType::Qdrant => "qdrant",
-        }
+        self.spec().port_name()
     }
 }
 
@@ -103,14 +178,16 @@ impl FromStr for ServiceType {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match &*s.to_lowercase() {
-            "postgresql" | "postgres" | "pg" => Ok(ServiceType::PostgreSQL),
-            "redis" => Ok(ServiceType::Redis),
-            "rabbitmq" | "rabbit" => Ok(ServiceType::RabbitMQ),
-            "qdrant" => Ok(ServiceType::Qdrant),
-            _ => Err(format!(
-                "unknown service type: {s} (supported: postgresql, redis, rabbitmq, qdrant)"
-            )),
+        let name = s.to_lowercase();
+        let builtin = ServiceType::ALL
+            .into_iter()
+            .find(|t| t.kbcli_name() == name || t.spec().aliases().contains(&name.as_str()));
+        if let Some(t) = builtin {
+            return Ok(t);
+        }
+        if let Some(d) = registry::find(s) {
+            return Ok(ServiceType::Custom(d));
         }
+        Err(format!("unknown service type: {s} (supported: {})", ServiceType::supported_names().join(", ")))
     }
 }
@@ -0,0 +1,79 @@
+//! CockroachDB, PostgreSQL wire-compatible.
+
+use super::{Service, Shell, libpq_env};
+use crate::health::HealthCheck;
+
+pub struct CockroachDB;
+
+impl Service for CockroachDB {
+    fn kbcli_name(&self) -> &str {
+        "cockroachdb"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["cockroach", "crdb"]
+    }
+
+    fn component_name(&self) -> &str {
+        // Every node serves SQL.
+        "cockroachdb"
+    }
+
+    fn default_port(&self) -> u16 {
+        26257
+    }
+
+    fn port_name(&self) -> &str {
+        "sql"
+    }
+
+    fn default_user(&self) -> &str {
+        "root"
+    }
+
+    fn has_password(&self) -> bool {
+        true
+    }
+
+    fn connection_string(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> String {
+        match password.filter(|p| !p.is_empty()) {
+            // Password logins need TLS; an insecure cluster takes the user alone.
+            Some(pass) => format!("postgresql://{user}:{pass}@{host}:{port}/defaultdb?sslmode=require"),
+            None => format!("postgresql://{user}@{host}:{port}/defaultdb?sslmode=disable"),
+        }
+    }
+
+    fn env_vars(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> Vec<(String, String)> {
+        let url = self.connection_string(user, password, host, port);
+        let sslmode = if password.is_some_and(|p| !p.is_empty()) { "require" } else { "disable" };
+        let mut vars = libpq_env(url, user, password, host, port, "defaultdb");
+        vars.push(("PGSSLMODE".to_string(), sslmode.to_string()));
+        vars
+    }
+
+    fn shell(&self, user: &str, password: Option<&str>, port: u16) -> Option<Shell> {
+        // psql speaks the wire protocol; TLS only where the connection string asks for it.
+        let sslmode = if password.is_some_and(|p| !p.is_empty()) { "require" } else { "disable" };
+        let mut env = vec![("PGSSLMODE", sslmode.to_string())];
+        env.extend(password.map(|p| ("PGPASSWORD", p.to_string())));
+        Some(Shell {
+            program: "psql",
+            args: ["-h", "127.0.0.1", "-U", user, "-d", "defaultdb", "-p", &port.to_string()]
+                .map(String::from)
+                .to_vec(),
+            env,
+        })
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (3, "20Gi", "1", "2Gi")
+    }
+
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck::SslRequest
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        ("cockroachdb", 8080, "/_status/vars")
+    }
+}
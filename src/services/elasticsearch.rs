@@ -0,0 +1,48 @@
+//! Elasticsearch.
+
+use super::{Service, search_url};
+
+pub struct Elasticsearch;
+
+impl Service for Elasticsearch {
+    fn kbcli_name(&self) -> &str {
+        "elasticsearch"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["es"]
+    }
+
+    fn default_port(&self) -> u16 {
+        9200
+    }
+
+    fn port_name(&self) -> &str {
+        "http"
+    }
+
+    fn default_user(&self) -> &str {
+        "elastic"
+    }
+
+    fn has_password(&self) -> bool {
+        true
+    }
+
+    fn connection_string(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> String {
+        search_url(user, password, host, port)
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (1, "10Gi", "1", "2Gi")
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        // elasticsearch_exporter sidecar.
+        ("elasticsearch", 9114, "/metrics")
+    }
+
+    fn exporter_sidecar(&self) -> bool {
+        true
+    }
+}
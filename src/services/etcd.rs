@@ -0,0 +1,46 @@
+//! etcd.
+
+use super::Service;
+use crate::health::HealthCheck;
+
+pub struct Etcd;
+
+impl Service for Etcd {
+    fn kbcli_name(&self) -> &str {
+        "etcd"
+    }
+
+    fn default_port(&self) -> u16 {
+        2379
+    }
+
+    fn port_name(&self) -> &str {
+        "client"
+    }
+
+    fn default_user(&self) -> &str {
+        "root"
+    }
+
+    fn connection_string(&self, _user: &str, _password: Option<&str>, host: &str, port: u16) -> String {
+        // etcdctl flag rather than a URL: `etcdctl $(fdb info NAME --uri) get KEY`.
+        format!("--endpoints=http://{host}:{port}")
+    }
+
+    fn connection_label(&self) -> &str {
+        "etcdctl"
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        // Raft needs a majority; a single member is rarely what anyone wants.
+        (3, "2Gi", "0.5", "0.5Gi")
+    }
+
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck::Http("/health")
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        ("etcd", 2379, "/metrics")
+    }
+}
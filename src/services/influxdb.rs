@@ -0,0 +1,65 @@
+//! InfluxDB 2, authenticated with an API token rather than a password.
+
+use super::Service;
+use crate::health::HealthCheck;
+
+pub struct InfluxDB;
+
+impl Service for InfluxDB {
+    fn kbcli_name(&self) -> &str {
+        "influxdb"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["influx"]
+    }
+
+    fn default_port(&self) -> u16 {
+        8086
+    }
+
+    fn port_name(&self) -> &str {
+        "http"
+    }
+
+    fn default_user(&self) -> &str {
+        "admin"
+    }
+
+    fn has_password(&self) -> bool {
+        true
+    }
+
+    fn password_keys(&self) -> &[&'static str] {
+        // The admin token, not the admin password.
+        &["token", "admin-token", "adminToken"]
+    }
+
+    fn connection_string(&self, _user: &str, _password: Option<&str>, host: &str, port: u16) -> String {
+        // The token travels separately (`Authorization: Token ...`, see `cli_command`).
+        format!("http://{host}:{port}")
+    }
+
+    fn password_label(&self) -> &str {
+        "Token"
+    }
+
+    fn cli_command(&self, password: Option<&str>, host: &str, port: u16) -> Option<String> {
+        Some(format!(
+            "influx config create --config-name fdb --host-url http://{host}:{port} --token {} --active",
+            password.unwrap_or("")
+        ))
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (1, "10Gi", "0.5", "1Gi")
+    }
+
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck::Http("/health")
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        ("influxdb", 8086, "/metrics")
+    }
+}
@@ -0,0 +1,59 @@
+//! Kafka in KRaft mode.
+
+use super::Service;
+use crate::health::HealthCheck;
+
+pub struct Kafka;
+
+impl Service for Kafka {
+    fn kbcli_name(&self) -> &str {
+        "kafka"
+    }
+
+    fn component_name(&self) -> &str {
+        // The addon's default topology runs broker and controller in one component.
+        "kafka-combine"
+    }
+
+    fn default_port(&self) -> u16 {
+        9092
+    }
+
+    fn port_name(&self) -> &str {
+        "broker"
+    }
+
+    fn extra_ports(&self) -> &[(&'static str, u16)] {
+        &[("controller", 9093)]
+    }
+
+    fn default_user(&self) -> &str {
+        "admin"
+    }
+
+    fn connection_string(&self, _user: &str, _password: Option<&str>, host: &str, port: u16) -> String {
+        // Clients take a bootstrap server list (`bootstrap.servers`), not a URL.
+        format!("{host}:{port}")
+    }
+
+    fn connection_label(&self) -> &str {
+        "Bootstrap servers"
+    }
+
+    fn env_vars(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> Vec<(String, String)> {
+        vec![("KAFKA_BOOTSTRAP_SERVERS".to_string(), self.connection_string(user, password, host, port))]
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (1, "10Gi", "0.5", "1Gi")
+    }
+
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck::Kafka
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        // The addon's JMX exporter in the Kafka pods.
+        ("kafka-combine", 5556, "/metrics")
+    }
+}
@@ -0,0 +1,61 @@
+//! MariaDB.
+
+use super::{Service, Shell, mysql_env, mysql_shell, mysql_url};
+use crate::health::HealthCheck;
+
+pub struct MariaDB;
+
+impl Service for MariaDB {
+    fn kbcli_name(&self) -> &str {
+        "mariadb"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["maria"]
+    }
+
+    fn default_port(&self) -> u16 {
+        3306
+    }
+
+    fn port_name(&self) -> &str {
+        "mysql"
+    }
+
+    fn default_user(&self) -> &str {
+        "root"
+    }
+
+    fn has_password(&self) -> bool {
+        true
+    }
+
+    fn connection_string(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> String {
+        mysql_url(user, password, host, port)
+    }
+
+    fn env_vars(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> Vec<(String, String)> {
+        mysql_env(user, password, host, port)
+    }
+
+    fn shell(&self, user: &str, password: Option<&str>, port: u16) -> Option<Shell> {
+        Some(mysql_shell(user, password, port))
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (1, "5Gi", "0.5", "1Gi")
+    }
+
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck::MySql
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        // mysqld_exporter sidecar.
+        ("mariadb", 9104, "/metrics")
+    }
+
+    fn exporter_sidecar(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,51 @@
+//! Memcached, a cache without credentials or (by default) a volume.
+
+use super::Service;
+use crate::health::HealthCheck;
+
+pub struct Memcached;
+
+impl Service for Memcached {
+    fn kbcli_name(&self) -> &str {
+        "memcached"
+    }
+
+    fn default_port(&self) -> u16 {
+        11211
+    }
+
+    fn port_name(&self) -> &str {
+        "memcache"
+    }
+
+    fn default_user(&self) -> &str {
+        "default"
+    }
+
+    fn connection_string(&self, _user: &str, _password: Option<&str>, host: &str, port: u16) -> String {
+        // Clients take a server list, not a URL.
+        format!("{host}:{port}")
+    }
+
+    fn connection_label(&self) -> &str {
+        "Server"
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        // Cache only: no volume unless `[memcached] storage` asks for one.
+        (1, "", "0.5", "1Gi")
+    }
+
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck::Memcached
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        // memcached_exporter sidecar.
+        ("memcached", 9150, "/metrics")
+    }
+
+    fn exporter_sidecar(&self) -> bool {
+        true
+    }
+}
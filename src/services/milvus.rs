@@ -0,0 +1,36 @@
+//! Milvus vector database.
+
+use super::Service;
+
+pub struct Milvus;
+
+impl Service for Milvus {
+    fn kbcli_name(&self) -> &str {
+        "milvus"
+    }
+
+    fn default_port(&self) -> u16 {
+        19530
+    }
+
+    fn port_name(&self) -> &str {
+        "grpc"
+    }
+
+    fn default_user(&self) -> &str {
+        "root"
+    }
+
+    fn connection_string(&self, _user: &str, _password: Option<&str>, host: &str, port: u16) -> String {
+        // The SDKs' `uri`; Milvus serves gRPC and REST on this port.
+        format!("http://{host}:{port}")
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (1, "10Gi", "1", "2Gi")
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        ("milvus", 9091, "/metrics")
+    }
+}
@@ -0,0 +1,68 @@
+//! MinIO S3-compatible object storage.
+
+use super::Service;
+use crate::health::HealthCheck;
+
+pub struct MinIO;
+
+impl Service for MinIO {
+    fn kbcli_name(&self) -> &str {
+        "minio"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["s3"]
+    }
+
+    fn default_port(&self) -> u16 {
+        9000
+    }
+
+    fn port_name(&self) -> &str {
+        "api"
+    }
+
+    fn extra_ports(&self) -> &[(&'static str, u16)] {
+        &[("console", 9001)]
+    }
+
+    fn default_user(&self) -> &str {
+        "root"
+    }
+
+    fn has_password(&self) -> bool {
+        true
+    }
+
+    fn password_keys(&self) -> &[&'static str] {
+        // The secret key.
+        &["secretkey", "rootPassword", "password"]
+    }
+
+    fn user_keys(&self) -> &[&'static str] {
+        // The access key.
+        &["accesskey", "rootUser", "username"]
+    }
+
+    fn connection_string(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> String {
+        // rclone/boto style summary; the S3 endpoint itself is http://host:port.
+        format!("s3://{user}:{}@{host}:{port}", password.unwrap_or(""))
+    }
+
+    fn connection_label(&self) -> &str {
+        "S3 endpoint"
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (1, "10Gi", "0.5", "1Gi")
+    }
+
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck::Http("/minio/health/live")
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        // Needs MINIO_PROMETHEUS_AUTH_TYPE=public, which the addon sets.
+        ("minio", 9000, "/minio/v2/metrics/cluster")
+    }
+}
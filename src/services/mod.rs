@@ -0,0 +1,243 @@
+//! What fdb knows about each engine: names, ports, secrets, connection strings, what the
+//! NodePort Service selects, default sizes, readiness check and metrics endpoint. Every built-in
+//! engine implements [`Service`] in its own module; custom engines from services.d implement it
+//! in [`crate::registry`]. [`ServiceType::spec`](crate::service::ServiceType::spec) picks the
+//! implementation.
+
+use crate::health::HealthCheck;
+
+mod cassandra;
+mod cockroachdb;
+mod elasticsearch;
+mod etcd;
+mod influxdb;
+mod kafka;
+mod mariadb;
+mod memcached;
+mod milvus;
+mod minio;
+mod mongodb;
+mod nats;
+mod neo4j;
+mod opensearch;
+mod postgresql;
+mod pulsar;
+mod qdrant;
+mod rabbitmq;
+mod redis;
+mod scylladb;
+mod tidb;
+mod weaviate;
+
+pub use cassandra::Cassandra;
+pub use cockroachdb::CockroachDB;
+pub use elasticsearch::Elasticsearch;
+pub use etcd::Etcd;
+pub use influxdb::InfluxDB;
+pub use kafka::Kafka;
+pub use mariadb::MariaDB;
+pub use memcached::Memcached;
+pub use milvus::Milvus;
+pub use minio::MinIO;
+pub use mongodb::MongoDB;
+pub use nats::Nats;
+pub use neo4j::Neo4j;
+pub use opensearch::OpenSearch;
+pub use postgresql::PostgreSQL;
+pub use pulsar::Pulsar;
+pub use qdrant::Qdrant;
+pub use rabbitmq::RabbitMQ;
+pub use redis::Redis;
+pub use scylladb::ScyllaDB;
+pub use tidb::TiDB;
+pub use weaviate::Weaviate;
+
+/// Local client command for `fdb connect`.
+pub struct Shell {
+    pub program: &'static str,
+    pub args: Vec<String>,
+    /// Environment the client reads the password from, for clients that keep it off the
+    /// command line that way.
+    pub env: Vec<(&'static str, String)>,
+}
+
+/// One engine. Only the names, port, user, connection string, defaults and metrics endpoint
+/// are required; the rest defaults to a single-component engine without a password.
+pub trait Service: Sync {
+    /// kbcli addon (`kbcli cluster create <kbcli_name>`), also the engine name in fdb.
+    fn kbcli_name(&self) -> &str;
+
+    /// Other names accepted on the command line.
+    fn aliases(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Component serving clients; KubeBlocks names its Service `<cluster>-<component>`.
+    fn component_name(&self) -> &str {
+        self.kbcli_name()
+    }
+
+    /// Components the NodePort Service may select, preferred first; fdb takes the first one
+    /// with pods.
+    fn client_components(&self) -> Vec<&str> {
+        vec![self.component_name()]
+    }
+
+    fn default_port(&self) -> u16;
+
+    /// Name of `default_port` in the NodePort Service.
+    fn port_name(&self) -> &str;
+
+    /// Ports exposed next to `default_port` on the NodePort Service: (name, port).
+    fn extra_ports(&self) -> &[(&'static str, u16)] {
+        &[]
+    }
+
+    /// Admin user for connection strings.
+    fn default_user(&self) -> &str;
+
+    /// KubeBlocks 1.x secret of the admin account.
+    fn account_secret(&self, cluster_name: &str) -> String {
+        format!("{cluster_name}-{}-account-{}", self.kbcli_name(), self.default_user())
+    }
+
+    /// Whether the admin account has a password in its secret.
+    fn has_password(&self) -> bool {
+        false
+    }
+
+    /// Secret fields holding the password, tried in order.
+    fn password_keys(&self) -> &[&'static str] {
+        &["password", "PASSWORD"]
+    }
+
+    /// Secret fields holding a generated admin user; empty when the user is fixed.
+    fn user_keys(&self) -> &[&'static str] {
+        &[]
+    }
+
+    fn connection_string(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> String;
+
+    /// What `connection_string` is called in the output.
+    fn connection_label(&self) -> &str {
+        "Connection string"
+    }
+
+    /// What the password is called in the output.
+    fn password_label(&self) -> &str {
+        "Password"
+    }
+
+    /// Command that points the engine's CLI at the cluster, for CLIs that keep their own
+    /// connection profiles.
+    fn cli_command(&self, _password: Option<&str>, _host: &str, _port: u16) -> Option<String> {
+        None
+    }
+
+    /// Environment variables for `fdb env`; `<ENGINE>_URL`, `_HOST`, `_PORT`, `_USER` and
+    /// `_PASSWORD` unless the engine's clients and drivers read other names.
+    fn env_vars(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> Vec<(String, String)> {
+        let prefix = self.kbcli_name().to_uppercase().replace('-', "_");
+        let mut vars = vec![
+            (format!("{prefix}_URL"), self.connection_string(user, password, host, port)),
+            (format!("{prefix}_HOST"), host.to_string()),
+            (format!("{prefix}_PORT"), port.to_string()),
+            (format!("{prefix}_USER"), user.to_string()),
+        ];
+        vars.extend(password.map(|p| (format!("{prefix}_PASSWORD"), p.to_string())));
+        vars
+    }
+
+    /// Interactive client `fdb connect` runs against 127.0.0.1:`port`; None for engines without
+    /// a shell-style client.
+    fn shell(&self, _user: &str, _password: Option<&str>, _port: u16) -> Option<Shell> {
+        None
+    }
+
+    /// (replicas, storage, cpu, memory) before fdb.toml and flags; empty storage means no
+    /// volume.
+    fn defaults(&self) -> (u32, &str, &str, &str);
+
+    /// How `fdb create` checks that the cluster accepts connections.
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck::Connect
+    }
+
+    /// Where Prometheus scrapes the pods: (component, container port, path).
+    fn metrics_endpoint(&self) -> (&str, u16, &str);
+
+    /// Whether the metrics come from a KubeBlocks exporter sidecar that must be turned on.
+    fn exporter_sidecar(&self) -> bool {
+        false
+    }
+}
+
+/// MySQL-protocol URL, as the MySQL, MariaDB and TiDB clients and drivers take it.
+fn mysql_url(user: &str, password: Option<&str>, host: &str, port: u16) -> String {
+    let pass = password.unwrap_or("");
+    format!("mysql://{user}:{pass}@{host}:{port}/")
+}
+
+/// `DATABASE_URL` plus the libpq variables, for PostgreSQL and CockroachDB.
+fn libpq_env(
+    url: String,
+    user: &str,
+    password: Option<&str>,
+    host: &str,
+    port: u16,
+    database: &str,
+) -> Vec<(String, String)> {
+    let mut vars = vec![
+        ("DATABASE_URL".to_string(), url),
+        ("PGHOST".to_string(), host.to_string()),
+        ("PGPORT".to_string(), port.to_string()),
+        ("PGUSER".to_string(), user.to_string()),
+        ("PGDATABASE".to_string(), database.to_string()),
+    ];
+    vars.extend(password.map(|p| ("PGPASSWORD".to_string(), p.to_string())));
+    vars
+}
+
+/// `DATABASE_URL` plus the variables the `mysql` client reads, for MariaDB and TiDB.
+fn mysql_env(user: &str, password: Option<&str>, host: &str, port: u16) -> Vec<(String, String)> {
+    let mut vars = vec![
+        ("DATABASE_URL".to_string(), mysql_url(user, password, host, port)),
+        ("MYSQL_HOST".to_string(), host.to_string()),
+        ("MYSQL_TCP_PORT".to_string(), port.to_string()),
+        ("MYSQL_USER".to_string(), user.to_string()),
+    ];
+    vars.extend(password.map(|p| ("MYSQL_PWD".to_string(), p.to_string())));
+    vars
+}
+
+/// `mysql` client for MariaDB and TiDB; the password goes through MYSQL_PWD.
+fn mysql_shell(user: &str, password: Option<&str>, port: u16) -> Shell {
+    Shell {
+        program: "mysql",
+        args: vec!["-h".into(), "127.0.0.1".into(), "-P".into(), port.to_string(), "-u".into(), user.into()],
+        env: password.map(|p| vec![("MYSQL_PWD", p.to_string())]).unwrap_or_default(),
+    }
+}
+
+/// Elasticsearch/OpenSearch URL: HTTPS with basic auth when security is on (it brings TLS on
+/// the HTTP layer), plain HTTP for a cluster running without credentials.
+fn search_url(user: &str, password: Option<&str>, host: &str, port: u16) -> String {
+    match password.filter(|p| !p.is_empty()) {
+        Some(pass) => format!("https://{user}:{pass}@{host}:{port}"),
+        None => format!("http://{host}:{port}"),
+    }
+}
+
+/// cqlsh arguments: `cqlsh $(fdb info NAME --uri)` prompts for the password.
+fn cqlsh_args(user: &str, host: &str, port: u16) -> String {
+    format!("{host} {port} -u {user}")
+}
+
+/// cqlsh for Cassandra and ScyllaDB, which reads the password from its arguments only.
+fn cqlsh_shell(user: &str, password: Option<&str>, port: u16) -> Shell {
+    let mut args = vec!["127.0.0.1".into(), port.to_string(), "-u".into(), user.into()];
+    if let Some(pass) = password {
+        args.extend(["-p".into(), pass.to_string()]);
+    }
+    Shell { program: "cqlsh", args, env: Vec::new() }
+}
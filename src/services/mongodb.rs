@@ -0,0 +1,73 @@
+//! MongoDB replica sets.
+
+use super::{Service, Shell};
+use crate::health::HealthCheck;
+
+pub struct MongoDB;
+
+impl Service for MongoDB {
+    fn kbcli_name(&self) -> &str {
+        "mongodb"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["mongo"]
+    }
+
+    fn default_port(&self) -> u16 {
+        27017
+    }
+
+    fn port_name(&self) -> &str {
+        "mongodb"
+    }
+
+    fn default_user(&self) -> &str {
+        "root"
+    }
+
+    fn has_password(&self) -> bool {
+        true
+    }
+
+    fn connection_string(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> String {
+        let pass = password.unwrap_or("");
+        // The admin user is defined in the admin database; connect straight to the member
+        // behind the NodePort rather than discovering the replica set.
+        format!("mongodb://{user}:{pass}@{host}:{port}/?authSource=admin&directConnection=true")
+    }
+
+    fn env_vars(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> Vec<(String, String)> {
+        vec![("MONGODB_URI".to_string(), self.connection_string(user, password, host, port))]
+    }
+
+    fn shell(&self, user: &str, password: Option<&str>, port: u16) -> Option<Shell> {
+        let mut args = vec![
+            format!("mongodb://127.0.0.1:{port}/?authSource=admin&directConnection=true"),
+            "-u".into(),
+            user.into(),
+        ];
+        // mongosh has no password variable.
+        if let Some(pass) = password {
+            args.extend(["-p".into(), pass.to_string()]);
+        }
+        Some(Shell { program: "mongosh", args, env: Vec::new() })
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (1, "5Gi", "0.5", "1Gi")
+    }
+
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck::MongoDB
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        // mongodb_exporter sidecar.
+        ("mongodb", 9216, "/metrics")
+    }
+
+    fn exporter_sidecar(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,45 @@
+//! NATS, with JetStream when `--jetstream` or `[nats] jetstream` asks for it.
+
+use super::Service;
+use crate::health::HealthCheck;
+
+pub struct Nats;
+
+impl Service for Nats {
+    fn kbcli_name(&self) -> &str {
+        "nats"
+    }
+
+    fn default_port(&self) -> u16 {
+        4222
+    }
+
+    fn port_name(&self) -> &str {
+        "client"
+    }
+
+    fn default_user(&self) -> &str {
+        "admin"
+    }
+
+    fn connection_string(&self, _user: &str, _password: Option<&str>, host: &str, port: u16) -> String {
+        format!("nats://{host}:{port}")
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (1, "5Gi", "0.5", "1Gi")
+    }
+
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck::Nats
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        // prometheus-nats-exporter sidecar.
+        ("nats", 7777, "/metrics")
+    }
+
+    fn exporter_sidecar(&self) -> bool {
+        true
+    }
+}
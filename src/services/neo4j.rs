@@ -0,0 +1,58 @@
+//! Neo4j graph database.
+
+use super::{Service, Shell};
+use crate::health::HealthCheck;
+
+pub struct Neo4j;
+
+impl Service for Neo4j {
+    fn kbcli_name(&self) -> &str {
+        "neo4j"
+    }
+
+    fn default_port(&self) -> u16 {
+        7687
+    }
+
+    fn port_name(&self) -> &str {
+        "bolt"
+    }
+
+    fn extra_ports(&self) -> &[(&'static str, u16)] {
+        // HTTP API and Neo4j Browser.
+        &[("http", 7474)]
+    }
+
+    fn default_user(&self) -> &str {
+        "neo4j"
+    }
+
+    fn has_password(&self) -> bool {
+        true
+    }
+
+    fn connection_string(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> String {
+        format!("bolt://{user}:{}@{host}:{port}", password.unwrap_or(""))
+    }
+
+    fn shell(&self, user: &str, password: Option<&str>, port: u16) -> Option<Shell> {
+        Some(Shell {
+            program: "cypher-shell",
+            args: vec!["-a".into(), format!("bolt://127.0.0.1:{port}"), "-u".into(), user.into()],
+            env: password.map(|p| vec![("NEO4J_PASSWORD", p.to_string())]).unwrap_or_default(),
+        })
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (1, "10Gi", "1", "2Gi")
+    }
+
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck::Bolt
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        // Prometheus endpoint, when enabled in the Neo4j config.
+        ("neo4j", 2004, "/metrics")
+    }
+}
@@ -0,0 +1,44 @@
+//! OpenSearch.
+
+use super::{Service, search_url};
+
+pub struct OpenSearch;
+
+impl Service for OpenSearch {
+    fn kbcli_name(&self) -> &str {
+        "opensearch"
+    }
+
+    fn default_port(&self) -> u16 {
+        9200
+    }
+
+    fn port_name(&self) -> &str {
+        "http"
+    }
+
+    fn default_user(&self) -> &str {
+        "admin"
+    }
+
+    fn has_password(&self) -> bool {
+        true
+    }
+
+    fn connection_string(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> String {
+        search_url(user, password, host, port)
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (1, "10Gi", "1", "2Gi")
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        // elasticsearch_exporter sidecar; it speaks the OpenSearch API too.
+        ("opensearch", 9114, "/metrics")
+    }
+
+    fn exporter_sidecar(&self) -> bool {
+        true
+    }
+}
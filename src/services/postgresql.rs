@@ -0,0 +1,66 @@
+//! PostgreSQL, managed by Patroni in the KubeBlocks addon.
+
+use super::{Service, Shell, libpq_env};
+use crate::health::HealthCheck;
+
+pub struct PostgreSQL;
+
+impl Service for PostgreSQL {
+    fn kbcli_name(&self) -> &str {
+        "postgresql"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["postgres", "pg"]
+    }
+
+    fn default_port(&self) -> u16 {
+        5432
+    }
+
+    fn port_name(&self) -> &str {
+        "postgresql"
+    }
+
+    fn default_user(&self) -> &str {
+        "postgres"
+    }
+
+    fn has_password(&self) -> bool {
+        true
+    }
+
+    fn connection_string(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> String {
+        let pass = password.unwrap_or("");
+        format!("postgresql://{user}:{pass}@{host}:{port}/postgres")
+    }
+
+    fn env_vars(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> Vec<(String, String)> {
+        libpq_env(self.connection_string(user, password, host, port), user, password, host, port, "postgres")
+    }
+
+    fn shell(&self, user: &str, password: Option<&str>, port: u16) -> Option<Shell> {
+        Some(Shell {
+            program: "psql",
+            args: ["-h", "127.0.0.1", "-U", user, "-d", "postgres", "-p", &port.to_string()].map(String::from).to_vec(),
+            env: password.map(|p| vec![("PGPASSWORD", p.to_string())]).unwrap_or_default(),
+        })
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (1, "2Gi", "0.5", "0.8Gi")
+    }
+
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck::Postgres
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        // postgres_exporter sidecar.
+        ("postgresql", 9187, "/metrics")
+    }
+
+    fn exporter_sidecar(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,50 @@
+//! Apache Pulsar: zookeeper, bookies and brokers, plus a proxy when one was requested.
+
+use super::Service;
+
+pub struct Pulsar;
+
+impl Service for Pulsar {
+    fn kbcli_name(&self) -> &str {
+        "pulsar"
+    }
+
+    fn component_name(&self) -> &str {
+        // Clients talk to the brokers; zookeeper and bookies stay internal.
+        "pulsar-broker"
+    }
+
+    fn client_components(&self) -> Vec<&str> {
+        // Clients go through the proxy when the cluster has one.
+        vec!["pulsar-proxy", "pulsar-broker"]
+    }
+
+    fn default_port(&self) -> u16 {
+        6650
+    }
+
+    fn port_name(&self) -> &str {
+        "pulsar"
+    }
+
+    fn extra_ports(&self) -> &[(&'static str, u16)] {
+        // Admin REST API (`pulsar-admin --admin-url`).
+        &[("http", 8080)]
+    }
+
+    fn default_user(&self) -> &str {
+        "admin"
+    }
+
+    fn connection_string(&self, _user: &str, _password: Option<&str>, host: &str, port: u16) -> String {
+        format!("pulsar://{host}:{port}")
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (1, "10Gi", "1", "2Gi")
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        ("pulsar-broker", 8080, "/metrics")
+    }
+}
@@ -0,0 +1,42 @@
+//! Qdrant vector database.
+
+use super::Service;
+use crate::health::HealthCheck;
+
+pub struct Qdrant;
+
+impl Service for Qdrant {
+    fn kbcli_name(&self) -> &str {
+        "qdrant"
+    }
+
+    fn default_port(&self) -> u16 {
+        6333
+    }
+
+    fn port_name(&self) -> &str {
+        "qdrant"
+    }
+
+    fn default_user(&self) -> &str {
+        "root"
+    }
+
+    // API keys are opt-in (`fdb credentials add`), so no password by default.
+
+    fn connection_string(&self, _user: &str, _password: Option<&str>, host: &str, port: u16) -> String {
+        format!("http://{host}:{port}")
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (1, "5Gi", "0.5", "1Gi")
+    }
+
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck::Http("/readyz")
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        ("qdrant", 6333, "/metrics")
+    }
+}
@@ -0,0 +1,55 @@
+//! RabbitMQ.
+
+use super::Service;
+use crate::health::HealthCheck;
+
+pub struct RabbitMQ;
+
+impl Service for RabbitMQ {
+    fn kbcli_name(&self) -> &str {
+        "rabbitmq"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["rabbit"]
+    }
+
+    fn default_port(&self) -> u16 {
+        5672
+    }
+
+    fn port_name(&self) -> &str {
+        "rabbitmq"
+    }
+
+    fn default_user(&self) -> &str {
+        "root"
+    }
+
+    fn has_password(&self) -> bool {
+        true
+    }
+
+    fn connection_string(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> String {
+        let pass = password.unwrap_or("");
+        format!("amqp://{user}:{pass}@{host}:{port}/")
+    }
+
+    fn env_vars(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> Vec<(String, String)> {
+        let url = self.connection_string(user, password, host, port);
+        vec![("AMQP_URL".to_string(), url.clone()), ("RABBITMQ_URL".to_string(), url)]
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (1, "2Gi", "0.5", "1Gi")
+    }
+
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck::Amqp
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        // The rabbitmq_prometheus plugin.
+        ("rabbitmq", 15692, "/metrics")
+    }
+}
@@ -0,0 +1,81 @@
+//! Redis.
+
+use super::{Service, Shell};
+use crate::health::HealthCheck;
+
+pub struct Redis;
+
+impl Service for Redis {
+    fn kbcli_name(&self) -> &str {
+        "redis"
+    }
+
+    fn default_port(&self) -> u16 {
+        6379
+    }
+
+    fn port_name(&self) -> &str {
+        "redis"
+    }
+
+    fn default_user(&self) -> &str {
+        "default"
+    }
+
+    fn has_password(&self) -> bool {
+        true
+    }
+
+    fn connection_string(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> String {
+        let pass = password.unwrap_or("");
+        if pass.is_empty() {
+            format!("redis://{host}:{port}")
+        } else if user != self.default_user() {
+            format!("redis://{user}:{pass}@{host}:{port}")
+        } else {
+            format!("redis://:{pass}@{host}:{port}")
+        }
+    }
+
+    fn env_vars(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> Vec<(String, String)> {
+        let mut vars = vec![
+            ("REDIS_URL".to_string(), self.connection_string(user, password, host, port)),
+            ("REDIS_HOST".to_string(), host.to_string()),
+            ("REDIS_PORT".to_string(), port.to_string()),
+        ];
+        if user != self.default_user() {
+            vars.push(("REDIS_USERNAME".to_string(), user.to_string()));
+        }
+        vars.extend(password.map(|p| ("REDIS_PASSWORD".to_string(), p.to_string())));
+        vars
+    }
+
+    fn shell(&self, user: &str, password: Option<&str>, port: u16) -> Option<Shell> {
+        let mut args = vec!["-h".into(), "127.0.0.1".into(), "-p".into(), port.to_string()];
+        if user != self.default_user() {
+            args.extend(["--user".into(), user.into()]);
+        }
+        Some(Shell {
+            program: "redis-cli",
+            args,
+            env: password.map(|p| vec![("REDISCLI_AUTH", p.to_string())]).unwrap_or_default(),
+        })
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (1, "1Gi", "0.5", "0.5Gi")
+    }
+
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck::Redis
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        // redis_exporter sidecar.
+        ("redis", 9121, "/metrics")
+    }
+
+    fn exporter_sidecar(&self) -> bool {
+        true
+    }
+}
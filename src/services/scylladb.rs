@@ -0,0 +1,57 @@
+//! ScyllaDB, Cassandra-compatible.
+
+use super::{Service, Shell, cqlsh_args, cqlsh_shell};
+use crate::health::HealthCheck;
+
+pub struct ScyllaDB;
+
+impl Service for ScyllaDB {
+    fn kbcli_name(&self) -> &str {
+        "scylladb"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["scylla"]
+    }
+
+    fn default_port(&self) -> u16 {
+        9042
+    }
+
+    fn port_name(&self) -> &str {
+        "cql"
+    }
+
+    fn default_user(&self) -> &str {
+        // The addon keeps the stock Cassandra superuser.
+        "cassandra"
+    }
+
+    fn has_password(&self) -> bool {
+        true
+    }
+
+    fn connection_string(&self, user: &str, _password: Option<&str>, host: &str, port: u16) -> String {
+        cqlsh_args(user, host, port)
+    }
+
+    fn connection_label(&self) -> &str {
+        "cqlsh"
+    }
+
+    fn shell(&self, user: &str, password: Option<&str>, port: u16) -> Option<Shell> {
+        Some(cqlsh_shell(user, password, port))
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (3, "20Gi", "1", "2Gi")
+    }
+
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck::Cql
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        ("scylladb", 9180, "/metrics")
+    }
+}
@@ -0,0 +1,57 @@
+//! TiDB, MySQL wire-compatible: PD, TiKV and the TiDB SQL layer.
+
+use super::{Service, Shell, mysql_env, mysql_shell, mysql_url};
+use crate::health::HealthCheck;
+
+pub struct TiDB;
+
+impl Service for TiDB {
+    fn kbcli_name(&self) -> &str {
+        "tidb"
+    }
+
+    fn component_name(&self) -> &str {
+        // The SQL layer; PD and TiKV stay internal.
+        "tidb"
+    }
+
+    fn default_port(&self) -> u16 {
+        4000
+    }
+
+    fn port_name(&self) -> &str {
+        "mysql"
+    }
+
+    fn default_user(&self) -> &str {
+        "root"
+    }
+
+    fn has_password(&self) -> bool {
+        true
+    }
+
+    fn connection_string(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> String {
+        mysql_url(user, password, host, port)
+    }
+
+    fn env_vars(&self, user: &str, password: Option<&str>, host: &str, port: u16) -> Vec<(String, String)> {
+        mysql_env(user, password, host, port)
+    }
+
+    fn shell(&self, user: &str, password: Option<&str>, port: u16) -> Option<Shell> {
+        Some(mysql_shell(user, password, port))
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (3, "20Gi", "1", "2Gi")
+    }
+
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck::MySql
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        ("tidb", 10080, "/metrics")
+    }
+}
@@ -0,0 +1,42 @@
+//! Weaviate vector database.
+
+use super::Service;
+use crate::health::HealthCheck;
+
+pub struct Weaviate;
+
+impl Service for Weaviate {
+    fn kbcli_name(&self) -> &str {
+        "weaviate"
+    }
+
+    fn default_port(&self) -> u16 {
+        8080
+    }
+
+    fn port_name(&self) -> &str {
+        "http"
+    }
+
+    fn default_user(&self) -> &str {
+        "root"
+    }
+
+    fn connection_string(&self, _user: &str, _password: Option<&str>, host: &str, port: u16) -> String {
+        // The SDKs' REST `uri`.
+        format!("http://{host}:{port}")
+    }
+
+    fn defaults(&self) -> (u32, &str, &str, &str) {
+        (1, "5Gi", "0.5", "1Gi")
+    }
+
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck::Http("/v1/.well-known/ready")
+    }
+
+    fn metrics_endpoint(&self) -> (&str, u16, &str) {
+        // Served with `PROMETHEUS_MONITORING_ENABLED`, which the addon sets.
+        ("weaviate", 2112, "/metrics")
+    }
+}
@@ -0,0 +1,92 @@
+//! Shell integration (`fdb shellenv`): `fdbcd <name>` points `DATABASE_URL` at a cluster in
+//! the current shell, and the prompt shows the active target.
+
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            _ => Err(format!("unsupported shell: {s} (supported: bash, zsh, fish)")),
+        }
+    }
+}
+
+impl Shell {
+    /// The login shell from `$SHELL`; bash when unknown.
+    pub fn detect() -> Shell {
+        std::env::var("SHELL")
+            .ok()
+            .and_then(|s| s.rsplit('/').next().and_then(|name| name.parse().ok()))
+            .unwrap_or(Shell::Bash)
+    }
+}
+
+const POSIX: &str = r#"fdbcd() {
+  if [ $# -eq 0 ] || [ "$1" = "-" ]; then
+    unset DATABASE_URL FDB_TARGET
+    return 0
+  fi
+  local url
+  url="$(command fdb info "$1" --uri)" || return $?
+  export DATABASE_URL="$url" FDB_TARGET="$1"
+  echo "DATABASE_URL -> $1"
+}
+fdb_ps1() {
+  if [ -n "$FDB_TARGET" ]; then
+    printf '(fdb:%s) ' "$FDB_TARGET"
+  fi
+}
+"#;
+
+const FISH: &str = r#"function fdbcd
+    if test (count $argv) -eq 0; or test "$argv[1]" = -
+        set -e DATABASE_URL
+        set -e FDB_TARGET
+        return 0
+    end
+    set -l url (command fdb info $argv[1] --uri); or return $status
+    set -gx DATABASE_URL $url
+    set -gx FDB_TARGET $argv[1]
+    echo "DATABASE_URL -> $argv[1]"
+end
+function fdb_ps1
+    if set -q FDB_TARGET
+        printf '(fdb:%s) ' $FDB_TARGET
+    end
+end
+if functions -q fish_prompt; and not functions -q __fdb_fish_prompt
+    functions -c fish_prompt __fdb_fish_prompt
+    function fish_prompt
+        fdb_ps1
+        __fdb_fish_prompt
+    end
+end
+"#;
+
+/// Script for `eval "$(fdb shellenv)"` (bash, zsh) or `fdb shellenv fish | source`.
+pub fn script(shell: Shell) -> String {
+    match shell {
+        Shell::Bash => format!(
+            "# fdb shell integration; add to ~/.bashrc: eval \"$(fdb shellenv bash)\"\n{POSIX}\
+             case \"$PS1\" in *fdb_ps1*) ;; *) PS1='$(fdb_ps1)'\"$PS1\" ;; esac\n"
+        ),
+        Shell::Zsh => format!(
+            "# fdb shell integration; add to ~/.zshrc: eval \"$(fdb shellenv zsh)\"\n{POSIX}\
+             setopt PROMPT_SUBST\n\
+             case \"$PS1\" in *fdb_ps1*) ;; *) PS1='$(fdb_ps1)'\"$PS1\" ;; esac\n"
+        ),
+        Shell::Fish => format!("# fdb shell integration; add to config.fish: fdb shellenv fish | source\n{FISH}"),
+    }
+}
@@ -0,0 +1,101 @@
+//! Write connection details as a SOPS-encrypted YAML or dotenv file.
+
+use crate::credentials::ConnectionDetails;
+use crate::exec::CommandExt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+/// Plaintext layout of the encrypted bundle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SopsFormat {
+    Yaml,
+    Dotenv,
+}
+
+impl FromStr for SopsFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &*s.to_lowercase() {
+            "yaml" | "yml" => Ok(SopsFormat::Yaml),
+            "env" | "dotenv" => Ok(SopsFormat::Dotenv),
+            _ => Err(format!("unknown sops format: {s} (supported: yaml, env)")),
+        }
+    }
+}
+
+impl SopsFormat {
+    /// Type name passed to sops --input-type/--output-type.
+    fn sops_type(&self) -> &'static str {
+        match self {
+            SopsFormat::Yaml => "yaml",
+            SopsFormat::Dotenv => "dotenv",
+        }
+    }
+
+    /// Default output file for a cluster, e.g. mydb.enc.yaml.
+    pub fn default_path(&self, cluster_name: &str) -> PathBuf {
+        match self {
+            SopsFormat::Yaml => PathBuf::from(format!("{cluster_name}.enc.yaml")),
+            SopsFormat::Dotenv => PathBuf::from(format!("{cluster_name}.enc.env")),
+        }
+    }
+}
+
+fn yaml_quote(v: &str) -> String {
+    format!("\"{}\"", v.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Plaintext document: `user: "..."` lines for YAML, `DB_USER=...` lines for dotenv.
+fn render_plaintext(details: &ConnectionDetails, format: SopsFormat) -> String {
+    let mut out = String::new();
+    for (key, value) in details.fields() {
+        match format {
+            SopsFormat::Yaml => out.push_str(&format!("{key}: {}\n", yaml_quote(&value))),
+            SopsFormat::Dotenv => {
+                let key = if key == "uri" { "url" } else { key };
+                out.push_str(&format!("DB_{}={value}\n", key.to_uppercase()));
+            }
+        }
+    }
+    out
+}
+
+/// Encrypt connection details for an age recipient with `sops` and write them to `out`.
+pub fn write_encrypted(
+    details: &ConnectionDetails,
+    age_recipient: &str,
+    format: SopsFormat,
+    out: &Path,
+) -> Result<(), String> {
+    let plain = render_plaintext(details, format);
+    let args = ["--age", age_recipient, "--input-type", format.sops_type(), "--output-type", format.sops_type()];
+    let encrypted = encrypt(plain.as_bytes(), &args, out)?;
+    std::fs::write(out, encrypted).map_err(|e| format!("write {}: {e}", out.display()))
+}
+
+/// `sops --encrypt` with the plaintext on stdin, so it never touches the disk. `filename` is
+/// what `.sops.yaml` creation rules are matched against.
+pub fn encrypt(plaintext: &[u8], args: &[&str], filename: &Path) -> Result<Vec<u8>, String> {
+    let mut child = Command::new("sops")
+        .arg("--encrypt")
+        .args(args)
+        .arg("--filename-override")
+        .arg(filename)
+        .arg("/dev/stdin")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn_logged()
+        .map_err(|e| format!("could not run sops: {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(plaintext).map_err(|e| format!("sops stdin: {e}"))?;
+    }
+    let output = child.wait_with_output().map_err(|e| format!("sops: {e}"))?;
+    if !output.status.success() {
+        return Err(format!("sops --encrypt failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(output.stdout)
+}
@@ -0,0 +1,197 @@
+//! Interactive SQL prompt for PostgreSQL clusters (`fdb sql`), no local psql needed.
+
+use crate::credentials;
+use crate::portforward;
+use crate::service::ServiceType;
+use postgres::{Client, NoTls, SimpleQueryMessage};
+use rustyline::DefaultEditor;
+use rustyline::error::ReadlineError;
+use std::path::Path;
+
+const HELP: &str = r"  \dt          list tables
+  \d TABLE     describe a table's columns
+  \dn          list schemas
+  \du          list roles
+  \l           list databases
+  \?           this help
+  \q           quit
+SQL statements run when a line ends with ';'.";
+
+/// Catalog query behind a backslash helper, or None for \q / \? / unknown.
+fn helper_query(command: &str) -> Result<Option<String>, String> {
+    let mut parts = command.split_whitespace();
+    let name = parts.next().unwrap_or_default();
+    let arg = parts.next();
+    let sql = match (name, arg) {
+        ("\\dt", _) => "SELECT table_schema AS schema, table_name AS name, table_type AS type \
+                        FROM information_schema.tables \
+                        WHERE table_schema NOT IN ('pg_catalog', 'information_schema') \
+                        ORDER BY 1, 2"
+            .to_string(),
+        ("\\d", Some(table)) => {
+            let (schema, table) = table.split_once('.').unwrap_or(("public", table));
+            format!(
+                "SELECT column_name AS column, data_type AS type, is_nullable AS nullable, column_default AS default \
+                 FROM information_schema.columns \
+                 WHERE table_schema = '{}' AND table_name = '{}' ORDER BY ordinal_position",
+                schema.replace('\'', "''"),
+                table.replace('\'', "''")
+            )
+        }
+        ("\\d", None) => return helper_query("\\dt"),
+        ("\\dn", _) => "SELECT nspname AS name, pg_get_userbyid(nspowner) AS owner FROM pg_namespace \
+                        WHERE nspname NOT LIKE 'pg\\_%' AND nspname <> 'information_schema' ORDER BY 1"
+            .to_string(),
+        ("\\du", _) => "SELECT rolname AS role, rolsuper AS superuser, rolcanlogin AS login, \
+                        rolvaliduntil AS valid_until FROM pg_roles WHERE rolname NOT LIKE 'pg\\_%' ORDER BY 1"
+            .to_string(),
+        ("\\l", _) => "SELECT datname AS name, pg_get_userbyid(datdba) AS owner, \
+                       pg_encoding_to_char(encoding) AS encoding FROM pg_database ORDER BY 1"
+            .to_string(),
+        ("\\q" | "\\?", _) => return Ok(None),
+        _ => return Err(format!("unknown command {name}; try \\?")),
+    };
+    Ok(Some(sql))
+}
+
+/// Print one result set as an aligned table, like psql.
+pub fn print_table(columns: &[String], rows: &[Vec<String>]) {
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| rows.iter().map(|r| r[i].chars().count()).chain([c.chars().count()]).max().unwrap_or(0))
+        .collect();
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(c, w)| format!(" {c:<w$} "))
+            .collect::<Vec<_>>()
+            .join("|")
+    };
+    println!("{}", line(columns));
+    println!("{}", widths.iter().map(|w| "-".repeat(w + 2)).collect::<Vec<_>>().join("+"));
+    for row in rows {
+        println!("{}", line(row));
+    }
+    println!("({} row{})", rows.len(), if rows.len() == 1 { "" } else { "s" });
+}
+
+/// Run SQL and print every result set; statements without rows print their row count.
+fn run_query(client: &mut Client, sql: &str) {
+    let messages = match client.simple_query(sql) {
+        Ok(m) => m,
+        Err(e) => {
+            match e.as_db_error() {
+                Some(db) => eprintln!("ERROR:  {}", db.message()),
+                None => eprintln!("error: {e}"),
+            }
+            return;
+        }
+    };
+    let mut columns: Vec<String> = Vec::new();
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    for message in messages {
+        match message {
+            SimpleQueryMessage::RowDescription(cols) => {
+                columns = cols.iter().map(|c| c.name().to_string()).collect();
+            }
+            SimpleQueryMessage::Row(row) => {
+                if columns.is_empty() {
+                    columns = row.columns().iter().map(|c| c.name().to_string()).collect();
+                }
+                rows.push((0..row.len()).map(|i| row.get(i).unwrap_or("").to_string()).collect());
+            }
+            SimpleQueryMessage::CommandComplete(n) => {
+                if columns.is_empty() {
+                    println!("OK ({n})");
+                } else {
+                    print_table(&columns, &rows);
+                }
+                columns.clear();
+                rows.clear();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Open an interactive prompt on the cluster's primary through a temporary port-forward.
+pub fn run_prompt(kubectl: &Path, cluster_name: &str, kubeconfig: &Path) -> Result<(), String> {
+    let service = ServiceType::PostgreSQL;
+    let password = credentials::get_password(kubectl, service, cluster_name, kubeconfig)?.unwrap_or_default();
+    let forward = portforward::start_port_forward(
+        kubectl,
+        &format!("svc/{cluster_name}-postgresql"),
+        service.default_port(),
+        None,
+        kubeconfig,
+    )?;
+    let mut client = postgres::Config::new()
+        .host("127.0.0.1")
+        .port(forward.local_port)
+        .user(service.default_user())
+        .password(&password)
+        .dbname("postgres")
+        .connect(NoTls)
+        .map_err(|e| format!("connect to \"{cluster_name}\": {e}"))?;
+
+    let mut editor = DefaultEditor::new().map_err(|e| format!("terminal: {e}"))?;
+    let history = crate::config::fdb_home().join("sql_history");
+    let _ = editor.load_history(&history);
+
+    println!("Connected to \"{cluster_name}\" as {}. Type \\? for help, \\q to quit.", service.default_user());
+    let mut buffer = String::new();
+    loop {
+        let prompt = if buffer.is_empty() {
+            format!("{cluster_name}=# ")
+        } else {
+            format!("{cluster_name}-# ")
+        };
+        let line = match editor.readline(&prompt) {
+            Ok(l) => l,
+            Err(ReadlineError::Interrupted) => {
+                buffer.clear();
+                continue;
+            }
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(format!("read input: {e}")),
+        };
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(trimmed);
+
+        if buffer.is_empty() && trimmed.starts_with('\\') {
+            match helper_query(trimmed) {
+                Ok(Some(sql)) => run_query(&mut client, &sql),
+                Ok(None) if trimmed == "\\q" => break,
+                Ok(None) => println!("{HELP}"),
+                Err(e) => eprintln!("{e}"),
+            }
+            continue;
+        }
+
+        if !buffer.is_empty() {
+            buffer.push('\n');
+        }
+        buffer.push_str(&line);
+        if trimmed.ends_with(';') {
+            run_query(&mut client, &buffer);
+            buffer.clear();
+        }
+    }
+
+    if let Some(dir) = history.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if editor.save_history(&history).is_ok() {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(&history, std::fs::Permissions::from_mode(0o600));
+        }
+    }
+    Ok(())
+}
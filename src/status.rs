@@ -0,0 +1,183 @@
+//! One-screen summary of a cluster (`fdb status`): its KubeBlocks phase, pod readiness, volume
+//! usage, the external NodePort and which credential secrets exist. Every part is gathered on
+//! its own; a part that cannot be read is reported instead of failing the whole summary.
+
+use crate::cluster::{self, ClusterInfo};
+use crate::credentials;
+use crate::expose;
+use crate::kubeblocks;
+use crate::pods;
+use crate::resources;
+use crate::service::ServiceType;
+use crate::top;
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Debug, Serialize)]
+struct Pod {
+    name: String,
+    status: String,
+    ready: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Volume {
+    pvc: String,
+    capacity_bytes: f64,
+    /// None when the kubelet stats are not readable (no nodes/proxy access).
+    used_bytes: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+struct Endpoint {
+    host: Option<String>,
+    node_port: u16,
+}
+
+#[derive(Debug, Serialize)]
+struct Secret {
+    name: String,
+    present: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Status {
+    name: String,
+    engine: String,
+    phase: String,
+    termination_policy: String,
+    created: String,
+    #[serde(skip)]
+    age: Option<String>,
+    pods: Vec<Pod>,
+    volumes: Vec<Volume>,
+    /// None without an fdb NodePort Service (`--expose none`, or not exposed yet).
+    endpoint: Option<Endpoint>,
+    secrets: Vec<Secret>,
+    /// Parts that could not be read.
+    warnings: Vec<String>,
+}
+
+/// Secrets that may hold the cluster's credentials: the engine's account secrets for the
+/// installed KubeBlocks release, then fdb's app account secret.
+fn secret_names(kubectl: &Path, service: ServiceType, cluster_name: &str, kubeconfig: &Path) -> Vec<String> {
+    let mut names = kubeblocks::api(kubectl, kubeconfig).secret_names(service, cluster_name);
+    // Custom engines without a `secret` have no account secret.
+    names.retain(|n| !n.is_empty());
+    names.push(format!("{cluster_name}-fdb-app"));
+    names
+}
+
+/// Gather the summary of `info`'s cluster.
+pub fn collect(kubectl: &Path, service: ServiceType, info: &ClusterInfo, kubeconfig: &Path) -> Status {
+    let name = info.name.as_str();
+    let mut warnings = Vec::new();
+
+    let pods = match pods::cluster_pod_states(kubectl, name, kubeconfig) {
+        Ok(states) => states.into_iter().map(|p| Pod { name: p.name, status: p.status, ready: p.ready }).collect(),
+        Err(e) => {
+            warnings.push(format!("pods: {e}"));
+            Vec::new()
+        }
+    };
+
+    let volumes = match resources::cluster_pvcs(kubectl, name, kubeconfig) {
+        Ok(pvcs) => {
+            let used = resources::cluster_containers(kubectl, name, kubeconfig)
+                .and_then(|containers| top::volume_usage(kubectl, &containers, kubeconfig));
+            if let Err(e) = &used {
+                warnings.push(format!("volume usage (needs nodes/proxy access): {e}"));
+            }
+            let used = used.unwrap_or_default();
+            pvcs.into_iter()
+                .map(|(pvc, capacity)| Volume {
+                    used_bytes: used.iter().find(|u| u.pvc == pvc).map(|u| u.used),
+                    pvc,
+                    capacity_bytes: capacity,
+                })
+                .collect()
+        }
+        Err(e) => {
+            warnings.push(format!("volumes: {e}"));
+            Vec::new()
+        }
+    };
+
+    let endpoint = expose::existing_nodeport(kubectl, service, name, kubeconfig).ok().map(|node_port| Endpoint {
+        host: expose::server_host_from_kubeconfig(kubectl, kubeconfig).ok(),
+        node_port,
+    });
+
+    let secrets = secret_names(kubectl, service, name, kubeconfig)
+        .into_iter()
+        .filter_map(|secret| match credentials::secret_exists(kubectl, &secret, kubeconfig) {
+            Ok(present) => Some(Secret { name: secret, present }),
+            Err(e) => {
+                warnings.push(format!("secret {secret}: {e}"));
+                None
+            }
+        })
+        .collect();
+
+    Status {
+        name: name.to_string(),
+        engine: service.kbcli_name().to_string(),
+        phase: info.status.clone(),
+        termination_policy: info.termination_policy.clone(),
+        created: info.created.clone(),
+        age: cluster::created_at(info).map(|t| cluster::format_age(chrono::Utc::now() - t)),
+        pods,
+        volumes,
+        endpoint,
+        secrets,
+        warnings,
+    }
+}
+
+impl Status {
+    pub fn print(&self) {
+        let phase = if self.phase.is_empty() { "-" } else { &self.phase };
+        println!("Cluster \"{}\" ({}): {phase}", self.name, self.engine);
+        let age = self.age.as_ref().map(|a| format!(" ({a} ago)")).unwrap_or_default();
+        println!("  {:<18} {}{age}", "Created:", self.created);
+        println!("  {:<18} {}", "Termination:", self.termination_policy);
+
+        let ready = self.pods.iter().filter(|p| p.ready).count();
+        println!("  {:<18} {ready}/{} ready", "Pods:", self.pods.len());
+        for pod in &self.pods {
+            println!("    {:<40} {}", pod.name, pod.status);
+        }
+
+        if self.volumes.is_empty() {
+            println!("  {:<18} none", "Volumes:");
+        } else {
+            println!("  Volumes:");
+        }
+        for v in &self.volumes {
+            let usage = match v.used_bytes {
+                Some(used) if v.capacity_bytes > 0.0 => format!(
+                    "{} / {} ({:.0}%)",
+                    resources::format_bytes(used),
+                    resources::format_bytes(v.capacity_bytes),
+                    used / v.capacity_bytes * 100.0
+                ),
+                _ => resources::format_bytes(v.capacity_bytes),
+            };
+            println!("    {:<40} {usage}", v.pvc);
+        }
+
+        match &self.endpoint {
+            Some(Endpoint { host: Some(host), node_port }) => println!("  {:<18} {host}:{node_port}", "NodePort:"),
+            Some(Endpoint { host: None, node_port }) => println!("  {:<18} {node_port}", "NodePort:"),
+            None => println!("  {:<18} none (reach the cluster with `fdb connect` or port-forward)", "NodePort:"),
+        }
+
+        println!("  Secrets:");
+        for s in &self.secrets {
+            println!("    {:<40} {}", s.name, if s.present { "present" } else { "missing" });
+        }
+        for w in &self.warnings {
+            eprintln!("warning: {w}");
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! "Did you mean ...?" suggestions for mistyped subcommands and cluster names.
+
+/// Levenshtein distance in characters.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            row.push((prev[j] + cost).min(prev[j + 1] + 1).min(row[j] + 1));
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+/// Closest candidate to `input`: within a few edits, or containing it (`pg` -> `mydb-pg`).
+pub fn did_you_mean<'a>(input: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let input_lower = input.to_lowercase();
+    let max = (input.chars().count() / 3).max(2);
+    candidates
+        .into_iter()
+        .filter(|c| *c != input)
+        .filter_map(|c| {
+            let d = distance(&input_lower, &c.to_lowercase());
+            if d <= max {
+                Some((d, c))
+            } else if input_lower.len() >= 2 && c.to_lowercase().contains(&input_lower) {
+                Some((max + 1, c))
+            } else {
+                None
+            }
+        })
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggests_close_or_containing_names() {
+        let names = ["mydb-pg", "cache", "orders"];
+        assert_eq!(did_you_mean("mydb-gp", names), Some("mydb-pg"));
+        assert_eq!(did_you_mean("cahce", names), Some("cache"));
+        assert_eq!(did_you_mean("pg", names), Some("mydb-pg"));
+        assert_eq!(did_you_mean("inventory", names), None);
+        assert_eq!(did_you_mean("lsit", ["list", "info", "delete"]), Some("list"));
+    }
+}
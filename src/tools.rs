@@ -1,20 +1,29 @@
 //! Resolve and optionally download kubectl and kbcli to ~/.fdb/bin.
 
+use crate::error::{self, Error, ExitCode};
 use nanospinner::Spinner;
 use std::fs;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 
 const KUBECTL_STABLE_URL: &str = "https://dl.k8s.io/release/stable.txt";
 const GITHUB_LATEST_API: &str = "https://api.github.com/repos/apecloud/kbcli/releases/latest";
 
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// `--offline`: never download tools or look up releases.
+pub fn set_offline(on: bool) {
+    OFFLINE.store(on, Ordering::Relaxed);
+}
+
+fn offline(config: &crate::config::ToolsConfig) -> bool {
+    OFFLINE.load(Ordering::Relaxed) || config.offline
+}
+
 /// Directory for fdb-managed binaries: $FDB_HOME/bin or $HOME/.fdb/bin.
 pub fn fdb_bin_dir() -> PathBuf {
-    if let Ok(home) = std::env::var("FDB_HOME") {
-        return PathBuf::from(home).join("bin");
-    }
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(home).join(".fdb").join("bin")
+    crate::config::fdb_home().join("bin")
 }
 
 /// Look for executable in PATH, then in ~/.fdb/bin.
@@ -43,22 +52,61 @@ pub fn resolve_kbcli() -> Result<PathBuf, String> {
     resolve_tool("kbcli").ok_or_else(|| "kbcli not found in PATH or ~/.fdb/bin".to_string())
 }
 
-/// Ensure kubectl and kbcli exist; download to ~/.fdb/bin if missing.
+/// Ensure both tools are available and return (kubectl, kbcli).
+pub fn prepare() -> Result<(PathBuf, PathBuf), Error> {
+    let missing = error::code(ExitCode::ToolsMissing);
+    ensure_tools().map_err(&missing)?;
+    Ok((resolve_kubectl().map_err(&missing)?, resolve_kbcli().map_err(&missing)?))
+}
+
+/// Ensure kubectl and kbcli exist. Missing ones are installed to ~/.fdb/bin from the local
+/// files in `[tools]` when configured, otherwise downloaded unless offline.
 pub fn ensure_tools() -> Result<(), String> {
     let need_kubectl = resolve_tool("kubectl").is_none();
     let need_kbcli = resolve_tool("kbcli").is_none();
     if !need_kubectl && !need_kbcli {
         return Ok(());
     }
+    let config = crate::config::load_tools_config();
     let bin_dir = fdb_bin_dir();
     fs::create_dir_all(&bin_dir).map_err(|e| format!("create {:?}: {e}", bin_dir))?;
 
     if need_kubectl {
-        download_kubectl(&bin_dir)?;
+        match &config.kubectl_file {
+            Some(file) => install_local(Path::new(file), &bin_dir.join("kubectl"))?,
+            None if offline(&config) => return Err(offline_missing("kubectl", "kubectl-file")),
+            None => download_kubectl(&bin_dir)?,
+        }
     }
     if need_kbcli {
-        download_kbcli(&bin_dir)?;
+        match &config.kbcli_file {
+            Some(file) if file.ends_with(".tar.gz") || file.ends_with(".tgz") => {
+                extract_kbcli_from_tar_gz(Path::new(file), &bin_dir)?
+            }
+            Some(file) => install_local(Path::new(file), &bin_dir.join("kbcli"))?,
+            None if offline(&config) => return Err(offline_missing("kbcli", "kbcli-file")),
+            None => download_kbcli(&bin_dir)?,
+        }
+    }
+    Ok(())
+}
+
+fn offline_missing(tool: &str, key: &str) -> String {
+    format!(
+        "{tool} not found in PATH or ~/.fdb/bin and offline mode forbids downloading it; \
+         install it or set [tools] {key} in fdb.toml"
+    )
+}
+
+/// Copy a local binary into place and make it executable.
+fn install_local(source: &Path, dest: &Path) -> Result<(), String> {
+    fs::copy(source, dest).map_err(|e| format!("copy {} to {}: {e}", source.display(), dest.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(dest, fs::Permissions::from_mode(0o755)).map_err(|e| format!("chmod: {e}"))?;
     }
+    println!("Installed {} from {}", dest.display(), source.display());
     Ok(())
 }
 
@@ -216,3 +264,241 @@ fn extract_kbcli_from_tar_gz(tar_gz_path: &Path, bin_dir: &Path) -> Result<(), S
     }
     Err("kbcli binary not found inside archive".to_string())
 }
+
+/// Version a binary reports: kubectl's client `gitVersion`, the `kbcli:` line of `kbcli version`.
+/// This doubles as the smoke test for a freshly downloaded binary.
+fn installed_version(tool: &str, path: &Path) -> Result<String, String> {
+    let args: &[&str] = match tool {
+        "kubectl" => &["version", "--client", "-o", "json"],
+        _ => &["version"],
+    };
+    let output = std::process::Command::new(path)
+        .args(args)
+        .output()
+        .map_err(|e| format!("run {} version: {e}", path.display()))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let version = match tool {
+        "kubectl" => serde_json::from_str::<serde_json::Value>(&stdout)
+            .ok()
+            .and_then(|v| v.pointer("/clientVersion/gitVersion")?.as_str().map(str::to_string)),
+        _ => stdout
+            .lines()
+            .find_map(|l| l.trim().strip_prefix("kbcli:"))
+            .map(|v| v.trim().to_string()),
+    };
+    match version {
+        Some(v) if output.status.success() && !v.is_empty() => Ok(v),
+        _ => Err(format!(
+            "{} version failed: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+    }
+}
+
+fn same_version(a: &str, b: &str) -> bool {
+    a.trim_start_matches('v') == b.trim_start_matches('v')
+}
+
+fn http_text(url: &str) -> Result<String, String> {
+    ureq::get(url)
+        .set("User-Agent", "fdb-cli")
+        .call()
+        .map_err(|e| format!("GET {url}: {e}"))?
+        .into_string()
+        .map_err(|e| format!("read {url}: {e}"))
+}
+
+fn sha256_file(path: &Path) -> Result<String, String> {
+    use sha2::{Digest, Sha256};
+    let mut file = fs::File::open(path).map_err(|e| format!("open {}: {e}", path.display()))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| format!("read {}: {e}", path.display()))?;
+    Ok(hasher.finalize().iter().map(|b| format!("{b:02x}")).collect())
+}
+
+fn verify_checksum(path: &Path, expected: &str) -> Result<(), String> {
+    let actual = sha256_file(path)?;
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        let _ = fs::remove_file(path);
+        return Err(format!(
+            "checksum mismatch for {}: expected {}, got {actual}",
+            path.display(),
+            expected.trim()
+        ));
+    }
+    Ok(())
+}
+
+/// SHA-256 for `file` from a checksum listing: either a bare hash (`<file>.sha256`) or
+/// `<hash>  <file>` lines (`checksums.txt`).
+fn find_checksum(listing: &str, file: &str) -> Option<String> {
+    let entries: Vec<Vec<&str>> = listing
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>())
+        .filter(|words| !words.is_empty())
+        .collect();
+    if let [only] = entries.as_slice()
+        && only.len() == 1
+    {
+        return Some(only[0].to_string());
+    }
+    entries
+        .iter()
+        .find(|words| words.len() >= 2 && words[1].trim_start_matches('*').rsplit('/').next() == Some(file))
+        .map(|words| words[0].to_string())
+}
+
+/// Latest stable kubectl release, e.g. "v1.31.2".
+fn latest_kubectl() -> Result<String, String> {
+    Ok(http_text(KUBECTL_STABLE_URL)?.trim().to_string())
+}
+
+fn kbcli_release(tag: Option<&str>) -> Result<serde_json::Value, String> {
+    let url = match tag {
+        Some(t) => format!("https://api.github.com/repos/apecloud/kbcli/releases/tags/{t}"),
+        None => GITHUB_LATEST_API.to_string(),
+    };
+    let body = ureq::get(&url)
+        .set("Accept", "application/vnd.github.v3+json")
+        .set("User-Agent", "fdb-cli")
+        .call()
+        .map_err(|e| format!("GET {url}: {e}"))?
+        .into_string()
+        .map_err(|e| format!("read {url}: {e}"))?;
+    serde_json::from_str(&body).map_err(|e| format!("parse kbcli release: {e}"))
+}
+
+/// Download kubectl `version` to `<bin>/kubectl.new` and check it against the published
+/// `.sha256`.
+fn stage_kubectl(bin_dir: &Path, version: &str) -> Result<PathBuf, String> {
+    let (os, arch) = target_os_arch();
+    let url = format!("https://dl.k8s.io/release/{version}/bin/{os}/{arch}/kubectl");
+    let staged = bin_dir.join("kubectl.new");
+    download_with_progress(&url, &staged, "kubectl", None)?;
+    let listing = http_text(&format!("{url}.sha256"))?;
+    let expected = find_checksum(&listing, "kubectl").ok_or("no checksum in kubectl .sha256 file")?;
+    verify_checksum(&staged, &expected)?;
+    Ok(staged)
+}
+
+/// Download the kbcli archive of `release`, verify it against the checksum asset of the
+/// release and unpack it to `<bin>/kbcli.new`.
+fn stage_kbcli(bin_dir: &Path, release: &serde_json::Value) -> Result<PathBuf, String> {
+    let tag = release["tag_name"].as_str().ok_or("kbcli release without tag_name")?;
+    let (os, arch) = target_os_arch();
+    let archive_name = format!("kbcli-{os}-{arch}-{tag}.tar.gz");
+    let assets: Vec<(&str, &str)> = release["assets"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|a| Some((a["name"].as_str()?, a["browser_download_url"].as_str()?)))
+        .collect();
+    let url = assets
+        .iter()
+        .find(|(name, _)| *name == archive_name)
+        .map(|(_, url)| url.to_string())
+        .unwrap_or_else(|| format!("https://github.com/apecloud/kbcli/releases/download/{tag}/{archive_name}"));
+    let sums_url = assets
+        .iter()
+        .find(|(name, _)| *name == format!("{archive_name}.sha256"))
+        .or_else(|| assets.iter().find(|(name, _)| name.contains("checksum")))
+        .map(|(_, url)| url.to_string())
+        .ok_or_else(|| format!("kbcli {tag} publishes no checksum for {archive_name}; not upgrading"))?;
+
+    let archive = bin_dir.join("kbcli-upgrade.tar.gz");
+    download_with_progress(&url, &archive, "kbcli", None)?;
+    let expected = find_checksum(&http_text(&sums_url)?, &archive_name)
+        .ok_or_else(|| format!("{archive_name} not listed in {sums_url}"))?;
+    verify_checksum(&archive, &expected)?;
+
+    let staging = bin_dir.join(".kbcli-upgrade");
+    let _ = fs::remove_dir_all(&staging);
+    fs::create_dir_all(&staging).map_err(|e| format!("create {}: {e}", staging.display()))?;
+    let extracted = extract_kbcli_from_tar_gz(&archive, &staging);
+    let _ = fs::remove_file(&archive);
+    extracted?;
+    let staged = bin_dir.join("kbcli.new");
+    fs::rename(staging.join("kbcli"), &staged).map_err(|e| format!("move kbcli: {e}"))?;
+    let _ = fs::remove_dir_all(&staging);
+    Ok(staged)
+}
+
+/// Swap a staged binary in once it passes the smoke test; the old binary stays as
+/// `<tool>.old` until then and is restored if the swap fails.
+fn install_staged(tool: &str, staged: &Path, current: &Path) -> Result<String, String> {
+    let version = match installed_version(tool, staged) {
+        Ok(v) => v,
+        Err(e) => {
+            let _ = fs::remove_file(staged);
+            return Err(format!("new {tool} failed the smoke test, keeping the old one: {e}"));
+        }
+    };
+    let backup = current.with_extension("old");
+    if current.exists() {
+        fs::rename(current, &backup).map_err(|e| format!("back up {}: {e}", current.display()))?;
+    }
+    if let Err(e) = fs::rename(staged, current) {
+        let _ = fs::rename(&backup, current);
+        return Err(format!("install {tool}: {e}"));
+    }
+    let _ = fs::remove_file(&backup);
+    Ok(version)
+}
+
+/// Compare the kubectl/kbcli in ~/.fdb/bin with the pinned (`[tools]` in fdb.toml) or latest
+/// versions and upgrade the ones that differ. Binaries found elsewhere in PATH are left alone.
+/// With `check_only`, just report.
+pub fn upgrade(check_only: bool) -> Result<(), String> {
+    let bin_dir = fdb_bin_dir();
+    let config = crate::config::load_tools_config();
+    if offline(&config) {
+        return Err("fdb tools upgrade needs network access and cannot run offline".to_string());
+    }
+    let mut outdated = 0;
+    for (tool, pin) in [("kubectl", config.kubectl), ("kbcli", config.kbcli)] {
+        let managed = bin_dir.join(tool);
+        if let Some(found) = resolve_tool(tool)
+            && found != managed
+        {
+            println!("{tool}: {} is not managed by fdb; skipping", found.display());
+            continue;
+        }
+        let current = if managed.is_file() { installed_version(tool, &managed).ok() } else { None };
+        let release = match tool {
+            "kbcli" => Some(kbcli_release(pin.as_deref())?),
+            _ => None,
+        };
+        let target = match (&release, pin) {
+            (Some(r), _) => r["tag_name"].as_str().unwrap_or_default().to_string(),
+            (None, Some(p)) => p,
+            (None, None) => latest_kubectl()?,
+        };
+        let current_label = current.clone().unwrap_or_else(|| "not installed".to_string());
+        if current.as_deref().is_some_and(|c| same_version(c, &target)) {
+            println!("{tool}: {current_label} is up to date");
+            continue;
+        }
+        outdated += 1;
+        if check_only {
+            println!("{tool}: {current_label} -> {target} available");
+            continue;
+        }
+        fs::create_dir_all(&bin_dir).map_err(|e| format!("create {}: {e}", bin_dir.display()))?;
+        let staged = match &release {
+            Some(r) => stage_kbcli(&bin_dir, r)?,
+            None => stage_kubectl(&bin_dir, &target)?,
+        };
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&staged, fs::Permissions::from_mode(0o755)).map_err(|e| format!("chmod: {e}"))?;
+        }
+        let installed = install_staged(tool, &staged, &managed)?;
+        println!("{tool}: {current_label} -> {installed}");
+    }
+    if check_only && outdated > 0 {
+        println!("Run `fdb tools upgrade` to upgrade.");
+    }
+    Ok(())
+}
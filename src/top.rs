@@ -0,0 +1,181 @@
+//! Live CPU/memory usage and volume utilization of a cluster's pods (`fdb top`).
+
+use crate::exec::CommandExt;
+use crate::resources::{self, ContainerSpec};
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::process::Command;
+
+const NAMESPACE: &str = "default";
+/// Usage at or above this share of the limit is flagged.
+const PRESSURE_RATIO: f64 = 0.9;
+
+/// (pod, container) -> (cpu millicores, memory bytes).
+type Usage = HashMap<(String, String), (f64, f64)>;
+
+/// Current usage of the cluster's containers from metrics-server.
+fn container_usage(kubectl: &Path, cluster_name: &str, kubeconfig: &Path) -> Result<Usage, String> {
+    let output = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args([
+            "top",
+            "pod",
+            "-n",
+            NAMESPACE,
+            "-l",
+            &format!("app.kubernetes.io/instance={cluster_name}"),
+            "--containers",
+            "--no-headers",
+        ])
+        .output_checked()?;
+    let mut usage = HashMap::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if let [pod, container, cpu, memory] = fields[..]
+            && let (Some(cpu), Some(memory)) = (resources::parse_cpu(cpu), resources::parse_bytes(memory))
+        {
+            usage.insert((pod.to_string(), container.to_string()), (cpu, memory));
+        }
+    }
+    Ok(usage)
+}
+
+#[derive(Deserialize)]
+struct StatsSummary {
+    #[serde(default)]
+    pods: Vec<PodStats>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PodStats {
+    pod_ref: PodRef,
+    #[serde(default)]
+    volume: Vec<VolumeStats>,
+}
+
+#[derive(Deserialize)]
+struct PodRef {
+    name: String,
+    namespace: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VolumeStats {
+    #[serde(default)]
+    pvc_ref: Option<PvcRef>,
+    #[serde(default)]
+    used_bytes: Option<f64>,
+    #[serde(default)]
+    capacity_bytes: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct PvcRef {
+    name: String,
+}
+
+/// One PVC's utilization from the kubelet stats summary.
+pub struct VolumeUsage {
+    pub pvc: String,
+    pub used: f64,
+    pub capacity: f64,
+}
+
+/// PVC usage of the given pods, read from each node's kubelet `/stats/summary`.
+pub fn volume_usage(kubectl: &Path, containers: &[ContainerSpec], kubeconfig: &Path) -> Result<Vec<VolumeUsage>, String> {
+    let pods: BTreeSet<&str> = containers.iter().map(|c| c.pod.as_str()).collect();
+    let nodes: BTreeSet<&str> = containers.iter().filter_map(|c| c.node.as_deref()).collect();
+    let mut volumes = Vec::new();
+    for node in nodes {
+        let output = Command::new(kubectl)
+            .arg("--kubeconfig")
+            .arg(kubeconfig)
+            .args(["get", "--raw", &format!("/api/v1/nodes/{node}/proxy/stats/summary")])
+            .output_checked()?;
+        let summary: StatsSummary =
+            serde_json::from_slice(&output.stdout).map_err(|e| format!("parse stats of node {node}: {e}"))?;
+        for pod in summary.pods {
+            if pod.pod_ref.namespace != NAMESPACE || !pods.contains(pod.pod_ref.name.as_str()) {
+                continue;
+            }
+            for v in pod.volume {
+                if let (Some(pvc), Some(used), Some(capacity)) = (v.pvc_ref, v.used_bytes, v.capacity_bytes) {
+                    volumes.push(VolumeUsage {
+                        pvc: pvc.name,
+                        used,
+                        capacity,
+                    });
+                }
+            }
+        }
+    }
+    volumes.sort_by(|a, b| a.pvc.cmp(&b.pvc));
+    Ok(volumes)
+}
+
+/// "used / request / limit", with a marker when usage is close to the limit.
+fn usage_cell(used: Option<f64>, request: Option<f64>, limit: Option<f64>, format: fn(f64) -> String) -> String {
+    let show = |v: Option<f64>| v.map_or("-".to_string(), format);
+    let mut cell = format!("{} / {} / {}", show(used), show(request), show(limit));
+    if let (Some(used), Some(limit)) = (used, limit)
+        && limit > 0.0
+        && used / limit >= PRESSURE_RATIO
+    {
+        cell.push_str("  (near limit)");
+    }
+    cell
+}
+
+/// Print per-container usage next to requests/limits, then PVC utilization.
+pub fn show(kubectl: &Path, cluster_name: &str, kubeconfig: &Path) -> Result<(), String> {
+    let containers = resources::cluster_containers(kubectl, cluster_name, kubeconfig)?;
+    if containers.is_empty() {
+        return Err(format!("no pods found for cluster \"{cluster_name}\""));
+    }
+    let usage = match container_usage(kubectl, cluster_name, kubeconfig) {
+        Ok(u) => u,
+        Err(e) => {
+            eprintln!("warning: live usage unavailable (is metrics-server installed?): {e}");
+            HashMap::new()
+        }
+    };
+
+    println!(
+        "{:<32} {:<16} {:<36} MEMORY (used / request / limit)",
+        "POD", "CONTAINER", "CPU (used / request / limit)"
+    );
+    for c in &containers {
+        let used = usage.get(&(c.pod.clone(), c.container.clone()));
+        println!(
+            "{:<32} {:<16} {:<36} {}",
+            c.pod,
+            c.container,
+            usage_cell(used.map(|u| u.0), c.cpu_request, c.cpu_limit, resources::format_cpu),
+            usage_cell(used.map(|u| u.1), c.memory_request, c.memory_limit, resources::format_bytes),
+        );
+    }
+
+    println!();
+    match volume_usage(kubectl, &containers, kubeconfig) {
+        Ok(volumes) if volumes.is_empty() => println!("No persistent volumes."),
+        Ok(volumes) => {
+            println!("{:<40} {:>10} {:>10} {:>5}", "PVC", "USED", "CAPACITY", "USE%");
+            for v in volumes {
+                let percent = if v.capacity > 0.0 { v.used / v.capacity * 100.0 } else { 0.0 };
+                println!(
+                    "{:<40} {:>10} {:>10} {:>4.0}%",
+                    v.pvc,
+                    resources::format_bytes(v.used),
+                    resources::format_bytes(v.capacity),
+                    percent
+                );
+            }
+        }
+        Err(e) => eprintln!("warning: volume usage unavailable (needs nodes/proxy access): {e}"),
+    }
+    Ok(())
+}
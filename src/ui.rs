@@ -0,0 +1,230 @@
+//! Full-screen dashboard of clusters with live status (`fdb ui`).
+
+use crate::cluster::{self, ClusterInfo, ListColumn};
+use crate::portforward;
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Table, TableState};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+const REFRESH: Duration = Duration::from_secs(3);
+const COLUMNS: [ListColumn; 5] = [
+    ListColumn::Name,
+    ListColumn::Engine,
+    ListColumn::Status,
+    ListColumn::Age,
+    ListColumn::Termination,
+];
+const HELP: &str = "↑/↓ select  i info  l logs  p port-forward  d delete  r refresh  q quit";
+
+struct App {
+    clusters: Vec<ClusterInfo>,
+    table: TableState,
+    /// Last refresh error or action result, shown above the help line.
+    message: String,
+    refreshed: Option<Instant>,
+}
+
+impl App {
+    fn selected(&self) -> Option<&ClusterInfo> {
+        self.table.selected().and_then(|i| self.clusters.get(i))
+    }
+
+    fn refresh(&mut self, kbcli: &Path, kubeconfig: &Path) {
+        match cluster::list_cluster_infos(kbcli, None, kubeconfig) {
+            Ok(clusters) => {
+                let selected = self.selected().map(|c| c.name.clone());
+                self.clusters = clusters;
+                let index = selected
+                    .and_then(|name| self.clusters.iter().position(|c| c.name == name))
+                    .or(if self.clusters.is_empty() { None } else { Some(0) });
+                self.table.select(index);
+                if self.message.starts_with("refresh failed") {
+                    self.message.clear();
+                }
+            }
+            Err(e) => self.message = format!("refresh failed: {e}"),
+        }
+        self.refreshed = Some(Instant::now());
+    }
+
+    fn step(&mut self, delta: isize) {
+        if self.clusters.is_empty() {
+            return;
+        }
+        let last = self.clusters.len() as isize - 1;
+        let current = self.table.selected().unwrap_or(0) as isize;
+        self.table.select(Some((current + delta).clamp(0, last) as usize));
+    }
+}
+
+fn status_style(status: &str) -> Style {
+    match status {
+        "Running" => Style::default().fg(Color::Green),
+        "Failed" | "Abnormal" => Style::default().fg(Color::Red),
+        "Deleting" | "Stopped" => Style::default().fg(Color::DarkGray),
+        _ => Style::default().fg(Color::Yellow),
+    }
+}
+
+fn draw(terminal: &mut DefaultTerminal, app: &mut App, context: &str) -> Result<(), String> {
+    let now = chrono::Utc::now();
+    terminal
+        .draw(|frame| {
+            let [main, footer] = Layout::vertical([Constraint::Min(3), Constraint::Length(2)]).areas(frame.area());
+            let rows = app.clusters.iter().map(|c| {
+                Row::new(COLUMNS.iter().map(|col| col.value(c, now, None))).style(status_style(&c.status))
+            });
+            let widths = COLUMNS.map(|col| Constraint::Min(col.header().1.max(col.header().0.len()) as u16));
+            let table = Table::new(rows, widths)
+                .header(Row::new(COLUMNS.map(|c| c.header().0)).style(Style::default().add_modifier(Modifier::BOLD)))
+                .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                .block(Block::bordered().title(format!(" fdb — {context} — {} clusters ", app.clusters.len())));
+            frame.render_stateful_widget(table, main, &mut app.table);
+            let footer_text = vec![Line::from(app.message.as_str()), Line::from(HELP)];
+            frame.render_widget(Paragraph::new(footer_text), footer);
+        })
+        .map_err(|e| format!("draw: {e}"))?;
+    Ok(())
+}
+
+/// Leave the full-screen view, run `action` in the normal terminal, wait for Enter, come back.
+fn suspended(terminal: &mut DefaultTerminal, action: impl FnOnce() -> Result<(), String>) -> String {
+    ratatui::restore();
+    let result = action();
+    if let Err(ref e) = result {
+        eprintln!("error: {e}");
+    }
+    print!("\nPress Enter to return to the dashboard...");
+    let _ = io::stdout().flush();
+    let _ = io::stdin().lock().read_line(&mut String::new());
+    *terminal = ratatui::init();
+    result.err().unwrap_or_default()
+}
+
+/// Run this fdb binary with `args` in the foreground (info, delete with its confirmation).
+fn run_fdb(args: &[&str], kubeconfig: &Path) -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("locate fdb: {e}"))?;
+    let status = Command::new(exe)
+        .args(args)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .status()
+        .map_err(|e| format!("run fdb {}: {e}", args.join(" ")))?;
+    if !status.success() {
+        return Err(format!("fdb {} failed", args.join(" ")));
+    }
+    Ok(())
+}
+
+/// Last 200 log lines of every pod of the cluster.
+fn show_logs(kubectl: &Path, name: &str, kubeconfig: &Path) -> Result<(), String> {
+    let status = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args([
+            "logs",
+            "-n",
+            "default",
+            "-l",
+            &format!("app.kubernetes.io/instance={name}"),
+            "--all-containers",
+            "--prefix",
+            "--tail",
+            "200",
+        ])
+        .status()
+        .map_err(|e| format!("kubectl logs: {e}"))?;
+    if !status.success() {
+        return Err("kubectl logs failed".to_string());
+    }
+    Ok(())
+}
+
+/// Forward the cluster's service to a local port until Enter is pressed.
+fn port_forward(kubectl: &Path, cluster: &ClusterInfo, kubeconfig: &Path) -> Result<(), String> {
+    let service = cluster::service_from_definition(&cluster.definition)?;
+    let forward = portforward::start_port_forward(
+        kubectl,
+        &format!("svc/{}-{}", cluster.name, service.component_name()),
+        service.default_port(),
+        None,
+        kubeconfig,
+    )?;
+    println!(
+        "Forwarding 127.0.0.1:{} -> {} port {}. Press Enter to stop.",
+        forward.local_port,
+        cluster.name,
+        service.default_port()
+    );
+    let _ = io::stdin().lock().read_line(&mut String::new());
+    drop(forward);
+    Ok(())
+}
+
+/// Dashboard of all clusters, refreshed every few seconds, with keys for common actions.
+pub fn run(kubectl: &Path, kbcli: &Path, context: &str, kubeconfig: &Path) -> Result<(), String> {
+    let mut app = App {
+        clusters: Vec::new(),
+        table: TableState::default(),
+        message: String::new(),
+        refreshed: None,
+    };
+    app.refresh(kbcli, kubeconfig);
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut app, kubectl, kbcli, context, kubeconfig);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+    kubectl: &Path,
+    kbcli: &Path,
+    context: &str,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    loop {
+        if app.refreshed.is_none_or(|t| t.elapsed() >= REFRESH) {
+            app.refresh(kbcli, kubeconfig);
+        }
+        draw(terminal, app, context)?;
+        if !event::poll(Duration::from_millis(250)).map_err(|e| format!("read input: {e}"))? {
+            continue;
+        }
+        let Event::Key(key) = event::read().map_err(|e| format!("read input: {e}"))? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let selected = app.selected().cloned();
+        match (key.code, selected) {
+            (KeyCode::Char('q') | KeyCode::Esc, _) => return Ok(()),
+            (KeyCode::Down | KeyCode::Char('j'), _) => app.step(1),
+            (KeyCode::Up | KeyCode::Char('k'), _) => app.step(-1),
+            (KeyCode::Char('r'), _) => app.refresh(kbcli, kubeconfig),
+            (KeyCode::Char('i'), Some(c)) => {
+                app.message = suspended(terminal, || run_fdb(&["info", &c.name], kubeconfig));
+            }
+            (KeyCode::Char('l'), Some(c)) => {
+                app.message = suspended(terminal, || show_logs(kubectl, &c.name, kubeconfig));
+            }
+            (KeyCode::Char('p'), Some(c)) => {
+                app.message = suspended(terminal, || port_forward(kubectl, &c, kubeconfig));
+            }
+            (KeyCode::Char('d'), Some(c)) => {
+                app.message = suspended(terminal, || run_fdb(&["delete", &c.name], kubeconfig));
+                app.refresh(kbcli, kubeconfig);
+            }
+            _ => {}
+        }
+    }
+}
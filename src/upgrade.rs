@@ -0,0 +1,73 @@
+//! Move a cluster to another engine version (`fdb upgrade`) with a KubeBlocks upgrade
+//! OpsRequest, after checking the version against the ones the operator offers.
+
+use crate::cluster;
+use crate::drift;
+use crate::error::{self, Error, ExitCode};
+use crate::exec::CommandExt;
+use crate::kubeblocks;
+use crate::progress::Progress;
+use crate::service::ServiceType;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+/// Upgrade the cluster's main component to `version`, wait for the OpsRequest and for the
+/// cluster to be Running, then print the version it runs.
+pub fn run(
+    kbcli: &Path,
+    kubectl: &Path,
+    service: ServiceType,
+    name: &str,
+    version: &str,
+    kubeconfig: &Path,
+    timeout: Duration,
+) -> Result<(), Error> {
+    let api = kubeblocks::api(kubectl, kubeconfig);
+    let target = api.service_version(service, version);
+    let available = api.available_versions(kubectl, service, kubeconfig).map_err(error::code(ExitCode::Kbcli))?;
+    if available.is_empty() {
+        return Err(Error::new(
+            ExitCode::Kbcli,
+            format!("KubeBlocks lists no versions for {}; is the addon installed?", service.kbcli_name()),
+        ));
+    }
+    if !available.contains(&target) {
+        return Err(Error::new(
+            ExitCode::Usage,
+            format!(
+                "version {target} is not available for {}; available: {}",
+                service.kbcli_name(),
+                available.join(", ")
+            ),
+        ));
+    }
+
+    let live = drift::live(kubectl, service, name, kubeconfig)?.ok_or_else(|| format!("cluster \"{name}\" not found"))?;
+    let current = live.spec.version.clone().unwrap_or_else(|| "?".to_string());
+    if current == target {
+        println!("\"{name}\" already runs {target}.");
+        return Ok(());
+    }
+    cluster::confirm_production(kubectl, name, "upgrade", kubeconfig)?;
+
+    println!("Upgrading \"{name}\" ({}) from {current} to {target}", live.component);
+    let output = Command::new(kbcli)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(api.upgrade_args(name, &live.component, &target))
+        .output_checked()
+        .map_err(error::code(ExitCode::Kbcli))?;
+    let ops = cluster::ops_request_name(&String::from_utf8_lossy(&output.stdout));
+    let mut progress = Progress::new();
+    cluster::wait_for_ops(kubectl, ops.as_slice(), kubeconfig, timeout, &mut progress)?;
+    cluster::wait_until_running(kbcli, kubectl, name, kubeconfig, timeout, &mut progress)?;
+    progress.finish();
+
+    let now = drift::live(kubectl, service, name, kubeconfig)?.and_then(|l| l.spec.version);
+    match now {
+        Some(v) if v != target => eprintln!("warning: \"{name}\" reports version {v} after the upgrade to {target}"),
+        _ => println!("Upgraded \"{name}\" to {target}."),
+    }
+    Ok(())
+}
@@ -0,0 +1,193 @@
+//! Open an engine's web UI in the browser (`fdb open`): the RabbitMQ management UI, the Qdrant
+//! dashboard, the MinIO console, Neo4j Browser, or pgAdmin for PostgreSQL when one is deployed in the namespace.
+
+use crate::accounts;
+use crate::credentials;
+use crate::exec::CommandExt;
+use crate::expose;
+use crate::portforward;
+use crate::redact;
+use crate::service::ServiceType;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+const NAMESPACE: &str = "default";
+/// Labels the pgAdmin charts put on their Service.
+const PGADMIN_SELECTOR: &str = "app.kubernetes.io/name in (pgadmin, pgadmin4)";
+
+/// A UI to reach: the Service serving it and how to log in.
+struct WebUi {
+    title: &'static str,
+    svc: String,
+    port: u16,
+    path: &'static str,
+    /// NodePort already serving the UI, if any.
+    node_port: Option<u16>,
+    /// Login printed for the user.
+    login: Vec<(&'static str, String)>,
+    /// URL fragment that logs the browser in.
+    auto_login: Option<String>,
+}
+
+/// First pgAdmin Service in the namespace: (name, port, nodePort).
+fn find_pgadmin(kubectl: &Path, kubeconfig: &Path) -> Result<Option<(String, u16, Option<u16>)>, String> {
+    let out = Command::new(kubectl)
+        .arg("--kubeconfig")
+        .arg(kubeconfig)
+        .args(["get", "svc", "-n", NAMESPACE, "-l", PGADMIN_SELECTOR])
+        .args(["-o", "jsonpath={.items[0].metadata.name} {.items[0].spec.ports[0].port} {.items[0].spec.ports[0].nodePort}"])
+        .output_checked()?;
+    let out = String::from_utf8_lossy(&out.stdout);
+    let mut fields = out.split_whitespace();
+    let (Some(name), Some(port)) = (fields.next(), fields.next().and_then(|p| p.parse().ok())) else {
+        return Ok(None);
+    };
+    Ok(Some((name.to_string(), port, fields.next().and_then(|p| p.parse().ok()))))
+}
+
+fn web_ui(kubectl: &Path, service: ServiceType, cluster_name: &str, kubeconfig: &Path) -> Result<WebUi, String> {
+    let password = credentials::get_password(kubectl, service, cluster_name, kubeconfig)?;
+    let user = service.default_user().to_string();
+    match service {
+        ServiceType::RabbitMQ => {
+            let password = password.unwrap_or_default();
+            Ok(WebUi {
+                title: "RabbitMQ management",
+                svc: format!("{cluster_name}-rabbitmq"),
+                port: accounts::RABBITMQ_MANAGEMENT_PORT,
+                path: "/",
+                node_port: None,
+                auto_login: Some(format!(
+                    "#/login/{}/{}",
+                    accounts::percent_encode(&user),
+                    accounts::percent_encode(&password)
+                )),
+                login: vec![("User", user), ("Password", redact::secret(&password))],
+            })
+        }
+        ServiceType::Qdrant => Ok(WebUi {
+            title: "Qdrant dashboard",
+            svc: format!("{cluster_name}-qdrant"),
+            port: service.default_port(),
+            path: "/dashboard",
+            node_port: expose::existing_nodeport(kubectl, service, cluster_name, kubeconfig).ok(),
+            auto_login: None,
+            login: password.map(|p| vec![("API key", redact::secret(&p))]).unwrap_or_default(),
+        }),
+        ServiceType::MinIO => {
+            let user = credentials::get_user(kubectl, service, cluster_name, kubeconfig)?;
+            Ok(WebUi {
+                title: "MinIO console",
+                svc: format!("{cluster_name}-minio"),
+                port: 9001,
+                path: "/",
+                node_port: None,
+                auto_login: None,
+                login: vec![("Access key", user), ("Secret key", redact::secret(&password.unwrap_or_default()))],
+            })
+        }
+        ServiceType::Neo4j => Ok(WebUi {
+            title: "Neo4j Browser",
+            svc: format!("{cluster_name}-neo4j"),
+            port: 7474,
+            path: "/browser/",
+            node_port: None,
+            auto_login: None,
+            login: vec![("User", user), ("Password", redact::secret(&password.unwrap_or_default()))],
+        }),
+        ServiceType::PostgreSQL => {
+            let Some((svc, port, node_port)) = find_pgadmin(kubectl, kubeconfig)? else {
+                return Err(format!(
+                    "PostgreSQL has no built-in web UI and no pgAdmin Service ({PGADMIN_SELECTOR}) runs in namespace \
+                     {NAMESPACE}; use `fdb sql {cluster_name}` instead"
+                ));
+            };
+            Ok(WebUi {
+                title: "pgAdmin",
+                svc,
+                port,
+                path: "/",
+                node_port,
+                auto_login: None,
+                login: vec![
+                    ("Server", format!("{cluster_name}-postgresql.{NAMESPACE}.svc:{}", service.default_port())),
+                    ("User", user),
+                    ("Password", redact::secret(&password.unwrap_or_default())),
+                ],
+            })
+        }
+        ServiceType::Redis => Err("Redis has no web UI; try `fdb exec-redis` or `fdb info`".to_string()),
+        ServiceType::Kafka => Err("Kafka has no built-in web UI; point a UI such as Kafka UI at `fdb info --uri`".to_string()),
+        ServiceType::MongoDB => Err("MongoDB has no web UI; connect with mongosh or Compass to `fdb info --uri`".to_string()),
+        ServiceType::Elasticsearch | ServiceType::OpenSearch => Err(format!(
+            "fdb does not deploy Kibana or OpenSearch Dashboards; query {} through `fdb info --uri`",
+            service.kbcli_name()
+        )),
+        _ => Err(format!("fdb has no web UI to open for {} clusters", service.kbcli_name())),
+    }
+}
+
+/// Hand `url` to the desktop's default browser.
+fn launch_browser(url: &str) -> Result<(), String> {
+    let mut cmd = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.args(["/C", "start", ""]);
+        c
+    } else {
+        Command::new("xdg-open")
+    };
+    let status = cmd
+        .arg(url)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("could not start a browser: {e}"))?;
+    if !status.success() {
+        return Err(format!("browser launcher exited with {status}"));
+    }
+    Ok(())
+}
+
+/// Open the cluster's web UI: through its NodePort when it has one, else through a
+/// port-forward that runs until Ctrl-C. With `no_browser`, only print the URL.
+pub fn open(
+    kubectl: &Path,
+    service: ServiceType,
+    cluster_name: &str,
+    no_browser: bool,
+    kubeconfig: &Path,
+) -> Result<(), String> {
+    let ui = web_ui(kubectl, service, cluster_name, kubeconfig)?;
+    let direct = match ui.node_port {
+        Some(port) => expose::server_host_from_kubeconfig(kubectl, kubeconfig).ok().map(|h| (h, port)),
+        None => None,
+    };
+    let mut forward = None;
+    let base = match direct {
+        Some((host, port)) => format!("http://{host}:{port}{}", ui.path),
+        None => {
+            let f = portforward::start_port_forward(kubectl, &format!("svc/{}", ui.svc), ui.port, None, kubeconfig)?;
+            let url = format!("http://127.0.0.1:{}{}", f.local_port, ui.path);
+            forward = Some(f);
+            url
+        }
+    };
+
+    println!("{} for \"{cluster_name}\": {base}", ui.title);
+    for (label, value) in &ui.login {
+        println!("  {:<11} {value}", format!("{label}:"));
+    }
+    if !no_browser {
+        let url = format!("{base}{}", ui.auto_login.as_deref().unwrap_or_default());
+        if let Err(e) = launch_browser(&url) {
+            eprintln!("warning: {e}; open {base} yourself");
+        }
+    }
+    if let Some(mut forward) = forward {
+        println!("Port-forward running; press Ctrl-C to stop.");
+        forward.wait()?;
+    }
+    Ok(())
+}
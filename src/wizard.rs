@@ -0,0 +1,99 @@
+//! Prompt-driven `fdb create` for a terminal with no arguments.
+
+use crate::names;
+use crate::service::ServiceType;
+use rustyline::DefaultEditor;
+use std::path::Path;
+
+/// (cpu, memory, storage) of a size preset.
+type Size = (&'static str, &'static str, &'static str);
+
+/// "small" keeps fdb.toml/default sizes.
+const PRESETS: [(&str, Option<Size>); 3] = [
+    ("small", None),
+    ("medium", Some(("1", "2Gi", "10Gi"))),
+    ("large", Some(("2", "4Gi", "50Gi"))),
+];
+
+fn ask(editor: &mut DefaultEditor, prompt: &str, default: &str) -> Result<String, String> {
+    let line = editor
+        .readline(&format!("{prompt} [{default}]: "))
+        .map_err(|e| format!("create wizard cancelled ({e})"))?;
+    let line = line.trim();
+    Ok(if line.is_empty() { default.to_string() } else { line.to_string() })
+}
+
+/// Ask until the answer is one of `options` (by name or 1-based number).
+fn choose(editor: &mut DefaultEditor, prompt: &str, options: &[&str]) -> Result<String, String> {
+    for (i, o) in options.iter().enumerate() {
+        println!("  {}) {o}", i + 1);
+    }
+    loop {
+        let answer = ask(editor, prompt, options[0])?;
+        if let Some(o) = options.iter().find(|o| o.eq_ignore_ascii_case(&answer)) {
+            return Ok(o.to_string());
+        }
+        if let Ok(n) = answer.parse::<usize>()
+            && (1..=options.len()).contains(&n)
+        {
+            return Ok(options[n - 1].to_string());
+        }
+        println!("Please pick one of: {}", options.join(", "));
+    }
+}
+
+/// Walk through engine, name, size, exposure and namespace, show the equivalent command and
+/// return its arguments (without the program name) once confirmed.
+pub fn run(kubeconfig: Option<&Path>) -> Result<Vec<String>, String> {
+    let mut editor = DefaultEditor::new().map_err(|e| format!("terminal: {e}"))?;
+    println!("Create a new cluster (Ctrl-C to cancel)\n");
+
+    let engine_names: Vec<&str> = ServiceType::ALL.iter().map(|e| e.kbcli_name()).collect();
+    let engine = choose(&mut editor, "Engine", &engine_names)?;
+
+    let suggested = names::generate(&[])?;
+    let name = loop {
+        let name = ask(&mut editor, "Name", &suggested)?;
+        match names::validate(&name) {
+            Ok(()) => break name,
+            Err(e) => println!("{e}"),
+        }
+    };
+
+    println!("Size:");
+    let preset_labels: Vec<String> = PRESETS
+        .iter()
+        .map(|(n, size)| match size {
+            Some((cpu, memory, storage)) => format!("{n} (cpu {cpu}, memory {memory}, storage {storage})"),
+            None => format!("{n} (fdb.toml or built-in defaults)"),
+        })
+        .collect();
+    let preset_refs: Vec<&str> = preset_labels.iter().map(String::as_str).collect();
+    let preset = choose(&mut editor, "Size", &preset_refs)?;
+
+    println!("Expose:");
+    let expose = choose(&mut editor, "Expose", &["nodeport", "none"])?;
+
+    println!("Namespace: default (fdb manages clusters in the default namespace)");
+
+    let mut args = vec!["create".to_string(), engine, name];
+    if let Some((_, Some((cpu, memory, storage)))) = PRESETS.iter().find(|(n, _)| preset.starts_with(n)) {
+        for (flag, value) in [("--cpu", cpu), ("--memory", memory), ("--storage", storage)] {
+            args.push(flag.to_string());
+            args.push(value.to_string());
+        }
+    }
+    if expose == "none" {
+        args.extend(["--expose".to_string(), "none".to_string()]);
+    }
+    if let Some(k) = kubeconfig {
+        args.extend(["--kubeconfig".to_string(), k.display().to_string()]);
+    }
+
+    println!("\nThis runs:\n\n  fdb {}\n", args.join(" "));
+    let confirm = ask(&mut editor, "Create it? (y/n)", "y")?;
+    if !matches!(confirm.to_lowercase().as_str(), "y" | "yes") {
+        return Err("create cancelled".to_string());
+    }
+    Ok(args)
+}